@@ -0,0 +1,61 @@
+use base64::Engine;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::CompanionGroup;
+
+// ===== Non-image Companions =====
+//
+// Files sharing a group's basename with an extension from
+// `asset_companion_extensions` (voice memos, drone logs) join the group:
+// stored with it and listed in image_file_list, marked `asset`. With
+// ImportOptions::upload_assets, content up to MAX_INLINE_BYTES goes along
+// base64 in the entry's imported_info.
+
+pub const MAX_INLINE_BYTES: u64 = 1024 * 1024;
+
+pub fn default_extensions() -> Vec<String> {
+    ["wav", "srt", "log"].iter().map(|s| s.to_string()).collect()
+}
+
+pub fn is_asset(file: &str, extensions: &[String]) -> bool {
+    Path::new(file)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| extensions.iter().any(|a| a.trim_start_matches('.').eq_ignore_ascii_case(&ext)))
+}
+
+// Add the assets found next to each group's master as companions. Each
+// directory is listed once.
+pub fn attach(groups: &mut [CompanionGroup], extensions: &[String]) {
+    if extensions.is_empty() {
+        return;
+    }
+    let mut listings: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    for group in groups.iter_mut() {
+        let Some(dir) = Path::new(&group.master_file).parent() else { continue };
+        let listing = listings.entry(dir.to_path_buf()).or_insert_with(|| {
+            fs::read_dir(dir)
+                .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+                .unwrap_or_default()
+        });
+        for path in listing.iter() {
+            let file = path.to_string_lossy().to_string();
+            let same_stem = path.file_stem().is_some_and(|s| s.to_string_lossy() == group.basename);
+            if same_stem && is_asset(&file, extensions) && !group.companion_files.contains(&file) {
+                group.companion_files.push(file);
+            }
+        }
+    }
+}
+
+// Base64 content of an asset small enough to send inline
+pub fn inline_content(file: &str) -> Option<String> {
+    let size = fs::metadata(file).ok()?.len();
+    if size > MAX_INLINE_BYTES {
+        return None;
+    }
+    let bytes = fs::read(file).ok()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::http::SendPaced;
+
+// ===== Credentials and Token Refresh =====
+//
+// Refresh tokens, the frontend's access token and remote source passwords
+// are kept in the OS credential store, never in settings. Backend calls
+// wrapped in with_refresh that get a 401 refresh the access token once
+// (POST /api/v1/auth/refresh/) and are repeated with it; the new token goes
+// to the frontend as `auth-token-refreshed`.
+
+const KEYRING_SERVICE: &str = "imalink-desktop";
+
+// Managed state
+#[derive(Default)]
+pub struct TokenRefresher {
+    // One refresh at a time; calls that waited use its result
+    refreshing: tokio::sync::Mutex<()>,
+    // Access token → the token that replaced it
+    replaced: Mutex<HashMap<String, String>>,
+}
+
+impl TokenRefresher {
+    // The newest token for `auth_token`
+    pub fn current(&self, auth_token: &str) -> String {
+        self.replaced.lock().unwrap().get(auth_token).cloned().unwrap_or_else(|| auth_token.to_string())
+    }
+
+    fn replace(&self, stale: &str, fresh: &str) {
+        let mut replaced = self.replaced.lock().unwrap();
+        for token in replaced.values_mut().filter(|t| *t == stale) {
+            *token = fresh.to_string();
+        }
+        replaced.insert(stale.to_string(), fresh.to_string());
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TokenRefreshed {
+    pub backend_url: String,
+    pub access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshResponse {
+    access_token: String,
+    // Present when the backend rotates refresh tokens
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Secret {
+    Access,
+    Refresh,
+    // Password of a remote source, by source name (see remote.rs)
+    Remote,
+}
+
+fn entry(secret: Secret, key: &str) -> Result<keyring::Entry, ImalinkError> {
+    let account = match secret {
+        Secret::Access => format!("access:{}", key),
+        Secret::Refresh => format!("refresh:{}", key),
+        Secret::Remote => format!("remote:{}", key),
+    };
+    keyring::Entry::new(KEYRING_SERVICE, &account)
+        .map_err(|e| ImalinkError::internal(format!("Credential store unavailable: {}", e)))
+}
+
+fn store(secret: Secret, key: &str, value: &str) -> Result<(), ImalinkError> {
+    entry(secret, key)?
+        .set_password(value)
+        .map_err(|e| ImalinkError::internal(format!("Failed to write to the credential store: {}", e)))
+}
+
+fn load(secret: Secret, key: &str) -> Result<Option<String>, ImalinkError> {
+    match entry(secret, key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(ImalinkError::internal(format!("Failed to read from the credential store: {}", e))),
+    }
+}
+
+fn forget(secret: Secret, key: &str) -> Result<(), ImalinkError> {
+    match entry(secret, key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(ImalinkError::internal(format!("Failed to remove from the credential store: {}", e))),
+    }
+}
+
+pub fn store_refresh_token(backend_url: &str, refresh_token: &str) -> Result<(), ImalinkError> {
+    store(Secret::Refresh, backend_url, refresh_token)
+}
+
+pub fn forget_refresh_token(backend_url: &str) {
+    if let Err(e) = forget(Secret::Refresh, backend_url) {
+        eprintln!("Failed to remove refresh token for {}: {}", backend_url, e);
+    }
+}
+
+pub fn store_remote_password(source: &str, password: &str) -> Result<(), ImalinkError> {
+    store(Secret::Remote, source, password)
+}
+
+pub fn remote_password(source: &str) -> Result<Option<String>, ImalinkError> {
+    load(Secret::Remote, source)
+}
+
+pub fn forget_remote_password(source: &str) {
+    if let Err(e) = forget(Secret::Remote, source) {
+        eprintln!("Failed to remove password of remote source {}: {}", source, e);
+    }
+}
+
+// New access token in place of `stale`
+async fn refresh(app: &tauri::AppHandle, backend_url: &str, stale: &str) -> Result<String, ImalinkError> {
+    let refresher = app.state::<TokenRefresher>();
+    let _refreshing = refresher.refreshing.lock().await;
+    let current = refresher.current(stale);
+    if current != stale {
+        return Ok(current);
+    }
+    let refresh_token = load(Secret::Refresh, backend_url)
+        .ok()
+        .flatten()
+        .ok_or_else(|| ImalinkError::Unauthorized { detail: "No refresh token".to_string() })?;
+
+    let response = crate::http::client(app)
+        .post(format!("{}/api/v1/auth/refresh/", backend_url))
+        .json(&serde_json::json!({ "refresh_token": refresh_token }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        // Expired or revoked; it won't work next time either
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            forget_refresh_token(backend_url);
+        }
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let refreshed: RefreshResponse = response.json().await.map_err(ImalinkError::parse)?;
+
+    if let Some(rotated) = &refreshed.refresh_token {
+        store_refresh_token(backend_url, rotated)?;
+    }
+    refresher.replace(stale, &refreshed.access_token);
+    app.state::<crate::session::Session>().replace_token(stale, &refreshed.access_token);
+    if load(Secret::Access, backend_url).ok().flatten().as_deref() == Some(stale) {
+        store(Secret::Access, backend_url, &refreshed.access_token)?;
+    }
+    let _ = app.emit(
+        "auth-token-refreshed",
+        TokenRefreshed { backend_url: backend_url.to_string(), access_token: refreshed.access_token.clone() },
+    );
+    Ok(refreshed.access_token)
+}
+
+// Run a backend call with the newest token for `auth_token`; on a 401,
+// refresh the token and run it once more
+pub async fn with_refresh<T, F, Fut>(
+    app: &tauri::AppHandle,
+    backend_url: &str,
+    auth_token: &str,
+    call: F,
+) -> Result<T, ImalinkError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, ImalinkError>>,
+{
+    let token = app.state::<TokenRefresher>().current(auth_token);
+    match call(token.clone()).await {
+        Err(ImalinkError::Unauthorized { detail }) => match refresh(app, backend_url, &token).await {
+            Ok(fresh) => call(fresh).await,
+            Err(e) => {
+                eprintln!("Token refresh for {} failed: {}", backend_url, e);
+                Err(ImalinkError::Unauthorized { detail })
+            }
+        },
+        result => result,
+    }
+}
+
+// ===== Commands =====
+
+// Keep the access token for `backend_url` in the credential store; None
+// removes it (logout)
+#[tauri::command]
+pub fn secure_store_token(
+    app: tauri::AppHandle,
+    backend_url: String,
+    auth_token: Option<String>,
+) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "store credentials")?;
+    match auth_token {
+        Some(auth_token) => store(Secret::Access, &backend_url, &auth_token),
+        None => forget(Secret::Access, &backend_url),
+    }
+}
+
+// The owner's access token; never handed to a guest
+#[tauri::command]
+pub fn secure_get_token(app: tauri::AppHandle, backend_url: String) -> Result<Option<String>, ImalinkError> {
+    guest::require_owner(&app, "read credentials")?;
+    load(Secret::Access, &backend_url)
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::camera_of;
+use crate::PhotoCreateSchema;
+
+// ===== Author Rules =====
+//
+// Studios with several photographers sharing one input channel set rules in
+// settings.json mapping a camera to the author of its photos:
+//
+//   { "serial": "032021001234", "author_id": 7 }
+//   { "model": "Canon EOS R6", "author_id": 9 }
+//
+// Rules are tried per photo during processing - serial rules first, since a
+// serial names one body and a model may be shared, then in the order given.
+// A match sets author_id on the photo, which takes precedence over the
+// default author of the input channel. When rules are configured but none
+// matches, the camera is listed in the session's `unmatched_cameras` so a
+// new body can be added to the rules.
+
+// EXIF tags that carry the body serial number, by camera vendor
+const SERIAL_TAGS: &[&str] = &["BodySerialNumber", "SerialNumber", "CameraSerialNumber", "InternalSerialNumber"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorRule {
+    #[serde(default)]
+    pub serial: Option<String>,
+    // Matched against "Make Model" as well as the bare model
+    #[serde(default)]
+    pub model: Option<String>,
+    pub author_id: i32,
+}
+
+pub fn serial_of(exif_dict: &serde_json::Value) -> Option<String> {
+    SERIAL_TAGS.iter().find_map(|tag| {
+        let value = exif_dict.get(*tag)?;
+        let serial = match value {
+            serde_json::Value::String(s) => s.trim().to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            _ => return None,
+        };
+        Some(serial).filter(|s| !s.is_empty())
+    })
+}
+
+fn same(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+// Set the photo's author from the first matching rule. Returns the camera
+// (model and serial) when there are rules but none matched.
+pub fn apply(rules: &[AuthorRule], schema: &mut PhotoCreateSchema) -> Option<String> {
+    if rules.is_empty() {
+        return None;
+    }
+    let serial = serial_of(&schema.exif_dict);
+    let camera = camera_of(&schema.exif_dict);
+    let model = schema.exif_dict.get("Model").and_then(|v| v.as_str());
+
+    let by_serial = rules
+        .iter()
+        .find(|r| matches!((&r.serial, &serial), (Some(rule), Some(serial)) if same(rule, serial)));
+    let by_model = || {
+        rules.iter().find(|r| {
+            r.serial.is_none()
+                && r.model.as_deref().is_some_and(|rule| {
+                    camera.as_deref().is_some_and(|c| same(rule, c)) || model.is_some_and(|m| same(rule, m))
+                })
+        })
+    };
+    if let Some(rule) = by_serial.or_else(by_model) {
+        schema.author_id = Some(rule.author_id);
+        return None;
+    }
+
+    Some(match (camera, serial) {
+        (Some(camera), Some(serial)) => format!("{} (serial {})", camera, serial),
+        (None, Some(serial)) => format!("serial {}", serial),
+        (Some(camera), None) => camera,
+        (None, None) => "Unknown camera".to_string(),
+    })
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::History;
+use crate::offline::OfflinePreviews;
+use crate::settings::{self, AppSettings};
+
+// ===== Library Backup =====
+//
+// create_backup writes a dated zip with everything the desktop app keeps
+// locally that can't be fetched from the backend again:
+//
+//   history.db                 snapshot of the local history (VACUUM INTO)
+//   settings.json              settings, including presets and schedules
+//   offline_previews.json      manifest of the offline preview cache
+//   storage_locations.json     folders holding originals, with photo counts
+//   manifest.json              size and BLAKE3 hash of every entry above
+//
+// The previews themselves are left out; they can be synced again. Settings
+// hold remote source credentials, so the archive should be kept as safe as
+// the settings file itself.
+//
+// verify_backup checks every entry against the manifest; restore_backup
+// verifies, then replaces the history database and settings.
+
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+const HISTORY_ENTRY: &str = "history.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+const PREVIEWS_ENTRY: &str = "offline_previews.json";
+const LOCATIONS_ENTRY: &str = "storage_locations.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupEntry {
+    pub name: String,
+    pub bytes: u64,
+    pub blake3: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub entries: Vec<BackupEntry>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupVerification {
+    pub path: String,
+    pub manifest: Option<BackupManifest>,
+    // Empty when the backup is intact
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StorageLocation {
+    pub path: String,
+    pub photos: usize,
+    pub exists: bool,
+}
+
+fn archive_error(path: &Path, e: impl std::fmt::Display) -> ImalinkError {
+    ImalinkError::parse(format!("Invalid backup {}: {}", path.display(), e))
+}
+
+// Folders holding originals: parents of the files in history, plus preset destinations
+fn storage_locations(history: &History, settings: &AppSettings) -> Result<Vec<StorageLocation>, ImalinkError> {
+    let files: Vec<String> = history.with(|conn| {
+        let mut stmt = conn.prepare("SELECT file_path FROM photos WHERE file_path IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    })?;
+
+    let mut counts: BTreeMap<PathBuf, usize> = BTreeMap::new();
+    for file in &files {
+        if let Some(parent) = Path::new(file).parent() {
+            *counts.entry(parent.to_path_buf()).or_default() += 1;
+        }
+    }
+    for preset in &settings.presets {
+        if let Some(dest) = &preset.destination_dir {
+            counts.entry(PathBuf::from(dest)).or_default();
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(path, photos)| StorageLocation {
+            exists: path.is_dir(),
+            path: path.to_string_lossy().to_string(),
+            photos,
+        })
+        .collect())
+}
+
+fn write_backup(app: &tauri::AppHandle, dest_dir: &Path) -> Result<(PathBuf, BackupManifest), ImalinkError> {
+    fs::create_dir_all(dest_dir).map_err(|e| ImalinkError::io(dest_dir.display(), e))?;
+    let created = chrono::Local::now();
+    let path = dest_dir.join(format!("imalink-backup-{}.zip", created.format("%Y%m%d_%H%M%S")));
+
+    let history = app.state::<History>();
+    let settings = settings::load(app);
+    let snapshot = std::env::temp_dir().join(format!("imalink-history-{}.db", uuid::Uuid::new_v4()));
+    history.snapshot(&snapshot)?;
+    let history_bytes = fs::read(&snapshot).map_err(|e| ImalinkError::io(snapshot.display(), e));
+    let _ = fs::remove_file(&snapshot);
+
+    let previews: Vec<serde_json::Value> = app
+        .state::<OfflinePreviews>()
+        .entries()
+        .into_iter()
+        .map(|(hothash, bytes)| serde_json::json!({ "hothash": hothash, "bytes": bytes }))
+        .collect();
+    let contents: Vec<(&str, Vec<u8>)> = vec![
+        (HISTORY_ENTRY, history_bytes?),
+        (SETTINGS_ENTRY, serde_json::to_vec_pretty(&settings)?),
+        (PREVIEWS_ENTRY, serde_json::to_vec_pretty(&previews)?),
+        (LOCATIONS_ENTRY, serde_json::to_vec_pretty(&storage_locations(&history, &settings)?)?),
+    ];
+
+    let manifest = BackupManifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: created.to_rfc3339(),
+        entries: contents
+            .iter()
+            .map(|(name, bytes)| BackupEntry {
+                name: name.to_string(),
+                bytes: bytes.len() as u64,
+                blake3: blake3::hash(bytes).to_hex().to_string(),
+            })
+            .collect(),
+    };
+
+    let file = fs::File::create(&path).map_err(|e| ImalinkError::io(path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+    for (name, bytes) in contents.iter().map(|(n, b)| (*n, b)).chain([(MANIFEST, &manifest_bytes)]) {
+        zip.start_file(name, options).map_err(|e| ImalinkError::io(path.display(), e))?;
+        zip.write_all(bytes).map_err(|e| ImalinkError::io(path.display(), e))?;
+    }
+    zip.finish().map_err(|e| ImalinkError::io(path.display(), e))?;
+    Ok((path, manifest))
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<fs::File>, path: &Path, name: &str) -> Result<Vec<u8>, ImalinkError> {
+    let mut entry = archive.by_name(name).map_err(|e| archive_error(path, format!("{}: {}", name, e)))?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).map_err(|e| ImalinkError::io(name, e))?;
+    Ok(bytes)
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<fs::File>, ImalinkError> {
+    let file = fs::File::open(path).map_err(|e| ImalinkError::io(path.display(), e))?;
+    zip::ZipArchive::new(file).map_err(|e| archive_error(path, e))
+}
+
+fn verify(path: &Path) -> Result<BackupVerification, ImalinkError> {
+    let mut archive = open_archive(path)?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&read_entry(&mut archive, path, MANIFEST)?).map_err(|e| archive_error(path, e))?;
+
+    let mut problems = Vec::new();
+    if manifest.format_version > FORMAT_VERSION {
+        problems.push(format!(
+            "Backup format {} is newer than this app supports ({})",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+    for entry in &manifest.entries {
+        match read_entry(&mut archive, path, &entry.name) {
+            Ok(bytes) if blake3::hash(&bytes).to_hex().as_str() != entry.blake3 => {
+                problems.push(format!("{} does not match its checksum", entry.name));
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(e.to_string()),
+        }
+    }
+    for required in [HISTORY_ENTRY, SETTINGS_ENTRY] {
+        if !manifest.entries.iter().any(|e| e.name == required) {
+            problems.push(format!("{} is missing", required));
+        }
+    }
+    Ok(BackupVerification {
+        path: path.to_string_lossy().to_string(),
+        manifest: Some(manifest),
+        problems,
+    })
+}
+
+// Write a dated backup archive into `dest` and return its path and manifest
+#[tauri::command]
+pub async fn create_backup(app: tauri::AppHandle, dest: String) -> Result<BackupVerification, ImalinkError> {
+    guest::require_owner(&app, "create backups")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (path, manifest) = write_backup(&app, Path::new(&dest))?;
+        Ok(BackupVerification {
+            path: path.to_string_lossy().to_string(),
+            manifest: Some(manifest),
+            problems: Vec::new(),
+        })
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn verify_backup(path: String) -> Result<BackupVerification, ImalinkError> {
+    tauri::async_runtime::spawn_blocking(move || verify(Path::new(&path)))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
+
+// Replace the history database and settings with the backup's. Nothing is
+// touched unless the backup verifies.
+#[tauri::command]
+pub async fn restore_backup(app: tauri::AppHandle, path: String) -> Result<BackupVerification, ImalinkError> {
+    guest::require_owner(&app, "restore backups")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(path);
+        let verification = verify(&path)?;
+        if !verification.problems.is_empty() {
+            return Err(ImalinkError::invalid(format!(
+                "Backup {} failed verification: {}",
+                path.display(),
+                verification.problems.join("; ")
+            )));
+        }
+
+        let mut archive = open_archive(&path)?;
+        let restored_settings: AppSettings = serde_json::from_slice(&read_entry(&mut archive, &path, SETTINGS_ENTRY)?)
+            .map_err(|e| archive_error(&path, e))?;
+        let snapshot = std::env::temp_dir().join(format!("imalink-restore-{}.db", uuid::Uuid::new_v4()));
+        fs::write(&snapshot, read_entry(&mut archive, &path, HISTORY_ENTRY)?)
+            .map_err(|e| ImalinkError::io(snapshot.display(), e))?;
+        let restored = app.state::<History>().restore(&snapshot);
+        let _ = fs::remove_file(&snapshot);
+        restored?;
+
+        settings::update_settings(app.clone(), restored_settings)?;
+        Ok(verification)
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
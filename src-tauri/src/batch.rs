@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::ImalinkError;
+
+// ===== Batch Result Reporting =====
+//
+// Shared return shape for every batch command (batch upload, batch copy,
+// bulk metadata edits, cleanup). A batch is never all-or-nothing: each item
+// ends up in exactly one of the three lists so the frontend can show partial
+// failures and retry only the items that failed.
+
+// Error for a single item in a batch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ItemError {
+    pub item: String,  // Identifier of the item (file path, hothash, photo id...)
+    pub code: String,  // Stable error code, see error.rs
+    pub error: String,
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+// Item that was deliberately not processed (already exists, filtered out, etc.)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedItem {
+    pub item: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchResult<T> {
+    pub succeeded: Vec<T>,
+    pub skipped: Vec<SkippedItem>,
+    pub failed: Vec<ItemError>,
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        BatchResult {
+            succeeded: Vec::new(),
+            skipped: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl<T> BatchResult<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn succeed(&mut self, value: T) {
+        self.succeeded.push(value);
+    }
+
+    pub fn skip(&mut self, item: impl Into<String>, reason: impl Into<String>) {
+        self.skipped.push(SkippedItem {
+            item: item.into(),
+            reason: reason.into(),
+        });
+    }
+
+    pub fn fail(&mut self, item: impl Into<String>, error: ImalinkError) {
+        self.failed.push(ItemError {
+            item: item.into(),
+            code: error.code().to_string(),
+            error: error.message(),
+            retryable: error.retryable(),
+        });
+    }
+
+    // Record the outcome of one item, routing errors to `failed`
+    pub fn record(&mut self, item: impl Into<String>, result: Result<T, ImalinkError>) {
+        match result {
+            Ok(value) => self.succeed(value),
+            Err(e) => self.fail(item, e),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::{Emitter, Manager};
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::operations::{self, OperationKind};
+use crate::session::Session;
+use crate::{auth, guest, maintenance, payload, privacy, settings, stall};
+use crate::{PhotoCreateResponse, PhotoCreateSchema};
+
+// ===== Batch Upload =====
+//
+// The old upload flow in the frontend calls process_image_file and then
+// upload_photo_create_schema for every file, two IPC round trips and a
+// preview store detour per photo. batch_upload_photos takes the whole list
+// and does both steps here, one file after the other or `concurrency` at a
+// time, emitting `upload-progress` as each file finishes.
+//
+// Every path is uploaded as a photo of its own, in register mode - there is
+// no companion grouping, copying or renaming. Imports that need those go
+// through start_import (pipeline.rs).
+
+const MAX_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UploadItemResult {
+    Uploaded { hothash: String, photo_id: i32 },
+    Duplicate { hothash: String, photo_id: i32 },
+    Failed { code: String, error: String },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UploadProgress {
+    pub file: String,
+    // Position of the file in the request
+    pub index: usize,
+    pub total: usize,
+    // Files finished so far, this one included
+    pub done: usize,
+    pub result: UploadItemResult,
+}
+
+// Register-mode storage info for the master, as the pipeline records it
+fn attach_file_info(schema: &mut PhotoCreateSchema, file: &str, input_channel_id: i32) {
+    let directory = std::path::Path::new(file).parent().map(|p| p.to_string_lossy().to_string());
+    if let Some(master) = schema.image_file_list.first_mut() {
+        master.local_storage_info = Some(serde_json::json!({
+            "import_mode": "register",
+            "source_path": file,
+            "storage_path": file,
+        }));
+        master.imported_info = Some(serde_json::json!({
+            "imported_at": chrono::Utc::now().to_rfc3339(),
+            "original_selection": directory,
+        }));
+    }
+    schema.input_channel_id = Some(input_channel_id);
+}
+
+// Process `files` through imalink-core and upload them, `concurrency` at a
+// time (default 1, at most 8). Cancelling the operation stops it before the
+// next file; files not started are reported as skipped.
+#[tauri::command]
+pub async fn batch_upload_photos(
+    app: tauri::AppHandle,
+    files: Vec<String>,
+    core_api_url: String,
+    backend_url: Option<String>,
+    input_channel_id: i32,
+    auth_token: Option<String>,
+    concurrency: Option<usize>,
+) -> Result<BatchResult<PhotoCreateResponse>, ImalinkError> {
+    guest::require_owner(&app, "upload")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let settings = settings::load(&app);
+    let stall = stall::StallPolicy::from_settings(&settings);
+    let limit = payload::PayloadLimit::from_settings(&settings);
+    let client = crate::http::client(&app);
+
+    let total = files.len();
+    let done = AtomicUsize::new(0);
+    let operation = operations::register(&app, OperationKind::Import, format!("Upload {} files", total)).cancellable();
+    operation.set_total(total as u64);
+
+    let outcomes: Vec<(String, Option<Result<PhotoCreateResponse, ImalinkError>>)> =
+        futures_util::stream::iter(files.into_iter().enumerate())
+            .map(|(index, file)| {
+                let (app, client, settings, stall, limit, done, operation) =
+                    (&app, &client, &settings, &stall, &limit, &done, &operation);
+                let (core_api_url, backend_url, auth_token) = (&core_api_url, &backend_url, &auth_token);
+                async move {
+                    if operation.is_cancelled() {
+                        return (file, None);
+                    }
+                    let uploaded = async {
+                        let mut schema = crate::process_file(client, &file, core_api_url).await?;
+                        attach_file_info(&mut schema, &file, input_channel_id);
+                        privacy::apply(&settings.privacy_zones, &mut schema);
+                        maintenance::wait_out(app, || {
+                            auth::with_refresh(app, backend_url, auth_token, |token| {
+                                let schema = schema.clone();
+                                async move {
+                                    crate::upload_schema(client, backend_url, &token, schema, input_channel_id, stall, limit)
+                                        .await
+                                }
+                            })
+                        })
+                        .await
+                    }
+                    .await;
+
+                    let result = match &uploaded {
+                        Ok(response) if response.is_duplicate => UploadItemResult::Duplicate {
+                            hothash: response.hothash.clone(),
+                            photo_id: response.id,
+                        },
+                        Ok(response) => UploadItemResult::Uploaded {
+                            hothash: response.hothash.clone(),
+                            photo_id: response.id,
+                        },
+                        Err(e) => UploadItemResult::Failed { code: e.code().to_string(), error: e.message() },
+                    };
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    operation.advance();
+                    let _ = app.emit("upload-progress", UploadProgress { file: file.clone(), index, total, done, result });
+                    (file, Some(uploaded))
+                }
+            })
+            .buffer_unordered(concurrency.unwrap_or(1).clamp(1, MAX_CONCURRENCY))
+            .collect()
+            .await;
+
+    let mut result = BatchResult::new();
+    for (file, outcome) in outcomes {
+        match outcome {
+            None => result.skip(file, "Cancelled"),
+            Some(uploaded) => result.record(file, uploaded),
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,202 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::error::ImalinkError;
+use crate::session::Session;
+
+// ===== Backend Benchmark =====
+//
+// "It's slow" is hard to act on. benchmark_backend times the three kinds of
+// request an import or a browse session is made of against the given
+// backend, a number of times each, and returns percentiles per kind:
+//
+//   auth      GET /api/v1/auth/me/ - a round trip with token validation
+//   upload    POST /api/v1/photos/create with UPLOAD_BYTES of padding and no
+//             photo fields; the backend reads the body and rejects it with a
+//             validation error, so the time is the upload without anything
+//             being stored
+//   preview   GET /api/v1/photos/{id}/hotpreview of the newest photo (none
+//             when the library is empty)
+//
+// Requests run one after the other so they don't compete, and the same
+// numbers come out for a hosted and a self-hosted backend side by side.
+
+const DEFAULT_SAMPLES: u32 = 10;
+const MAX_SAMPLES: u32 = 50;
+const UPLOAD_BYTES: usize = 64 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct LatencyStats {
+    // Requests that got the expected answer; only those are in the percentiles
+    pub succeeded: u32,
+    pub failed: u32,
+    pub min_ms: Option<u64>,
+    pub p50_ms: Option<u64>,
+    pub p90_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    // Last failure, if any
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackendBenchmark {
+    pub backend_url: String,
+    pub ran_at: String,
+    pub samples: u32,
+    pub auth: LatencyStats,
+    pub upload: LatencyStats,
+    pub preview: LatencyStats,
+    // Photo whose hotpreview was downloaded
+    pub preview_photo_id: Option<i64>,
+}
+
+// Nearest-rank percentile of sorted timings
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+fn stats(mut timings: Vec<u64>, failed: u32, error: Option<String>) -> LatencyStats {
+    timings.sort_unstable();
+    LatencyStats {
+        succeeded: timings.len() as u32,
+        failed,
+        min_ms: timings.first().copied(),
+        p50_ms: percentile(&timings, 50.0),
+        p90_ms: percentile(&timings, 90.0),
+        p99_ms: percentile(&timings, 99.0),
+        max_ms: timings.last().copied(),
+        error,
+    }
+}
+
+// Time `samples` runs of `request`; it returns an error for an unexpected answer
+async fn measure<F, Fut>(samples: u32, request: F) -> LatencyStats
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<(), ImalinkError>>,
+{
+    let mut timings = Vec::new();
+    let mut failed = 0;
+    let mut error = None;
+    for _ in 0..samples {
+        let started = Instant::now();
+        match request().await {
+            Ok(()) => timings.push(started.elapsed().as_millis() as u64),
+            Err(e) => {
+                failed += 1;
+                error = Some(e.to_string());
+            }
+        }
+    }
+    stats(timings, failed, error)
+}
+
+async fn expect_success(response: reqwest::Response) -> Result<(), ImalinkError> {
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    // The body is part of the round trip
+    response.bytes().await?;
+    Ok(())
+}
+
+async fn newest_photo_id(client: &reqwest::Client, backend_url: &str, auth_token: &str) -> Result<Option<i64>, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/photos/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .query(&[("offset", "0"), ("limit", "1")])
+        .send()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let page: serde_json::Value = response.json().await?;
+    Ok(page["data"].get(0).and_then(|photo| photo["id"].as_i64()))
+}
+
+// Latency of auth, upload and preview requests against the backend, with
+// `samples` requests of each (default 10, at most 50)
+#[tauri::command]
+pub async fn benchmark_backend(
+    session: tauri::State<'_, Session>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    samples: Option<u32>,
+) -> Result<BackendBenchmark, ImalinkError> {
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    let samples = samples.unwrap_or(DEFAULT_SAMPLES).clamp(1, MAX_SAMPLES);
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| ImalinkError::internal(format!("Failed to create HTTP client: {}", e)))?;
+    let bearer = format!("Bearer {}", auth_token);
+
+    let auth = measure(samples, || async {
+        let response = client
+            .get(format!("{}/api/v1/auth/me/", backend_url))
+            .header("Authorization", &bearer)
+            .send()
+            .await
+            .map_err(|e| ImalinkError::network(&backend_url, e))?;
+        expect_success(response).await
+    })
+    .await;
+
+    let padding = "0".repeat(UPLOAD_BYTES);
+    let upload = measure(samples, || async {
+        let response = client
+            .post(format!("{}/api/v1/photos/create", backend_url))
+            .header("Authorization", &bearer)
+            .json(&serde_json::json!({ "benchmark_padding": padding }))
+            .send()
+            .await
+            .map_err(|e| ImalinkError::network(&backend_url, e))?;
+        // Rejected as invalid is the expected answer; anything else means
+        // the request did not make it to validation
+        let status = response.status();
+        if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY || status == reqwest::StatusCode::BAD_REQUEST {
+            return Ok(());
+        }
+        let error_text = response.text().await.unwrap_or_default();
+        Err(ImalinkError::from_backend(status, error_text))
+    })
+    .await;
+
+    let preview_photo_id = newest_photo_id(&client, &backend_url, &auth_token).await?;
+    let preview = match preview_photo_id {
+        Some(photo_id) => {
+            measure(samples, || async {
+                let response = client
+                    .get(format!("{}/api/v1/photos/{}/hotpreview", backend_url, photo_id))
+                    .header("Authorization", &bearer)
+                    .send()
+                    .await
+                    .map_err(|e| ImalinkError::network(&backend_url, e))?;
+                expect_success(response).await
+            })
+            .await
+        }
+        None => LatencyStats { error: Some("No photos to download a preview of".to_string()), ..Default::default() },
+    };
+
+    Ok(BackendBenchmark {
+        backend_url,
+        ran_at: chrono::Utc::now().to_rfc3339(),
+        samples,
+        auth,
+        upload,
+        preview,
+        preview_photo_id,
+    })
+}
@@ -0,0 +1,252 @@
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::{guest, hothash, originals};
+
+// ===== Memory Card Fingerprints =====
+//
+// Imports from a removable volume record the card in `memory_cards`: its
+// serial, the content hashes of its oldest and newest image, and the newest
+// modification time imported. check_card recognises a card by serial (or by
+// first/last file) and pre-selects only files modified since its last
+// import. Frontend-run imports call record_card_import when done.
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CardFingerprint {
+    // Mount point
+    pub volume: String,
+    pub label: Option<String>,
+    pub serial: Option<String>,
+    pub first_hash: Option<String>,
+    pub last_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KnownCard {
+    pub id: String,
+    pub label: Option<String>,
+    pub last_import_at: String,
+    // RFC 3339; files modified later are new
+    pub newest_imported: Option<String>,
+    pub imports: u32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CardCheck {
+    // None when the folder isn't on a removable volume
+    pub fingerprint: Option<CardFingerprint>,
+    pub known: Option<KnownCard>,
+    pub files: usize,
+    // Files to pre-select: the new ones on a known card, all otherwise
+    pub selected: Vec<String>,
+}
+
+struct CardRow {
+    card: KnownCard,
+    newest_modified: i64,
+}
+
+fn modified_secs(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+// Serial number or UUID of the filesystem mounted at `root`
+#[cfg(target_os = "linux")]
+fn volume_serial(root: &Path) -> Option<String> {
+    // Field 5 of mountinfo is the mount point, the source device follows " - <fstype>"
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+    let device = mountinfo.lines().find_map(|line| {
+        let (fields, rest) = line.split_once(" - ")?;
+        let mount_point = fields.split(' ').nth(4)?;
+        (Path::new(&mount_point.replace("\\040", " ")) == root).then(|| rest.split(' ').nth(1).map(PathBuf::from))?
+    })?;
+    let device = fs::canonicalize(device).ok()?;
+    fs::read_dir("/dev/disk/by-uuid").ok()?.flatten().find_map(|entry| {
+        (fs::canonicalize(entry.path()).ok()? == device).then(|| entry.file_name().to_string_lossy().to_string())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn volume_serial(root: &Path) -> Option<String> {
+    let output = std::process::Command::new("diskutil").arg("info").arg(root).output().ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let value = line.trim().strip_prefix("Volume UUID:")?.trim();
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    })
+}
+
+#[cfg(windows)]
+fn volume_serial(root: &Path) -> Option<String> {
+    let drive = root.to_string_lossy().trim_end_matches('\\').to_string();
+    let output = std::process::Command::new("cmd").args(["/C", "vol", &drive]).output().ok()?;
+    // "Volume Serial Number is 1A2B-3C4D"; the wording is localized, the format isn't
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .find(|word| word.len() == 9 && word.as_bytes()[4] == b'-')
+        .map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn volume_serial(_root: &Path) -> Option<String> {
+    None
+}
+
+// Fingerprint of the card `path` is on; None off removable volumes
+pub fn fingerprint(history: &History, path: &Path) -> Result<Option<CardFingerprint>, ImalinkError> {
+    let Some(root) = originals::volume_root(path) else {
+        return Ok(None);
+    };
+    let mut files: Vec<(i64, String)> = crate::collect_image_files(&root)?
+        .into_iter()
+        .filter_map(|f| Some((modified_secs(Path::new(&f))?, f)))
+        .collect();
+    files.sort();
+    let hash = |entry: Option<&(i64, String)>| entry.and_then(|(_, f)| hothash::content_hash(history, f).ok());
+    Ok(Some(CardFingerprint {
+        label: root.file_name().map(|n| n.to_string_lossy().to_string()),
+        serial: volume_serial(&root),
+        first_hash: hash(files.first()),
+        last_hash: hash(files.last()),
+        volume: root.display().to_string(),
+    }))
+}
+
+// The recorded card matching `fingerprint`, last imported first
+fn find(history: &History, fingerprint: &CardFingerprint) -> Result<Option<CardRow>, ImalinkError> {
+    history.with(|conn| {
+        conn.query_row(
+            "SELECT id, label, last_import_at, newest_modified, imports FROM memory_cards
+             WHERE CASE WHEN ?1 IS NOT NULL THEN serial = ?1
+                        ELSE serial IS NULL AND (first_hash = ?2 OR last_hash = ?3) END
+             ORDER BY last_import_at DESC LIMIT 1",
+            params![fingerprint.serial, fingerprint.first_hash, fingerprint.last_hash],
+            |row| {
+                let newest_modified: i64 = row.get(3)?;
+                Ok(CardRow {
+                    card: KnownCard {
+                        id: row.get(0)?,
+                        label: row.get(1)?,
+                        last_import_at: row.get(2)?,
+                        newest_imported: chrono::DateTime::from_timestamp(newest_modified, 0)
+                            .filter(|_| newest_modified > 0)
+                            .map(|t| t.to_rfc3339()),
+                        imports: row.get(4)?,
+                    },
+                    newest_modified,
+                })
+            },
+        )
+        .optional()
+    })
+}
+
+// Record an import of `files` from the card they are on. Returns the card,
+// or None when they aren't on a removable volume.
+pub fn record(history: &History, files: &[String]) -> Result<Option<KnownCard>, ImalinkError> {
+    let Some(first) = files.first() else {
+        return Ok(None);
+    };
+    let Some(fingerprint) = fingerprint(history, Path::new(first))? else {
+        return Ok(None);
+    };
+    let newest = files.iter().filter_map(|f| modified_secs(Path::new(f))).max().unwrap_or(0);
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = match find(history, &fingerprint)? {
+        Some(row) => {
+            history.with(|conn| {
+                conn.execute(
+                    "UPDATE memory_cards SET label = ?2, serial = ?3, first_hash = ?4, last_hash = ?5,
+                     newest_modified = MAX(newest_modified, ?6), last_import_at = ?7, imports = imports + 1
+                     WHERE id = ?1",
+                    params![
+                        row.card.id,
+                        fingerprint.label,
+                        fingerprint.serial,
+                        fingerprint.first_hash,
+                        fingerprint.last_hash,
+                        newest,
+                        now
+                    ],
+                )
+            })?;
+            row.card.id
+        }
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            history.with(|conn| {
+                conn.execute(
+                    "INSERT INTO memory_cards
+                     (id, label, serial, first_hash, last_hash, newest_modified, last_import_at, imports)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)",
+                    params![
+                        id,
+                        fingerprint.label,
+                        fingerprint.serial,
+                        fingerprint.first_hash,
+                        fingerprint.last_hash,
+                        newest,
+                        now
+                    ],
+                )
+            })?;
+            id
+        }
+    };
+    println!("Recorded import from card {} ({})", fingerprint.label.as_deref().unwrap_or("?"), id);
+    Ok(find(history, &fingerprint)?.map(|row| row.card))
+}
+
+// Whether the card `dir_path` is on has been imported from before, and which
+// of `files` (the scan of `dir_path` when None) to pre-select
+#[tauri::command]
+pub async fn check_card(
+    app: tauri::AppHandle,
+    dir_path: String,
+    files: Option<Vec<String>>,
+) -> Result<CardCheck, ImalinkError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(&dir_path);
+        let files = match files {
+            Some(files) => files,
+            None => crate::collect_image_files(&path)?,
+        };
+        let history = app.state::<History>();
+        let fingerprint = fingerprint(&history, &path)?;
+        let known = match &fingerprint {
+            Some(fingerprint) => find(&history, fingerprint)?,
+            None => None,
+        };
+        let selected = match &known {
+            Some(row) => files
+                .iter()
+                .filter(|f| modified_secs(Path::new(f)).is_none_or(|m| m > row.newest_modified))
+                .cloned()
+                .collect(),
+            None => files.clone(),
+        };
+        Ok(CardCheck {
+            fingerprint,
+            known: known.map(|row| row.card),
+            files: files.len(),
+            selected,
+        })
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
+
+// Remember that `files` were imported, for imports the frontend ran itself
+#[tauri::command]
+pub async fn record_card_import(app: tauri::AppHandle, files: Vec<String>) -> Result<Option<KnownCard>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    tauri::async_runtime::spawn_blocking(move || record(&app.state::<History>(), &files))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
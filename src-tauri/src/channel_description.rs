@@ -0,0 +1,160 @@
+use chrono::NaiveDate;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::events::capture_time;
+use crate::history::History;
+use crate::pipeline::{self, MasterOrder};
+use crate::schema_cache::SchemaCache;
+use crate::{checksums, hothash, originals, settings};
+
+// ===== Channel Descriptions =====
+//
+// A channel created for an import gets a description saying what went into
+// it, so it still means something months later: where the files came from,
+// the memory card, the dates of the photos and how many there were. The
+// text comes from `channel_description_template` (settings) with these
+// placeholders:
+//
+//   {source}       folder the files were imported from
+//   {card}         label of the card or drive holding it, when removable
+//   {date_range}   "2026-07-14" or "2026-07-14 – 2026-07-18"
+//   {first_date}   {last_date}
+//   {photos}       photos, i.e. companion groups
+//   {files}        files, companions included
+//   {imported_at}  today
+//
+// A line whose placeholders all come out empty (no card, undated photos) is
+// left out. An empty template turns the description off.
+//
+// Capture dates come from core's schemas where the schema cache has them -
+// prefetching and event clustering put them there - and from the file's
+// modification time otherwise, which for camera files on a card is the
+// capture time as well.
+
+const PLACEHOLDERS: &[&str] = &["source", "card", "date_range", "first_date", "last_date", "photos", "files", "imported_at"];
+
+pub const DEFAULT_TEMPLATE: &str = "Importert fra {source}\nMinnekort: {card}\n{date_range}\n{photos} bilder, {files} filer";
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub source: String,
+    pub card: Option<String>,
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+    pub photos: usize,
+    pub files: usize,
+}
+
+impl ImportSummary {
+    fn date_range(&self) -> Option<String> {
+        match (self.first_date, self.last_date) {
+            (Some(first), Some(last)) if first == last => Some(first.to_string()),
+            (Some(first), Some(last)) => Some(format!("{} – {}", first, last)),
+            _ => None,
+        }
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "source" => Some(self.source.clone()).filter(|s| !s.is_empty()),
+            "card" => self.card.clone(),
+            "date_range" => self.date_range(),
+            "first_date" => self.first_date.map(|d| d.to_string()),
+            "last_date" => self.last_date.map(|d| d.to_string()),
+            "photos" => Some(self.photos.to_string()),
+            "files" => Some(self.files.to_string()),
+            "imported_at" => Some(chrono::Local::now().date_naive().to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn capture_date(app: &tauri::AppHandle, path: &str) -> Option<NaiveDate> {
+    let cached = hothash::known_content_hash(&app.state::<History>(), path)
+        .and_then(|hash| app.state::<SchemaCache>().get(&hash))
+        .and_then(|schema| capture_time(schema.taken_at.as_deref()));
+    if let Some(taken) = cached {
+        return Some(taken.date());
+    }
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Local>::from(modified).date_naive())
+}
+
+pub fn summarize(app: &tauri::AppHandle, files: &[String], source_dir: Option<&str>) -> ImportSummary {
+    let source = source_dir.map(PathBuf::from).or_else(|| {
+        let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        checksums::common_parent(&paths)
+    });
+    let groups = pipeline::group_companions(files, &MasterOrder::default());
+    let dates: Vec<NaiveDate> = groups.iter().filter_map(|g| capture_date(app, &g.master_file)).collect();
+    ImportSummary {
+        card: source
+            .as_deref()
+            .and_then(originals::volume_root)
+            .and_then(|root| root.file_name().map(|n| n.to_string_lossy().to_string())),
+        source: source.map(|s| s.display().to_string()).unwrap_or_default(),
+        first_date: dates.iter().min().copied(),
+        last_date: dates.iter().max().copied(),
+        photos: groups.len(),
+        files: files.len(),
+    }
+}
+
+// Fill in `template`, leaving out lines with nothing to say
+pub fn render(template: &str, summary: &ImportSummary) -> String {
+    let mut lines = Vec::new();
+    for line in template.lines() {
+        let (mut text, mut rest) = (String::new(), line);
+        let (mut placeholders, mut filled) = (0, 0);
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else { break };
+            let name = &rest[start + 1..start + len];
+            text.push_str(&rest[..start]);
+            if PLACEHOLDERS.contains(&name) {
+                placeholders += 1;
+                if let Some(value) = summary.value(name) {
+                    filled += 1;
+                    text.push_str(&value);
+                }
+            } else {
+                // Not ours; stays as written
+                text.push_str(&rest[start..=start + len]);
+            }
+            rest = &rest[start + len + 1..];
+        }
+        text.push_str(rest);
+        if placeholders == 0 || filled > 0 {
+            lines.push(text.trim_end().to_string());
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+// Description for a channel importing `files`, from the configured template;
+// None when the template is empty
+pub fn for_files(app: &tauri::AppHandle, files: &[String]) -> Option<String> {
+    let template = settings::load(app).channel_description_template;
+    if template.trim().is_empty() || files.is_empty() {
+        return None;
+    }
+    Some(render(&template, &summarize(app, files, None))).filter(|d| !d.is_empty())
+}
+
+// The description a channel for `files` would get, for the UI to show and
+// edit before creating it. `template` overrides the configured one.
+#[tauri::command]
+pub fn suggest_channel_description(
+    app: tauri::AppHandle,
+    files: Vec<String>,
+    source_dir: Option<String>,
+    template: Option<String>,
+) -> Result<String, ImalinkError> {
+    if files.is_empty() {
+        return Err(ImalinkError::invalid("No files to describe"));
+    }
+    let template = template.unwrap_or_else(|| settings::load(&app).channel_description_template);
+    Ok(render(&template, &summarize(&app, &files, source_dir.as_deref())))
+}
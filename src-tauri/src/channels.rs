@@ -0,0 +1,180 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::existence::ExistenceCache;
+use crate::guest::AccessMode;
+use crate::history::History;
+use crate::http::{HttpClient, SendPaced};
+use crate::session::Session;
+use crate::InputChannel;
+
+// ===== Channel Deletion =====
+//
+// Deleting an input channel takes its photos with it on the backend, so it
+// is never a single call:
+//
+//   1. preview_channel_deletion fetches the channel and pages through its
+//      photos, returning the image count and the bytes of all their files
+//      together with a confirmation token, e.g. "delete 12 (248 photos)"
+//   2. delete_input_channel takes that token, typed by the user, and checks
+//      it against a fresh count before sending the DELETE
+//
+// The token carries the photo count, so a script can't delete a channel
+// without having looked at it first, and a token from an earlier preview
+// stops matching once photos are added to or removed from the channel.
+//
+// The channel's photos are dropped from local history as well, so a later
+// import of the same files doesn't take them for already imported.
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChannelDeletionSummary {
+    pub input_channel_id: i32,
+    pub title: Option<String>,
+    pub images_count: usize,
+    // Files of those photos (masters and companions) and their total size
+    pub files: usize,
+    pub bytes: i64,
+    // To be typed by the user and passed to delete_input_channel
+    pub confirmation: String,
+    // Of the channel's photos, dropped from the existence cache on deletion
+    #[serde(skip)]
+    pub hothashes: Vec<String>,
+}
+
+fn confirmation_token(input_channel_id: i32, images_count: usize) -> String {
+    format!("delete {} ({} photos)", input_channel_id, images_count)
+}
+
+pub async fn fetch_channel(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    input_channel_id: i32,
+) -> Result<InputChannel, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/input-channels/{}/", backend_url, input_channel_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::NOT_FOUND {
+        return Err(ImalinkError::ChannelForbidden { channel_id: input_channel_id });
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let response_text = response.text().await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))
+}
+
+// Count the channel's photos and add up their file sizes
+async fn summarize(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    input_channel_id: i32,
+) -> Result<ChannelDeletionSummary, ImalinkError> {
+    let channel = fetch_channel(client, backend_url, auth_token, input_channel_id).await?;
+    let (mut images_count, mut files, mut bytes) = (0, 0, 0);
+    let mut hothashes = Vec::new();
+    loop {
+        let response = client
+            .get(format!("{}/api/v1/photos/", backend_url))
+            .header("Authorization", format!("Bearer {}", auth_token))
+            .query(&[
+                ("input_channel_id", input_channel_id.to_string()),
+                ("offset", images_count.to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+            ])
+            .send_paced()
+            .await
+            .map_err(|e| ImalinkError::network(backend_url, e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ImalinkError::from_backend(status, error_text));
+        }
+
+        let page: Value = response.json().await?;
+        let data = page["data"].as_array().cloned().unwrap_or_default();
+        for photo in &data {
+            hothashes.extend(photo["hothash"].as_str().map(str::to_string));
+            for file in photo["image_file_list"].as_array().into_iter().flatten() {
+                files += 1;
+                bytes += file["file_size"].as_i64().unwrap_or_default();
+            }
+        }
+        images_count += data.len();
+        if data.len() < PAGE_SIZE {
+            break;
+        }
+    }
+
+    Ok(ChannelDeletionSummary {
+        input_channel_id,
+        title: channel.title,
+        images_count,
+        files,
+        bytes,
+        confirmation: confirmation_token(input_channel_id, images_count),
+        hothashes,
+    })
+}
+
+// What deleting the channel would remove, and the token that confirms it
+#[tauri::command]
+pub async fn preview_channel_deletion(
+    session: tauri::State<'_, Session>,
+    http: tauri::State<'_, HttpClient>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    input_channel_id: i32,
+) -> Result<ChannelDeletionSummary, ImalinkError> {
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    summarize(&http.client(), &backend_url, &auth_token, input_channel_id).await
+}
+
+// Delete the channel and its photos. `confirmation` must equal the token of
+// a preview taken with the channel as it is now.
+#[tauri::command]
+pub async fn delete_input_channel(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    history: tauri::State<'_, History>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    input_channel_id: i32,
+    confirmation: String,
+) -> Result<ChannelDeletionSummary, ImalinkError> {
+    access.require_owner("delete input channels")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = crate::http::client(&app);
+    let summary = summarize(&client, &backend_url, &auth_token, input_channel_id).await?;
+    if confirmation.trim() != summary.confirmation {
+        return Err(ImalinkError::ConfirmationMismatch { action: format!("delete channel {}", input_channel_id) });
+    }
+
+    let response = client
+        .delete(format!("{}/api/v1/input-channels/{}/", backend_url, input_channel_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(&backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    app.state::<ExistenceCache>().forget(&summary.hothashes);
+    history.with(|conn| {
+        conn.execute("DELETE FROM photos WHERE input_channel_id = ?1", [input_channel_id]).map(|_| ())
+    })?;
+    Ok(summary)
+}
@@ -0,0 +1,179 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::pipeline::ImportSessions;
+use crate::streaming;
+
+// ===== Checksum Manifests =====
+//
+// write_checksum_manifest hashes the originals of a storage location (every
+// file under a folder) or of an import session with BLAKE3 and writes them to
+// a `.b3sums` file in the format of the b3sum tool:
+//
+//   <64 hex digits>  <path relative to the manifest>
+//
+// so an archive can be checked years later without the app, with
+// `b3sum --check imalink.b3sums` from the manifest's folder. Paths use `/`
+// separators; files outside the manifest's folder are listed with their
+// absolute path. Names containing a backslash or newline are escaped the way
+// b3sum does it (leading `\` on the line).
+
+const DEFAULT_MANIFEST_NAME: &str = "imalink.b3sums";
+const MANIFEST_EXTENSION: &str = "b3sums";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChecksumEntry {
+    pub file: String,
+    pub bytes: u64,
+    pub blake3: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChecksumManifest {
+    pub path: String,
+    pub bytes: u64,
+    pub files: BatchResult<ChecksumEntry>,
+}
+
+// Every file under `dir`, leaving out earlier manifests
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ImalinkError> {
+    let entries = fs::read_dir(dir).map_err(|e| ImalinkError::io(dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| ImalinkError::io(dir.display(), e))?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() && path.extension().is_none_or(|ext| ext != MANIFEST_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Deepest folder containing all of `files`
+pub fn common_parent(files: &[PathBuf]) -> Option<PathBuf> {
+    let mut parent = files.first()?.parent()?.to_path_buf();
+    for file in &files[1..] {
+        while !file.starts_with(&parent) {
+            parent = parent.parent()?.to_path_buf();
+        }
+    }
+    Some(parent)
+}
+
+fn manifest_line(blake3: &str, file: &Path, base: &Path) -> String {
+    let name = file
+        .strip_prefix(base)
+        .map(|relative| {
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_else(|_| file.to_string_lossy().to_string());
+    if name.contains(['\\', '\n', '\r']) {
+        let escaped = name.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+        format!("\\{}  {}\n", blake3, escaped)
+    } else {
+        format!("{}  {}\n", blake3, name)
+    }
+}
+
+fn hash_entry(file: &Path) -> Result<ChecksumEntry, ImalinkError> {
+    let file_str = file.to_string_lossy().to_string();
+    let bytes = fs::metadata(file).map_err(|e| ImalinkError::io(file.display(), e))?.len();
+    Ok(ChecksumEntry {
+        blake3: streaming::hash_file(&file_str)?,
+        file: file_str,
+        bytes,
+    })
+}
+
+fn write_manifest(files: Vec<PathBuf>, output: &Path) -> Result<ChecksumManifest, ImalinkError> {
+    let base = output.parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut result = BatchResult::new();
+    let mut contents = String::new();
+    for file in &files {
+        let entry = hash_entry(file);
+        if let Ok(entry) = &entry {
+            contents.push_str(&manifest_line(&entry.blake3, file, &base));
+        }
+        result.record(file.to_string_lossy(), entry);
+    }
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+    }
+    fs::write(output, contents).map_err(|e| ImalinkError::io(output.display(), e))?;
+
+    Ok(ChecksumManifest {
+        path: output.to_string_lossy().to_string(),
+        bytes: result.succeeded.iter().map(|e: &ChecksumEntry| e.bytes).sum(),
+        files: result,
+    })
+}
+
+// Stored files of a finished import session
+fn session_files(app: &tauri::AppHandle, session_id: &str) -> Result<Vec<PathBuf>, ImalinkError> {
+    let session = app
+        .state::<ImportSessions>()
+        .get(session_id)
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown import session: {}", session_id)))?;
+    let mut files: Vec<PathBuf> = session
+        .result
+        .succeeded
+        .iter()
+        .flat_map(|photo| photo.stored_files.iter().map(PathBuf::from))
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+// Write a BLAKE3 manifest for the files under `location`, or for the files
+// stored by import session `session_id`. `output` defaults to imalink.b3sums
+// in the location, or in the folder holding all of the session's files.
+#[tauri::command]
+pub async fn write_checksum_manifest(
+    app: tauri::AppHandle,
+    location: Option<String>,
+    session_id: Option<String>,
+    output: Option<String>,
+) -> Result<ChecksumManifest, ImalinkError> {
+    guest::require_owner(&app, "write checksum manifests")?;
+    let (files, default_dir) = match (location, session_id) {
+        (Some(location), None) => {
+            let dir = PathBuf::from(location);
+            let scan_dir = dir.clone();
+            let files = tauri::async_runtime::spawn_blocking(move || {
+                let mut files = Vec::new();
+                collect_files(&scan_dir, &mut files)?;
+                files.sort();
+                Ok::<_, ImalinkError>(files)
+            })
+            .await
+            .map_err(|e| ImalinkError::internal(e.to_string()))??;
+            (files, dir)
+        }
+        (None, Some(session_id)) => {
+            let files = session_files(&app, &session_id)?;
+            let dir = common_parent(&files);
+            (files, dir.unwrap_or_default())
+        }
+        _ => return Err(ImalinkError::invalid("Give either a storage location or an import session")),
+    };
+    if files.is_empty() {
+        return Err(ImalinkError::invalid("No files to write checksums for"));
+    }
+
+    let output = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_dir.join(DEFAULT_MANIFEST_NAME));
+    tauri::async_runtime::spawn_blocking(move || write_manifest(files, &output))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
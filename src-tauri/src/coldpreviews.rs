@@ -0,0 +1,163 @@
+use rusqlite::params;
+use serde::Deserialize;
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+use crate::history::History;
+use crate::http::SendPaced;
+use crate::session::Session;
+use crate::PhotoCreateSchema;
+
+// ===== Coldpreviews on Demand =====
+//
+// Most frames of a large archive import are never opened at full preview
+// size, yet each upload carries an 800px coldpreview. With
+// ImportOptions::omit_coldpreviews the pipeline strips it before upload and
+// notes the photo in the `pending_coldpreviews` table of local history.
+//
+// backfill_coldpreviews later generates the coldpreview for the photos that
+// are actually viewed - the frontend passes their hothashes - or for the
+// oldest pending ones: the original goes through imalink-core again, and the
+// coldpreview it returns is PATCHed onto the backend photo. A file that no
+// longer produces the same hothash was changed since the import and is left
+// alone.
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackfillSelection {
+    // Photos being viewed
+    Hothashes { hothashes: Vec<String> },
+    // Any pending photos, oldest first
+    Pending { limit: Option<usize> },
+}
+
+struct PendingColdpreview {
+    hothash: String,
+    photo_id: i32,
+    file_path: String,
+}
+
+// Leave the coldpreview out of the upload
+pub fn omit(schema: &mut PhotoCreateSchema) {
+    schema.coldpreview_base64 = None;
+    schema.coldpreview_width = None;
+    schema.coldpreview_height = None;
+}
+
+// Note an uploaded photo that has no coldpreview on the backend yet
+pub fn mark_pending(app: &tauri::AppHandle, hothash: &str, photo_id: i32, file_path: &str) {
+    let recorded = app.state::<History>().with(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO pending_coldpreviews (hothash, photo_id, file_path, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![hothash, photo_id, file_path, chrono::Utc::now().to_rfc3339()],
+        )
+        .map(|_| ())
+    });
+    if let Err(e) = recorded {
+        eprintln!("Failed to note missing coldpreview of {}: {}", hothash, e);
+    }
+}
+
+fn pending(history: &History, selection: &BackfillSelection) -> Result<Vec<PendingColdpreview>, ImalinkError> {
+    let row = |row: &rusqlite::Row| {
+        Ok(PendingColdpreview {
+            hothash: row.get(0)?,
+            photo_id: row.get(1)?,
+            file_path: row.get(2)?,
+        })
+    };
+    history.with(|conn| match selection {
+        BackfillSelection::Hothashes { hothashes } => {
+            let mut stmt =
+                conn.prepare("SELECT hothash, photo_id, file_path FROM pending_coldpreviews WHERE hothash = ?1")?;
+            let mut found = Vec::new();
+            for hothash in hothashes {
+                found.extend(stmt.query_map([hothash], row)?.collect::<rusqlite::Result<Vec<_>>>()?);
+            }
+            Ok(found)
+        }
+        BackfillSelection::Pending { limit } => {
+            let mut stmt = conn.prepare(
+                "SELECT hothash, photo_id, file_path FROM pending_coldpreviews ORDER BY recorded_at LIMIT ?1",
+            )?;
+            let limit = limit.map(|l| l as i64).unwrap_or(-1);
+            let rows = stmt.query_map([limit], row)?;
+            rows.collect()
+        }
+    })
+}
+
+async fn backfill_one(
+    client: &reqwest::Client,
+    core_api_url: &str,
+    backend_url: &str,
+    auth_token: &str,
+    photo: &PendingColdpreview,
+) -> Result<(), ImalinkError> {
+    let schema = crate::process_file(client, &photo.file_path, core_api_url).await?;
+    if schema.hothash != photo.hothash {
+        return Err(ImalinkError::invalid(format!("{} has changed since it was imported", photo.file_path)));
+    }
+    let Some(coldpreview) = schema.coldpreview_base64 else {
+        return Err(ImalinkError::Core { status: 200, detail: "No coldpreview in the response".to_string() });
+    };
+
+    let response = client
+        .patch(format!("{}/api/v1/photos/{}", backend_url, photo.photo_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({
+            "coldpreview_base64": coldpreview,
+            "coldpreview_width": schema.coldpreview_width,
+            "coldpreview_height": schema.coldpreview_height,
+        }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(())
+}
+
+// Generate and upload the coldpreviews that imports left out, per photo.
+// Selected photos that have one already are skipped.
+#[tauri::command]
+pub async fn backfill_coldpreviews(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    history: tauri::State<'_, History>,
+    selection: BackfillSelection,
+    core_api_url: String,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<BatchResult<String>, ImalinkError> {
+    access.require_owner("upload")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let pending = pending(&history, &selection)?;
+    let mut result = BatchResult::new();
+    if let BackfillSelection::Hothashes { hothashes } = &selection {
+        for hothash in hothashes.iter().filter(|h| !pending.iter().any(|p| &p.hothash == *h)) {
+            result.skip(hothash.clone(), "Coldpreview already uploaded");
+        }
+    }
+
+    let client = crate::http::client(&app);
+    for photo in pending {
+        match backfill_one(&client, &core_api_url, &backend_url, &auth_token, &photo).await {
+            Ok(()) => {
+                history.with(|conn| {
+                    conn.execute("DELETE FROM pending_coldpreviews WHERE hothash = ?1", [&photo.hothash])
+                        .map(|_| ())
+                })?;
+                result.succeed(photo.hothash);
+            }
+            Err(e) => result.fail(photo.hothash, e),
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,205 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::duplicates;
+use crate::error::ImalinkError;
+use crate::pipeline;
+use crate::prefetch;
+use crate::preview_store::{PreviewKind, PreviewStore};
+use crate::PhotoCreateSchema;
+
+// ===== Photo Comparison =====
+//
+// compare_photos puts two local files side by side for culling - typically
+// near duplicates or neighbouring burst frames. Both are processed through
+// the schema cache, so comparing photos that were already prefetched costs
+// nothing. The result has, per photo, the previews' display size scaled to a
+// common height (so the images line up in a two-column view; load them via
+// imalink-preview:// by hothash) and a sharpness score, plus one row per
+// exposure field with both values and whether they differ.
+//
+// Sharpness is the variance of the Laplacian of the coldpreview (hotpreview
+// when there is none) scaled to a fixed width, so the scores of two photos
+// compare even when their previews differ in size. Higher is sharper; the
+// absolute value means little on its own.
+
+// Width both previews are scaled to before scoring sharpness
+const SHARPNESS_WIDTH: u32 = 512;
+
+// Rows of the comparison, with the EXIF tags each may be stored under
+const COMPARED_FIELDS: &[(&str, &[&str])] = &[
+    ("exposure_time", &["ExposureTime"]),
+    ("f_number", &["FNumber"]),
+    ("iso", &["ISOSpeedRatings", "PhotographicSensitivity", "ISO"]),
+    ("exposure_bias", &["ExposureBiasValue", "ExposureCompensation"]),
+    ("focal_length", &["FocalLength"]),
+    ("flash", &["Flash"]),
+    ("lens", &["LensModel"]),
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ComparedPhoto {
+    pub file_path: String,
+    pub hothash: String,
+    pub width: i32,
+    pub height: i32,
+    pub taken_at: Option<String>,
+    pub camera: Option<String>,
+    // Whether a coldpreview is available; otherwise show the hotpreview
+    pub has_coldpreview: bool,
+    // Preview size scaled to the common display height
+    pub display_width: u32,
+    pub display_height: u32,
+    pub sharpness: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FieldComparison {
+    pub field: String,
+    pub a: Value,
+    pub b: Value,
+    pub differs: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PhotoComparison {
+    pub a: ComparedPhoto,
+    pub b: ComparedPhoto,
+    pub fields: Vec<FieldComparison>,
+    // dHash distance of the hotpreviews (0 = visually identical)
+    pub phash_distance: Option<u32>,
+    // Which photo scores sharper, when both could be scored
+    pub sharper: Option<String>,
+}
+
+// Variance of the 4-neighbour Laplacian over the greyscale image
+fn sharpness(image_bytes: &[u8]) -> Option<f64> {
+    let img = image::load_from_memory(image_bytes).ok()?;
+    let height = (img.height() as u64 * SHARPNESS_WIDTH as u64 / img.width().max(1) as u64).max(3) as u32;
+    let grey = img
+        .resize_exact(SHARPNESS_WIDTH, height, image::imageops::FilterType::Triangle)
+        .to_luma8();
+
+    let pixel = |x: u32, y: u32| grey.get_pixel(x, y)[0] as f64;
+    let (mut sum, mut sum_sq, mut n) = (0.0, 0.0, 0.0);
+    for y in 1..grey.height() - 1 {
+        for x in 1..grey.width() - 1 {
+            let laplacian = pixel(x - 1, y) + pixel(x + 1, y) + pixel(x, y - 1) + pixel(x, y + 1) - 4.0 * pixel(x, y);
+            sum += laplacian;
+            sum_sq += laplacian * laplacian;
+            n += 1.0;
+        }
+    }
+    let mean = sum / n;
+    Some(sum_sq / n - mean * mean)
+}
+
+fn exif_value(exif_dict: &Value, tags: &[&str]) -> Value {
+    tags.iter()
+        .find_map(|tag| exif_dict.get(*tag).filter(|v| !v.is_null()))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+// Preview size of the schema: coldpreview when present, else hotpreview
+fn preview_size(schema: &PhotoCreateSchema) -> (u32, u32) {
+    match (schema.coldpreview_width, schema.coldpreview_height) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => (w as u32, h as u32),
+        _ => (schema.hotpreview_width.max(1) as u32, schema.hotpreview_height.max(1) as u32),
+    }
+}
+
+async fn describe(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    core_api_url: &str,
+    file_path: &str,
+) -> Result<(ComparedPhoto, PhotoCreateSchema, Option<u64>), ImalinkError> {
+    let (schema, _) = prefetch::prefetch_one(app, client, core_api_url, file_path).await?;
+    let store = app.state::<PreviewStore>();
+    let cold = store.get(PreviewKind::Cold, &schema.hothash);
+    let hot = store.get(PreviewKind::Hot, &schema.hothash);
+    let phash = hot.as_deref().and_then(|bytes| duplicates::dhash(bytes));
+    let preview = cold.clone().or(hot);
+    let score = match preview {
+        Some(bytes) => tauri::async_runtime::spawn_blocking(move || sharpness(&bytes))
+            .await
+            .map_err(|e| ImalinkError::internal(e.to_string()))?,
+        None => None,
+    };
+
+    let (display_width, display_height) = preview_size(&schema);
+    let photo = ComparedPhoto {
+        file_path: file_path.to_string(),
+        hothash: schema.hothash.clone(),
+        width: schema.width,
+        height: schema.height,
+        taken_at: schema.taken_at.clone(),
+        camera: pipeline::camera_of(&schema.exif_dict),
+        has_coldpreview: cold.is_some(),
+        display_width,
+        display_height,
+        sharpness: score,
+    };
+    Ok((photo, schema, phash))
+}
+
+// Scale both display sizes to the smaller of the two preview heights
+fn align(a: &mut ComparedPhoto, b: &mut ComparedPhoto) {
+    let height = a.display_height.min(b.display_height).max(1);
+    for photo in [a, b] {
+        photo.display_width = (photo.display_width as u64 * height as u64 / photo.display_height as u64) as u32;
+        photo.display_height = height;
+    }
+}
+
+// Compare two local files for culling
+#[tauri::command]
+pub async fn compare_photos(
+    app: tauri::AppHandle,
+    core_api_url: String,
+    a: String,
+    b: String,
+) -> Result<PhotoComparison, ImalinkError> {
+    let client = crate::http::client(&app);
+    let ((mut photo_a, schema_a, phash_a), (mut photo_b, schema_b, phash_b)) = tokio::try_join!(
+        describe(&app, &client, &core_api_url, &a),
+        describe(&app, &client, &core_api_url, &b),
+    )?;
+    align(&mut photo_a, &mut photo_b);
+
+    let mut fields: Vec<FieldComparison> = COMPARED_FIELDS
+        .iter()
+        .map(|(field, tags)| {
+            let (a, b) = (exif_value(&schema_a.exif_dict, tags), exif_value(&schema_b.exif_dict, tags));
+            FieldComparison {
+                field: field.to_string(),
+                differs: a != b,
+                a,
+                b,
+            }
+        })
+        .collect();
+    fields.push(FieldComparison {
+        field: "resolution".to_string(),
+        a: serde_json::json!([schema_a.width, schema_a.height]),
+        b: serde_json::json!([schema_b.width, schema_b.height]),
+        differs: (schema_a.width, schema_a.height) != (schema_b.width, schema_b.height),
+    });
+
+    let phash_distance = phash_a.zip(phash_b).map(|(x, y)| duplicates::distance(x, y));
+    let sharper = match (photo_a.sharpness, photo_b.sharpness) {
+        (Some(x), Some(y)) if x >= y => Some(photo_a.file_path.clone()),
+        (Some(_), Some(_)) => Some(photo_b.file_path.clone()),
+        _ => None,
+    };
+
+    Ok(PhotoComparison {
+        a: photo_a,
+        b: photo_b,
+        fields,
+        phash_distance,
+        sharper,
+    })
+}
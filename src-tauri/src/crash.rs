@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+use crate::http::{HttpClient, SendPaced};
+
+// ===== Crash Reporting =====
+//
+// Panics and fatal imalink-core exits are written as JSON reports to
+// <app data>/crash_reports, but only when the user has opted in via
+// `crash_reporting_enabled`. Nothing leaves the machine until the user
+// explicitly calls `submit_crash_report` for a specific report.
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REPORTS_DIR: OnceLock<PathBuf> = OnceLock::new();
+static CORE_VERSION: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    Panic,
+    CoreExit,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    pub created_at: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    pub exit_code: Option<i32>,
+    pub app_version: String,
+    pub core_version: Option<String>,
+    pub os: String,
+    pub arch: String,
+    #[serde(default)]
+    pub submitted: bool,
+}
+
+impl CrashReport {
+    fn new(kind: CrashKind, message: String) -> Self {
+        let now = chrono::Utc::now();
+        let prefix = match kind {
+            CrashKind::Panic => "panic",
+            CrashKind::CoreExit => "core",
+        };
+        CrashReport {
+            id: format!("{}-{}", prefix, now.format("%Y%m%dT%H%M%S%3f")),
+            kind,
+            created_at: now.to_rfc3339(),
+            message,
+            location: None,
+            thread: None,
+            backtrace: None,
+            exit_code: None,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            core_version: CORE_VERSION.lock().ok().and_then(|v| v.clone()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            submitted: false,
+        }
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+// Remember the core version reported by /health so reports can include it
+pub fn set_core_version(version: String) {
+    if let Ok(mut current) = CORE_VERSION.lock() {
+        *current = Some(version);
+    }
+}
+
+// Install the panic hook. Must be called once during app setup.
+pub fn init(app: &tauri::AppHandle, enabled: bool) {
+    set_enabled(enabled);
+
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = REPORTS_DIR.set(dir.join("crash_reports"));
+    }
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::SeqCst) {
+            let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = info.payload().downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Unknown panic payload".to_string()
+            };
+
+            let mut report = CrashReport::new(CrashKind::Panic, message);
+            report.location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            report.thread = std::thread::current().name().map(|n| n.to_string());
+            report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+
+            if let Err(e) = write_report(&report) {
+                eprintln!("Failed to write crash report: {}", e);
+            }
+        }
+        previous_hook(info);
+    }));
+}
+
+// Record a fatal (non-zero) exit of the imalink-core sidecar
+pub fn record_core_exit(code: Option<i32>, last_stderr: Vec<String>) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let mut report = CrashReport::new(
+        CrashKind::CoreExit,
+        format!("imalink-core terminated with code {:?}", code),
+    );
+    report.exit_code = code;
+    if !last_stderr.is_empty() {
+        report.backtrace = Some(last_stderr.join("\n"));
+    }
+
+    if let Err(e) = write_report(&report) {
+        eprintln!("Failed to write crash report: {}", e);
+    }
+}
+
+fn reports_dir() -> Result<&'static PathBuf, ImalinkError> {
+    REPORTS_DIR.get().ok_or_else(|| ImalinkError::internal("Crash reporting is not initialized"))
+}
+
+fn report_path(id: &str) -> Result<PathBuf, ImalinkError> {
+    // Report ids are generated by us; reject anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ImalinkError::invalid(format!("Invalid crash report id: {}", id)));
+    }
+    Ok(reports_dir()?.join(format!("{}.json", id)))
+}
+
+fn write_report(report: &CrashReport) -> Result<(), ImalinkError> {
+    let dir = reports_dir()?;
+    fs::create_dir_all(dir)
+        .map_err(|e| ImalinkError::io(dir.display(), e))?;
+    let path = report_path(&report.id)?;
+    let text = serde_json::to_string_pretty(report)?;
+    fs::write(&path, text)
+        .map_err(|e| ImalinkError::io(path.display(), e))
+}
+
+fn read_report(id: &str) -> Result<CrashReport, ImalinkError> {
+    let path = report_path(id)?;
+    let text = fs::read_to_string(&path)
+        .map_err(|e| ImalinkError::io(path.display(), e))?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[tauri::command]
+pub fn list_crash_reports() -> Result<Vec<CrashReport>, ImalinkError> {
+    let dir = reports_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir)
+        .map_err(|e| ImalinkError::io(dir.display(), e))?;
+
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|text| serde_json::from_str(&text).ok())
+        .collect();
+
+    // Newest first
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(reports)
+}
+
+#[tauri::command]
+pub fn delete_crash_report(
+    access: tauri::State<'_, AccessMode>,
+    report_id: String,
+) -> Result<(), ImalinkError> {
+    access.require_owner("delete crash reports")?;
+    let path = report_path(&report_id)?;
+    fs::remove_file(&path)
+        .map_err(|e| ImalinkError::io(path.display(), e))
+}
+
+// Upload a single report. Calling this is the user's explicit consent for
+// that report; nothing is ever sent automatically.
+#[tauri::command]
+pub async fn submit_crash_report(
+    access: tauri::State<'_, AccessMode>,
+    http: tauri::State<'_, HttpClient>,
+    backend_url: String,
+    report_id: String,
+    auth_token: Option<String>,
+) -> Result<(), ImalinkError> {
+    access.require_owner("upload crash reports")?;
+    let mut report = read_report(&report_id)?;
+
+    let client = http.client();
+    let mut request = client
+        .post(format!("{}/api/v1/crash-reports/", backend_url))
+        .header("Content-Type", "application/json")
+        .json(&report);
+    if let Some(token) = auth_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = request
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(&backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+
+    report.submitted = true;
+    write_report(&report)
+}
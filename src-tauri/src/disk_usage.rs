@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::{guest, settings};
+
+// ===== App Storage Usage =====
+//
+// Everything the app keeps on disk, by area:
+//
+//   schema_cache       <app cache>/schemas             prunable
+//   workspaces         <app cache>/workspaces          see workspace.rs
+//   offline_previews   <app data>/offline_previews     prunable
+//   crash_reports      <app data>/crash_reports        prunable
+//   logs               <app log dir>                   prunable
+//   staging            <app data>/staging              kept - register-mode
+//                                                      imports live there
+//   import_journal     <app data>/import_journal       see recovery.rs
+//   history            <app data>/history.db*
+//
+// get_app_storage_usage reports size and file count per area.
+// prune_caches removes files from the prunable areas by age and then by
+// size, oldest first, using the limits in `cache_limits` (settings) unless
+// the call brings its own. With `prune_caches_on_startup` the same runs in
+// the background every time the app starts.
+
+const MB: u64 = 1024 * 1024;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageArea {
+    SchemaCache,
+    Workspaces,
+    OfflinePreviews,
+    CrashReports,
+    Logs,
+    Staging,
+    ImportJournal,
+    History,
+}
+
+// Limits for one area; None = no limit
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CacheLimit {
+    #[serde(default)]
+    pub max_mb: Option<u64>,
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PrunePolicy {
+    pub schema_cache: CacheLimit,
+    pub offline_previews: CacheLimit,
+    pub crash_reports: CacheLimit,
+    pub logs: CacheLimit,
+}
+
+impl Default for PrunePolicy {
+    fn default() -> Self {
+        PrunePolicy {
+            // Its size limit is schema_cache_max_mb, enforced on every write
+            schema_cache: CacheLimit { max_mb: None, max_age_days: Some(90) },
+            // Downloaded on purpose for offline use, so kept until cleared
+            offline_previews: CacheLimit::default(),
+            crash_reports: CacheLimit { max_mb: Some(50), max_age_days: Some(90) },
+            logs: CacheLimit { max_mb: Some(100), max_age_days: Some(30) },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AreaUsage {
+    pub area: StorageArea,
+    pub path: String,
+    pub bytes: u64,
+    pub files: usize,
+    pub prunable: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StorageUsage {
+    pub areas: Vec<AreaUsage>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AreaPrune {
+    pub area: StorageArea,
+    pub removed: usize,
+    pub bytes: u64,
+}
+
+fn area_paths(app: &tauri::AppHandle) -> Result<Vec<(StorageArea, PathBuf)>, ImalinkError> {
+    let resolve = |e: tauri::Error| ImalinkError::internal(format!("Failed to resolve app directory: {}", e));
+    let cache = app.path().app_cache_dir().map_err(resolve)?;
+    let data = app.path().app_data_dir().map_err(resolve)?;
+    let logs = app.path().app_log_dir().map_err(resolve)?;
+    Ok(vec![
+        (StorageArea::SchemaCache, cache.join("schemas")),
+        (StorageArea::Workspaces, cache.join("workspaces")),
+        (StorageArea::OfflinePreviews, data.join("offline_previews")),
+        (StorageArea::CrashReports, data.join("crash_reports")),
+        (StorageArea::Logs, logs),
+        (StorageArea::Staging, data.join("staging")),
+        (StorageArea::ImportJournal, data.join("import_journal")),
+        (StorageArea::History, data.join("history.db")),
+    ])
+}
+
+// Size and file count of a file or directory tree. For the history
+// database this includes its -wal/-shm files.
+fn usage_of(path: &Path) -> (u64, usize) {
+    let Ok(meta) = fs::metadata(path) else { return (0, 0) };
+    if meta.is_file() {
+        let siblings = ["-wal", "-shm"].iter().filter_map(|suffix| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            fs::metadata(PathBuf::from(name)).ok()
+        });
+        return siblings.fold((meta.len(), 1), |(bytes, files), m| (bytes + m.len(), files + 1));
+    }
+    let Ok(entries) = fs::read_dir(path) else { return (0, 0) };
+    entries.flatten().map(|entry| usage_of(&entry.path())).fold((0, 0), |(b, f), (eb, ef)| (b + eb, f + ef))
+}
+
+fn is_prunable(area: StorageArea) -> bool {
+    matches!(
+        area,
+        StorageArea::SchemaCache | StorageArea::OfflinePreviews | StorageArea::CrashReports | StorageArea::Logs
+    )
+}
+
+fn limit_for(policy: &PrunePolicy, area: StorageArea) -> Option<&CacheLimit> {
+    match area {
+        StorageArea::SchemaCache => Some(&policy.schema_cache),
+        StorageArea::OfflinePreviews => Some(&policy.offline_previews),
+        StorageArea::CrashReports => Some(&policy.crash_reports),
+        StorageArea::Logs => Some(&policy.logs),
+        _ => None,
+    }
+}
+
+// Remove files in `dir` older than the age limit, then the oldest until the
+// rest fits the size limit
+fn prune_dir(area: StorageArea, dir: &Path, limit: &CacheLimit) -> AreaPrune {
+    let mut pruned = AreaPrune { area, removed: 0, bytes: 0 };
+    let Ok(entries) = fs::read_dir(dir) else { return pruned };
+    let mut files: Vec<(PathBuf, u64, SystemTime)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        })
+        .collect();
+    files.sort_by_key(|(_, _, modified)| *modified);
+
+    let cutoff = limit
+        .max_age_days
+        .and_then(|days| SystemTime::now().checked_sub(Duration::from_secs(days.saturating_mul(DAY_SECS))));
+    let max_bytes = limit.max_mb.map(|mb| mb * MB);
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    for (path, size, modified) in files {
+        let too_old = cutoff.is_some_and(|cutoff| modified < cutoff);
+        let too_big = max_bytes.is_some_and(|max| total > max);
+        if !too_old && !too_big {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(size);
+            pruned.removed += 1;
+            pruned.bytes += size;
+        }
+    }
+    pruned
+}
+
+pub fn usage(app: &tauri::AppHandle) -> Result<StorageUsage, ImalinkError> {
+    let areas: Vec<AreaUsage> = area_paths(app)?
+        .into_iter()
+        .map(|(area, path)| {
+            let (bytes, files) = usage_of(&path);
+            AreaUsage {
+                area,
+                path: path.display().to_string(),
+                bytes,
+                files,
+                prunable: is_prunable(area),
+            }
+        })
+        .collect();
+    Ok(StorageUsage { total_bytes: areas.iter().map(|a| a.bytes).sum(), areas })
+}
+
+pub fn prune(app: &tauri::AppHandle, policy: &PrunePolicy) -> Result<Vec<AreaPrune>, ImalinkError> {
+    Ok(area_paths(app)?
+        .into_iter()
+        .filter_map(|(area, path)| Some(prune_dir(area, &path, limit_for(policy, area)?)))
+        .collect())
+}
+
+// Prune with the configured limits in the background, if enabled
+pub fn prune_on_startup(app: &tauri::AppHandle) {
+    let settings = settings::load(app);
+    if !settings.prune_caches_on_startup {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match prune(&app, &settings.cache_limits) {
+        Ok(pruned) => {
+            let (removed, bytes) = pruned.iter().fold((0, 0), |(n, b), p| (n + p.removed, b + p.bytes));
+            if removed > 0 {
+                println!("Pruned {} cached files ({} MB) at startup", removed, bytes / MB);
+            }
+        }
+        Err(e) => eprintln!("Cache pruning at startup failed: {}", e),
+    });
+}
+
+#[tauri::command]
+pub async fn get_app_storage_usage(app: tauri::AppHandle) -> Result<StorageUsage, ImalinkError> {
+    tauri::async_runtime::spawn_blocking(move || usage(&app))
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Storage scan failed: {}", e)))?
+}
+
+// Prune with `policy`, or the configured limits when none is given
+#[tauri::command]
+pub async fn prune_caches(app: tauri::AppHandle, policy: Option<PrunePolicy>) -> Result<Vec<AreaPrune>, ImalinkError> {
+    guest::require_owner(&app, "delete cached files")?;
+    let policy = policy.unwrap_or_else(|| settings::load(&app).cache_limits);
+    tauri::async_runtime::spawn_blocking(move || prune(&app, &policy))
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Cache pruning failed: {}", e)))?
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::ImalinkError;
+use crate::pipeline::{self, CompanionGroup};
+
+// ===== DNG Conversion =====
+//
+// With ImportOptions::convert_to_dng, proprietary RAW files (CR3, NEF, ARW,
+// ...) are converted to DNG during import so the archive doesn't depend on
+// vendor formats. The RAW is kept; the DNG becomes one more companion of the
+// photo and both end up in image_file_list, the DNG with `converted_from`
+// naming its RAW.
+//
+// Conversion runs an external converter configured in settings.json
+// (`dng_converter`), e.g. dnglab or Adobe DNG Converter. Its arguments may
+// use these placeholders:
+//
+//   {input}        the RAW file
+//   {output}       the DNG file to write
+//   {output_dir}   folder of {output}
+//   {output_name}  file name of {output}
+//
+// In copy mode the DNG is written to the session's temporary workspace,
+// archived next to the RAW's destination and then removed from the
+// workspace; in register mode it is written next to the RAW. Groups that already have a DNG are left alone.
+
+const DNG_EXTENSION: &str = "dng";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DngConverter {
+    pub program: String,
+    #[serde(default = "default_args")]
+    pub args: Vec<String>,
+}
+
+fn default_args() -> Vec<String> {
+    ["convert", "{input}", "{output}"].iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for DngConverter {
+    fn default() -> Self {
+        DngConverter {
+            program: "dnglab".to_string(),
+            args: default_args(),
+        }
+    }
+}
+
+fn is_dng(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(DNG_EXTENSION))
+}
+
+pub fn is_proprietary_raw(file: &str) -> bool {
+    let ext = Path::new(file)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    pipeline::master_priority(&ext) == 10 && !is_dng(file)
+}
+
+// Proprietary RAW of the group to convert, unless the group has a DNG already
+pub fn raw_to_convert(group: &CompanionGroup) -> Option<String> {
+    let files = group.all_files();
+    if files.iter().any(|f| is_dng(f)) {
+        return None;
+    }
+    files.into_iter().find(|f| is_proprietary_raw(f))
+}
+
+// Where the DNG of `raw` goes: `workspace` in copy mode, next to the RAW otherwise
+pub fn output_path(raw: &str, workspace: Option<&Path>) -> PathBuf {
+    let raw = Path::new(raw);
+    match workspace {
+        // Unique name so same-named RAWs from different folders don't collide
+        Some(dir) => {
+            let stem = raw.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            dir.join(format!("{}_{}.{}", stem, uuid::Uuid::new_v4().simple(), DNG_EXTENSION))
+        }
+        None => raw.with_extension(DNG_EXTENSION),
+    }
+}
+
+// Run the converter on `raw`, writing `output`
+pub async fn convert(converter: &DngConverter, raw: &str, output: &Path) -> Result<(), ImalinkError> {
+    if output.exists() {
+        return Err(ImalinkError::DestinationExists { path: output.display().to_string() });
+    }
+    let output_str = output.to_string_lossy().to_string();
+    let output_dir = output.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    let output_name = output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let args: Vec<String> = converter
+        .args
+        .iter()
+        .map(|arg| {
+            arg.replace("{input}", raw)
+                .replace("{output_dir}", &output_dir)
+                .replace("{output_name}", &output_name)
+                .replace("{output}", &output_str)
+        })
+        .collect();
+
+    let result = tokio::process::Command::new(&converter.program)
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImalinkError::invalid(format!(
+                "{} not found - install a DNG converter or fix dng_converter in settings",
+                converter.program
+            )),
+            _ => ImalinkError::io(&converter.program, e),
+        })?;
+    if !result.status.success() {
+        return Err(ImalinkError::invalid(format!(
+            "DNG conversion of {} failed: {}",
+            raw,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+    if !output.is_file() {
+        return Err(ImalinkError::invalid(format!(
+            "{} did not write {} - check the dng_converter arguments",
+            converter.program, output_str
+        )));
+    }
+    Ok(())
+}
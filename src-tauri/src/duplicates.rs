@@ -0,0 +1,278 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::PhotoCreateSchema;
+
+// ===== Duplicate Detection =====
+//
+// Exact duplicates are caught by hothash (the backend answers 409), but the
+// same shot saved twice - re-exported, resized, recompressed - gets a new
+// hothash. For those every photo gets a perceptual hash: a 64-bit difference
+// hash (dHash) of its hotpreview, stored in local history next to its
+// resolution. Two photos whose hashes differ in at most `threshold` bits
+// (default 6) are considered near duplicates; identical hashes are exact
+// duplicates.
+//
+// propose_duplicate_actions clusters the history and suggests, per cluster,
+// which photo to keep (highest resolution, then largest file) and what to do
+// with the rest: skip exact copies, stack near ones with the keeper. Imports
+// can apply the same rule up front via ImportOptions::duplicate_policy.
+
+pub const DEFAULT_THRESHOLD: u32 = 6;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    // Upload everything (near duplicates are only reported)
+    #[default]
+    Upload,
+    // Skip photos with a near duplicate already in history
+    Skip,
+    // Skip only when the existing photo has at least the same resolution
+    KeepBest,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    Exact,
+    Near,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestedAction {
+    Keep,
+    Skip,
+    Stack,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ClusterMember {
+    pub hothash: String,
+    pub photo_id: Option<i32>,
+    pub file_path: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub bytes: i64,
+    // Bits differing from the keeper's hash
+    pub distance: u32,
+    pub action: SuggestedAction,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DuplicateCluster {
+    pub kind: DuplicateKind,
+    pub keeper: String,
+    pub members: Vec<ClusterMember>,
+}
+
+// Photo with a perceptual hash, as read from history
+#[derive(Debug, Clone)]
+pub struct HashedPhoto {
+    pub hothash: String,
+    pub photo_id: Option<i32>,
+    pub file_path: Option<String>,
+    pub phash: u64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub bytes: i64,
+}
+
+impl HashedPhoto {
+    fn pixels(&self) -> i64 {
+        self.width.unwrap_or(0) as i64 * self.height.unwrap_or(0) as i64
+    }
+}
+
+// 64-bit dHash: brightness gradients of a 9x8 greyscale thumbnail
+pub fn dhash(image_bytes: &[u8]) -> Option<u64> {
+    let img = image::load_from_memory(image_bytes).ok()?;
+    let small = img.resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Some(hash)
+}
+
+// Hex dHash of the schema's hotpreview
+pub fn phash_of(schema: &PhotoCreateSchema) -> Option<String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&schema.hotpreview_base64)
+        .ok()?;
+    dhash(&bytes).map(|h| format!("{:016x}", h))
+}
+
+pub fn parse_phash(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex, 16).ok()
+}
+
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn hashed_photos(history: &History) -> Result<Vec<HashedPhoto>, ImalinkError> {
+    history.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT hothash, photo_id, file_path, phash, width, height, bytes
+             FROM photos WHERE phash IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let phash: String = row.get(3)?;
+            let photo = HashedPhoto {
+                hothash: row.get(0)?,
+                photo_id: row.get(1)?,
+                file_path: row.get(2)?,
+                phash: 0,
+                width: row.get(4)?,
+                height: row.get(5)?,
+                bytes: row.get(6)?,
+            };
+            // Unparseable hashes are left out
+            Ok(parse_phash(&phash).map(|phash| HashedPhoto { phash, ..photo }))
+        })?;
+        rows.filter_map(|r| r.transpose()).collect()
+    })
+}
+
+// Closest photo in history within `threshold`, other than `hothash` itself
+pub fn nearest(
+    history: &History,
+    hothash: &str,
+    phash: &str,
+    threshold: u32,
+) -> Result<Option<(HashedPhoto, u32)>, ImalinkError> {
+    let Some(phash) = parse_phash(phash) else { return Ok(None) };
+    Ok(hashed_photos(history)?
+        .into_iter()
+        .filter(|p| p.hothash != hothash)
+        .map(|p| {
+            let d = distance(p.phash, phash);
+            (p, d)
+        })
+        .filter(|(_, d)| *d <= threshold)
+        .min_by_key(|(_, d)| *d))
+}
+
+// Reason to skip a new photo under `policy`, given its nearest match
+pub fn skip_reason(
+    policy: DuplicatePolicy,
+    existing: &HashedPhoto,
+    distance: u32,
+    width: i32,
+    height: i32,
+) -> Option<String> {
+    let keep_existing = match policy {
+        DuplicatePolicy::Upload => false,
+        DuplicatePolicy::Skip => true,
+        DuplicatePolicy::KeepBest => existing.pixels() >= width as i64 * height as i64,
+    };
+    keep_existing.then(|| {
+        format!(
+            "Near duplicate of {} ({} bits apart, {}x{})",
+            existing.hothash,
+            distance,
+            existing.width.unwrap_or(0),
+            existing.height.unwrap_or(0)
+        )
+    })
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    // Path compression
+    let mut i = i;
+    while parent[i] != root {
+        let next = parent[i];
+        parent[i] = root;
+        i = next;
+    }
+    root
+}
+
+pub fn cluster(photos: Vec<HashedPhoto>, threshold: u32) -> Vec<DuplicateCluster> {
+    let mut parent: Vec<usize> = (0..photos.len()).collect();
+    for i in 0..photos.len() {
+        for j in (i + 1)..photos.len() {
+            if distance(photos[i].phash, photos[j].phash) <= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..photos.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .filter(|g| g.len() > 1)
+        .map(|mut group| {
+            // Best first: most pixels, then largest file
+            group.sort_by_key(|&i| std::cmp::Reverse((photos[i].pixels(), photos[i].bytes)));
+            let keeper = &photos[group[0]];
+            let members: Vec<ClusterMember> = group
+                .iter()
+                .enumerate()
+                .map(|(rank, &i)| {
+                    let photo = &photos[i];
+                    let distance = distance(photo.phash, keeper.phash);
+                    ClusterMember {
+                        hothash: photo.hothash.clone(),
+                        photo_id: photo.photo_id,
+                        file_path: photo.file_path.clone(),
+                        width: photo.width,
+                        height: photo.height,
+                        bytes: photo.bytes,
+                        distance,
+                        action: match (rank, distance) {
+                            (0, _) => SuggestedAction::Keep,
+                            (_, 0) => SuggestedAction::Skip,
+                            _ => SuggestedAction::Stack,
+                        },
+                    }
+                })
+                .collect();
+            DuplicateCluster {
+                kind: if members.iter().all(|m| m.distance == 0) { DuplicateKind::Exact } else { DuplicateKind::Near },
+                keeper: keeper.hothash.clone(),
+                members,
+            }
+        })
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+    clusters
+}
+
+// Clusters of exact and near duplicates in local history, with suggested actions
+#[tauri::command]
+pub async fn propose_duplicate_actions(
+    app: tauri::AppHandle,
+    threshold: Option<u32>,
+) -> Result<Vec<DuplicateCluster>, ImalinkError> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).min(32);
+    tauri::async_runtime::spawn_blocking(move || {
+        let photos = hashed_photos(&app.state::<History>())?;
+        Ok(cluster(photos, threshold))
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager};
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::http::SendPaced;
+use crate::session::Session;
+use crate::{guest, originals, pipeline, presets, sequences};
+
+// ===== External Editor Round Trip =====
+//
+// open_in_editor launches an editor configured in settings.json (`editors`)
+// on the local original - found by hothash through local history, see
+// originals.rs - or on a path given directly. Arguments may use `{file}`;
+// without it the file is appended. Examples:
+//
+//   { "name": "Photoshop", "program": "open", "args": ["-a", "Adobe Photoshop 2025", "{file}"] }
+//   { "name": "darktable", "program": "darktable", "args": [] }
+//
+// Editors don't say when they are done, so the folder of the original (and
+// the darktable_exported folder darktable writes into) is watched for image
+// files named after the original - IMG_0001.tif, IMG_0001-Edit.psd,
+// IMG_0001_01.jpg - that are written after the editor was started. Once such
+// a file stops changing, an `editor-result` event names it, and
+// import_edited_version uploads it as a new photo in an "edit" stack
+// together with the original. Watching ends after WATCH_DURATION, with
+// stop_editing, or when an edited version has been imported.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const WATCH_DURATION: Duration = Duration::from_secs(4 * 60 * 60);
+// Folder darktable exports into, next to the original
+const DARKTABLE_EXPORT_DIR: &str = "darktable_exported";
+// What editors typically save as
+const EDIT_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "tif", "tiff", "psd", "heic", "dng"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalEditor {
+    pub name: String,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EditSession {
+    pub id: String,
+    pub editor: String,
+    pub original: String,
+    // Set when the original was opened by hothash
+    pub hothash: Option<String>,
+    pub started_at: String,
+    // Saved results found so far, oldest first
+    pub edited_files: Vec<String>,
+    pub watching: bool,
+}
+
+// Managed state: edit sessions by id
+#[derive(Default)]
+pub struct EditSessions(Mutex<HashMap<String, Arc<Mutex<EditSession>>>>);
+
+impl EditSessions {
+    fn get(&self, id: &str) -> Result<Arc<Mutex<EditSession>>, ImalinkError> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ImalinkError::invalid(format!("Unknown edit session: {}", id)))
+    }
+}
+
+fn snapshot(session: &Arc<Mutex<EditSession>>) -> EditSession {
+    session.lock().unwrap().clone()
+}
+
+fn editor_args(editor: &ExternalEditor, file: &str) -> Vec<String> {
+    if editor.args.iter().any(|a| a.contains("{file}")) {
+        editor.args.iter().map(|a| a.replace("{file}", file)).collect()
+    } else {
+        editor.args.iter().cloned().chain(std::iter::once(file.to_string())).collect()
+    }
+}
+
+// Local file for a hothash or an existing path
+fn resolve_target(app: &tauri::AppHandle, target: &str) -> Result<(String, Option<String>), ImalinkError> {
+    if Path::new(target).is_file() {
+        return Ok((target.to_string(), None));
+    }
+    let located = originals::locate(&app.state::<History>(), target)?;
+    match located.available {
+        Some(path) => Ok((path, Some(target.to_string()))),
+        None => {
+            let offline = located.files.iter().find_map(|f| f.volume.as_ref().filter(|_| !f.volume_mounted));
+            Err(match offline {
+                Some(volume) => ImalinkError::invalid(format!("The original is on {}, which is not mounted", volume)),
+                None => ImalinkError::FileNotFound { path: target.to_string() },
+            })
+        }
+    }
+}
+
+// Whether `name` looks like a saved edit of `stem`: the same name, or the
+// name followed by a separator (IMG_0001-Edit, IMG_0001_01, "IMG_0001 copy")
+fn is_edit_of(path: &Path, stem: &str) -> bool {
+    let Some(ext) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else { return false };
+    if !EDIT_EXTENSIONS.contains(&ext.as_str()) {
+        return false;
+    }
+    let name = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let stem = stem.to_lowercase();
+    name == stem || name.strip_prefix(&stem).is_some_and(|rest| rest.starts_with(['-', '_', ' ', '.']))
+}
+
+// Edit candidates written since `since`, with modification time and size
+fn scan_edits(original: &Path, since: SystemTime) -> Vec<(PathBuf, SystemTime, u64)> {
+    let Some(dir) = original.parent() else { return Vec::new() };
+    let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    [dir.to_path_buf(), dir.join(DARKTABLE_EXPORT_DIR)]
+        .iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            let modified = meta.modified().ok().filter(|m| *m >= since)?;
+            Some((entry.path(), modified, meta.len()))
+        })
+        .filter(|(path, _, _)| is_edit_of(path, &stem))
+        .collect()
+}
+
+// Poll for saved edits until the session stops watching or times out
+async fn watch(app: tauri::AppHandle, session: Arc<Mutex<EditSession>>, since: SystemTime) {
+    let original = PathBuf::from(&snapshot(&session).original);
+    let deadline = tokio::time::Instant::now() + WATCH_DURATION;
+    // Last seen (modified, size) per file, and the version already reported
+    let mut seen: HashMap<PathBuf, (SystemTime, u64)> = HashMap::new();
+    let mut reported: HashMap<PathBuf, SystemTime> = HashMap::new();
+
+    while snapshot(&session).watching && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let scan_from = original.clone();
+        let Ok(found) = tauri::async_runtime::spawn_blocking(move || scan_edits(&scan_from, since)).await else {
+            continue;
+        };
+        for (path, modified, size) in found {
+            // Still being written if it changed since the last poll
+            let stable = seen.insert(path.clone(), (modified, size)) == Some((modified, size));
+            if !stable || reported.get(&path) == Some(&modified) {
+                continue;
+            }
+            reported.insert(path.clone(), modified);
+
+            let file = path.to_string_lossy().to_string();
+            let updated = {
+                let mut session = session.lock().unwrap();
+                session.edited_files.retain(|f| f != &file);
+                session.edited_files.push(file);
+                session.clone()
+            };
+            let _ = app.emit("editor-result", &updated);
+        }
+    }
+    session.lock().unwrap().watching = false;
+}
+
+// Open the original of `target` (hothash or path) in the editor named
+// `editor` and start watching for the saved result
+#[tauri::command]
+pub async fn open_in_editor(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, EditSessions>,
+    target: String,
+    editor: String,
+) -> Result<EditSession, ImalinkError> {
+    guest::require_owner(&app, "edit photos")?;
+    let configured = crate::settings::load(&app)
+        .editors
+        .into_iter()
+        .find(|e| e.name == editor)
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown editor: {}", editor)))?;
+    let (original, hothash) = resolve_target(&app, &target)?;
+
+    let since = SystemTime::now();
+    tokio::process::Command::new(&configured.program)
+        .args(editor_args(&configured, &original))
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImalinkError::invalid(format!(
+                "{} not found - check the editor \"{}\" in settings",
+                configured.program, configured.name
+            )),
+            _ => ImalinkError::io(&configured.program, e),
+        })?;
+
+    let session = Arc::new(Mutex::new(EditSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        editor: configured.name,
+        original,
+        hothash,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        edited_files: Vec::new(),
+        watching: true,
+    }));
+    let started = snapshot(&session);
+    sessions.0.lock().unwrap().insert(started.id.clone(), session.clone());
+    tauri::async_runtime::spawn(watch(app.clone(), session, since));
+    Ok(started)
+}
+
+#[tauri::command]
+pub fn get_edit_session(sessions: tauri::State<'_, EditSessions>, id: String) -> Result<EditSession, ImalinkError> {
+    Ok(snapshot(&sessions.get(&id)?))
+}
+
+#[tauri::command]
+pub fn stop_editing(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, EditSessions>,
+    id: String,
+) -> Result<EditSession, ImalinkError> {
+    guest::require_owner(&app, "edit photos")?;
+    let session = sessions.get(&id)?;
+    session.lock().unwrap().watching = false;
+    Ok(snapshot(&session))
+}
+
+// Put the original into a new "edit" stack on the backend and return the
+// stack id
+async fn stack_with_original(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    photo_id: i32,
+    original: &str,
+) -> Result<i32, ImalinkError> {
+    let name = Path::new(original).file_name().map(|n| n.to_string_lossy().to_string());
+    let description = name.map(|n| format!("Edits of {}", n));
+    let stack_id = sequences::create_stack(client, backend_url, auth_token, "edit", description.as_deref()).await?;
+
+    let response = client
+        .patch(format!("{}/api/v1/photos/{}", backend_url, photo_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({ "stack_id": stack_id }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(stack_id)
+}
+
+// Import an edited version (default: the latest found) with `preset` as a
+// new photo, stacked with the original when that is on the backend. Returns
+// the import session id.
+#[tauri::command]
+pub async fn import_edited_version(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, EditSessions>,
+    id: String,
+    file: Option<String>,
+    preset: String,
+    auth_token: Option<String>,
+) -> Result<String, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let session = sessions.get(&id)?;
+    let current = snapshot(&session);
+    let file = file
+        .or_else(|| current.edited_files.last().cloned())
+        .ok_or_else(|| ImalinkError::invalid("No edited version has been saved yet"))?;
+    let preset = presets::find(&app, &preset)?;
+
+    let photo_id = match &current.hothash {
+        Some(hothash) => app.state::<History>().get(hothash)?.and_then(|p| p.photo_id),
+        None => None,
+    };
+    let stack_id = match photo_id {
+        Some(photo_id) => {
+            let client = crate::http::client(&app);
+            match stack_with_original(&client, &preset.backend_url, &auth_token, photo_id, &current.original).await {
+                Ok(stack_id) => Some(stack_id),
+                Err(e) => {
+                    // The edit is still imported, just not stacked
+                    eprintln!("Failed to stack edit of {} with the original: {}", current.original, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let source_dir = Path::new(&file)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let options = pipeline::ImportOptions {
+        stack_id,
+        ..preset.import_options(&source_dir, Some(vec![file]), &auth_token)
+    };
+    let session_id = pipeline::spawn_import(&app, options)?;
+    session.lock().unwrap().watching = false;
+    Ok(session_id)
+}
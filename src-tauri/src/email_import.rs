@@ -0,0 +1,694 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::invocations::{self, CallOptions};
+use crate::session::Session;
+use crate::staging::{self, StagedFile};
+use crate::{pipeline, presets};
+
+// ===== Import from Email =====
+//
+// Clients send photos as email attachments. Saved messages dropped on the
+// window - .eml (MIME, as saved by most mail clients) or .msg (Outlook's
+// compound file format) - have their image attachments extracted into
+// staging (<app data>/staging/email/<id>, see staging.rs) and imported from
+// there. Attachments are found in nested multiparts and forwarded messages
+// as well; anything that isn't a supported image type is left out.
+//
+// Sender, date and subject of the message go into `imported_info.email` of
+// each photo, so a photo can be traced back to the mail it came in.
+// extract_email_attachments only stages, for the frontend to import the
+// files itself; import_from_email imports them with a preset.
+
+const STAGING_SOURCE: &str = "email";
+
+// Largest message read
+const MAX_MESSAGE_BYTES: u64 = 200 * 1024 * 1024;
+
+// Where a staged attachment came from, recorded in imported_info
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EmailSource {
+    // "Name <address>" as in the message
+    pub from: Option<String>,
+    // RFC 3339 when it could be parsed, as written otherwise
+    pub date: Option<String>,
+    pub subject: Option<String>,
+    // File name of the message
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EmailMessage {
+    pub path: String,
+    pub source: EmailSource,
+    // `source` of each is the attachment's name in the message
+    pub attachments: Vec<StagedFile>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EmailExtraction {
+    // Staging folder holding the attachments of all messages
+    pub dir: String,
+    pub messages: BatchResult<EmailMessage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EmailImport {
+    // None when no message had images attached
+    pub session_id: Option<String>,
+    pub messages: BatchResult<EmailMessage>,
+}
+
+struct Attachment {
+    name: String,
+    data: Vec<u8>,
+}
+
+struct Parsed {
+    source: EmailSource,
+    attachments: Vec<Attachment>,
+}
+
+pub fn is_email_file(path: &str) -> bool {
+    let ext = Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase());
+    matches!(ext.as_deref(), Some("eml" | "msg"))
+}
+
+fn is_image_name(name: &str) -> bool {
+    let ext = Path::new(name).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    pipeline::master_priority(&ext) < 99
+}
+
+// ===== MIME (.eml) =====
+
+// Header block and body of a message or part
+fn split_part(raw: &[u8]) -> (String, &[u8]) {
+    let crlf = raw.windows(4).position(|w| w == b"\r\n\r\n").map(|i| (i, i + 4));
+    let lf = raw.windows(2).position(|w| w == b"\n\n").map(|i| (i, i + 2));
+    let split = match (crlf, lf) {
+        (Some(a), Some(b)) => Some(if a.0 < b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    };
+    match split {
+        Some((end, body)) => (String::from_utf8_lossy(&raw[..end]).to_string(), &raw[body..]),
+        None => (String::from_utf8_lossy(raw).to_string(), &[]),
+    }
+}
+
+// Headers with folded lines joined, names lowercased
+fn parse_headers(text: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    headers
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()))
+            .flatten();
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// Bytes in `charset` as text; UTF-8 and Latin-1 cover nearly all mail
+fn decode_charset(charset: &str, bytes: &[u8]) -> String {
+    match charset.to_lowercase().as_str() {
+        "iso-8859-1" | "iso-8859-15" | "latin1" | "windows-1252" | "us-ascii" => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+// A parameter of a header value, e.g. the filename of Content-Disposition.
+// RFC 2231 (`filename*=utf-8''...`, also split over `filename*0*=`, ...) included.
+fn param(value: &str, name: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended: Vec<(u32, bool, String)> = Vec::new();
+    for item in value.split(';').skip(1) {
+        let Some((key, val)) = item.split_once('=') else { continue };
+        let key = key.trim().to_lowercase();
+        let val = val.trim().trim_matches('"').to_string();
+        if key == name {
+            plain = Some(val);
+        } else if let Some(rest) = key.strip_prefix(name).and_then(|r| r.strip_prefix('*')) {
+            let encoded = rest.ends_with('*') || rest.is_empty();
+            let index = rest.trim_end_matches('*').parse().unwrap_or(0);
+            extended.push((index, encoded, val));
+        }
+    }
+    if extended.is_empty() {
+        return plain.map(|p| decode_words(&p));
+    }
+    extended.sort_by_key(|(index, _, _)| *index);
+    let mut charset = "utf-8".to_string();
+    let mut bytes = Vec::new();
+    for (index, encoded, val) in extended {
+        let mut val = val.as_str();
+        if index == 0 && encoded {
+            // charset'language'text
+            let mut parts = val.splitn(3, '\'');
+            if let (Some(cs), Some(_), Some(text)) = (parts.next(), parts.next(), parts.next()) {
+                charset = cs.to_string();
+                val = text;
+            }
+        }
+        if encoded {
+            bytes.extend(percent_decode(val));
+        } else {
+            bytes.extend_from_slice(val.as_bytes());
+        }
+    }
+    Some(decode_charset(&charset, &bytes))
+}
+
+fn decode_quoted_printable(body: &[u8], header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut i = 0;
+    while i < body.len() {
+        match body[i] {
+            b'=' if body[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if body[i + 1..].starts_with(b"\n") => i += 2,
+            // Soft line break at the very end
+            b'=' if i + 1 == body.len() => i += 1,
+            b'=' if i + 2 < body.len() => {
+                match std::str::from_utf8(&body[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn decode_base64(body: &[u8]) -> Option<Vec<u8>> {
+    let clean: Vec<u8> = body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(&clean)
+        .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(clean.trim_ascii_end()))
+        .ok()
+}
+
+// RFC 2047 encoded words (=?utf-8?B?...?=) in a header
+fn decode_words(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut last_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        // =?charset?encoding?data?=; Q-encoded data can start with "=", so
+        // the end is looked for after the encoding
+        let decoded = (|| {
+            let mut parts = rest[start + 2..].splitn(3, '?');
+            let (charset, encoding, tail) = (parts.next()?, parts.next()?, parts.next()?);
+            let len = tail.find("?=")?;
+            let data = &tail[..len];
+            let bytes = match encoding.to_ascii_uppercase().as_str() {
+                "B" => decode_base64(data.as_bytes())?,
+                "Q" => decode_quoted_printable(data.as_bytes(), true),
+                _ => return None,
+            };
+            let end = start + 2 + charset.len() + encoding.len() + 2 + len + 2;
+            Some((decode_charset(charset, &bytes), end))
+        })();
+        let Some((word, end)) = decoded else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            last_was_word = false;
+            continue;
+        };
+        // Whitespace between two encoded words is dropped
+        let between = &rest[..start];
+        if !(last_was_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&word);
+        rest = &rest[end..];
+        last_was_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_body(body: &[u8], encoding: Option<&str>) -> Option<Vec<u8>> {
+    match encoding.map(|e| e.trim().to_lowercase()).as_deref() {
+        Some("base64") => decode_base64(body),
+        Some("quoted-printable") => Some(decode_quoted_printable(body, false)),
+        _ => Some(body.to_vec()),
+    }
+}
+
+// Bodies of the parts of a multipart body
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                // The line break before the delimiter belongs to it
+                let mut end = offset;
+                if body[..end].ends_with(b"\r\n") {
+                    end -= 2;
+                } else if body[..end].ends_with(b"\n") {
+                    end -= 1;
+                }
+                parts.push(&body[start..end.max(start)]);
+            }
+            if trimmed == format!("{}--", delimiter).as_bytes() {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    // Unterminated; keep what there is
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+// Collect image attachments from a part and everything under it
+fn walk_part(raw: &[u8], attachments: &mut Vec<Attachment>, depth: usize) {
+    let (head, body) = split_part(raw);
+    let headers = parse_headers(&head);
+    let content_type = header(&headers, "content-type").unwrap_or("text/plain");
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+
+    if mime.starts_with("multipart/") && depth < 16 {
+        if let Some(boundary) = param(content_type, "boundary") {
+            for part in split_multipart(body, &boundary) {
+                walk_part(part, attachments, depth + 1);
+            }
+        }
+        return;
+    }
+    let encoding = header(&headers, "content-transfer-encoding");
+    if mime == "message/rfc822" && depth < 16 {
+        // Forwarded as attachment
+        if let Some(inner) = decode_body(body, encoding) {
+            walk_part(&inner, attachments, depth + 1);
+        }
+        return;
+    }
+
+    let name = header(&headers, "content-disposition")
+        .and_then(|d| param(d, "filename"))
+        .or_else(|| param(content_type, "name"))
+        .or_else(|| {
+            // Inline images without a name
+            let ext = crate::url_import::extension_for(&mime)?;
+            Some(format!("image.{}", ext))
+        });
+    let Some(name) = name.filter(|n| is_image_name(n)) else { return };
+    if let Some(data) = decode_body(body, encoding).filter(|d| !d.is_empty()) {
+        attachments.push(Attachment { name, data });
+    }
+}
+
+fn parse_eml(raw: &[u8], message: String) -> Parsed {
+    let (head, _) = split_part(raw);
+    let headers = parse_headers(&head);
+    let date = header(&headers, "date").map(|d| {
+        chrono::DateTime::parse_from_rfc2822(d).map(|t| t.to_rfc3339()).unwrap_or_else(|_| d.to_string())
+    });
+    let mut attachments = Vec::new();
+    walk_part(raw, &mut attachments, 0);
+    Parsed {
+        source: EmailSource {
+            from: header(&headers, "from").map(decode_words),
+            date,
+            subject: header(&headers, "subject").map(decode_words),
+            message,
+        },
+        attachments,
+    }
+}
+
+// ===== Outlook (.msg) =====
+//
+// A compound file; each property is a stream named __substg1.0_<id><type>,
+// fixed-size ones (dates) are in the __properties_version1.0 table instead.
+
+const PR_SUBJECT: u16 = 0x0037;
+const PR_CLIENT_SUBMIT_TIME: u16 = 0x0039;
+const PR_MESSAGE_DELIVERY_TIME: u16 = 0x0E06;
+const PR_SENDER_NAME: u16 = 0x0C1A;
+const PR_SENDER_EMAIL_ADDRESS: u16 = 0x0C1F;
+const PR_SENDER_SMTP_ADDRESS: u16 = 0x5D01;
+const PR_ATTACH_DATA: u16 = 0x3701;
+const PR_ATTACH_FILENAME: u16 = 0x3704;
+const PR_ATTACH_LONG_FILENAME: u16 = 0x3707;
+const PR_DISPLAY_NAME: u16 = 0x3001;
+const PT_SYSTIME: u16 = 0x0040;
+// Seconds from 1601 (FILETIME) to 1970
+const FILETIME_EPOCH: i64 = 11_644_473_600;
+
+type Msg = cfb::CompoundFile<fs::File>;
+
+fn msg_stream(msg: &mut Msg, path: &str) -> Option<Vec<u8>> {
+    let mut stream = msg.open_stream(path).ok()?;
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+// String property of the message (storage "") or an attachment
+fn msg_string(msg: &mut Msg, storage: &str, id: u16) -> Option<String> {
+    let text = match msg_stream(msg, &format!("{}/__substg1.0_{:04X}001F", storage, id)) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        None => String::from_utf8_lossy(&msg_stream(msg, &format!("{}/__substg1.0_{:04X}001E", storage, id))?).to_string(),
+    };
+    Some(text.trim_end_matches('\0').trim().to_string()).filter(|t| !t.is_empty())
+}
+
+fn msg_time(msg: &mut Msg, id: u16) -> Option<String> {
+    let table = msg_stream(msg, "/__properties_version1.0")?;
+    // 32 byte header on the message's table, then 16 byte entries
+    table.get(32..)?.chunks_exact(16).find_map(|entry| {
+        let tag = u32::from_le_bytes(entry[0..4].try_into().ok()?);
+        if (tag >> 16) as u16 != id || tag as u16 != PT_SYSTIME {
+            return None;
+        }
+        let filetime = i64::from_le_bytes(entry[8..16].try_into().ok()?);
+        let time = chrono::DateTime::from_timestamp(filetime / 10_000_000 - FILETIME_EPOCH, 0)?;
+        Some(time.to_rfc3339())
+    })
+}
+
+fn parse_msg(path: &Path, message: String) -> Result<Parsed, ImalinkError> {
+    let mut msg = cfb::open(path).map_err(|e| ImalinkError::invalid(format!("Not an Outlook message {}: {}", path.display(), e)))?;
+    let name = msg_string(&mut msg, "", PR_SENDER_NAME);
+    let address = msg_string(&mut msg, "", PR_SENDER_SMTP_ADDRESS)
+        .or_else(|| msg_string(&mut msg, "", PR_SENDER_EMAIL_ADDRESS).filter(|a| a.contains('@')));
+    let from = match (name, address) {
+        (Some(name), Some(address)) if name != address => Some(format!("{} <{}>", name, address)),
+        (name, address) => address.or(name),
+    };
+    let source = EmailSource {
+        from,
+        date: msg_time(&mut msg, PR_CLIENT_SUBMIT_TIME).or_else(|| msg_time(&mut msg, PR_MESSAGE_DELIVERY_TIME)),
+        subject: msg_string(&mut msg, "", PR_SUBJECT),
+        message,
+    };
+
+    let storages: Vec<String> = msg
+        .read_root_storage()
+        .filter(|entry| entry.is_storage() && entry.name().starts_with("__attach_version1.0_"))
+        .map(|entry| format!("/{}", entry.name()))
+        .collect();
+    let mut attachments = Vec::new();
+    for storage in storages {
+        let name = msg_string(&mut msg, &storage, PR_ATTACH_LONG_FILENAME)
+            .or_else(|| msg_string(&mut msg, &storage, PR_ATTACH_FILENAME))
+            .or_else(|| msg_string(&mut msg, &storage, PR_DISPLAY_NAME));
+        let Some(name) = name.filter(|n| is_image_name(n)) else { continue };
+        // Attached messages are a storage rather than a binary stream and are skipped
+        if let Some(data) = msg_stream(&mut msg, &format!("{}/__substg1.0_{:04X}0102", storage, PR_ATTACH_DATA)) {
+            attachments.push(Attachment { name, data });
+        }
+    }
+    Ok(Parsed { source, attachments })
+}
+
+// ===== Extraction =====
+
+// Safe local file name; the prefix keeps attachments of the same name apart
+fn staged_name(name: &str, index: usize) -> String {
+    let name = Path::new(name).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    format!("{:03}_{}", index + 1, safe.trim_start_matches('.'))
+}
+
+// Read the message at `path` and write its image attachments to `dir`
+fn extract(path: &str, dir: &Path, index: &mut usize) -> Result<EmailMessage, ImalinkError> {
+    let file = Path::new(path);
+    let size = fs::metadata(file).map_err(|e| ImalinkError::io(path, e))?.len();
+    if size > MAX_MESSAGE_BYTES {
+        return Err(ImalinkError::invalid(format!("Message too large: {}", path)));
+    }
+    let message = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let is_msg = file.extension().is_some_and(|e| e.eq_ignore_ascii_case("msg"));
+    let parsed = if is_msg {
+        parse_msg(file, message)?
+    } else {
+        parse_eml(&fs::read(file).map_err(|e| ImalinkError::io(path, e))?, message)
+    };
+
+    let mut attachments = Vec::new();
+    for attachment in parsed.attachments {
+        let target = dir.join(staged_name(&attachment.name, *index));
+        *index += 1;
+        fs::write(&target, &attachment.data).map_err(|e| ImalinkError::io(target.display(), e))?;
+        attachments.push(StagedFile {
+            source: attachment.name,
+            path: target.to_string_lossy().to_string(),
+            bytes: attachment.data.len() as u64,
+        });
+    }
+    Ok(EmailMessage { path: path.to_string(), source: parsed.source, attachments })
+}
+
+fn extract_all(paths: &[String], dir: &Path) -> BatchResult<EmailMessage> {
+    let mut messages = BatchResult::new();
+    let mut index = 0;
+    for path in paths {
+        if !is_email_file(path) {
+            messages.skip(path.clone(), "Not an .eml or .msg file");
+            continue;
+        }
+        match extract(path, dir, &mut index) {
+            Ok(message) if message.attachments.is_empty() => messages.skip(path.clone(), "No images attached"),
+            result => messages.record(path.clone(), result),
+        }
+    }
+    messages
+}
+
+async fn stage(app: &tauri::AppHandle, paths: Vec<String>) -> Result<(PathBuf, BatchResult<EmailMessage>), ImalinkError> {
+    let dir = staging::workspace(app, STAGING_SOURCE, &uuid::Uuid::new_v4().to_string())?;
+    let staged = dir.clone();
+    let messages = tauri::async_runtime::spawn_blocking(move || extract_all(&paths, &staged))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))?;
+    if messages.succeeded.is_empty() {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    Ok((dir, messages))
+}
+
+// Where each staged attachment came from, for ImportOptions::source_emails
+fn sources(messages: &BatchResult<EmailMessage>) -> std::collections::HashMap<String, EmailSource> {
+    messages
+        .succeeded
+        .iter()
+        .flat_map(|m| m.attachments.iter().map(move |a| (a.path.clone(), m.source.clone())))
+        .collect()
+}
+
+// Stage the image attachments of dropped messages, for the frontend to import
+#[tauri::command]
+pub async fn extract_email_attachments(app: tauri::AppHandle, paths: Vec<String>) -> Result<EmailExtraction, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let (dir, messages) = stage(&app, paths).await?;
+    Ok(EmailExtraction { dir: dir.to_string_lossy().to_string(), messages })
+}
+
+// Import the image attachments of one or more messages with the given preset
+#[tauri::command]
+pub async fn import_from_email(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    preset: String,
+    auth_token: Option<String>,
+    call: Option<CallOptions>,
+) -> Result<EmailImport, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let preset = presets::find(&app, &preset)?;
+    let (dir, messages) = invocations::run(&app, call, stage(&app, paths)).await?;
+    if messages.succeeded.is_empty() {
+        return Ok(EmailImport { session_id: None, messages });
+    }
+
+    let files: Vec<String> = messages.succeeded.iter().flat_map(|m| m.attachments.iter().map(|a| a.path.clone())).collect();
+    let mut options = preset.import_options(&dir.to_string_lossy(), Some(files), &auth_token);
+    options.source_emails = sources(&messages);
+    let session_id = pipeline::spawn_import(&app, options)?;
+    Ok(EmailImport { session_id: Some(session_id), messages })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn param_plain_and_quoted() {
+        let disposition = r#"attachment; filename="IMG 0001.jpg"; size=1234"#;
+        assert_eq!(param(disposition, "filename").as_deref(), Some("IMG 0001.jpg"));
+        assert_eq!(param(disposition, "size").as_deref(), Some("1234"));
+        assert_eq!(param(disposition, "name"), None);
+        assert_eq!(param("image/jpeg; name==?utf-8?Q?=C3=A6.jpg?=", "name").as_deref(), Some("æ.jpg"));
+    }
+
+    #[test]
+    fn param_rfc2231() {
+        assert_eq!(param("attachment; filename*=utf-8''%C3%A6r%C3%B8.jpg", "filename").as_deref(), Some("ærø.jpg"));
+        assert_eq!(param("attachment; filename*=iso-8859-1'no'%E6.jpg", "filename").as_deref(), Some("æ.jpg"));
+        // Continuations, given out of order and mixing encoded and plain
+        let split = "attachment; filename*1=\"ferie \"; filename*0*=utf-8''Bilde%20fra%20; filename*2=\"2024.jpg\"";
+        assert_eq!(param(split, "filename").as_deref(), Some("Bilde fra ferie 2024.jpg"));
+        // The extended form wins over the plain one
+        let both = "attachment; filename=\"fallback.jpg\"; filename*=utf-8''%C3%A6.jpg";
+        assert_eq!(param(both, "filename").as_deref(), Some("æ.jpg"));
+    }
+
+    #[test]
+    fn encoded_words() {
+        assert_eq!(decode_words("=?utf-8?B?w6ZyZQ==?="), "ære");
+        assert_eq!(decode_words("=?ISO-8859-1?Q?Hei_p=E5_deg?="), "Hei på deg");
+        assert_eq!(decode_words("Re: =?utf-8?Q?Bilder?= fra turen"), "Re: Bilder fra turen");
+        // Whitespace between adjacent encoded words goes
+        assert_eq!(decode_words("=?utf-8?Q?Sommer?= \t =?utf-8?Q?_2024?="), "Sommer 2024");
+        assert_eq!(decode_words("Pris =?x"), "Pris =?x");
+        assert_eq!(decode_words("=?utf-8?X?abc?= ok"), "=?utf-8?X?abc?= ok");
+    }
+
+    #[test]
+    fn quoted_printable() {
+        assert_eq!(decode_quoted_printable(b"Hei=3Dp=C3=A5", false), "Hei=på".as_bytes());
+        assert_eq!(decode_quoted_printable(b"soft=\r\nbreak=\nhere", false), b"softbreakhere");
+        assert_eq!(decode_quoted_printable(b"trailing=", false), b"trailing");
+        assert_eq!(decode_quoted_printable(b"short=4", false), b"short=4");
+        assert_eq!(decode_quoted_printable(b"bad=ZZ", false), b"bad=ZZ");
+        assert_eq!(decode_quoted_printable(b"a_b", true), b"a b");
+        assert_eq!(decode_quoted_printable(b"a_b", false), b"a_b");
+    }
+
+    #[test]
+    fn multipart_crlf_and_lf() {
+        let crlf = b"preamble\r\n--b\r\nfirst\r\n--b\r\nsecond\r\nline\r\n--b--\r\nepilogue\r\n";
+        assert_eq!(split_multipart(crlf, "b"), vec![&b"first"[..], &b"second\r\nline"[..]]);
+        let lf = b"--b\nfirst\n--b\nsecond\n--b--\n";
+        assert_eq!(split_multipart(lf, "b"), vec![&b"first"[..], &b"second"[..]]);
+    }
+
+    #[test]
+    fn multipart_unterminated() {
+        let body = b"--b\nfirst\n--b\nsecond, cut off\n";
+        assert_eq!(split_multipart(body, "b"), vec![&b"first"[..], &b"second, cut off\n"[..]]);
+        assert!(split_multipart(b"no delimiter at all", "b").is_empty());
+    }
+
+    #[test]
+    fn nested_forwarded_message() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 1, 2, 3];
+        let encoded = base64::engine::general_purpose::STANDARD.encode(jpeg);
+        let inner = format!(
+            "From: Kari <kari@example.com>\r\n\
+             Subject: Original\r\n\
+             Content-Type: multipart/mixed; boundary=\"inner\"\r\n\
+             \r\n\
+             --inner\r\n\
+             Content-Type: text/plain\r\n\
+             \r\n\
+             Se vedlegg\r\n\
+             --inner\r\n\
+             Content-Type: image/jpeg\r\n\
+             Content-Disposition: attachment;\r\n\
+             \tfilename*=utf-8''str%C3%A5nd.jpg\r\n\
+             Content-Transfer-Encoding: base64\r\n\
+             \r\n\
+             {}\r\n\
+             --inner--\r\n",
+            encoded
+        );
+        let outer = format!(
+            "From: Ola <ola@example.com>\n\
+             Date: Tue, 1 Oct 2024 12:30:00 +0200\n\
+             Subject: =?utf-8?Q?Fwd:_Bilder_fra_stranden?=\n\
+             Content-Type: multipart/mixed; boundary=outer\n\
+             \n\
+             --outer\n\
+             Content-Type: text/plain\n\
+             \n\
+             Videresendt\n\
+             --outer\n\
+             Content-Type: message/rfc822\n\
+             \n\
+             {}\n\
+             --outer\n\
+             Content-Type: application/pdf\n\
+             Content-Disposition: attachment; filename=kvittering.pdf\n\
+             \n\
+             %PDF\n\
+             --outer--\n",
+            inner
+        );
+
+        let parsed = parse_eml(outer.as_bytes(), "fwd.eml".to_string());
+        assert_eq!(parsed.source.from.as_deref(), Some("Ola <ola@example.com>"));
+        assert_eq!(parsed.source.subject.as_deref(), Some("Fwd: Bilder fra stranden"));
+        assert_eq!(parsed.source.date.as_deref(), Some("2024-10-01T12:30:00+02:00"));
+        assert_eq!(parsed.attachments.len(), 1);
+        assert_eq!(parsed.attachments[0].name, "strånd.jpg");
+        assert_eq!(parsed.attachments[0].data, jpeg);
+    }
+}
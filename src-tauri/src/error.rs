@@ -0,0 +1,347 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ===== Command Errors =====
+//
+// Every command returns `ImalinkError`, serialized to the frontend as
+// `{ code, message, params, retryable }`. `code` is stable and never changes
+// once released; `params` holds the values substituted into the message
+// template so the UI can render the error in the user's language via
+// `get_error_catalog`. `message` is the English rendering, for logs.
+// `retryable` tells whether the same call may succeed when repeated (a
+// network hiccup, a busy server) or needs the user to change something
+// first (log in again, pick another file).
+
+#[derive(Debug, Clone)]
+pub enum ImalinkError {
+    FileNotFound { path: String },
+    NotAFile { path: String },
+    NotADirectory { path: String },
+    DestinationExists { path: String },
+    Io { path: String, detail: String },
+    // Read back, a copy differed from its source; see streaming::copy_verified
+    CopyMismatch { source: String, destination: String },
+    InvalidInput { detail: String },
+    Network { url: String, detail: String },
+    Stalled { url: String, seconds: u64 },
+    Cancelled { id: String },
+    TimedOut { seconds: u64 },
+    // An import item ran over its stage timeout, see watchdog.rs
+    StageStuck { stage: String, file: String, seconds: u64 },
+    // Not enough free space for an import, see low_disk.rs
+    LowDiskSpace { path: String, free_mb: u64, needed_mb: u64 },
+    Unauthorized { detail: String },
+    ReadOnly { action: String },
+    ChannelForbidden { channel_id: i32 },
+    ConfirmationMismatch { action: String },
+    Backend { status: u16, detail: String },
+    // 503 with Retry-After; `until` is RFC 3339, see maintenance.rs
+    Maintenance { until: String },
+    // 429; `until` is RFC 3339, see ratelimit.rs
+    RateLimited { until: String },
+    // Backend echoed other checksums than sent, see integrity.rs
+    ChecksumMismatch { hothash: String, part: String },
+    Core { status: u16, detail: String },
+    Parse { detail: String },
+    Database { detail: String },
+    Internal { detail: String },
+}
+
+impl ImalinkError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ImalinkError::FileNotFound { .. } => "file_not_found",
+            ImalinkError::NotAFile { .. } => "not_a_file",
+            ImalinkError::NotADirectory { .. } => "not_a_directory",
+            ImalinkError::DestinationExists { .. } => "destination_exists",
+            ImalinkError::Io { .. } => "io_error",
+            ImalinkError::CopyMismatch { .. } => "copy_mismatch",
+            ImalinkError::InvalidInput { .. } => "invalid_input",
+            ImalinkError::Network { .. } => "network_error",
+            ImalinkError::Stalled { .. } => "transfer_stalled",
+            ImalinkError::Cancelled { .. } => "cancelled",
+            ImalinkError::TimedOut { .. } => "timed_out",
+            ImalinkError::StageStuck { .. } => "stage_stuck",
+            ImalinkError::LowDiskSpace { .. } => "low_disk_space",
+            ImalinkError::Unauthorized { .. } => "unauthorized",
+            ImalinkError::ReadOnly { .. } => "read_only",
+            ImalinkError::ChannelForbidden { .. } => "channel_forbidden",
+            ImalinkError::ConfirmationMismatch { .. } => "confirmation_mismatch",
+            ImalinkError::Backend { .. } => "backend_error",
+            ImalinkError::Maintenance { .. } => "backend_maintenance",
+            ImalinkError::RateLimited { .. } => "rate_limited",
+            ImalinkError::ChecksumMismatch { .. } => "checksum_mismatch",
+            ImalinkError::Core { .. } => "core_error",
+            ImalinkError::Parse { .. } => "parse_error",
+            ImalinkError::Database { .. } => "database_error",
+            ImalinkError::Internal { .. } => "internal_error",
+        }
+    }
+
+    pub fn params(&self) -> BTreeMap<&'static str, String> {
+        let mut params = BTreeMap::new();
+        match self {
+            ImalinkError::FileNotFound { path }
+            | ImalinkError::NotAFile { path }
+            | ImalinkError::NotADirectory { path }
+            | ImalinkError::DestinationExists { path } => {
+                params.insert("path", path.clone());
+            }
+            ImalinkError::Io { path, detail } => {
+                params.insert("path", path.clone());
+                params.insert("detail", detail.clone());
+            }
+            ImalinkError::CopyMismatch { source, destination } => {
+                params.insert("source", source.clone());
+                params.insert("destination", destination.clone());
+            }
+            ImalinkError::Network { url, detail } => {
+                params.insert("url", url.clone());
+                params.insert("detail", detail.clone());
+            }
+            ImalinkError::Stalled { url, seconds } => {
+                params.insert("url", url.clone());
+                params.insert("seconds", seconds.to_string());
+            }
+            ImalinkError::Cancelled { id } => {
+                params.insert("id", id.clone());
+            }
+            ImalinkError::TimedOut { seconds } => {
+                params.insert("seconds", seconds.to_string());
+            }
+            ImalinkError::LowDiskSpace { path, free_mb, needed_mb } => {
+                params.insert("path", path.clone());
+                params.insert("free_mb", free_mb.to_string());
+                params.insert("needed_mb", needed_mb.to_string());
+            }
+            ImalinkError::StageStuck { stage, file, seconds } => {
+                params.insert("stage", stage.clone());
+                params.insert("file", file.clone());
+                params.insert("seconds", seconds.to_string());
+            }
+            ImalinkError::ReadOnly { action } | ImalinkError::ConfirmationMismatch { action } => {
+                params.insert("action", action.clone());
+            }
+            ImalinkError::ChannelForbidden { channel_id } => {
+                params.insert("channel_id", channel_id.to_string());
+            }
+            ImalinkError::Maintenance { until } | ImalinkError::RateLimited { until } => {
+                let local = chrono::DateTime::parse_from_rfc3339(until)
+                    .map(|u| u.with_timezone(&chrono::Local).format("%H:%M").to_string());
+                params.insert("until", local.unwrap_or_else(|_| until.clone()));
+            }
+            ImalinkError::ChecksumMismatch { hothash, part } => {
+                params.insert("hothash", hothash.clone());
+                params.insert("part", part.clone());
+            }
+            ImalinkError::Backend { status, detail } | ImalinkError::Core { status, detail } => {
+                params.insert("status", status.to_string());
+                params.insert("detail", detail.clone());
+            }
+            ImalinkError::InvalidInput { detail }
+            | ImalinkError::Unauthorized { detail }
+            | ImalinkError::Parse { detail }
+            | ImalinkError::Database { detail }
+            | ImalinkError::Internal { detail } => {
+                params.insert("detail", detail.clone());
+            }
+        }
+        params
+    }
+
+    pub fn retryable(&self) -> bool {
+        match self {
+            ImalinkError::Network { .. }
+            | ImalinkError::Stalled { .. }
+            | ImalinkError::TimedOut { .. }
+            | ImalinkError::Maintenance { .. }
+            | ImalinkError::RateLimited { .. }
+            | ImalinkError::ChecksumMismatch { .. }
+            | ImalinkError::CopyMismatch { .. } => true,
+            // 429 Too Many Requests, or the server/core failing on its side
+            ImalinkError::Backend { status, .. } | ImalinkError::Core { status, .. } => {
+                *status == 429 || *status >= 500
+            }
+            _ => false,
+        }
+    }
+
+    // English message rendered from the catalog
+    pub fn message(&self) -> String {
+        render(message_template("en", self.code()), &self.params())
+    }
+
+    pub fn io(path: impl fmt::Display, e: impl fmt::Display) -> Self {
+        ImalinkError::Io { path: path.to_string(), detail: e.to_string() }
+    }
+
+    pub fn invalid(detail: impl Into<String>) -> Self {
+        ImalinkError::InvalidInput { detail: detail.into() }
+    }
+
+    pub fn network(url: impl Into<String>, e: impl fmt::Display) -> Self {
+        ImalinkError::Network { url: url.into(), detail: e.to_string() }
+    }
+
+    pub fn parse(e: impl fmt::Display) -> Self {
+        ImalinkError::Parse { detail: e.to_string() }
+    }
+
+    pub fn internal(detail: impl Into<String>) -> Self {
+        ImalinkError::Internal { detail: detail.into() }
+    }
+
+    // Map a non-success backend response to an error
+    pub fn from_backend(status: reqwest::StatusCode, body: String) -> Self {
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            ImalinkError::Unauthorized { detail: body }
+        } else {
+            ImalinkError::Backend { status: status.as_u16(), detail: body }
+        }
+    }
+}
+
+impl fmt::Display for ImalinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ImalinkError {}
+
+impl From<reqwest::Error> for ImalinkError {
+    fn from(e: reqwest::Error) -> Self {
+        let url = e.url().map(|u| u.to_string()).unwrap_or_default();
+        ImalinkError::Network { url, detail: e.to_string() }
+    }
+}
+
+impl From<serde_json::Error> for ImalinkError {
+    fn from(e: serde_json::Error) -> Self {
+        ImalinkError::parse(e)
+    }
+}
+
+impl From<rusqlite::Error> for ImalinkError {
+    fn from(e: rusqlite::Error) -> Self {
+        ImalinkError::Database { detail: e.to_string() }
+    }
+}
+
+impl Serialize for ImalinkError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ImalinkError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message())?;
+        state.serialize_field("params", &self.params())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+// ===== Message Catalog =====
+
+const CODES: &[&str] = &[
+    "file_not_found",
+    "not_a_file",
+    "not_a_directory",
+    "destination_exists",
+    "io_error",
+    "copy_mismatch",
+    "invalid_input",
+    "network_error",
+    "transfer_stalled",
+    "cancelled",
+    "timed_out",
+    "stage_stuck",
+    "low_disk_space",
+    "unauthorized",
+    "read_only",
+    "channel_forbidden",
+    "confirmation_mismatch",
+    "backend_error",
+    "backend_maintenance",
+    "rate_limited",
+    "checksum_mismatch",
+    "core_error",
+    "parse_error",
+    "database_error",
+    "internal_error",
+];
+
+fn message_template(locale: &str, code: &str) -> &'static str {
+    match (locale, code) {
+        ("nb", "file_not_found") => "Fant ikke filen: {path}",
+        ("nb", "not_a_file") => "Ikke en fil: {path}",
+        ("nb", "not_a_directory") => "Ikke en katalog: {path}",
+        ("nb", "destination_exists") => "Målfilen finnes allerede: {path}",
+        ("nb", "io_error") => "Filsystemfeil for {path}: {detail}",
+        ("nb", "copy_mismatch") => "Kopien {destination} er ikke identisk med {source} – kopien er slettet",
+        ("nb", "invalid_input") => "Ugyldig verdi: {detail}",
+        ("nb", "network_error") => "Kunne ikke koble til {url}: {detail}",
+        ("nb", "transfer_stalled") => "Overføringen til {url} stoppet opp – ingen fremdrift på {seconds} sekunder",
+        ("nb", "cancelled") => "Avbrutt",
+        ("nb", "timed_out") => "Tidsavbrudd – ikke ferdig etter {seconds} sekunder",
+        ("nb", "low_disk_space") => "For lite ledig plass på {path}: {needed_mb} MB trengs, {free_mb} MB ledig",
+        ("nb", "stage_stuck") => "{file} ble ikke ferdig i steget {stage} på {seconds} sekunder – avbrutt og satt i karantene",
+        ("nb", "unauthorized") => "Ikke autorisert – logg inn på nytt",
+        ("nb", "read_only") => "Ikke tillatt i gjestemodus: {action}",
+        ("nb", "channel_forbidden") => "Du har ikke tilgang til å laste opp til kanal {channel_id}",
+        ("nb", "confirmation_mismatch") => "Bekreftelsen stemmer ikke – kontroller antallet på nytt før du prøver igjen: {action}",
+        ("nb", "backend_error") => "Serveren svarte med feil {status}: {detail}",
+        ("nb", "backend_maintenance") => "Serveren er under vedlikehold – fortsetter kl. {until}",
+        ("nb", "rate_limited") => "Serveren ber om færre forespørsler – prøver igjen kl. {until}",
+        ("nb", "checksum_mismatch") => "Serveren mottok ikke {part} for {hothash} uskadd – kontrollsummen stemmer ikke",
+        ("nb", "core_error") => "imalink-core svarte med feil {status}: {detail}",
+        ("nb", "parse_error") => "Kunne ikke tolke svaret: {detail}",
+        ("nb", "database_error") => "Feil i lokal database: {detail}",
+        ("nb", "internal_error") => "Intern feil: {detail}",
+
+        (_, "file_not_found") => "File not found: {path}",
+        (_, "not_a_file") => "Not a file: {path}",
+        (_, "not_a_directory") => "Path is not a directory: {path}",
+        (_, "destination_exists") => "Destination file already exists: {path}",
+        (_, "io_error") => "File system error for {path}: {detail}",
+        (_, "copy_mismatch") => "Copy {destination} differs from {source} - the copy was removed",
+        (_, "invalid_input") => "Invalid input: {detail}",
+        (_, "network_error") => "Failed to connect to {url}: {detail}",
+        (_, "transfer_stalled") => "Transfer to {url} stalled - no progress for {seconds} seconds",
+        (_, "cancelled") => "Cancelled",
+        (_, "timed_out") => "Timed out - not finished after {seconds} seconds",
+        (_, "low_disk_space") => "Not enough free space on {path}: {needed_mb} MB needed, {free_mb} MB free",
+        (_, "stage_stuck") => "{file} did not finish the {stage} stage in {seconds} seconds - abandoned and quarantined",
+        (_, "unauthorized") => "Not authorized - please log in again",
+        (_, "read_only") => "Not allowed in guest mode: {action}",
+        (_, "channel_forbidden") => "No permission to upload to channel {channel_id}",
+        (_, "confirmation_mismatch") => "Confirmation does not match - review the counts again before retrying: {action}",
+        (_, "backend_error") => "Backend returned error {status}: {detail}",
+        (_, "backend_maintenance") => "Server under maintenance - resuming at {until}",
+        (_, "rate_limited") => "Rate limited by the server - trying again at {until}",
+        (_, "checksum_mismatch") => "The backend did not receive the {part} of {hothash} intact - checksum mismatch",
+        (_, "core_error") => "imalink-core returned error {status}: {detail}",
+        (_, "parse_error") => "Failed to parse response: {detail}",
+        (_, "database_error") => "Local database error: {detail}",
+        (_, _) => "Internal error: {detail}",
+    }
+}
+
+fn render(template: &str, params: &BTreeMap<&'static str, String>) -> String {
+    params.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{}}}", key), value)
+    })
+}
+
+// Message templates for every error code in the requested locale (falls back
+// to English). Placeholders like `{path}` match the keys in `params`.
+#[tauri::command]
+pub fn get_error_catalog(locale: String) -> BTreeMap<String, String> {
+    let locale = match locale.split(['-', '_']).next().unwrap_or("en").to_lowercase().as_str() {
+        "nb" | "nn" | "no" => "nb",
+        _ => "en",
+    };
+    CODES
+        .iter()
+        .map(|code| (code.to_string(), message_template(locale, code).to_string()))
+        .collect()
+}
@@ -0,0 +1,219 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::pipeline::{self, CompanionGroup, ImportOptions, MasterOrder};
+use crate::{prefetch, privacy, PhotoCreateSchema};
+
+// ===== Event Clustering =====
+//
+// Splits a pending import into "events" (a day out, a trip) so a card with
+// three trips doesn't all land in one input channel. Photos are sorted by
+// capture time and a new event starts whenever the gap to the previous photo
+// exceeds `max_gap_hours` or the position jumps more than `max_jump_km` from
+// the last geotagged photo. Photos without a capture time end up in one
+// trailing event of their own.
+//
+// Capture time and position come from core, so clustering processes the
+// masters the same way prefetching does - the import afterwards finds them
+// in the schema cache. import_events then starts one import per event, each
+// with its own input channel (existing or created on the spot) and category.
+
+const DEFAULT_MAX_GAP_HOURS: f64 = 6.0;
+const DEFAULT_MAX_JUMP_KM: f64 = 50.0;
+const CONCURRENCY: usize = 4;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PendingEvent {
+    // Master files and companions, ready to pass back to import_events
+    pub files: Vec<String>,
+    pub photo_count: usize,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    // Mean position of the geotagged photos
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    // "2026-07-14" or "2026-07-14 – 2026-07-18", None for undated photos
+    pub suggested_title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EventAssignment {
+    pub files: Vec<String>,
+    // Existing channel; when absent a channel titled `new_channel_title` is created
+    #[serde(default)]
+    pub input_channel_id: Option<i32>,
+    #[serde(default)]
+    pub new_channel_title: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+struct Located {
+    group: CompanionGroup,
+    taken_at: Option<chrono::NaiveDateTime>,
+    position: Option<(f64, f64)>,
+}
+
+// Capture time from the first 14 digits of taken_at (ISO or EXIF format)
+pub fn capture_time(taken_at: Option<&str>) -> Option<chrono::NaiveDateTime> {
+    let digits: String = taken_at?.chars().filter(|c| c.is_ascii_digit()).take(14).collect();
+    chrono::NaiveDateTime::parse_from_str(&digits, "%Y%m%d%H%M%S").ok()
+}
+
+fn build_event(photos: Vec<Located>) -> PendingEvent {
+    let start = photos.iter().filter_map(|p| p.taken_at).min();
+    let end = photos.iter().filter_map(|p| p.taken_at).max();
+    let positions: Vec<(f64, f64)> = photos.iter().filter_map(|p| p.position).collect();
+    let mean = |f: fn(&(f64, f64)) -> f64| {
+        (!positions.is_empty()).then(|| positions.iter().map(f).sum::<f64>() / positions.len() as f64)
+    };
+    let suggested_title = match (start.map(|s| s.date()), end.map(|e| e.date())) {
+        (Some(first), Some(last)) if first == last => Some(first.to_string()),
+        (Some(first), Some(last)) => Some(format!("{} – {}", first, last)),
+        _ => None,
+    };
+    PendingEvent {
+        photo_count: photos.len(),
+        files: photos.iter().flat_map(|p| p.group.all_files()).collect(),
+        start: start.map(|s| s.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        end: end.map(|e| e.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        latitude: mean(|p| p.0),
+        longitude: mean(|p| p.1),
+        suggested_title,
+    }
+}
+
+fn split_events(photos: Vec<Located>, max_gap_hours: f64, max_jump_km: f64) -> Vec<PendingEvent> {
+    let (mut dated, undated): (Vec<_>, Vec<_>) = photos.into_iter().partition(|p| p.taken_at.is_some());
+    dated.sort_by_key(|p| p.taken_at);
+
+    let max_gap = chrono::Duration::seconds((max_gap_hours * 3600.0) as i64);
+    let mut events = Vec::new();
+    let mut current: Vec<Located> = Vec::new();
+    let mut last_position: Option<(f64, f64)> = None;
+    for photo in dated {
+        let gap = current
+            .last()
+            .and_then(|prev| Some(photo.taken_at? - prev.taken_at?))
+            .is_some_and(|gap| gap > max_gap);
+        let jump = match (last_position, photo.position) {
+            (Some((lat1, lon1)), Some((lat2, lon2))) => {
+                privacy::distance_meters(lat1, lon1, lat2, lon2) > max_jump_km * 1000.0
+            }
+            _ => false,
+        };
+        if gap || jump {
+            events.push(build_event(std::mem::take(&mut current)));
+            last_position = None;
+        }
+        last_position = photo.position.or(last_position);
+        current.push(photo);
+    }
+    if !current.is_empty() {
+        events.push(build_event(current));
+    }
+    if !undated.is_empty() {
+        events.push(build_event(undated));
+    }
+    events
+}
+
+// Group `files` and process each master (through the schema cache). A file
+// core can't read still belongs to the import; it just has no schema.
+pub async fn process_groups(
+    app: &tauri::AppHandle,
+    core_api_url: &str,
+    files: &[String],
+    master_order: &MasterOrder,
+) -> Vec<(CompanionGroup, Option<PhotoCreateSchema>)> {
+    let client = crate::http::client(app);
+    futures_util::stream::iter(pipeline::group_companions(files, master_order))
+        .map(|group| {
+            let client = &client;
+            async move {
+                match prefetch::prefetch_one(app, client, core_api_url, &group.master_file).await {
+                    Ok((schema, _)) => (group, Some(schema)),
+                    Err(e) => {
+                        eprintln!("Could not process {}: {}", group.master_file, e);
+                        (group, None)
+                    }
+                }
+            }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await
+}
+
+// Cluster `files` into events by capture time gaps and location jumps
+#[tauri::command]
+pub async fn cluster_pending_events(
+    app: tauri::AppHandle,
+    core_api_url: String,
+    files: Vec<String>,
+    max_gap_hours: Option<f64>,
+    max_jump_km: Option<f64>,
+    master_order: Option<MasterOrder>,
+) -> Result<Vec<PendingEvent>, ImalinkError> {
+    let located: Vec<Located> = process_groups(&app, &core_api_url, &files, &master_order.unwrap_or_default())
+        .await
+        .into_iter()
+        .map(|(group, schema)| Located {
+            taken_at: schema.as_ref().and_then(|s| capture_time(s.taken_at.as_deref())),
+            position: schema.as_ref().and_then(|s| Some((s.gps_latitude?, s.gps_longitude?))),
+            group,
+        })
+        .collect();
+
+    Ok(split_events(
+        located,
+        max_gap_hours.unwrap_or(DEFAULT_MAX_GAP_HOURS),
+        max_jump_km.unwrap_or(DEFAULT_MAX_JUMP_KM),
+    ))
+}
+
+// Start one import per event. `options` supplies everything but the file
+// selection, channel and category. Returns the session id per event.
+#[tauri::command]
+pub async fn import_events(
+    app: tauri::AppHandle,
+    options: ImportOptions,
+    events: Vec<EventAssignment>,
+) -> Result<BatchResult<String>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let mut result = BatchResult::new();
+    for (i, event) in events.into_iter().enumerate() {
+        let label = event.new_channel_title.clone().unwrap_or_else(|| format!("Event {}", i + 1));
+        let started = async {
+            let input_channel_id = match event.input_channel_id {
+                Some(id) => id,
+                None => {
+                    crate::create_input_channel(
+                        app.clone(),
+                        Some(options.backend_url.clone()),
+                        event.new_channel_title.clone(),
+                        None,
+                        None,
+                        Some(options.auth_token.clone()),
+                        Some(event.files.clone()),
+                    )
+                    .await?
+                    .id
+                }
+            };
+            let event_options = ImportOptions {
+                files: Some(event.files),
+                input_channel_id,
+                category: event.category,
+                ..options.clone()
+            };
+            pipeline::spawn_import(&app, event_options)
+        }
+        .await;
+        result.record(label, started);
+    }
+    Ok(result)
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::hothash;
+
+// ===== Backend Existence Cache =====
+//
+// Answers to "is this hothash on the backend" per backend, in memory and in
+// an append-only file (`<checked_at> <photo_id|-> <hothash> <backend_url>`),
+// compacted at startup. Photos found are trusted for EXISTS_TTL, missing ones
+// for MISSING_TTL. Uploads and the sync change feed record photos; deleting
+// a channel or a 404 on sync forgets them. recovery.rs asks the backend
+// directly.
+
+const CACHE_FILE: &str = "backend_existence.txt";
+const EXISTS_TTL: i64 = 24 * 60 * 60;
+const MISSING_TTL: i64 = 10 * 60;
+const ALL_BACKENDS: &str = "*";
+
+#[derive(Clone, Copy)]
+struct Entry {
+    photo_id: Option<i32>,
+    checked_at: i64,
+}
+
+impl Entry {
+    fn live(&self, now: i64) -> bool {
+        let ttl = if self.photo_id.is_some() { EXISTS_TTL } else { MISSING_TTL };
+        now - self.checked_at < ttl
+    }
+}
+
+// Managed state
+pub struct ExistenceCache {
+    path: PathBuf,
+    // (backend_url, hothash) → answer
+    entries: Mutex<HashMap<(String, String), Entry>>,
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn parse_line(line: &str) -> Option<(i64, Option<i32>, &str, &str)> {
+    let mut fields = line.splitn(4, ' ');
+    let checked_at = fields.next()?.parse().ok()?;
+    let photo_id = match fields.next()? {
+        "-" => None,
+        id => Some(id.parse().ok()?),
+    };
+    Some((checked_at, photo_id, fields.next()?, fields.next()?.trim()))
+}
+
+impl ExistenceCache {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(CACHE_FILE);
+        let mut entries: HashMap<(String, String), Entry> = HashMap::new();
+        for line in fs::read_to_string(&path).unwrap_or_default().lines() {
+            let Some((checked_at, photo_id, hothash, backend_url)) = parse_line(line) else { continue };
+            if backend_url == ALL_BACKENDS {
+                entries.retain(|(_, h), _| h != hothash);
+            } else {
+                entries.insert((backend_url.to_string(), hothash.to_string()), Entry { photo_id, checked_at });
+            }
+        }
+        let now = now();
+        entries.retain(|_, entry| entry.live(now));
+
+        let cache = ExistenceCache { path, entries: Mutex::new(entries) };
+        if let Err(e) = cache.compact() {
+            eprintln!("Failed to compact backend existence cache: {}", e);
+        }
+        cache
+    }
+
+    fn compact(&self) -> std::io::Result<()> {
+        let entries = self.entries.lock().unwrap();
+        if entries.is_empty() && !self.path.exists() {
+            return Ok(());
+        }
+        let tmp = self.path.with_extension("txt.tmp");
+        let mut file = fs::File::create(&tmp)?;
+        for ((backend_url, hothash), entry) in entries.iter() {
+            write_line(&mut file, entry, hothash, backend_url)?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    fn append(&self, entry: &Entry, hothash: &str, backend_url: &str) {
+        let appended = (|| {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = fs::File::options().create(true).append(true).open(&self.path)?;
+            write_line(&mut file, entry, hothash, backend_url)
+        })();
+        if let Err(e) = appended {
+            eprintln!("Failed to update backend existence cache: {}", e);
+        }
+    }
+
+    // Some(photo id) or Some(None) (missing) while the answer is fresh, None
+    // when the backend has to be asked
+    pub fn get(&self, backend_url: &str, hothash: &str) -> Option<Option<i32>> {
+        let entries = self.entries.lock().ok()?;
+        let entry = entries.get(&(backend_url.to_string(), hothash.to_string()))?;
+        entry.live(now()).then_some(entry.photo_id)
+    }
+
+    // The backend answered that `hothash` is `photo_id`, or missing when None
+    pub fn record(&self, backend_url: &str, hothash: &str, photo_id: Option<i32>) {
+        if backend_url.is_empty() || hothash.is_empty() || hothash.contains(char::is_whitespace) {
+            return;
+        }
+        let entry = Entry { photo_id, checked_at: now() };
+        let Ok(mut entries) = self.entries.lock() else { return };
+        let key = (backend_url.to_string(), hothash.to_string());
+        // Only the answer changing is worth a line
+        if entries.get(&key).is_some_and(|e| e.photo_id == photo_id && e.live(entry.checked_at) && photo_id.is_some()) {
+            return;
+        }
+        entries.insert(key, entry);
+        drop(entries);
+        self.append(&entry, hothash, backend_url);
+    }
+
+    // Photos removed on the backend, or otherwise not to be believed
+    pub fn forget(&self, hothashes: &[String]) {
+        let Ok(mut entries) = self.entries.lock() else { return };
+        entries.retain(|(_, h), _| !hothashes.contains(h));
+        drop(entries);
+        let tombstone = Entry { photo_id: None, checked_at: now() };
+        for hothash in hothashes {
+            self.append(&tombstone, hothash, ALL_BACKENDS);
+        }
+    }
+}
+
+fn write_line(file: &mut fs::File, entry: &Entry, hothash: &str, backend_url: &str) -> std::io::Result<()> {
+    let photo_id = entry.photo_id.map(|id| id.to_string()).unwrap_or_else(|| "-".to_string());
+    writeln!(file, "{} {} {} {}", entry.checked_at, photo_id, hothash, backend_url)
+}
+
+// The photo id of `hothash` on the backend: from the cache while fresh,
+// asking the backend (and remembering the answer) otherwise
+pub async fn find_backend_photo(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    hothash: &str,
+) -> Result<Option<i32>, ImalinkError> {
+    let cache = app.state::<ExistenceCache>();
+    if let Some(known) = cache.get(backend_url, hothash) {
+        return Ok(known);
+    }
+    let photo_id = hothash::find_backend_photo(client, backend_url, auth_token, hothash).await?;
+    cache.record(backend_url, hothash, photo_id);
+    Ok(photo_id)
+}
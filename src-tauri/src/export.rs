@@ -0,0 +1,395 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::http::SendPaced;
+use crate::operations::{self, OperationKind};
+use crate::preview_store::{PreviewKind, PreviewStore};
+use crate::session::Session;
+use crate::streaming;
+
+// ===== Export =====
+//
+// The reverse of import: photos selected by hothash are written to a folder
+// as originals (from local storage, where the import left them) or previews
+// (downloaded from the backend), each with optional XMP and/or JSON sidecars
+// carrying rating, tags, capture time and GPS. JPEG/PNG output can be
+// downscaled to a maximum dimension; RAW originals are always copied as-is.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportContent {
+    #[default]
+    Original,
+    Coldpreview,
+    Hotpreview,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarFormat {
+    None,
+    #[default]
+    Xmp,
+    Json,
+    Both,
+}
+
+fn default_jpeg_quality() -> u8 {
+    90
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportOptions {
+    // Taken from the login session when empty
+    #[serde(default)]
+    pub backend_url: String,
+    #[serde(default)]
+    pub auth_token: String,
+    #[serde(default)]
+    pub content: ExportContent,
+    #[serde(default)]
+    pub sidecars: SidecarFormat,
+    // Longest edge in pixels; larger images are downscaled
+    #[serde(default)]
+    pub max_dimension: Option<u32>,
+    // Also export RAW/companion files next to the master (originals only)
+    #[serde(default)]
+    pub include_companions: bool,
+    #[serde(default = "default_jpeg_quality")]
+    pub jpeg_quality: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedPhoto {
+    pub hothash: String,
+    pub files: Vec<String>,
+}
+
+// Metadata written to sidecars
+#[derive(Debug, Serialize, Clone, Default)]
+struct SidecarData {
+    hothash: String,
+    rating: Option<i64>,
+    tags: Vec<String>,
+    taken_at: Option<String>,
+    gps_latitude: Option<f64>,
+    gps_longitude: Option<f64>,
+}
+
+impl SidecarData {
+    fn from_photo(hothash: &str, photo: &serde_json::Value) -> Self {
+        // Tags come either as plain strings or as objects with a name
+        let tags = photo
+            .get("tags")
+            .and_then(|t| t.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str().or_else(|| t.get("name").and_then(|n| n.as_str())))
+                    .map(|t| t.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        SidecarData {
+            hothash: hothash.to_string(),
+            rating: photo.get("rating").and_then(|r| r.as_i64()),
+            tags,
+            taken_at: photo.get("taken_at").and_then(|t| t.as_str()).map(|t| t.to_string()),
+            gps_latitude: photo.get("gps_latitude").and_then(|g| g.as_f64()),
+            gps_longitude: photo.get("gps_longitude").and_then(|g| g.as_f64()),
+        }
+    }
+}
+
+async fn fetch_photo(
+    client: &reqwest::Client,
+    options: &ExportOptions,
+    hothash: &str,
+) -> Result<serde_json::Value, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/photos/hothash/{}", options.backend_url, hothash))
+        .header("Authorization", format!("Bearer {}", options.auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(&options.backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(response.json().await?)
+}
+
+async fn fetch_preview(
+    client: &reqwest::Client,
+    options: &ExportOptions,
+    photo_id: i64,
+    kind: &str,
+) -> Result<Vec<u8>, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/photos/{}/{}", options.backend_url, photo_id, kind))
+        .header("Authorization", format!("Bearer {}", options.auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(&options.backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+// Local paths of the photo's files that still exist, master first
+fn local_files(photo: &serde_json::Value) -> Vec<PathBuf> {
+    photo
+        .get("image_files")
+        .or_else(|| photo.get("image_file_list"))
+        .and_then(|f| f.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|f| f.get("local_storage_info"))
+                .filter_map(|info| {
+                    ["storage_path", "source_path"]
+                        .iter()
+                        .filter_map(|key| info.get(*key).and_then(|p| p.as_str()))
+                        .map(PathBuf::from)
+                        .find(|p| p.is_file())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Downscale JPEG/PNG bytes so the longest edge fits max_dimension.
+// Anything that isn't decodable (RAW) or already small enough is returned unchanged.
+fn resize_image(bytes: Vec<u8>, max_dimension: u32, jpeg_quality: u8) -> Result<Vec<u8>, ImalinkError> {
+    let Ok(format) = image::guess_format(&bytes) else {
+        return Ok(bytes);
+    };
+    if format != image::ImageFormat::Jpeg && format != image::ImageFormat::Png {
+        return Ok(bytes);
+    }
+
+    let img = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| ImalinkError::parse(format!("Failed to decode image: {}", e)))?;
+    if img.width().max(img.height()) <= max_dimension {
+        return Ok(bytes);
+    }
+
+    let resized = img.resize(max_dimension, max_dimension, image::imageops::FilterType::Lanczos3);
+    let mut out = Vec::new();
+    match format {
+        image::ImageFormat::Jpeg => {
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, jpeg_quality);
+            resized.to_rgb8().write_with_encoder(encoder)
+        }
+        _ => resized.write_to(&mut std::io::Cursor::new(&mut out), format),
+    }
+    .map_err(|e| ImalinkError::internal(format!("Failed to encode image: {}", e)))?;
+    Ok(out)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// XMP GPS coordinate, e.g. "59,54.1234N"
+fn xmp_coordinate(value: f64, positive: char, negative: char) -> String {
+    let hemisphere = if value >= 0.0 { positive } else { negative };
+    let value = value.abs();
+    format!("{},{:.4}{}", value.trunc() as i64, value.fract() * 60.0, hemisphere)
+}
+
+fn xmp_sidecar(data: &SidecarData) -> String {
+    let mut properties = String::new();
+    if let Some(rating) = data.rating {
+        properties.push_str(&format!("   <xmp:Rating>{}</xmp:Rating>\n", rating));
+    }
+    if let Some(taken_at) = &data.taken_at {
+        properties.push_str(&format!("   <xmp:CreateDate>{}</xmp:CreateDate>\n", xml_escape(taken_at)));
+    }
+    if let (Some(lat), Some(lon)) = (data.gps_latitude, data.gps_longitude) {
+        properties.push_str(&format!("   <exif:GPSLatitude>{}</exif:GPSLatitude>\n", xmp_coordinate(lat, 'N', 'S')));
+        properties.push_str(&format!("   <exif:GPSLongitude>{}</exif:GPSLongitude>\n", xmp_coordinate(lon, 'E', 'W')));
+    }
+    if !data.tags.is_empty() {
+        properties.push_str("   <dc:subject>\n    <rdf:Bag>\n");
+        for tag in &data.tags {
+            properties.push_str(&format!("     <rdf:li>{}</rdf:li>\n", xml_escape(tag)));
+        }
+        properties.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+    }
+
+    format!(
+        concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+            "  <rdf:Description rdf:about=\"\"\n",
+            "    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n",
+            "    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+            "    xmlns:exif=\"http://ns.adobe.com/exif/1.0/\">\n",
+            "{}",
+            "  </rdf:Description>\n",
+            " </rdf:RDF>\n",
+            "</x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>\n"
+        ),
+        properties
+    )
+}
+
+fn write_new(path: &Path, bytes: &[u8]) -> Result<(), ImalinkError> {
+    if path.exists() {
+        return Err(ImalinkError::DestinationExists { path: path.display().to_string() });
+    }
+    fs::write(path, bytes).map_err(|e| ImalinkError::io(path.display(), e))
+}
+
+fn write_sidecars(
+    image_path: &Path,
+    data: &SidecarData,
+    format: SidecarFormat,
+    written: &mut Vec<String>,
+) -> Result<(), ImalinkError> {
+    if matches!(format, SidecarFormat::Xmp | SidecarFormat::Both) {
+        let path = image_path.with_extension("xmp");
+        write_new(&path, xmp_sidecar(data).as_bytes())?;
+        written.push(path.to_string_lossy().to_string());
+    }
+    if matches!(format, SidecarFormat::Json | SidecarFormat::Both) {
+        let path = image_path.with_extension("json");
+        write_new(&path, serde_json::to_string_pretty(data)?.as_bytes())?;
+        written.push(path.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+async fn export_one(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    hothash: &str,
+    dest: &Path,
+    options: &ExportOptions,
+) -> Result<ExportedPhoto, ImalinkError> {
+    let photo = fetch_photo(client, options, hothash).await?;
+    let sidecar = SidecarData::from_photo(hothash, &photo);
+    let mut written = Vec::new();
+
+    match options.content {
+        ExportContent::Original => {
+            let files = local_files(&photo);
+            let Some(master) = files.first() else {
+                return Err(ImalinkError::FileNotFound {
+                    path: format!("original of {}", hothash),
+                });
+            };
+            let count = if options.include_companions { files.len() } else { 1 };
+
+            for source in &files[..count] {
+                let target = dest.join(source.file_name().unwrap_or_default());
+                let source_str = source.to_string_lossy().to_string();
+                match options.max_dimension {
+                    Some(max) => {
+                        let bytes = fs::read(source).map_err(|e| ImalinkError::io(&source_str, e))?;
+                        write_new(&target, &resize_image(bytes, max, options.jpeg_quality)?)?;
+                    }
+                    None => {
+                        if target.exists() {
+                            return Err(ImalinkError::DestinationExists { path: target.display().to_string() });
+                        }
+                        streaming::copy_file(&source_str, &target)?;
+                    }
+                }
+                written.push(target.to_string_lossy().to_string());
+            }
+            let master_target = dest.join(master.file_name().unwrap_or_default());
+            if options.sidecars != SidecarFormat::None {
+                write_sidecars(&master_target, &sidecar, options.sidecars, &mut written)?;
+            }
+        }
+        ExportContent::Coldpreview | ExportContent::Hotpreview => {
+            let (kind, endpoint) = match options.content {
+                ExportContent::Hotpreview => (PreviewKind::Hot, "hotpreview"),
+                _ => (PreviewKind::Cold, "coldpreview"),
+            };
+            let bytes = match app.state::<PreviewStore>().get(kind, hothash) {
+                Some(bytes) => bytes.as_ref().clone(),
+                None => {
+                    let photo_id = photo
+                        .get("id")
+                        .and_then(|id| id.as_i64())
+                        .ok_or_else(|| ImalinkError::parse(format!("Photo {} has no id", hothash)))?;
+                    fetch_preview(client, options, photo_id, endpoint).await?
+                }
+            };
+            let bytes = match options.max_dimension {
+                Some(max) => resize_image(bytes, max, options.jpeg_quality)?,
+                None => bytes,
+            };
+
+            // Name previews after the original where we know it
+            let stem = local_files(&photo)
+                .first()
+                .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .unwrap_or_else(|| hothash.to_string());
+            let target = dest.join(format!("{}.jpg", stem));
+            write_new(&target, &bytes)?;
+            written.push(target.to_string_lossy().to_string());
+            if options.sidecars != SidecarFormat::None {
+                write_sidecars(&target, &sidecar, options.sidecars, &mut written)?;
+            }
+        }
+    }
+
+    Ok(ExportedPhoto {
+        hothash: hothash.to_string(),
+        files: written,
+    })
+}
+
+// Export the selected photos (by hothash) into `dest`. Photos whose target
+// file already exists are skipped; other failures are reported per photo.
+#[tauri::command]
+pub async fn export_photos(
+    app: tauri::AppHandle,
+    selection: Vec<String>,
+    dest: String,
+    mut options: ExportOptions,
+) -> Result<BatchResult<ExportedPhoto>, ImalinkError> {
+    guest::require_owner(&app, "export")?;
+    app.state::<Session>().fill(&mut options.backend_url, &mut options.auth_token)?;
+    let dest = PathBuf::from(&dest);
+    fs::create_dir_all(&dest).map_err(|e| ImalinkError::io(dest.display(), e))?;
+
+    let operation = operations::register(&app, OperationKind::Export, format!("Export to {}", dest.display())).cancellable();
+    operation.set_total(selection.len() as u64);
+    let client = crate::http::client(&app);
+    let mut result = BatchResult::new();
+    for hothash in selection {
+        if operation.is_cancelled() {
+            result.skip(hothash, "Cancelled");
+            continue;
+        }
+        operation.advance();
+        match export_one(&app, &client, &hothash, &dest, &options).await {
+            Err(ImalinkError::DestinationExists { path }) => {
+                result.skip(hothash, format!("Destination file already exists: {}", path));
+            }
+            exported => result.record(hothash, exported),
+        }
+    }
+    Ok(result)
+}
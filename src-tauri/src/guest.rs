@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::settings;
+
+// ===== Guest / Review Mode =====
+//
+// Lets a second person - an assistant doing the culling, a client reviewing a
+// shoot - use the app on the owner's machine without being able to change
+// anything. The owner creates a guest token (create_guest_token) and hands it
+// over; the guest opens the app and calls enter_guest_mode with it. From then
+// on every command that uploads, deletes or changes settings fails with a
+// `read_only` error. The check is made here in the command layer, so a
+// modified or misbehaving frontend can't get around it. Browsing local
+// history, previews, stats and slideshows keeps working.
+//
+// The rule for commands: anything that changes app state, the backend or
+// files on disk, writes an export, touches credentials or stops the owner's
+// work starts with require_owner. Only these are left open:
+//
+//   - get_*, list_*, *_status, *_stats and was_file_imported reads
+//   - scanning, grouping and processing by core without storing anything
+//     (scan_directory*, process_image_file, cluster_pending_events,
+//     detect_*, check_card, estimate_session, locate_original)
+//   - previews and comparisons that only read (compare_photos,
+//     propose_duplicate_actions, preview_*, suggest_channel_description,
+//     verify_backup, verify_interrupted_import, read_cull_marks)
+//   - checks (validate_token, check_channel_permission, check_import_space,
+//     benchmark_backend, scan_remote_source) and open_web_gallery
+//   - slideshows
+//   - entering and leaving guest mode itself
+//
+// Guest mode survives a restart (it is recorded in the app data directory)
+// and only ends through exit_guest_mode with the owner passcode, which is set
+// when the first guest token is created. Tokens are stored as BLAKE3 hashes
+// in settings, the passcode stretched over many rounds so that it can't be
+// guessed quickly from a copy of the profile. This restricts the app, not
+// the disk: anyone with file access to the profile can still edit it.
+
+const GUEST_FILE: &str = "guest_session.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuestToken {
+    pub label: String,
+    pub token_hash: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuestSession {
+    // Label of the token the guest entered with
+    pub label: String,
+    pub started_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AccessStatus {
+    pub guest: Option<GuestSession>,
+}
+
+// Rounds for new passcode hashes; each hash records its own
+const PASSCODE_ROUNDS: u32 = 500_000;
+const MIN_PASSCODE_CHARS: usize = 8;
+
+// Keyed by the passcode, BLAKE3 is chained `rounds` times from the salt and
+// the blocks XORed together (the PBKDF2 construction), so every guess costs
+// as many hashes
+fn stretch(secret: &str, salt: &str, rounds: u32) -> String {
+    let key = blake3::derive_key("imalink-desktop owner passcode", secret.as_bytes());
+    let mut block = *blake3::keyed_hash(&key, salt.as_bytes()).as_bytes();
+    let mut out = block;
+    for _ in 1..rounds {
+        block = *blake3::keyed_hash(&key, &block).as_bytes();
+        out.iter_mut().zip(block).for_each(|(o, b)| *o ^= b);
+    }
+    blake3::Hash::from(out).to_hex().to_string()
+}
+
+// "<rounds>$<salt>$<hash>" - the salt keeps equal passcodes from hashing alike
+fn hash_secret(secret: &str, salt: &str, rounds: u32) -> String {
+    format!("{}${}${}", rounds, salt, stretch(secret, salt, rounds))
+}
+
+fn new_passcode_hash(secret: &str) -> String {
+    hash_secret(secret, &uuid::Uuid::new_v4().simple().to_string(), PASSCODE_ROUNDS)
+}
+
+// "<salt>:<hash>", a single round, as earlier versions wrote it
+fn is_legacy(hashed: &str) -> bool {
+    !hashed.contains('$')
+}
+
+fn matches_secret(secret: &str, hashed: &str) -> bool {
+    if is_legacy(hashed) {
+        return hashed.split_once(':').is_some_and(|(salt, _)| {
+            format!("{}:{}", salt, blake3::hash(format!("{}:{}", salt, secret).as_bytes()).to_hex()) == hashed
+        });
+    }
+    let mut parts = hashed.splitn(3, '$');
+    match (parts.next().and_then(|r| r.parse().ok()), parts.next()) {
+        (Some(rounds), Some(salt)) => hash_secret(secret, salt, rounds) == hashed,
+        _ => false,
+    }
+}
+
+// Managed state: whether the app is in guest mode, and as which guest
+pub struct AccessMode {
+    path: PathBuf,
+    guest: RwLock<Option<GuestSession>>,
+}
+
+impl AccessMode {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(GUEST_FILE);
+        let guest = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        AccessMode { path, guest: RwLock::new(guest) }
+    }
+
+    pub fn status(&self) -> AccessStatus {
+        AccessStatus { guest: self.guest.read().unwrap().clone() }
+    }
+
+    // Refuse `action` (e.g. "upload") while a guest is using the app
+    pub fn require_owner(&self, action: &str) -> Result<(), ImalinkError> {
+        if self.guest.read().unwrap().is_some() {
+            return Err(ImalinkError::ReadOnly { action: action.to_string() });
+        }
+        Ok(())
+    }
+
+    fn set(&self, guest: Option<GuestSession>) -> Result<(), ImalinkError> {
+        match &guest {
+            Some(session) => {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+                }
+                fs::write(&self.path, serde_json::to_string_pretty(session)?)
+                    .map_err(|e| ImalinkError::io(self.path.display(), e))?;
+            }
+            None => {
+                if let Err(e) = fs::remove_file(&self.path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        return Err(ImalinkError::io(self.path.display(), e));
+                    }
+                }
+            }
+        }
+        *self.guest.write().unwrap() = guest;
+        Ok(())
+    }
+}
+
+// See AccessMode::require_owner
+pub fn require_owner(app: &tauri::AppHandle, action: &str) -> Result<(), ImalinkError> {
+    app.state::<AccessMode>().require_owner(action)
+}
+
+#[tauri::command]
+pub fn get_access_mode(access: tauri::State<'_, AccessMode>) -> AccessStatus {
+    access.status()
+}
+
+// Create a guest token and return it; it is shown this once and only its
+// hash is kept. The first call sets the owner passcode, later calls must
+// repeat it.
+#[tauri::command]
+pub fn create_guest_token(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    label: String,
+    owner_passcode: String,
+) -> Result<String, ImalinkError> {
+    access.require_owner("create guest tokens")?;
+    if label.trim().is_empty() {
+        return Err(ImalinkError::invalid("Guest token needs a label"));
+    }
+    let mut settings = settings::load(&app);
+    match &settings.owner_passcode_hash {
+        Some(hashed) if !matches_secret(&owner_passcode, hashed) => {
+            return Err(ImalinkError::Unauthorized { detail: "Wrong owner passcode".to_string() });
+        }
+        // Rehashed as it is known now
+        Some(hashed) if is_legacy(hashed) => settings.owner_passcode_hash = Some(new_passcode_hash(&owner_passcode)),
+        Some(_) => {}
+        None if owner_passcode.chars().count() < MIN_PASSCODE_CHARS => {
+            return Err(ImalinkError::invalid(format!(
+                "Owner passcode must be at least {} characters",
+                MIN_PASSCODE_CHARS
+            )));
+        }
+        None => settings.owner_passcode_hash = Some(new_passcode_hash(&owner_passcode)),
+    }
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    settings.guest_tokens.retain(|t| t.label != label);
+    settings.guest_tokens.push(GuestToken {
+        label,
+        token_hash: blake3::hash(token.as_bytes()).to_hex().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    });
+    settings::save(&app, &settings)?;
+    Ok(token)
+}
+
+#[tauri::command]
+pub fn revoke_guest_token(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    label: String,
+) -> Result<Vec<GuestToken>, ImalinkError> {
+    access.require_owner("revoke guest tokens")?;
+    let mut settings = settings::load(&app);
+    settings.guest_tokens.retain(|t| t.label != label);
+    settings::save(&app, &settings)?;
+    Ok(settings.guest_tokens)
+}
+
+#[tauri::command]
+pub fn enter_guest_mode(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    token: String,
+) -> Result<AccessStatus, ImalinkError> {
+    let token_hash = blake3::hash(token.trim().as_bytes()).to_hex().to_string();
+    let guest_token = settings::load(&app)
+        .guest_tokens
+        .into_iter()
+        .find(|t| t.token_hash == token_hash)
+        .ok_or_else(|| ImalinkError::Unauthorized { detail: "Unknown guest token".to_string() })?;
+
+    access.set(Some(GuestSession {
+        label: guest_token.label,
+        started_at: chrono::Utc::now().to_rfc3339(),
+    }))?;
+    Ok(access.status())
+}
+
+#[tauri::command]
+pub fn exit_guest_mode(
+    app: tauri::AppHandle,
+    access: tauri::State<'_, AccessMode>,
+    owner_passcode: String,
+) -> Result<AccessStatus, ImalinkError> {
+    let mut settings = settings::load(&app);
+    let Some(hashed) = settings.owner_passcode_hash.as_deref().filter(|h| matches_secret(&owner_passcode, h)) else {
+        return Err(ImalinkError::Unauthorized { detail: "Wrong owner passcode".to_string() });
+    };
+    if is_legacy(hashed) {
+        settings.owner_passcode_hash = Some(new_passcode_hash(&owner_passcode));
+        settings::save(&app, &settings)?;
+    }
+    access.set(None)?;
+    Ok(access.status())
+}
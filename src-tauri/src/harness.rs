@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::{self, CompanionGroup};
+use crate::{hothash, payload, settings, stall, PhotoCreateResponse, PhotoCreateSchema};
+
+pub use crate::error::ImalinkError;
+pub use crate::mock::{start as start_mock, MockServer};
+pub use crate::rename::Claims;
+
+// ===== Test Harness =====
+//
+// Only built with the `mock` feature. The steps of an import that don't need
+// a running app - scanning, grouping, processing by core, the backend lookup
+// and the upload - for the integration tests in tests/, run against the
+// mock server, and archiving into storage in plain copy mode. Each calls
+// the same code the pipeline does; uploads use the default settings.
+
+pub fn scan(dir: &Path) -> Result<Vec<String>, ImalinkError> {
+    crate::collect_image_files(dir)
+}
+
+pub fn group(files: Vec<String>) -> Vec<CompanionGroup> {
+    pipeline::group_companion_files(files, None)
+}
+
+// Archive a group under its original names, `claims` being the session's
+// reservations. Returns the destinations written.
+pub fn archive(
+    claims: &Claims,
+    group: &CompanionGroup,
+    dest_dir: &Path,
+    source_dir: &Path,
+    preserve_structure: bool,
+) -> Result<Vec<PathBuf>, ImalinkError> {
+    let mut written = Vec::new();
+    let archived = pipeline::archive_group(
+        claims,
+        group,
+        &dest_dir.to_string_lossy(),
+        preserve_structure,
+        &source_dir.to_string_lossy(),
+        |_, dest| written.push(dest.to_path_buf()),
+    );
+    archived.map(|()| written)
+}
+
+// The multipart file part uploads to core are built from
+pub async fn file_part(path: &Path) -> Result<reqwest::multipart::Part, ImalinkError> {
+    crate::streaming::file_part(&path.to_string_lossy()).await
+}
+
+pub async fn process(client: &reqwest::Client, file_path: &str, core_api_url: &str) -> Result<PhotoCreateSchema, ImalinkError> {
+    crate::process_file(client, file_path, core_api_url).await
+}
+
+pub async fn find_backend_photo(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    hothash: &str,
+) -> Result<Option<i32>, ImalinkError> {
+    hothash::find_backend_photo(client, backend_url, auth_token, hothash).await
+}
+
+pub async fn upload(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    schema: PhotoCreateSchema,
+    input_channel_id: i32,
+) -> Result<PhotoCreateResponse, ImalinkError> {
+    let settings = settings::AppSettings::default();
+    let stall = stall::StallPolicy::from_settings(&settings);
+    let limit = payload::PayloadLimit::from_settings(&settings);
+    crate::upload_schema(client, backend_url, auth_token, schema, input_channel_id, &stall, &limit).await
+}
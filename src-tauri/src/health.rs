@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::error::ImalinkError;
+use crate::{crash, remote_core, CoreProcess};
+
+// ===== Health Status =====
+//
+// get_health_status reports the state of imalink-core and the backend as a
+// typed struct instead of a sentence for the UI to pick apart:
+//
+//   core      up, version (from /health), latency
+//   backend   reachable, authenticated (when a token is given), latency
+//   sidecar   PID of the imalink-core process the app started, if running
+//
+// A core check against the sidecar also updates its lifecycle state (see
+// get_core_status): Ready when it answers, Unreachable when it doesn't.
+//
+// The URLs and token of the last call are remembered, and a background loop
+// re-checks them every REFRESH_INTERVAL and emits the result as a
+// `health-status` event, so the status line stays current without polling
+// from the frontend.
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CoreHealth {
+    pub url: String,
+    pub up: bool,
+    pub version: Option<String>,
+    pub latency_ms: Option<u64>,
+    // Why the core is down
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackendHealth {
+    pub url: String,
+    pub reachable: bool,
+    // None when checked without a token
+    pub authenticated: Option<bool>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HealthStatus {
+    pub checked_at: String,
+    pub core: CoreHealth,
+    pub backend: BackendHealth,
+    pub sidecar_pid: Option<u32>,
+}
+
+#[derive(Clone)]
+struct HealthTargets {
+    core_api_url: String,
+    backend_url: String,
+    auth_token: Option<String>,
+}
+
+// Managed state: what the background loop checks, and its latest result
+#[derive(Default)]
+pub struct HealthMonitor {
+    targets: Mutex<Option<HealthTargets>>,
+    last: Mutex<Option<HealthStatus>>,
+}
+
+fn elapsed_ms(started: Instant) -> Option<u64> {
+    Some(started.elapsed().as_millis() as u64)
+}
+
+async fn check_core(client: &reqwest::Client, core_api_url: &str) -> CoreHealth {
+    let started = Instant::now();
+    let mut health = CoreHealth {
+        url: remote_core::url(core_api_url),
+        up: false,
+        version: None,
+        latency_ms: None,
+        error: None,
+    };
+    match remote_core::request(client, reqwest::Method::GET, core_api_url, "/health").send().await {
+        Ok(response) if response.status().is_success() => {
+            health.latency_ms = elapsed_ms(started);
+            health.up = true;
+            health.version = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v.get("version").and_then(|v| v.as_str()).map(str::to_string));
+            if let Some(version) = &health.version {
+                crash::set_core_version(version.clone());
+            }
+        }
+        Ok(response) => {
+            health.latency_ms = elapsed_ms(started);
+            let status = response.status().as_u16();
+            health.error = Some(ImalinkError::Core { status, detail: "Health check failed".to_string() }.to_string());
+        }
+        Err(e) => health.error = Some(ImalinkError::network(remote_core::url(core_api_url), e).to_string()),
+    }
+    health
+}
+
+async fn check_backend(client: &reqwest::Client, backend_url: &str, auth_token: Option<&str>) -> BackendHealth {
+    let started = Instant::now();
+    let mut request = client.get(format!("{}/api/v1/auth/me/", backend_url));
+    if let Some(token) = auth_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+    let mut health = BackendHealth {
+        url: backend_url.to_string(),
+        reachable: false,
+        authenticated: None,
+        latency_ms: None,
+        error: None,
+    };
+    match request.send().await {
+        // Any answer short of a server error means the backend is there; a
+        // 401 without a token is expected
+        Ok(response) => {
+            let status = response.status();
+            health.latency_ms = elapsed_ms(started);
+            health.reachable = !status.is_server_error();
+            health.authenticated = auth_token.map(|_| status.is_success());
+            if !status.is_success() && (auth_token.is_some() || status.is_server_error()) {
+                health.error = Some(ImalinkError::from_backend(status, String::new()).to_string());
+            }
+        }
+        Err(e) => health.error = Some(ImalinkError::network(backend_url, e).to_string()),
+    }
+    health
+}
+
+fn sidecar_pid(app: &tauri::AppHandle) -> Option<u32> {
+    let core = app.try_state::<Mutex<CoreProcess>>()?;
+    let state = core.lock().ok()?;
+    state.child.as_ref().map(|child| child.pid())
+}
+
+async fn check(app: &tauri::AppHandle, targets: &HealthTargets) -> HealthStatus {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let (core, backend) = tokio::join!(
+        check_core(&client, &targets.core_api_url),
+        check_backend(&client, &targets.backend_url, targets.auth_token.as_deref()),
+    );
+    if !remote_core::is_remote() && core.url == remote_core::url("") {
+        crate::core_health_checked(app, core.up);
+    }
+    let status = HealthStatus {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        core,
+        backend,
+        sidecar_pid: sidecar_pid(app),
+    };
+    if let Ok(mut last) = app.state::<HealthMonitor>().last.lock() {
+        *last = Some(status.clone());
+    }
+    status
+}
+
+pub fn start(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            tick.tick().await;
+            let targets = app.state::<HealthMonitor>().targets.lock().ok().and_then(|t| t.clone());
+            if let Some(targets) = targets {
+                let status = check(&app, &targets).await;
+                let _ = app.emit("health-status", &status);
+            }
+        }
+    });
+}
+
+// Check core and backend now; later checks on the timer use the same URLs
+// and token
+#[tauri::command]
+pub async fn get_health_status(
+    app: tauri::AppHandle,
+    core_api_url: String,
+    backend_url: String,
+    auth_token: Option<String>,
+) -> Result<HealthStatus, ImalinkError> {
+    let targets = HealthTargets { core_api_url, backend_url, auth_token };
+    if let Ok(mut current) = app.state::<HealthMonitor>().targets.lock() {
+        *current = Some(targets.clone());
+    }
+    Ok(check(&app, &targets).await)
+}
+
+// Result of the latest check, without checking again
+#[tauri::command]
+pub fn get_last_health_status(monitor: tauri::State<'_, HealthMonitor>) -> Option<HealthStatus> {
+    monitor.last.lock().ok().and_then(|last| last.clone())
+}
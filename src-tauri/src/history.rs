@@ -0,0 +1,571 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+
+// ===== Local History =====
+//
+// SQLite database in the app data dir with one row per photo the desktop app
+// knows about, keyed by hothash. Imports record photos here after upload, and
+// the row also carries the user-editable metadata (rating, visibility) so it
+// can be browsed and edited offline; `dirty` marks local edits that haven't
+// been pushed to the backend yet (see sync.rs). `stored_files` lists where
+// the files of each photo were left on disk - the archive copies in copy
+// mode, the imported files otherwise - for finding originals by hothash.
+// `session_files` lists what each import session wrote into storage, for
+// undoing it (see undo.rs), and `session_notes` the user's notes on
+// sessions (see session_notes.rs). `file_metrics` sums processing and
+// upload times per extension for estimates (see metrics.rs), and
+// `file_fingerprints` the content hash per path for re-imports (see
+// hothash.rs). `imported_files` logs every file an import handled, with
+// its outcome, for get_import_history and was_file_imported.
+// `memory_cards` fingerprints the cards imported from (see cards.rs).
+//
+// Schema changes are appended to MIGRATIONS and applied in order on open;
+// `PRAGMA user_version` records how many have run. Never edit a released
+// migration - add a new one.
+
+const DB_FILE: &str = "history.db";
+
+const MIGRATIONS: &[&str] = &[
+    "
+CREATE TABLE IF NOT EXISTS photos (
+    hothash TEXT PRIMARY KEY,
+    photo_id INTEGER,
+    file_path TEXT,
+    input_channel_id INTEGER,
+    rating INTEGER,
+    visibility TEXT,
+    imported_at TEXT,
+    local_modified_at TEXT,
+    remote_modified_at TEXT,
+    dirty INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS sync_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS schedule_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id TEXT NOT NULL,
+    rule_name TEXT NOT NULL,
+    triggered_at TEXT NOT NULL,
+    status TEXT NOT NULL,
+    session_id TEXT,
+    detail TEXT
+);
+",
+    "
+ALTER TABLE photos ADD COLUMN taken_at TEXT;
+ALTER TABLE photos ADD COLUMN camera TEXT;
+ALTER TABLE photos ADD COLUMN bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE photos ADD COLUMN archived_bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE photos ADD COLUMN duplicate_count INTEGER NOT NULL DEFAULT 0;
+",
+    "
+ALTER TABLE photos ADD COLUMN phash TEXT;
+ALTER TABLE photos ADD COLUMN width INTEGER;
+ALTER TABLE photos ADD COLUMN height INTEGER;
+",
+    "
+CREATE TABLE IF NOT EXISTS stored_files (
+    hothash TEXT NOT NULL,
+    path TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    PRIMARY KEY (hothash, path)
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS session_files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    dest TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    moved INTEGER NOT NULL DEFAULT 0,
+    root TEXT,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS session_files_session ON session_files (session_id);
+",
+    "
+CREATE TABLE IF NOT EXISTS pending_coldpreviews (
+    hothash TEXT PRIMARY KEY,
+    photo_id INTEGER NOT NULL,
+    file_path TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS session_notes (
+    session_id TEXT PRIMARY KEY,
+    note TEXT NOT NULL,
+    labels TEXT NOT NULL DEFAULT '[]',
+    input_channel_id INTEGER,
+    updated_at TEXT NOT NULL,
+    appended_at TEXT
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS file_metrics (
+    extension TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    files INTEGER NOT NULL DEFAULT 0,
+    bytes INTEGER NOT NULL DEFAULT 0,
+    millis INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (extension, stage)
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS file_fingerprints (
+    path TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL,
+    content_hash TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE IF NOT EXISTS imported_files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    hothash TEXT,
+    photo_id INTEGER,
+    input_channel_id INTEGER,
+    status TEXT NOT NULL,
+    detail TEXT,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS imported_files_path ON imported_files (path);
+CREATE INDEX IF NOT EXISTS imported_files_session ON imported_files (session_id);
+",
+    "
+CREATE TABLE IF NOT EXISTS memory_cards (
+    id TEXT PRIMARY KEY,
+    label TEXT,
+    serial TEXT,
+    first_hash TEXT,
+    last_hash TEXT,
+    newest_modified INTEGER NOT NULL DEFAULT 0,
+    last_import_at TEXT NOT NULL,
+    imports INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS memory_cards_serial ON memory_cards (serial);
+",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryPhoto {
+    pub hothash: String,
+    pub photo_id: Option<i32>,
+    pub file_path: Option<String>,
+    pub input_channel_id: Option<i32>,
+    pub rating: Option<i32>,
+    pub visibility: Option<String>,
+    pub imported_at: Option<String>,
+    pub local_modified_at: Option<String>,
+    pub remote_modified_at: Option<String>,
+    pub dirty: bool,
+    pub taken_at: Option<String>,
+    pub camera: Option<String>,
+    pub bytes: i64,
+    pub archived_bytes: i64,
+    // Times the photo was found to be on the backend already during import
+    pub duplicate_count: i64,
+    // Perceptual hash of the hotpreview, see duplicates.rs
+    pub phash: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+// What an import (or migration) knows about an uploaded photo
+#[derive(Debug, Clone, Default)]
+pub struct UploadRecord {
+    pub hothash: String,
+    pub photo_id: i32,
+    pub file_path: String,
+    pub input_channel_id: i32,
+    pub taken_at: Option<String>,
+    pub camera: Option<String>,
+    pub bytes: u64,
+    // Bytes copied into storage (copy mode)
+    pub archived_bytes: u64,
+    pub is_duplicate: bool,
+    pub phash: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    // Master and companions as stored by the import
+    pub stored_files: Vec<String>,
+}
+
+// One file handled by an import. `status` is imported, duplicate (the
+// backend had it already), skipped, rejected or failed.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ImportedFile {
+    pub path: String,
+    pub session_id: String,
+    pub hothash: Option<String>,
+    pub photo_id: Option<i32>,
+    pub input_channel_id: Option<i32>,
+    pub status: String,
+    // Skip reason or error
+    pub detail: Option<String>,
+    pub recorded_at: String,
+}
+
+impl ImportedFile {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(ImportedFile {
+            path: row.get("path")?,
+            session_id: row.get("session_id")?,
+            hothash: row.get("hothash")?,
+            photo_id: row.get("photo_id")?,
+            input_channel_id: row.get("input_channel_id")?,
+            status: row.get("status")?,
+            detail: row.get("detail")?,
+            recorded_at: row.get("recorded_at")?,
+        })
+    }
+}
+
+impl HistoryPhoto {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(HistoryPhoto {
+            hothash: row.get("hothash")?,
+            photo_id: row.get("photo_id")?,
+            file_path: row.get("file_path")?,
+            input_channel_id: row.get("input_channel_id")?,
+            rating: row.get("rating")?,
+            visibility: row.get("visibility")?,
+            imported_at: row.get("imported_at")?,
+            local_modified_at: row.get("local_modified_at")?,
+            remote_modified_at: row.get("remote_modified_at")?,
+            dirty: row.get("dirty")?,
+            taken_at: row.get("taken_at")?,
+            camera: row.get("camera")?,
+            bytes: row.get("bytes")?,
+            archived_bytes: row.get("archived_bytes")?,
+            duplicate_count: row.get("duplicate_count")?,
+            phash: row.get("phash")?,
+            width: row.get("width")?,
+            height: row.get("height")?,
+        })
+    }
+}
+
+pub struct History {
+    conn: Mutex<Connection>,
+    path: PathBuf,
+}
+
+impl History {
+    pub fn open(dir: &Path) -> Result<Self, ImalinkError> {
+        std::fs::create_dir_all(dir).map_err(|e| ImalinkError::io(dir.display(), e))?;
+        let path = dir.join(DB_FILE);
+        let mut conn = Connection::open(&path)?;
+        migrate(&mut conn)?;
+        Ok(History { conn: Mutex::new(conn), path })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, ImalinkError> {
+        self.conn
+            .lock()
+            .map_err(|_| ImalinkError::internal("History database lock poisoned"))
+    }
+
+    // Run `f` with the connection held
+    pub fn with<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, ImalinkError> {
+        let conn = self.lock()?;
+        Ok(f(&conn)?)
+    }
+
+    // Consistent copy of the database at `dest` (which must not exist)
+    pub fn snapshot(&self, dest: &Path) -> Result<(), ImalinkError> {
+        let dest = dest.to_string_lossy().to_string();
+        self.with(|conn| conn.execute("VACUUM INTO ?1", [dest]).map(|_| ()))
+    }
+
+    // Replace the database with a snapshot. The snapshot is migrated on a
+    // copy first, so a broken file leaves the current database untouched.
+    pub fn restore(&self, snapshot: &Path) -> Result<(), ImalinkError> {
+        let staged = self.path.with_extension("db.restore");
+        std::fs::copy(snapshot, &staged).map_err(|e| ImalinkError::io(snapshot.display(), e))?;
+        let checked = Connection::open(&staged).map_err(ImalinkError::from).and_then(|mut conn| migrate(&mut conn));
+        if let Err(e) = checked {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e);
+        }
+
+        let mut conn = self.lock()?;
+        // Close the current file before replacing it
+        *conn = Connection::open_in_memory()?;
+        let renamed = std::fs::rename(&staged, &self.path);
+        // Back on the file either way; on failure that is the old database
+        *conn = Connection::open(&self.path)?;
+        if let Err(e) = renamed {
+            let _ = std::fs::remove_file(&staged);
+            return Err(ImalinkError::io(self.path.display(), e));
+        }
+        Ok(())
+    }
+
+    // Record a photo the backend has accepted (or already had)
+    pub fn record_upload(&self, record: &UploadRecord) -> Result<(), ImalinkError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let file_path = Some(record.file_path.as_str()).filter(|p| !p.is_empty());
+        self.with(|conn| {
+            for path in &record.stored_files {
+                conn.execute(
+                    "INSERT OR IGNORE INTO stored_files (hothash, path, recorded_at) VALUES (?1, ?2, ?3)",
+                    params![record.hothash, path, now],
+                )?;
+            }
+            conn.execute(
+                "INSERT INTO photos (hothash, photo_id, file_path, input_channel_id, imported_at,
+                                     taken_at, camera, bytes, archived_bytes, duplicate_count,
+                                     phash, width, height)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                 ON CONFLICT(hothash) DO UPDATE SET
+                    photo_id = excluded.photo_id,
+                    file_path = COALESCE(photos.file_path, excluded.file_path),
+                    input_channel_id = COALESCE(photos.input_channel_id, excluded.input_channel_id),
+                    imported_at = COALESCE(photos.imported_at, excluded.imported_at),
+                    taken_at = COALESCE(photos.taken_at, excluded.taken_at),
+                    camera = COALESCE(photos.camera, excluded.camera),
+                    bytes = MAX(photos.bytes, excluded.bytes),
+                    archived_bytes = photos.archived_bytes + excluded.archived_bytes,
+                    duplicate_count = photos.duplicate_count + excluded.duplicate_count,
+                    phash = COALESCE(photos.phash, excluded.phash),
+                    width = COALESCE(photos.width, excluded.width),
+                    height = COALESCE(photos.height, excluded.height)",
+                params![
+                    record.hothash,
+                    record.photo_id,
+                    file_path,
+                    record.input_channel_id,
+                    now,
+                    record.taken_at,
+                    record.camera,
+                    record.bytes as i64,
+                    record.archived_bytes as i64,
+                    record.is_duplicate as i64,
+                    record.phash,
+                    record.width,
+                    record.height,
+                ],
+            )
+            .map(|_| ())
+        })
+    }
+
+    // Log a file handled by an import; recorded_at is set here
+    pub fn record_file(&self, file: &ImportedFile) -> Result<(), ImalinkError> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO imported_files (path, session_id, hothash, photo_id, input_channel_id,
+                                             status, detail, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    file.path,
+                    file.session_id,
+                    file.hothash,
+                    file.photo_id,
+                    file.input_channel_id,
+                    file.status,
+                    file.detail,
+                    chrono::Utc::now().to_rfc3339(),
+                ],
+            )
+            .map(|_| ())
+        })
+    }
+
+    pub fn get(&self, hothash: &str) -> Result<Option<HistoryPhoto>, ImalinkError> {
+        self.with(|conn| {
+            conn.query_row("SELECT * FROM photos WHERE hothash = ?1", [hothash], HistoryPhoto::from_row)
+                .optional()
+        })
+    }
+
+    // Hothash of the photo imported from or stored at `path`
+    pub fn find_by_file(&self, path: &str) -> Result<Option<String>, ImalinkError> {
+        self.with(|conn| {
+            conn.query_row(
+                "SELECT hothash FROM photos WHERE file_path = ?1
+                 UNION SELECT hothash FROM stored_files WHERE path = ?1
+                 LIMIT 1",
+                [path],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+    }
+
+    // Paths recorded for the photo, in the order recorded (master first)
+    pub fn stored_files(&self, hothash: &str) -> Result<Vec<String>, ImalinkError> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare("SELECT path FROM stored_files WHERE hothash = ?1 ORDER BY rowid")?;
+            let rows = stmt.query_map([hothash], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    pub fn all_photos(&self) -> Result<Vec<HistoryPhoto>, ImalinkError> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare("SELECT * FROM photos ORDER BY imported_at, hothash")?;
+            let rows = stmt.query_map([], HistoryPhoto::from_row)?;
+            rows.collect()
+        })
+    }
+
+    pub fn dirty_photos(&self) -> Result<Vec<HistoryPhoto>, ImalinkError> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare("SELECT * FROM photos WHERE dirty = 1")?;
+            let rows = stmt.query_map([], HistoryPhoto::from_row)?;
+            rows.collect()
+        })
+    }
+
+    pub fn sync_value(&self, key: &str) -> Result<Option<String>, ImalinkError> {
+        self.with(|conn| {
+            conn.query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |row| row.get(0))
+                .optional()
+        })
+    }
+
+    pub fn set_sync_value(&self, key: &str, value: &str) -> Result<(), ImalinkError> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO sync_state (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )
+            .map(|_| ())
+        })
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), ImalinkError> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+// Edit rating/visibility locally; the change is pushed on the next sync
+#[tauri::command]
+pub fn update_local_metadata(
+    access: tauri::State<'_, AccessMode>,
+    history: tauri::State<'_, History>,
+    hothash: String,
+    rating: Option<i32>,
+    visibility: Option<String>,
+) -> Result<HistoryPhoto, ImalinkError> {
+    access.require_owner("edit metadata")?;
+    if let Some(rating) = rating {
+        if !(0..=5).contains(&rating) {
+            return Err(ImalinkError::invalid(format!("Rating must be 0-5, got {}", rating)));
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    history.with(|conn| {
+        conn.execute(
+            "INSERT INTO photos (hothash, rating, visibility, local_modified_at, dirty)
+             VALUES (?1, ?2, ?3, ?4, 1)
+             ON CONFLICT(hothash) DO UPDATE SET
+                rating = COALESCE(excluded.rating, photos.rating),
+                visibility = COALESCE(excluded.visibility, photos.visibility),
+                local_modified_at = excluded.local_modified_at,
+                dirty = 1",
+            params![hothash, rating, visibility, now],
+        )
+        .map(|_| ())
+    })?;
+
+    history
+        .get(&hothash)?
+        .ok_or_else(|| ImalinkError::internal(format!("Photo {} missing after update", hothash)))
+}
+
+#[tauri::command]
+pub fn get_history_photo(
+    history: tauri::State<'_, History>,
+    hothash: String,
+) -> Result<Option<HistoryPhoto>, ImalinkError> {
+    history.get(&hothash)
+}
+
+// Files handled by imports, newest first; optionally of one session or
+// with one status
+#[tauri::command]
+pub fn get_import_history(
+    history: tauri::State<'_, History>,
+    session_id: Option<String>,
+    status: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<ImportedFile>, ImalinkError> {
+    history.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM imported_files
+             WHERE (?1 IS NULL OR session_id = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY id DESC LIMIT ?3 OFFSET ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![session_id, status, limit.map_or(-1, |l| l as i64), offset.unwrap_or(0) as i64],
+            ImportedFile::from_row,
+        )?;
+        rows.collect()
+    })
+}
+
+// The latest import of `path` that got it onto the backend, if any
+#[tauri::command]
+pub fn was_file_imported(history: tauri::State<'_, History>, path: String) -> Result<Option<ImportedFile>, ImalinkError> {
+    history.with(|conn| {
+        conn.query_row(
+            "SELECT * FROM imported_files WHERE path = ?1 AND status IN ('imported', 'duplicate')
+             ORDER BY id DESC LIMIT 1",
+            [path],
+            ImportedFile::from_row,
+        )
+        .optional()
+    })
+}
+
+// `before` of clear_history as recorded_at is written: UTC, RFC 3339
+fn parse_cutoff(before: &str) -> Result<String, ImalinkError> {
+    let instant = match chrono::DateTime::parse_from_rfc3339(before) {
+        Ok(time) => time.with_timezone(&chrono::Utc),
+        Err(_) => chrono::NaiveDate::parse_from_str(before, "%Y-%m-%d")
+            .map_err(|_| ImalinkError::invalid(format!("Not a date or RFC 3339 time: {}", before)))?
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc(),
+    };
+    Ok(instant.to_rfc3339_opts(chrono::SecondsFormat::Nanos, false))
+}
+
+// Clear the import log, or the part of it recorded before `before` (RFC
+// 3339, or YYYY-MM-DD for midnight UTC). Photos and their local edits are
+// kept. Returns the entries removed.
+#[tauri::command]
+pub fn clear_history(
+    access: tauri::State<'_, AccessMode>,
+    history: tauri::State<'_, History>,
+    before: Option<String>,
+) -> Result<usize, ImalinkError> {
+    access.require_owner("clear the import history")?;
+    let before = before.as_deref().map(parse_cutoff).transpose()?;
+    history.with(|conn| conn.execute("DELETE FROM imported_files WHERE ?1 IS NULL OR recorded_at < ?1", [before]))
+}
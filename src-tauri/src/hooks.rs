@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::ImalinkError;
+use crate::pipeline::ImportSession;
+use crate::settings;
+
+// ===== Post-Import Hooks =====
+//
+// User-configured actions fired when an import session completes: run a
+// program or POST to a webhook, with the import report as JSON. Programs get
+// the report on stdin and are started directly (no shell), with the session
+// id in IMALINK_SESSION_ID. Hooks are opt-in: nothing runs unless
+// `hooks_enabled` is set, and each hook is bounded by a timeout.
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_timeout() -> u64 {
+    60
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PostImportHook {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub action: HookAction,
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+}
+
+// JSON payload sent to every hook
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportReport<'a> {
+    pub event: &'static str,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rejected: usize,
+    pub session: &'a ImportSession,
+}
+
+async fn run_command(program: &str, args: &[String], session_id: &str, payload: &[u8]) -> Result<(), ImalinkError> {
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .env("IMALINK_SESSION_ID", session_id)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ImalinkError::io(program, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input is fine
+        let _ = stdin.write_all(payload).await;
+    }
+    let output = child.wait_with_output().await.map_err(|e| ImalinkError::io(program, e))?;
+    if !output.status.success() {
+        return Err(ImalinkError::internal(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+async fn run_webhook(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HashMap<String, String>,
+    payload: Vec<u8>,
+) -> Result<(), ImalinkError> {
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| ImalinkError::network(url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::Backend { status: status.as_u16(), detail: error_text });
+    }
+    Ok(())
+}
+
+async fn run_hook(
+    client: &reqwest::Client,
+    hook: &PostImportHook,
+    session_id: &str,
+    payload: Vec<u8>,
+) -> Result<(), ImalinkError> {
+    let run = async {
+        match &hook.action {
+            HookAction::Command { program, args } => run_command(program, args, session_id, &payload).await,
+            HookAction::Webhook { url, headers } => run_webhook(client, url, headers, payload.clone()).await,
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(hook.timeout_seconds.max(1)), run)
+        .await
+        .map_err(|_| ImalinkError::internal(format!("Timed out after {}s", hook.timeout_seconds)))?
+}
+
+// Fire all enabled hooks for a completed session, in the background
+pub fn fire(app: &tauri::AppHandle, session: &ImportSession) {
+    let settings = settings::load(app);
+    if !settings.hooks_enabled {
+        return;
+    }
+    let hooks: Vec<PostImportHook> = settings.hooks.into_iter().filter(|h| h.enabled).collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let report = ImportReport {
+        event: "import_complete",
+        succeeded: session.result.succeeded.len(),
+        skipped: session.result.skipped.len(),
+        failed: session.result.failed.len(),
+        rejected: session.rejected,
+        session,
+    };
+    let payload = match serde_json::to_vec(&report) {
+        Ok(payload) => payload,
+        Err(e) => {
+            eprintln!("Failed to serialize import report for hooks: {}", e);
+            return;
+        }
+    };
+    let session_id = session.id.clone();
+    let client = crate::http::client(app);
+
+    tauri::async_runtime::spawn(async move {
+        for hook in hooks {
+            match run_hook(&client, &hook, &session_id, payload.clone()).await {
+                Ok(()) => println!("Post-import hook '{}' done for {}", hook.name, session_id),
+                Err(e) => eprintln!("Post-import hook '{}' failed for {}: {}", hook.name, session_id, e),
+            }
+        }
+    });
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use rusqlite::{params, OptionalExtension};
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::http::SendPaced;
+
+// ===== Local Hothash Resolution =====
+//
+// The hothash is computed by imalink-core from the hotpreview it generates,
+// so it can't be reproduced locally from the file bytes alone. Instead we keep
+// an index of content hash (BLAKE3 of the original) → hothash for every file
+// core has processed, and fall back to core's lightweight `/v1/hothash`
+// endpoint, which hashes without building previews or reading EXIF. With a
+// hothash in hand the pipeline can ask the backend whether the photo already
+// exists and skip both processing and upload.
+//
+// The index is an append-only text file of `<content_hash> <hothash>` lines.
+//
+// Hashing is itself a full read of every file, which dominates when the same
+// card goes in again. content_hash remembers the hash per path together with
+// the file's size and modification time (`file_fingerprints` in the history
+// database) and reuses it while both are unchanged. A hothash that local
+// history records as imported skips the backend lookup as well, unless the
+// import asks to recheck (ImportOptions::recheck_imported).
+
+const INDEX_FILE: &str = "hothash_index.txt";
+
+pub struct HothashIndex {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, String>>,
+    // Cleared when core answers 404/405, so we stop asking this session
+    core_endpoint_available: AtomicBool,
+}
+
+impl HothashIndex {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(INDEX_FILE);
+        let entries = fs::read_to_string(&path)
+            .map(|text| {
+                text.lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .map(|(content_hash, hothash)| (content_hash.to_string(), hothash.trim().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        HothashIndex {
+            path,
+            entries: Mutex::new(entries),
+            core_endpoint_available: AtomicBool::new(true),
+        }
+    }
+
+    pub fn get(&self, content_hash: &str) -> Option<String> {
+        self.entries.lock().ok()?.get(content_hash).cloned()
+    }
+
+    pub fn insert(&self, content_hash: &str, hothash: &str) {
+        if content_hash.is_empty() || hothash.is_empty() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else { return };
+        if entries.get(content_hash).map(|h| h.as_str()) == Some(hothash) {
+            return;
+        }
+        entries.insert(content_hash.to_string(), hothash.to_string());
+
+        let appended = (|| {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = fs::File::options().create(true).append(true).open(&self.path)?;
+            writeln!(file, "{} {}", content_hash, hothash)
+        })();
+        if let Err(e) = appended {
+            eprintln!("Failed to update hothash index: {}", e);
+        }
+    }
+
+    // Hothash for a file: local index first, then core's hash-only endpoint.
+    // Returns None when neither can tell us without full processing.
+    pub async fn resolve(
+        &self,
+        client: &reqwest::Client,
+        core_api_url: &str,
+        content_hash: &str,
+        file_path: &str,
+    ) -> Option<String> {
+        if let Some(hothash) = self.get(content_hash) {
+            return Some(hothash);
+        }
+        if !self.core_endpoint_available.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        match request_core_hothash(client, core_api_url, file_path).await {
+            Ok(Some(hothash)) => {
+                self.insert(content_hash, &hothash);
+                Some(hothash)
+            }
+            Ok(None) => {
+                println!("imalink-core has no /v1/hothash endpoint, falling back to full processing");
+                self.core_endpoint_available.store(false, Ordering::SeqCst);
+                None
+            }
+            Err(e) => {
+                eprintln!("Hothash request failed for {}: {}", file_path, e);
+                None
+            }
+        }
+    }
+}
+
+// Size and modification time (ns since the epoch) of a file
+fn fingerprint(path: &str) -> Result<(i64, i64), ImalinkError> {
+    let metadata = fs::metadata(path).map_err(|e| ImalinkError::io(path, e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or_default();
+    Ok((metadata.len() as i64, modified))
+}
+
+// Content hash remembered for a file, while its size and modification time
+// are unchanged
+fn remembered_hash(history: &History, path: &str, size: i64, modified: i64) -> Result<Option<String>, ImalinkError> {
+    history.with(|conn| {
+        conn.query_row(
+            "SELECT content_hash FROM file_fingerprints WHERE path = ?1 AND size = ?2 AND modified = ?3",
+            params![path, size, modified],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+}
+
+// Content hash of a file if an earlier import took it, without hashing
+pub fn known_content_hash(history: &History, path: &str) -> Option<String> {
+    let (size, modified) = fingerprint(path).ok()?;
+    remembered_hash(history, path, size, modified).ok().flatten()
+}
+
+// Content hash of a file, read from history while its size and modification
+// time are unchanged and computed (and remembered) otherwise
+pub fn content_hash(history: &History, path: &str) -> Result<String, ImalinkError> {
+    let (size, modified) = fingerprint(path)?;
+    if let Some(hash) = remembered_hash(history, path, size, modified)? {
+        return Ok(hash);
+    }
+
+    let hash = crate::streaming::hash_file(path)?;
+    let recorded = history.with(|conn| {
+        conn.execute(
+            "INSERT INTO file_fingerprints (path, size, modified, content_hash, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (path) DO UPDATE SET size = ?2, modified = ?3, content_hash = ?4, recorded_at = ?5",
+            params![path, size, modified, hash, chrono::Utc::now().to_rfc3339()],
+        )
+        .map(|_| ())
+    });
+    if let Err(e) = recorded {
+        eprintln!("Failed to remember content hash of {}: {}", path, e);
+    }
+    Ok(hash)
+}
+
+// Backend id of the photo when local history has it as imported
+pub fn find_imported(history: &History, hothash: &str) -> Option<i32> {
+    history.get(hothash).ok().flatten()?.photo_id
+}
+
+// Ask core to compute only the hothash. Ok(None) means the endpoint doesn't exist.
+async fn request_core_hothash(
+    client: &reqwest::Client,
+    core_api_url: &str,
+    file_path: &str,
+) -> Result<Option<String>, ImalinkError> {
+    let form = reqwest::multipart::Form::new().part("file", crate::streaming::file_part(file_path).await?);
+
+    let response = crate::remote_core::request(client, reqwest::Method::POST, core_api_url, "/v1/hothash")
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| ImalinkError::network(crate::remote_core::url(core_api_url), e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::Core { status: status.as_u16(), detail: error_text });
+    }
+
+    let body: serde_json::Value = response.json().await?;
+    Ok(body.get("hothash").and_then(|h| h.as_str()).map(|h| h.to_string()))
+}
+
+// Look up a photo on the backend by hothash. Returns the photo id if it exists.
+pub async fn find_backend_photo(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    hothash: &str,
+) -> Result<Option<i32>, ImalinkError> {
+    let photo = fetch_backend_photo(client, backend_url, auth_token, hothash).await?;
+    Ok(photo.and_then(|p| p.get("id")?.as_i64()).map(|id| id as i32))
+}
+
+// The backend's photo with this hothash, as returned
+pub async fn fetch_backend_photo(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    hothash: &str,
+) -> Result<Option<serde_json::Value>, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/photos/hothash/{}", backend_url, hothash))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+
+    Ok(Some(response.json().await?))
+}
@@ -0,0 +1,86 @@
+use base64::Engine;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+
+use crate::error::ImalinkError;
+use crate::PhotoCreateSchema;
+
+// ===== Hotpreview Size Guard =====
+//
+// Some sources - old phones, scanned thumbnails, files whose embedded preview
+// core picks up - come back from processing with a hotpreview of a few dozen
+// pixels, which the grids then upscale into a smear. Before upload the
+// pipeline checks the hotpreview's longer edge against
+// `min_hotpreview_px` (settings) and, when it falls short, builds a new one:
+//
+//   1. from the original, when it is a JPEG or PNG we can decode locally
+//      (EXIF orientation applied, as core does)
+//   2. otherwise from the coldpreview core already sent along, which is
+//      requested at 800px
+//
+// A replacement is only used if it is actually larger than what core made,
+// so a genuinely tiny original keeps its preview rather than being upscaled.
+// The hothash is left alone: it identifies the photo, not the preview bytes.
+
+// Longer edge of regenerated hotpreviews, the size core makes them
+pub const HOTPREVIEW_SIZE: u32 = 150;
+pub const DEFAULT_MIN_PX: u32 = 100;
+
+const JPEG_QUALITY: u8 = 85;
+
+fn decode_original(path: &str) -> Option<DynamicImage> {
+    let reader = ImageReader::open(path).ok()?.with_guessed_format().ok()?;
+    match reader.format() {
+        Some(image::ImageFormat::Jpeg) | Some(image::ImageFormat::Png) => {}
+        _ => return None,
+    }
+    let mut decoder = reader.into_decoder().ok()?;
+    let orientation = decoder.orientation().ok();
+    let mut img = DynamicImage::from_decoder(decoder).ok()?;
+    if let Some(orientation) = orientation {
+        img.apply_orientation(orientation);
+    }
+    Some(img)
+}
+
+fn decode_coldpreview(schema: &PhotoCreateSchema) -> Option<DynamicImage> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(schema.coldpreview_base64.as_deref()?)
+        .ok()?;
+    image::load_from_memory(&bytes).ok()
+}
+
+fn encode(img: &DynamicImage) -> Result<(Vec<u8>, u32, u32), ImalinkError> {
+    let small = if img.width().max(img.height()) > HOTPREVIEW_SIZE {
+        img.thumbnail(HOTPREVIEW_SIZE, HOTPREVIEW_SIZE).to_rgb8()
+    } else {
+        img.to_rgb8()
+    };
+    let mut out = Vec::new();
+    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY);
+    small
+        .write_with_encoder(encoder)
+        .map_err(|e| ImalinkError::internal(format!("Failed to encode hotpreview: {}", e)))?;
+    Ok((out, small.width(), small.height()))
+}
+
+// Replace the schema's hotpreview when its longer edge is below `min_px`.
+// Returns whether it was replaced. Blocking - decodes the original.
+pub fn ensure_size(schema: &mut PhotoCreateSchema, original: &str, min_px: u32) -> Result<bool, ImalinkError> {
+    let current = schema.hotpreview_width.max(schema.hotpreview_height).max(0) as u32;
+    if min_px == 0 || current >= min_px {
+        return Ok(false);
+    }
+
+    let Some(img) = decode_original(original).or_else(|| decode_coldpreview(schema)) else {
+        return Ok(false);
+    };
+    if img.width().max(img.height()) <= current {
+        return Ok(false);
+    }
+
+    let (bytes, width, height) = encode(&img)?;
+    schema.hotpreview_base64 = base64::engine::general_purpose::STANDARD.encode(bytes);
+    schema.hotpreview_width = width as i32;
+    schema.hotpreview_height = height as i32;
+    Ok(true)
+}
@@ -0,0 +1,77 @@
+use std::future::Future;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::ratelimit;
+
+// ===== Shared HTTP Client =====
+//
+// One reqwest::Client in managed state, cloned by every command so calls to
+// core and the backend share its connection pool. It has no overall
+// timeout: long transfers are guarded by stall.rs and invocations.rs.
+// Backend requests go out with send_paced, which honours rate limits (see
+// ratelimit.rs).
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+// Managed state
+pub struct HttpClient(reqwest::Client);
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("imalink-desktop/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .build()
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to configure HTTP client, using defaults: {}", e);
+                reqwest::Client::new()
+            });
+        HttpClient(client)
+    }
+}
+
+impl HttpClient {
+    pub fn client(&self) -> reqwest::Client {
+        self.0.clone()
+    }
+}
+
+pub fn client(app: &tauri::AppHandle) -> reqwest::Client {
+    app.state::<HttpClient>().client()
+}
+
+// Times a paced request is sent again after a 429
+const RATE_LIMIT_RETRIES: usize = 3;
+
+pub trait SendPaced {
+    // send, waiting out a rate limit pause first and after a 429. Requests
+    // whose body can't be replayed get the 429 back instead.
+    fn send_paced(self) -> impl Future<Output = reqwest::Result<reqwest::Response>> + Send;
+}
+
+impl SendPaced for reqwest::RequestBuilder {
+    async fn send_paced(self) -> reqwest::Result<reqwest::Response> {
+        let mut request = self;
+        let mut attempt = 0;
+        loop {
+            ratelimit::wait().await;
+            let retry = request.try_clone().filter(|_| attempt < RATE_LIMIT_RETRIES);
+            let response = request.send().await?;
+            let limited = ratelimit::observe(response.status(), response.headers());
+            match (limited, retry) {
+                (Some(_), Some(again)) => {
+                    request = again;
+                    attempt += 1;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ImalinkError;
+use crate::{PhotoCreateRequest, PhotoCreateSchema};
+
+// ===== Upload Integrity =====
+//
+// Every photo create request carries BLAKE3 checksums of the preview bytes
+// it sends and of the original, so a request truncated or corrupted on the
+// way (a proxy cutting the body short, a bad base64 re-encode) is caught:
+//
+//   hotpreview   the decoded hotpreview JPEG
+//   coldpreview  the decoded coldpreview, when sent
+//   original     the master file, from the content hash the pipeline took
+//
+// The backend doesn't know a field for them yet, so they ride along in
+// `exif_dict` under CHECKSUM_KEY; with `upload_checksum_field` (settings) on,
+// for backends that accept it, they go in the request's own `checksums`
+// field instead. Whatever of them the backend echoes back in its answer,
+// either way, must match what was sent, or the upload fails with
+// ChecksumMismatch (retryable - sending again usually gets it through).
+// Backends that echo nothing are taken on trust.
+
+pub const CHECKSUM_KEY: &str = "imalink_checksums";
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UploadChecksums {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotpreview: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coldpreview: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
+}
+
+fn preview_checksum(base64: &str) -> Option<String> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(base64).ok()?;
+    Some(format!("blake3:{}", blake3::hash(&bytes).to_hex()))
+}
+
+// Checksums of what `schema` carries now
+pub fn checksums(schema: &PhotoCreateSchema) -> UploadChecksums {
+    let original = schema
+        .image_file_list
+        .first()
+        .and_then(|master| master.local_storage_info.as_ref()?.get("content_hash")?.as_str())
+        .map(str::to_string);
+    UploadChecksums {
+        hotpreview: Some(schema.hotpreview_base64.as_str()).filter(|p| !p.is_empty()).and_then(preview_checksum),
+        coldpreview: schema.coldpreview_base64.as_deref().and_then(preview_checksum),
+        original,
+    }
+}
+
+// Put the checksums of the request's previews in, where `dedicated` says.
+// Returns whether they changed, i.e. the request needs serializing again -
+// payload::fit may have shrunk the coldpreview since they were taken.
+pub fn attach(request: &mut PhotoCreateRequest, dedicated: bool) -> bool {
+    let sums = checksums(&request.photo_create_schema);
+    if dedicated {
+        if request.checksums.as_ref() == Some(&sums) {
+            return false;
+        }
+        request.checksums = Some(sums);
+        return true;
+    }
+
+    let exif_dict = &mut request.photo_create_schema.exif_dict;
+    if !exif_dict.is_object() {
+        *exif_dict = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let value = serde_json::to_value(&sums).unwrap_or_default();
+    if exif_dict.get(CHECKSUM_KEY) == Some(&value) {
+        return false;
+    }
+    exif_dict[CHECKSUM_KEY] = value;
+    true
+}
+
+// Compare the checksums the backend echoed in `response_text` (a created
+// photo) with those sent
+pub fn verify(sent: &UploadChecksums, hothash: &str, response_text: &str) -> Result<(), ImalinkError> {
+    let Ok(response) = serde_json::from_str::<serde_json::Value>(response_text) else {
+        return Ok(());
+    };
+    let echoed = response
+        .get("checksums")
+        .filter(|c| !c.is_null())
+        .or_else(|| response.get("exif_dict")?.get(CHECKSUM_KEY))
+        .and_then(|c| serde_json::from_value::<UploadChecksums>(c.clone()).ok());
+    let Some(echoed) = echoed else {
+        return Ok(());
+    };
+
+    let parts = [
+        ("hotpreview", &sent.hotpreview, &echoed.hotpreview),
+        ("coldpreview", &sent.coldpreview, &echoed.coldpreview),
+        ("original", &sent.original, &echoed.original),
+    ];
+    for (part, sent, echoed) in parts {
+        if let (Some(sent), Some(echoed)) = (sent, echoed) {
+            if !sent.eq_ignore_ascii_case(echoed) {
+                eprintln!("Checksum mismatch for {} of {}: sent {}, backend has {}", part, hothash, sent, echoed);
+                return Err(ImalinkError::ChecksumMismatch { hothash: hothash.to_string(), part: part.to_string() });
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+use tokio::sync::Notify;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+
+// ===== Command Cancellation =====
+//
+// Long single commands take an optional `call` argument,
+// `{ id, timeout_seconds }`: cancel_command(id) stops the command with
+// `cancelled`, and after `timeout_seconds` it returns `timed_out`. Background
+// work (imports, prefetch, exports) is cancelled through operations.rs.
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CallOptions {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
+
+// Managed state: abort signal per running invocation id
+#[derive(Default)]
+pub struct Invocations(Mutex<HashMap<String, Arc<Notify>>>);
+
+// Removes the invocation when the command returns, however it returns
+struct Registration<'a> {
+    invocations: &'a Invocations,
+    id: Option<String>,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            self.invocations.0.lock().unwrap().remove(id);
+        }
+    }
+}
+
+// Run a command body under the call's id and timeout
+pub async fn run<T>(
+    app: &tauri::AppHandle,
+    call: Option<CallOptions>,
+    body: impl Future<Output = Result<T, ImalinkError>>,
+) -> Result<T, ImalinkError> {
+    let call = call.unwrap_or_default();
+    let invocations = app.state::<Invocations>();
+    let abort = Arc::new(Notify::new());
+    if let Some(id) = &call.id {
+        let mut running = invocations.0.lock().unwrap();
+        if running.contains_key(id) {
+            return Err(ImalinkError::invalid(format!("A command with id {} is already running", id)));
+        }
+        running.insert(id.clone(), abort.clone());
+    }
+    let _registration = Registration { invocations: &invocations, id: call.id.clone() };
+
+    let timeout = async {
+        match call.timeout_seconds {
+            Some(seconds) => tokio::time::sleep(Duration::from_secs(seconds)).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = body => result,
+        _ = abort.notified() => Err(ImalinkError::Cancelled { id: call.id.unwrap_or_default() }),
+        _ = timeout => Err(ImalinkError::TimedOut { seconds: call.timeout_seconds.unwrap_or_default() }),
+    }
+}
+
+// Stop the running command with this id. Returns false when none is running
+// (it may just have finished).
+#[tauri::command]
+pub fn cancel_command(
+    invocations: tauri::State<'_, Invocations>,
+    access: tauri::State<'_, AccessMode>,
+    id: String,
+) -> Result<bool, ImalinkError> {
+    access.require_owner("cancel operations")?;
+    match invocations.0.lock().unwrap().get(&id) {
+        Some(abort) => {
+            abort.notify_one();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::session::Session;
+use crate::staging::{self, StagedFile};
+use crate::{pipeline, presets};
+
+// ===== iPhone Import over USB =====
+//
+// Talks to a connected (and trusted) iOS device through the libimobiledevice
+// command line tools, which must be installed and on PATH:
+//
+//   idevice_id -l                  connected device UDIDs
+//   ideviceinfo -u <udid>          device name, model, iOS version
+//   afcclient -u <udid> ls|get     the camera roll under /DCIM over AFC
+//
+// Selected assets are copied to a staging folder and imported through the
+// normal pipeline with a preset. Live Photos arrive as IMG_0001.HEIC +
+// IMG_0001.MOV and are grouped as master + companion like any other pair.
+
+const DCIM: &str = "/DCIM";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IosDevice {
+    pub udid: String,
+    pub name: Option<String>,
+    pub product_type: Option<String>,
+    pub ios_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IosAssetKind {
+    Photo,
+    Video,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IosAsset {
+    // Path on the device, e.g. /DCIM/100APPLE/IMG_0001.HEIC
+    pub path: String,
+    pub name: String,
+    pub kind: IosAssetKind,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IosImport {
+    // None when nothing could be copied off the device
+    pub session_id: Option<String>,
+    pub downloads: BatchResult<StagedFile>,
+}
+
+fn asset_kind(name: &str) -> Option<IosAssetKind> {
+    let ext = Path::new(name).extension()?.to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "heic" | "heif" | "jpg" | "jpeg" | "png" | "dng" => Some(IosAssetKind::Photo),
+        "mov" | "mp4" => Some(IosAssetKind::Video),
+        _ => None,
+    }
+}
+
+// Run a libimobiledevice tool and return its stdout
+async fn run_tool(program: &str, args: &[&str]) -> Result<String, ImalinkError> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImalinkError::invalid(format!(
+                "{} not found - install libimobiledevice to import from iPhone",
+                program
+            )),
+            _ => ImalinkError::io(program, e),
+        })?;
+    if !output.status.success() {
+        return Err(ImalinkError::internal(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// `Key: value` lines from ideviceinfo
+fn parse_info(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once(": "))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+// Entry names in a device directory (without . and ..)
+async fn list_dir(udid: &str, path: &str) -> Result<Vec<String>, ImalinkError> {
+    let text = run_tool("afcclient", &["-u", udid, "ls", path]).await?;
+    Ok(text
+        .lines()
+        .map(|line| line.trim().trim_end_matches('/').to_string())
+        .filter(|name| !name.is_empty() && name != "." && name != "..")
+        .collect())
+}
+
+#[tauri::command]
+pub async fn list_ios_devices() -> Result<Vec<IosDevice>, ImalinkError> {
+    let ids = run_tool("idevice_id", &["-l"]).await?;
+    let mut devices = Vec::new();
+    for udid in ids.lines().map(str::trim).filter(|id| !id.is_empty()) {
+        // A device that hasn't been trusted yet still shows up, just without info
+        let info = run_tool("ideviceinfo", &["-u", udid]).await.map(|text| parse_info(&text)).unwrap_or_default();
+        devices.push(IosDevice {
+            udid: udid.to_string(),
+            name: info.get("DeviceName").cloned(),
+            product_type: info.get("ProductType").cloned(),
+            ios_version: info.get("ProductVersion").cloned(),
+        });
+    }
+    Ok(devices)
+}
+
+// Photos and videos in the device's camera roll, oldest folder first
+#[tauri::command]
+pub async fn list_ios_camera_roll(udid: String) -> Result<Vec<IosAsset>, ImalinkError> {
+    let mut folders = list_dir(&udid, DCIM).await?;
+    folders.sort();
+
+    let mut assets = Vec::new();
+    for folder in folders {
+        let folder_path = format!("{}/{}", DCIM, folder);
+        let mut names = list_dir(&udid, &folder_path).await?;
+        names.sort();
+        assets.extend(names.into_iter().filter_map(|name| {
+            let kind = asset_kind(&name)?;
+            Some(IosAsset { path: format!("{}/{}", folder_path, name), name, kind })
+        }));
+    }
+    Ok(assets)
+}
+
+async fn copy_off_device(udid: &str, remote: &str, workspace: &Path) -> Result<StagedFile, ImalinkError> {
+    if !remote.starts_with(DCIM) || remote.contains("..") {
+        return Err(ImalinkError::invalid(format!("Not a camera roll path: {}", remote)));
+    }
+    // Keep the folder in the name: 100APPLE/IMG_0001 and 101APPLE/IMG_0001 both exist
+    let relative = remote.trim_start_matches(DCIM).trim_start_matches('/');
+    let local = workspace.join(relative);
+    if let Some(parent) = local.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+    }
+
+    let local_str = local.to_string_lossy().to_string();
+    run_tool("afcclient", &["-u", udid, "get", remote, &local_str]).await?;
+    let bytes = std::fs::metadata(&local).map_err(|e| ImalinkError::io(local.display(), e))?.len();
+    Ok(StagedFile {
+        source: remote.to_string(),
+        path: local_str,
+        bytes,
+    })
+}
+
+// Copy the selected assets off the device and import them with a preset
+#[tauri::command]
+pub async fn import_from_ios(
+    app: tauri::AppHandle,
+    udid: String,
+    assets: Vec<String>,
+    preset: String,
+    auth_token: Option<String>,
+) -> Result<IosImport, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    if udid.is_empty() || !udid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ImalinkError::invalid(format!("Invalid device id: {}", udid)));
+    }
+    let preset = presets::find(&app, &preset)?;
+    let batch = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let workspace = staging::workspace(&app, &format!("ios/{}", udid), &batch)?;
+
+    let mut downloads = BatchResult::new();
+    for remote in &assets {
+        downloads.record(remote.clone(), copy_off_device(&udid, remote, &workspace).await);
+    }
+    if downloads.succeeded.is_empty() {
+        let _ = std::fs::remove_dir_all(&workspace);
+        return Ok(IosImport { session_id: None, downloads });
+    }
+
+    let files: Vec<String> = downloads.succeeded.iter().map(|f| f.path.clone()).collect();
+    let options = preset.import_options(&workspace.to_string_lossy(), Some(files), &auth_token);
+    let session_id = pipeline::spawn_import(&app, options)?;
+    Ok(IosImport {
+        session_id: Some(session_id),
+        downloads,
+    })
+}
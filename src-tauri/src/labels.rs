@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::CompanionGroup;
+use crate::PhotoCreateSchema;
+
+// ===== Color Labels and Flags =====
+//
+// Culling marks beyond the 0-5 star rating: a color label and a pick/reject
+// flag. They come from the user in the pending import
+// (ImportOptions::marks, per file) or from an XMP sidecar next to the
+// master - IMG_0001.xmp or IMG_0001.CR3.xmp - as written by Lightroom,
+// Bridge, Capture One or darktable:
+//
+//   xmp:Rating      0-5 stars; -1 (Lightroom's "rejected") is a reject flag
+//   xmp:Label       Red, Yellow, Green, Blue, Purple (or Bridge's Select,
+//                   Second, Approved, Review, To Do in that order)
+//   xmpDM:pick      1 = pick, -1 = reject
+//
+// Marks set in the app win over the sidecar. The backend has no fields for
+// them, so they are sent as photo tags with a fixed prefix:
+//
+//   label:red  label:yellow  label:green  label:blue  label:purple
+//   flag:pick  flag:reject
+//
+// The rating goes into the normal rating field.
+
+pub const LABEL_TAG_PREFIX: &str = "label:";
+pub const FLAG_TAG_PREFIX: &str = "flag:";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorLabel {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl ColorLabel {
+    fn name(self) -> &'static str {
+        match self {
+            ColorLabel::Red => "red",
+            ColorLabel::Yellow => "yellow",
+            ColorLabel::Green => "green",
+            ColorLabel::Blue => "blue",
+            ColorLabel::Purple => "purple",
+        }
+    }
+
+    // xmp:Label value, by color name or Bridge's default label names
+    fn from_xmp(label: &str) -> Option<Self> {
+        match label.trim().to_lowercase().as_str() {
+            "red" | "select" => Some(ColorLabel::Red),
+            "yellow" | "second" => Some(ColorLabel::Yellow),
+            "green" | "approved" => Some(ColorLabel::Green),
+            "blue" | "review" => Some(ColorLabel::Blue),
+            "purple" | "to do" => Some(ColorLabel::Purple),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    Pick,
+    Reject,
+}
+
+impl Flag {
+    fn name(self) -> &'static str {
+        match self {
+            Flag::Pick => "pick",
+            Flag::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CullMarks {
+    #[serde(default)]
+    pub rating: Option<i32>,
+    #[serde(default)]
+    pub color_label: Option<ColorLabel>,
+    #[serde(default)]
+    pub flag: Option<Flag>,
+}
+
+impl CullMarks {
+    pub fn is_empty(&self) -> bool {
+        *self == CullMarks::default()
+    }
+
+    // Fields set here replace those of `base`
+    fn over(self, base: CullMarks) -> CullMarks {
+        CullMarks {
+            rating: self.rating.or(base.rating),
+            color_label: self.color_label.or(base.color_label),
+            flag: self.flag.or(base.flag),
+        }
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        let label = self.color_label.map(|l| format!("{}{}", LABEL_TAG_PREFIX, l.name()));
+        let flag = self.flag.map(|f| format!("{}{}", FLAG_TAG_PREFIX, f.name()));
+        label.into_iter().chain(flag).collect()
+    }
+}
+
+fn sidecar_candidates(file: &str) -> [PathBuf; 4] {
+    let path = Path::new(file);
+    [
+        path.with_extension("xmp"),
+        path.with_extension("XMP"),
+        PathBuf::from(format!("{}.xmp", file)),
+        PathBuf::from(format!("{}.XMP", file)),
+    ]
+}
+
+// Value of an XMP property, written either as an attribute (xmp:Label="Red")
+// or as an element (<xmp:Label>Red</xmp:Label>)
+fn xmp_property<'a>(xmp: &'a str, name: &str) -> Option<&'a str> {
+    let attribute = format!("{}=\"", name);
+    if let Some(start) = xmp.find(&attribute).map(|i| i + attribute.len()) {
+        return xmp[start..].split('"').next();
+    }
+    let open = format!("<{}>", name);
+    let start = xmp.find(&open)? + open.len();
+    xmp[start..].split('<').next().map(str::trim)
+}
+
+pub fn parse_xmp(xmp: &str) -> CullMarks {
+    let rating = xmp_property(xmp, "xmp:Rating").and_then(|r| r.trim().parse::<f64>().ok());
+    let pick = xmp_property(xmp, "xmpDM:pick").and_then(|p| p.trim().parse::<i32>().ok());
+    let flag = match (pick, rating) {
+        (Some(1), _) => Some(Flag::Pick),
+        (Some(-1), _) => Some(Flag::Reject),
+        (_, Some(r)) if r < 0.0 => Some(Flag::Reject),
+        _ => None,
+    };
+    CullMarks {
+        rating: rating.filter(|r| *r >= 0.0).map(|r| (r.round() as i32).min(5)),
+        color_label: xmp_property(xmp, "xmp:Label").and_then(ColorLabel::from_xmp),
+        flag,
+    }
+}
+
+// Marks from the first XMP sidecar found for any of `files`
+pub fn read_sidecar(files: &[String]) -> CullMarks {
+    files
+        .iter()
+        .flat_map(|f| sidecar_candidates(f))
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|xmp| parse_xmp(&xmp))
+        .unwrap_or_default()
+}
+
+// Marks for a group, the same whichever of its files was marked: the user's
+// over the sidecar's. When files of the group were marked differently, the
+// master's marks win field by field and companions fill in what it lacks.
+pub fn marks_for(group: &CompanionGroup, user_marks: &HashMap<String, CullMarks>) -> CullMarks {
+    let files = group.all_files();
+    let user = files
+        .iter()
+        .filter_map(|f| user_marks.get(f).cloned())
+        .fold(CullMarks::default(), CullMarks::over);
+    user.over(read_sidecar(&files))
+}
+
+// Put the marks into the schema: rating (clamped to 0-5), label and flag as tags
+pub fn apply(schema: &mut PhotoCreateSchema, marks: &CullMarks) {
+    if let Some(rating) = marks.rating {
+        schema.rating = Some(rating.clamp(0, 5));
+    }
+    for tag in marks.tags() {
+        if !schema.tags.contains(&tag) {
+            schema.tags.push(tag);
+        }
+    }
+}
+
+// Marks from the XMP sidecars of `files`, for showing in the pending import.
+// Files without a sidecar or marks are left out.
+#[tauri::command]
+pub fn read_cull_marks(files: Vec<String>) -> HashMap<String, CullMarks> {
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let marks = read_sidecar(std::slice::from_ref(&file));
+            (!marks.is_empty()).then_some((file, marks))
+        })
+        .collect()
+}
@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::{History, UploadRecord};
+use crate::privacy::{self, GpsScrub, PrivacyZone};
+use crate::session::Session;
+use crate::{ImageFileSchema, PhotoCreateSchema};
+
+// ===== Legacy PhotoEgg Migration =====
+//
+// imalink v1 exported photos as PhotoEgg JSON: flat objects with the hothash,
+// previews and a fixed set of EXIF fields (camera, exposure, lens). Exports
+// come as a single egg, an array of eggs, `{ "photo_eggs": [...] }`, or a zip
+// archive of such JSON files.
+//
+// Each egg is mapped onto a PhotoCreateSchema. Eggs exported without
+// previews are completed by running the referenced original through core;
+// the legacy metadata (capture time, GPS, rating) is kept over what core
+// reads, since it may have been corrected by hand in v1. The result is
+// uploaded like any other import and recorded in local history.
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+struct PhotoEgg {
+    hothash: Option<String>,
+    hotpreview_base64: Option<String>,
+    hotpreview_width: Option<i32>,
+    hotpreview_height: Option<i32>,
+    coldpreview_base64: Option<String>,
+    coldpreview_width: Option<i32>,
+    coldpreview_height: Option<i32>,
+    width: Option<i32>,
+    height: Option<i32>,
+    taken_at: Option<String>,
+    gps_latitude: Option<f64>,
+    gps_longitude: Option<f64>,
+    rating: Option<i32>,
+    #[serde(alias = "filename")]
+    primary_filename: Option<String>,
+    #[serde(alias = "original_path", alias = "file_path")]
+    source_path: Option<String>,
+    file_size: Option<i64>,
+    camera_make: Option<String>,
+    camera_model: Option<String>,
+    lens_make: Option<String>,
+    lens_model: Option<String>,
+    iso: Option<i64>,
+    aperture: Option<f64>,
+    shutter_speed: Option<String>,
+    focal_length: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EggFile {
+    Wrapped { photo_eggs: Vec<PhotoEgg> },
+    Many(Vec<PhotoEgg>),
+    One(Box<PhotoEgg>),
+}
+
+impl EggFile {
+    fn into_eggs(self) -> Vec<PhotoEgg> {
+        match self {
+            EggFile::Wrapped { photo_eggs } => photo_eggs,
+            EggFile::Many(eggs) => eggs,
+            EggFile::One(egg) => vec![*egg],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LegacyImportOptions {
+    pub core_api_url: String,
+    // Taken from the login session when empty
+    #[serde(default)]
+    pub backend_url: String,
+    #[serde(default)]
+    pub auth_token: String,
+    pub input_channel_id: i32,
+    // Where to look for originals referenced by relative paths/filenames;
+    // defaults to the export's directory
+    #[serde(default)]
+    pub originals_dir: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MigratedPhoto {
+    pub hothash: String,
+    pub photo_id: i32,
+    pub is_duplicate: bool,
+    // True when previews had to be regenerated from the original
+    pub reprocessed: bool,
+    pub gps_scrubbed: Option<GpsScrub>,
+}
+
+fn parse_eggs(text: &str, source: &str) -> Result<Vec<PhotoEgg>, ImalinkError> {
+    serde_json::from_str::<EggFile>(text)
+        .map(EggFile::into_eggs)
+        .map_err(|e| ImalinkError::parse(format!("{} is not a PhotoEgg export: {}", source, e)))
+}
+
+// Read every egg from a .json export or a .zip of them
+fn read_eggs(path: &Path) -> Result<Vec<PhotoEgg>, ImalinkError> {
+    let is_zip = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        let text = fs::read_to_string(path).map_err(|e| ImalinkError::io(path.display(), e))?;
+        return parse_eggs(&text, &path.display().to_string());
+    }
+
+    let file = fs::File::open(path).map_err(|e| ImalinkError::io(path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| ImalinkError::parse(format!("Invalid archive {}: {}", path.display(), e)))?;
+
+    let mut eggs = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| ImalinkError::parse(format!("Invalid archive {}: {}", path.display(), e)))?;
+        if !entry.is_file() || !entry.name().to_lowercase().ends_with(".json") {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut text = String::new();
+        entry.read_to_string(&mut text).map_err(|e| ImalinkError::io(&name, e))?;
+        eggs.extend(parse_eggs(&text, &name)?);
+    }
+    Ok(eggs)
+}
+
+// Resolve the egg's original on disk, trying the recorded path first
+fn find_original(egg: &PhotoEgg, originals_dir: &Path) -> Option<PathBuf> {
+    let recorded = egg.source_path.as_deref().map(PathBuf::from);
+    let candidates = [
+        recorded.clone().filter(|p| p.is_absolute()),
+        recorded.map(|p| originals_dir.join(p)),
+        egg.primary_filename.as_deref().map(|name| originals_dir.join(name)),
+    ];
+    candidates.into_iter().flatten().find(|p| p.is_file())
+}
+
+fn legacy_exif(egg: &PhotoEgg) -> serde_json::Value {
+    let mut exif = serde_json::Map::new();
+    let mut put = |key: &str, value: serde_json::Value| {
+        if !value.is_null() {
+            exif.insert(key.to_string(), value);
+        }
+    };
+    put("Make", serde_json::json!(egg.camera_make));
+    put("Model", serde_json::json!(egg.camera_model));
+    put("LensMake", serde_json::json!(egg.lens_make));
+    put("LensModel", serde_json::json!(egg.lens_model));
+    put("ISOSpeedRatings", serde_json::json!(egg.iso));
+    put("FNumber", serde_json::json!(egg.aperture));
+    put("ExposureTime", serde_json::json!(egg.shutter_speed));
+    put("FocalLength", serde_json::json!(egg.focal_length));
+    serde_json::Value::Object(exif)
+}
+
+fn has_previews(egg: &PhotoEgg) -> bool {
+    egg.hothash.as_deref().is_some_and(|h| !h.is_empty())
+        && egg.hotpreview_base64.as_deref().is_some_and(|p| !p.is_empty())
+}
+
+// Map an egg onto a schema, starting from core's output when previews had to be regenerated
+fn to_schema(egg: &PhotoEgg, base: Option<PhotoCreateSchema>, original: Option<&Path>) -> PhotoCreateSchema {
+    let reprocessed = base.is_some();
+    let mut schema = base.unwrap_or_else(|| PhotoCreateSchema {
+        hothash: egg.hothash.clone().unwrap_or_default(),
+        hotpreview_base64: egg.hotpreview_base64.clone().unwrap_or_default(),
+        hotpreview_width: egg.hotpreview_width.unwrap_or_default(),
+        hotpreview_height: egg.hotpreview_height.unwrap_or_default(),
+        coldpreview_base64: egg.coldpreview_base64.clone(),
+        coldpreview_width: egg.coldpreview_width,
+        coldpreview_height: egg.coldpreview_height,
+        width: egg.width.unwrap_or_default(),
+        height: egg.height.unwrap_or_default(),
+        exif_dict: legacy_exif(egg),
+        ..Default::default()
+    });
+
+    // Legacy metadata wins over what core read from the file
+    if egg.taken_at.is_some() {
+        schema.taken_at = egg.taken_at.clone();
+    }
+    if egg.gps_latitude.is_some() && egg.gps_longitude.is_some() {
+        schema.gps_latitude = egg.gps_latitude;
+        schema.gps_longitude = egg.gps_longitude;
+    }
+    if egg.rating.is_some() {
+        schema.rating = egg.rating;
+    }
+
+    if schema.image_file_list.is_empty() {
+        if let Some(filename) = egg.primary_filename.clone().or_else(|| {
+            original.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string())
+        }) {
+            schema.image_file_list.push(ImageFileSchema {
+                filename,
+                file_size: egg.file_size.unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+    }
+    let imported_info = serde_json::json!({
+        "imported_at": chrono::Utc::now().to_rfc3339(),
+        "migrated_from": "photoegg",
+        "reprocessed": reprocessed,
+    });
+    for file in &mut schema.image_file_list {
+        file.imported_info = Some(imported_info.clone());
+        if let Some(original) = original {
+            file.local_storage_info = Some(serde_json::json!({
+                "import_mode": "register",
+                "source_path": original.to_string_lossy(),
+                "storage_path": original.to_string_lossy(),
+            }));
+        }
+    }
+    schema
+}
+
+async fn migrate_one(
+    client: &reqwest::Client,
+    egg: &PhotoEgg,
+    originals_dir: &Path,
+    options: &LegacyImportOptions,
+    privacy_zones: &[PrivacyZone],
+    stall: &crate::stall::StallPolicy,
+    limit: &crate::payload::PayloadLimit,
+) -> Result<(MigratedPhoto, UploadRecord), ImalinkError> {
+    let original = find_original(egg, originals_dir);
+
+    let base = if has_previews(egg) {
+        None
+    } else {
+        let Some(original) = &original else {
+            return Err(ImalinkError::FileNotFound {
+                path: egg
+                    .source_path
+                    .clone()
+                    .or_else(|| egg.primary_filename.clone())
+                    .unwrap_or_else(|| "original of PhotoEgg without previews".to_string()),
+            });
+        };
+        let path = original.to_string_lossy().to_string();
+        Some(crate::process_file(client, &path, &options.core_api_url).await?)
+    };
+
+    let reprocessed = base.is_some();
+    let mut schema = to_schema(egg, base, original.as_deref());
+    let gps_scrubbed = privacy::apply(privacy_zones, &mut schema);
+    let phash = crate::duplicates::phash_of(&schema);
+    let (width, height) = (schema.width, schema.height);
+    let response = crate::upload_schema(
+        client,
+        &options.backend_url,
+        &options.auth_token,
+        schema,
+        options.input_channel_id,
+        stall,
+        limit,
+    )
+    .await?;
+
+    let record = UploadRecord {
+        hothash: response.hothash.clone(),
+        photo_id: response.id,
+        file_path: original.map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+        input_channel_id: options.input_channel_id,
+        taken_at: egg.taken_at.clone(),
+        camera: crate::pipeline::camera_of(&legacy_exif(egg)),
+        bytes: egg.file_size.unwrap_or_default().max(0) as u64,
+        is_duplicate: response.is_duplicate,
+        phash,
+        width: Some(width).filter(|w| *w > 0),
+        height: Some(height).filter(|h| *h > 0),
+        ..Default::default()
+    };
+    Ok((
+        MigratedPhoto {
+            hothash: response.hothash,
+            photo_id: response.id,
+            is_duplicate: response.is_duplicate,
+            reprocessed,
+            gps_scrubbed,
+        },
+        record,
+    ))
+}
+
+// Import a legacy PhotoEgg export (.json or .zip) into the current backend
+#[tauri::command]
+pub async fn migrate_photo_eggs(
+    app: tauri::AppHandle,
+    path: String,
+    mut options: LegacyImportOptions,
+) -> Result<BatchResult<MigratedPhoto>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    app.state::<Session>().fill(&mut options.backend_url, &mut options.auth_token)?;
+    let export_path = PathBuf::from(&path);
+    if !export_path.is_file() {
+        return Err(ImalinkError::FileNotFound { path });
+    }
+    let originals_dir = options
+        .originals_dir
+        .as_deref()
+        .map(PathBuf::from)
+        .or_else(|| export_path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    let read_path = export_path.clone();
+    let eggs = tauri::async_runtime::spawn_blocking(move || read_eggs(&read_path))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))??;
+
+    let settings = crate::settings::load(&app);
+    let stall = crate::stall::StallPolicy::from_settings(&settings);
+    let limit = crate::payload::PayloadLimit::from_settings(&settings);
+    let privacy_zones = settings.privacy_zones;
+    let client = crate::http::client(&app);
+    let history = app.state::<History>();
+    let mut result = BatchResult::new();
+    for (i, egg) in eggs.iter().enumerate() {
+        let item = egg
+            .hothash
+            .clone()
+            .or_else(|| egg.primary_filename.clone())
+            .unwrap_or_else(|| format!("#{}", i + 1));
+
+        match migrate_one(&client, egg, &originals_dir, &options, &privacy_zones, &stall, &limit).await {
+            Ok((migrated, record)) => {
+                if let Err(e) = history.record_upload(&record) {
+                    eprintln!("Failed to record {} in history: {}", migrated.hothash, e);
+                }
+                result.succeed(migrated);
+            }
+            Err(e) => result.fail(item, e),
+        }
+    }
+    Ok(result)
+}
@@ -1,21 +1,174 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::Mutex;
-use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_shell::ShellExt;
 
+mod assets;
+mod auth;
+mod authors;
+mod backup;
+mod batch;
+mod batch_upload;
+mod benchmark;
+mod cards;
+mod channel_description;
+mod channels;
+mod checksums;
+mod coldpreviews;
+mod compare;
+mod crash;
+mod disk_usage;
+mod dng;
+mod editor;
+mod email_import;
+mod duplicates;
+mod error;
+mod events;
+mod existence;
+mod export;
+mod guest;
+#[cfg(feature = "mock")]
+pub mod harness;
+mod health;
+mod history;
+mod hooks;
+mod hothash;
+mod hotpreview;
+mod http;
+mod integrity;
+mod invocations;
+mod ios;
+mod labels;
+mod legacy;
+mod low_disk;
+mod maintenance;
+mod metrics;
+#[cfg(feature = "mock")]
+mod mock;
+mod multishot;
+mod offline;
+mod operations;
+mod originals;
+mod payload;
+mod personal_data;
+mod photo_lookup;
+mod pipeline;
+mod plugins;
+mod prefetch;
+mod preflight;
+mod presets;
+mod preview_store;
+mod privacy;
+mod progress;
+mod quarantine;
+mod queue;
+mod ratelimit;
+mod recovery;
+mod reimport;
+mod remote;
+mod remote_core;
+mod rename;
+mod scan;
+mod schema_cache;
+mod scheduler;
+mod self_author;
+mod sequences;
+mod session;
+mod session_notes;
+mod settings;
+mod settings_sync;
+mod slideshow;
+mod snapshot;
+mod staging;
+mod stall;
+mod stats;
+mod streaming;
+mod sync;
+mod tether;
+mod transcode;
+mod undo;
+mod visibility;
+mod url_import;
+mod watchdog;
+mod workspace;
+
+use batch::BatchResult;
+use error::ImalinkError;
+use guest::AccessMode;
+use http::{HttpClient, SendPaced};
+use preview_store::PreviewStore;
+use session::Session;
+
 // Global state to track imalink-core process
 struct CoreProcess {
     child: Option<tauri_plugin_shell::process::CommandChild>,
+    started_at: Option<std::time::Instant>,
+    // Restarts after crashes in a row, see start_core_server
+    crash_restarts: u32,
+    status: CoreStatus,
+    // Last /health poll of the sidecar, by start_core_server or health.rs
+    last_health_check: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl CoreProcess {
     fn new() -> Self {
-        CoreProcess { child: None }
+        CoreProcess {
+            child: None,
+            started_at: None,
+            crash_restarts: 0,
+            status: CoreStatus::default(),
+            last_health_check: None,
+        }
+    }
+
+    // Current status with uptime and last health check filled in
+    fn snapshot(&self) -> CoreStatus {
+        CoreStatus {
+            uptime_seconds: self.child.as_ref().and(self.started_at).map(|started| started.elapsed().as_secs()),
+            last_health_check: self.last_health_check.map(|checked| checked.to_rfc3339()),
+            ..self.status.clone()
+        }
     }
 }
 
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoreState {
+    // Not spawned (yet), or core is remote
+    #[default]
+    NotStarted,
+    // Spawned, not answering /health yet
+    Starting,
+    // Answering /health
+    Ready,
+    // Spawned, but not answering /health: not after CORE_READY_TIMEOUT, or
+    // no longer in a health check (health.rs)
+    Unreachable,
+    // Crashed, coming back after `retry_in_seconds`
+    Restarting,
+    // Crashed with `exit_code` (None: killed by a signal) and left down,
+    // after more crashes in a row than CORE_RESTART_ATTEMPTS allows
+    Crashed,
+    // Exited cleanly or stopped by the app
+    Stopped,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CoreStatus {
+    pub state: CoreState,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub retry_in_seconds: Option<u64>,
+    // While a sidecar process is there
+    pub uptime_seconds: Option<u64>,
+    // RFC 3339
+    pub last_health_check: Option<String>,
+}
+
 // ===== Authentication Structures =====
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -44,6 +197,9 @@ pub struct LoginResponse {
     pub access_token: String,
     pub token_type: String,
     pub user: User,
+    // Kept in the credential store, never sent to the frontend (see auth.rs)
+    #[serde(default, skip_serializing)]
+    pub refresh_token: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -105,6 +261,9 @@ pub struct PhotoCreateSchema {
     pub author_id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stack_id: Option<i32>,
+    // Color label and flag tags, see labels.rs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 // ImageFile schema from imalink-core response
@@ -146,6 +305,7 @@ impl Default for PhotoCreateSchema {
             category: None,
             author_id: None,
             stack_id: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -168,6 +328,16 @@ struct InputChannelListResponse {
     pub total: i32,
 }
 
+const CHANNEL_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelSort {
+    #[default]
+    ImportedAt,
+    Title,
+}
+
 // Structure for creating input channel
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InputChannelCreate {
@@ -208,6 +378,11 @@ pub struct PhotoCreateRequest {
     pub author_id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,  // New in v2.3 - user-defined category
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Only for backends that accept it, see integrity.rs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<integrity::UploadChecksums>,
 }
 
 // Structure for PhotoCreateSchema upload response - API v2.4
@@ -233,6 +408,10 @@ pub struct PhotoCreateResponse {
     pub updated_at: Option<String>,  // Optional - backend may not return it
     #[serde(default)]
     pub is_duplicate: bool,  // NEW in API v2.4 - indicates if photo already existed
+    // Set by upload_photo_create_schema when a privacy zone changed the
+    // position before upload (see privacy.rs); not sent by the backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gps_scrub: Option<privacy::GpsScrub>,
 }
 
 impl Default for PhotoCreateResponse {
@@ -252,6 +431,7 @@ impl Default for PhotoCreateResponse {
             created_at: String::new(),
             updated_at: None,
             is_duplicate: false,
+            gps_scrub: None,
         }
     }
 }
@@ -263,143 +443,391 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+// Previews are kept in the preview store and stripped from the returned
+// schema; the UI loads them via the imalink-preview:// scheme
 #[tauri::command]
-async fn process_image_file(file_path: String, core_api_url: String) -> Result<PhotoCreateSchema, String> {
-    let path = PathBuf::from(&file_path);
+async fn process_image_file(
+    app: tauri::AppHandle,
+    previews: tauri::State<'_, PreviewStore>,
+    file_path: String,
+    core_api_url: String,
+    call: Option<invocations::CallOptions>,
+) -> Result<PhotoCreateSchema, ImalinkError> {
+    let client = http::client(&app);
+    let mut schema = invocations::run(&app, call, process_file(&client, &file_path, &core_api_url)).await?;
+    previews.strip(&mut schema);
+    Ok(schema)
+}
+
+// Send one file to imalink-core and parse the resulting PhotoCreateSchema
+async fn process_file(
+    client: &reqwest::Client,
+    file_path: &str,
+    core_api_url: &str,
+) -> Result<PhotoCreateSchema, ImalinkError> {
+    let path = PathBuf::from(file_path);
     
     if !path.exists() {
-        return Err(format!("File not found: {}", file_path));
+        return Err(ImalinkError::FileNotFound { path: file_path.to_string() });
+    }
+    if !remote_core::is_remote() {
+        wait_core_ready(CORE_READY_TIMEOUT).await;
     }
 
-    let file_bytes = std::fs::read(&path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or("Invalid filename")?
-        .to_string();
-
-    let client = reqwest::Client::new();
+    // Streamed from disk rather than read into memory
     let form = reqwest::multipart::Form::new()
         .part(
             "file",
-            reqwest::multipart::Part::bytes(file_bytes)
-                .file_name(file_name.clone())
+            streaming::file_part(file_path)
+                .await?
                 .mime_str("image/*")
-                .map_err(|e| format!("Failed to set mime type: {}", e))?,
+                .map_err(|e| ImalinkError::internal(format!("Failed to set mime type: {}", e)))?,
         )
         .text("coldpreview_size", "800"); // Request coldpreview with max 800px
 
-    let response = client
-        .post(format!("{}/v1/process", core_api_url))
+    let response = remote_core::request(client, reqwest::Method::POST, core_api_url, "/v1/process")
         .multipart(form)
         .send()
         .await
-        .map_err(|e| format!("Failed to send request to core API: {}", e))?;
+        .map_err(|e| ImalinkError::network(remote_core::url(core_api_url), e))?;
 
     if !response.status().is_success() {
-        return Err(format!(
-            "Core API returned error: {}",
-            response.status()
-        ));
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::Core {
+            status: status.as_u16(),
+            detail: error_text,
+        });
     }
 
-    let response_text = response.text().await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let response_text = response.text().await?;
     let photo_create_schema: PhotoCreateSchema = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse PhotoCreateSchema response: {} | Response start: {}", e, 
-                            if response_text.len() > 500 { &response_text[..500] } else { &response_text }))?;
+        .map_err(|e| ImalinkError::parse(format!("Invalid PhotoCreateSchema: {} | Response start: {}", e,
+                            response_text.chars().take(500).collect::<String>())))?;
 
     Ok(photo_create_schema)
 }
 
+// Address of the mock backend/core when running with IMALINK_MOCK (mock builds only)
+#[tauri::command]
+fn get_mock_server_url(app: tauri::AppHandle) -> Option<String> {
+    #[cfg(feature = "mock")]
+    return app.try_state::<mock::MockServer>().map(|server| server.url.clone());
+    #[cfg(not(feature = "mock"))]
+    {
+        let _ = app;
+        None
+    }
+}
+
 // Get file size in bytes
 #[tauri::command]
-fn get_file_size(file_path: String) -> Result<i64, String> {
+fn get_file_size(file_path: String) -> Result<i64, ImalinkError> {
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
-        return Err(format!("File not found: {}", file_path));
+        return Err(ImalinkError::FileNotFound { path: file_path });
     }
     
     let metadata = fs::metadata(&path)
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+        .map_err(|e| ImalinkError::io(path.display(), e))?;
     
     Ok(metadata.len() as i64)
 }
 
-// Copy file to destination directory with optional structure preservation
+// Whether files put in storage stay at their source too
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMode {
+    #[default]
+    Copy,
+    // Relocate, e.g. off a memory card; see streaming::move_file
+    Move,
+}
+
+impl TransferMode {
+    // Put the file in place; with `verify`, returns the BLAKE3 checksum
+    // ("blake3:<hex>") source and copy were found to share, and whether the
+    // source is gone
+    fn transfer(self, source_path: &str, dest_path: &Path, verify: bool) -> Result<(Option<String>, bool), ImalinkError> {
+        let (hash, source_removed) = match (self, verify) {
+            (TransferMode::Copy, false) => (streaming::copy_file(source_path, dest_path).map(|_| None)?, false),
+            (TransferMode::Copy, true) => (Some(streaming::copy_verified(source_path, dest_path)?), false),
+            (TransferMode::Move, _) => {
+                let moved = streaming::move_file(source_path, dest_path)?;
+                (moved.hash, moved.source_removed)
+            }
+        };
+        // A rename moves the very same bytes; hash them for the record
+        let hash = match hash {
+            None if verify => Some(streaming::hash_file(&dest_path.to_string_lossy())?),
+            hash => hash,
+        };
+        Ok((hash.filter(|_| verify).map(|h| format!("blake3:{}", h)), source_removed))
+    }
+}
+
+// What to do when a file is already at the destination
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    #[default]
+    Error,
+    Skip,
+    // Replaced only once the new file is completely in place next to it
+    Overwrite,
+    // IMG_0001.JPG → IMG_0001_1.JPG, the first free number
+    RenameSuffix,
+}
+
+// How copy_file(s)_to_storage put files in place
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct StorageOptions {
+    pub mode: TransferMode,
+    pub verify: bool,
+    // Error for a single file, Skip for a batch when not given
+    pub collision_policy: Option<CollisionPolicy>,
+    // Import session the files belong to, so undo_session_copies can take
+    // them back out (and put moved files back)
+    pub session_id: Option<String>,
+}
+
+// What happened to a file put in storage
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageAction {
+    Stored,
+    Skipped,
+    Overwritten,
+    Renamed,
+}
+
+const MAX_SUFFIX: u32 = 9999;
+
+// First free `<stem>_<n>.<ext>` next to `dest_path`
+fn suffixed_path(dest_path: &Path) -> Result<PathBuf, ImalinkError> {
+    let stem = dest_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = dest_path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..=MAX_SUFFIX)
+        .map(|n| dest_path.with_file_name(format!("{}_{}{}", stem, n, ext)))
+        .find(|candidate| !candidate.exists())
+        .ok_or_else(|| ImalinkError::invalid(format!("No free name for {}_N{}", stem, ext)))
+}
+
+// Put one file in storage at `dest_path`, or next to it per `policy`
+fn store_file(
+    source_path: &str,
+    dest_path: PathBuf,
+    mode: TransferMode,
+    verify: bool,
+    policy: CollisionPolicy,
+) -> Result<CopiedFile, ImalinkError> {
+    let stored = |dest_path: &Path, (checksum, source_removed), action| CopiedFile {
+        source_path: source_path.to_string(),
+        destination_path: dest_path.to_string_lossy().to_string(),
+        checksum,
+        action,
+        source_removed,
+    };
+    if !dest_path.exists() {
+        let transferred = mode.transfer(source_path, &dest_path, verify)?;
+        return Ok(stored(&dest_path, transferred, StorageAction::Stored));
+    }
+    
+    match policy {
+        CollisionPolicy::Error => Err(ImalinkError::DestinationExists { path: dest_path.display().to_string() }),
+        CollisionPolicy::Skip => Ok(stored(&dest_path, (None, false), StorageAction::Skipped)),
+        CollisionPolicy::RenameSuffix => {
+            let renamed = suffixed_path(&dest_path)?;
+            let transferred = mode.transfer(source_path, &renamed, verify)?;
+            Ok(stored(&renamed, transferred, StorageAction::Renamed))
+        }
+        CollisionPolicy::Overwrite => {
+            // Transfer beside the old file, then swap it in
+            let name = dest_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let partial = dest_path.with_file_name(format!(".{}.imalink-partial", name));
+            let transferred = mode.transfer(source_path, &partial, verify)?;
+            fs::rename(&partial, &dest_path).map_err(|e| {
+                let _ = fs::remove_file(&partial);
+                ImalinkError::io(dest_path.display(), e)
+            })?;
+            Ok(stored(&dest_path, transferred, StorageAction::Overwritten))
+        }
+    }
+}
+
+// Copy (or move) file to destination directory with optional structure
+// preservation. With `verify`, the copy is read back and compared with the
+// source, and the checksum returned for local_storage_info. An existing
+// destination is an error unless `collision_policy` says otherwise.
 #[tauri::command]
 fn copy_file_to_storage(
+    app: tauri::AppHandle,
     source_path: String,
     destination_dir: String,
     preserve_structure: bool,
-    source_base_dir: Option<String>
-) -> Result<String, String> {
-    let source = PathBuf::from(&source_path);
-    let dest_dir = PathBuf::from(&destination_dir);
+    source_base_dir: Option<String>,
+    options: Option<StorageOptions>,
+) -> Result<CopiedFile, ImalinkError> {
+    guest::require_owner(&app, "copy to storage")?;
+    let dest_path = storage_destination_path(
+        &source_path,
+        &destination_dir,
+        preserve_structure,
+        source_base_dir.as_deref(),
+    )?;
+    
+    let options = options.unwrap_or_default();
+    let copied = store_file(
+        &source_path,
+        dest_path,
+        options.mode,
+        options.verify,
+        options.collision_policy.unwrap_or_default(),
+    )?;
+    record_transfer(&app, options.session_id.as_deref(), &destination_dir, &copied);
+    Ok(copied)
+}
+
+// Record a file put in storage for undo, when it was for an import session
+fn record_transfer(app: &tauri::AppHandle, session_id: Option<&str>, destination_dir: &str, copied: &CopiedFile) {
+    let Some(session_id) = session_id else { return };
+    if copied.action != StorageAction::Skipped {
+        let dest = Path::new(&copied.destination_path);
+        undo::record(app, session_id, &copied.source_path, dest, Some(destination_dir), copied.source_removed);
+    }
+}
+
+// Result entry for a successfully copied file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CopiedFile {
+    pub source_path: String,
+    pub destination_path: String,
+    // Verified checksum, when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    pub action: StorageAction,
+    // Whether the source is gone: false for a copy, and for a move whose
+    // source could not be deleted (e.g. a write-protected card)
+    #[serde(default)]
+    pub source_removed: bool,
+}
+
+// Copy (or move) many files to storage, reporting per-file results instead
+// of aborting on the first failure. Files whose destination already exists
+// are skipped unless `collision_policy` says otherwise.
+#[tauri::command]
+fn copy_files_to_storage(
+    app: tauri::AppHandle,
+    source_paths: Vec<String>,
+    destination_dir: String,
+    preserve_structure: bool,
+    source_base_dir: Option<String>,
+    options: Option<StorageOptions>,
+) -> Result<BatchResult<CopiedFile>, ImalinkError> {
+    guest::require_owner(&app, "copy to storage")?;
+    let options = options.unwrap_or_default();
+    let policy = options.collision_policy.unwrap_or(CollisionPolicy::Skip);
+    let mut result = BatchResult::new();
+    
+    for source_path in source_paths {
+        let dest_path = match storage_destination_path(
+            &source_path,
+            &destination_dir,
+            preserve_structure,
+            source_base_dir.as_deref(),
+        ) {
+            Ok(path) => path,
+            Err(e) => {
+                result.fail(source_path, e);
+                continue;
+            }
+        };
+        
+        match store_file(&source_path, dest_path, options.mode, options.verify, policy) {
+            Ok(copied) if copied.action == StorageAction::Skipped => {
+                result.skip(source_path, format!("Destination file already exists: {}", copied.destination_path));
+            }
+            copied => {
+                if let Ok(copied) = &copied {
+                    record_transfer(&app, options.session_id.as_deref(), &destination_dir, copied);
+                }
+                result.record(source_path, copied)
+            }
+        }
+    }
+    
+    Ok(result)
+}
+
+// Validate the source file and work out where it should land in storage,
+// creating the destination directories as needed
+fn storage_destination_path(
+    source_path: &str,
+    destination_dir: &str,
+    preserve_structure: bool,
+    source_base_dir: Option<&str>
+) -> Result<PathBuf, ImalinkError> {
+    let source = PathBuf::from(source_path);
+    let dest_dir = PathBuf::from(destination_dir);
     
     if !source.exists() {
-        return Err(format!("Source file not found: {}", source_path));
+        return Err(ImalinkError::FileNotFound { path: source_path.to_string() });
     }
     
     if !source.is_file() {
-        return Err(format!("Source is not a file: {}", source_path));
+        return Err(ImalinkError::NotAFile { path: source_path.to_string() });
     }
     
     if !dest_dir.exists() {
         fs::create_dir_all(&dest_dir)
-            .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+            .map_err(|e| ImalinkError::io(dest_dir.display(), e))?;
     }
     
     // Determine final destination path
-    let dest_path = if preserve_structure && source_base_dir.is_some() {
-        // Preserve directory structure relative to base
-        let base = PathBuf::from(source_base_dir.unwrap());
-        let relative = source.strip_prefix(&base)
-            .map_err(|_| "Source path not under base directory".to_string())?;
-        let final_dest = dest_dir.join(relative);
-        
-        // Create parent directories if needed
-        if let Some(parent) = final_dest.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create parent directories: {}", e))?;
+    match source_base_dir {
+        Some(base_dir) if preserve_structure => {
+            // Preserve directory structure relative to base
+            let base = PathBuf::from(base_dir);
+            let relative = source.strip_prefix(&base)
+                .map_err(|_| ImalinkError::invalid(format!("Source path not under base directory: {}", source_path)))?;
+            let final_dest = dest_dir.join(relative);
+            
+            // Create parent directories if needed
+            if let Some(parent) = final_dest.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| ImalinkError::io(parent.display(), e))?;
+            }
+            
+            Ok(final_dest)
+        }
+        _ => {
+            // Flat copy - just filename
+            let filename = source.file_name()
+                .ok_or_else(|| ImalinkError::invalid(format!("Invalid source filename: {}", source_path)))?;
+            Ok(dest_dir.join(filename))
         }
-        
-        final_dest
-    } else {
-        // Flat copy - just filename
-        let filename = source.file_name()
-            .ok_or("Invalid source filename")?;
-        dest_dir.join(filename)
-    };
-    
-    // Check if destination exists
-    if dest_path.exists() {
-        return Err(format!("Destination file already exists: {}", dest_path.display()));
     }
-    
-    // Copy file
-    fs::copy(&source, &dest_path)
-        .map_err(|e| format!("Failed to copy file: {}", e))?;
-    
-    // Return destination path as string
-    Ok(dest_path.to_string_lossy().to_string())
 }
 
-#[tauri::command]
-fn scan_directory(dir_path: String) -> Result<Vec<String>, String> {
-    let path = PathBuf::from(&dir_path);
+// Recursively collect supported image files under a directory, sorted
+fn collect_image_files(path: &Path) -> Result<Vec<String>, ImalinkError> {
+    let mut image_files = walk_image_files(path, &mut |_, _| {})?;
     
-    if !path.exists() {
-        return Err(format!("Directory not found: {}", dir_path));
-    }
-    
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", dir_path));
-    }
+    // Sort files for consistent ordering
+    image_files.sort();
     
+    Ok(image_files)
+}
+
+// Collect supported image files under a directory in the order read, calling
+// `on_dir` after each directory with the files collected so far (scan.rs
+// reports progress and takes pages out from there)
+fn walk_image_files(
+    path: &Path,
+    on_dir: &mut dyn FnMut(&Path, &mut Vec<String>),
+) -> Result<Vec<String>, ImalinkError> {
     let mut image_files: Vec<String> = Vec::new();
     
     // Supported image extensions for companion detection
@@ -410,22 +838,30 @@ fn scan_directory(dir_path: String) -> Result<Vec<String>, String> {
         "heic", "heif",
         // PNG format (master priority 3)
         "png",
+        // TIFF and JPEG XL (master priority 4), transcoded for previews
+        "tif", "tiff", "jxl",
         // RAW formats (master priority 10)
         "arw", "cr2", "cr3", "nef", "dng", "orf", "raf", "rw2", "raw"
     ];
     
     // Recursive function to scan directories
-    fn scan_recursive(path: &PathBuf, files: &mut Vec<String>, extensions: &Vec<&str>) -> Result<(), String> {
+    fn scan_recursive(
+        path: &Path,
+        files: &mut Vec<String>,
+        extensions: &Vec<&str>,
+        on_dir: &mut dyn FnMut(&Path, &mut Vec<String>),
+    ) -> Result<(), ImalinkError> {
         let entries = fs::read_dir(path)
-            .map_err(|e| format!("Failed to read directory: {}", e))?;
+            .map_err(|e| ImalinkError::io(path.display(), e))?;
+        let mut subdirs = Vec::new();
         
         for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            let entry = entry.map_err(|e| ImalinkError::io(path.display(), e))?;
             let entry_path = entry.path();
             
             if entry_path.is_dir() {
-                // Recurse into subdirectory
-                scan_recursive(&entry_path, files, extensions)?;
+                // Recurse once this directory's own files are in
+                subdirs.push(entry_path);
             } else if entry_path.is_file() {
                 // Check if it's a supported image file
                 if let Some(ext) = entry_path.extension() {
@@ -438,59 +874,114 @@ fn scan_directory(dir_path: String) -> Result<Vec<String>, String> {
                 }
             }
         }
+        on_dir(path, files);
+        
+        for subdir in subdirs {
+            scan_recursive(&subdir, files, extensions, on_dir)?;
+        }
         
         Ok(())
     }
     
-    scan_recursive(&path, &mut image_files, &supported_extensions)?;
-    
-    // Sort files for consistent ordering
-    image_files.sort();
+    scan_recursive(path, &mut image_files, &supported_extensions, on_dir)?;
     
     Ok(image_files)
 }
 
-#[tauri::command]
-async fn list_input_channels(
-    backend_url: String,
-    auth_token: String,
-) -> Result<Vec<InputChannel>, String> {
-    let client = reqwest::Client::new();
-    
+// One page of input channels, starting at `offset`
+async fn fetch_channel_page(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    offset: usize,
+) -> Result<InputChannelListResponse, ImalinkError> {
     let response = client
         .get(format!("{}/api/v1/input-channels/", backend_url))
         .header("Authorization", format!("Bearer {}", auth_token))
-        .send()
+        .query(&[("offset", offset), ("limit", CHANNEL_PAGE_SIZE)])
+        .send_paced()
         .await
-        .map_err(|e| format!("Failed to send request to backend: {}", e))?;
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
     
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Backend returned error {}: {}",
-            status, error_text
-        ));
+        return Err(ImalinkError::from_backend(status, error_text));
     }
     
-    let response_text = response.text().await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    let response_data: InputChannelListResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {} | Response was: {}", e, response_text))?;
-    
-    Ok(response_data.channels)
+    let response_text = response.text().await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))
 }
 
+// All input channels, page by page until `total` is reached. `search`
+// matches title and description (case-insensitive); `sort` orders by
+// imported_at (default, newest first) or title, `ascending` flips it.
+#[tauri::command]
+async fn list_input_channels(
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    search: Option<String>,
+    sort: Option<ChannelSort>,
+    ascending: Option<bool>,
+) -> Result<Vec<InputChannel>, ImalinkError> {
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = http::client(&app);
+    let mut channels: Vec<InputChannel> = Vec::new();
+    loop {
+        let offset = channels.len();
+        let page = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+            let (client, backend_url) = (&client, &backend_url);
+            async move { fetch_channel_page(client, backend_url, &token, offset).await }
+        })
+        .await?;
+        let count = page.channels.len();
+        channels.extend(page.channels);
+        // Backends without paging send everything at once
+        if count == 0 || channels.len() >= page.total.max(0) as usize {
+            break;
+        }
+    }
+    // Pages can overlap when channels are created while paging
+    let mut seen = std::collections::HashSet::new();
+    channels.retain(|c| seen.insert(c.id));
+
+    if let Some(search) = search.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()) {
+        let matches = |text: &Option<String>| text.as_deref().is_some_and(|t| t.to_lowercase().contains(&search));
+        channels.retain(|c| matches(&c.title) || matches(&c.description));
+    }
+    match sort.unwrap_or_default() {
+        ChannelSort::ImportedAt => channels.sort_by(|a, b| b.imported_at.cmp(&a.imported_at)),
+        ChannelSort::Title => channels.sort_by(|a, b| {
+            let title = |c: &InputChannel| c.title.as_deref().unwrap_or_default().to_lowercase();
+            title(b).cmp(&title(a))
+        }),
+    }
+    if ascending.unwrap_or(false) {
+        channels.reverse();
+    }
+    Ok(channels)
+}
+
+// Create a channel. Without a description, `files` (those about to be
+// imported into it) get it one from the template, see channel_description.rs.
 #[tauri::command]
 async fn create_input_channel(
-    backend_url: String,
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
     title: Option<String>,
     description: Option<String>,
     default_author_id: Option<i32>,
-    auth_token: String,
-) -> Result<InputChannel, String> {
-    let client = reqwest::Client::new();
+    auth_token: Option<String>,
+    files: Option<Vec<String>>,
+) -> Result<InputChannel, ImalinkError> {
+    app.state::<AccessMode>().require_owner("create input channels")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = http::client(&app);
+    let description = description.filter(|d| !d.trim().is_empty()).or_else(|| {
+        channel_description::for_files(&app, files.as_deref().unwrap_or_default())
+    });
     
     let request_body = InputChannelCreate {
         title,
@@ -498,53 +989,92 @@ async fn create_input_channel(
         default_author_id,
     };
     
-    let response = client
-        .post(format!("{}/api/v1/input-channels/", backend_url))
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to backend: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Backend returned error {}: {}",
-            status, error_text
-        ));
-    }
-    
-    let response_text = response.text().await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    let input_channel: InputChannel = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {} | Response was: {}", e, response_text))?;
-    
-    Ok(input_channel)
+    auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url, request_body) = (&client, &backend_url, &request_body);
+        async move {
+            let response = client
+                .post(format!("{}/api/v1/input-channels/", backend_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("Content-Type", "application/json")
+                .json(request_body)
+                .send_paced()
+                .await
+                .map_err(|e| ImalinkError::network(backend_url, e))?;
+            
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(ImalinkError::from_backend(status, error_text));
+            }
+            
+            let response_text = response.text().await?;
+            
+            let input_channel: InputChannel = serde_json::from_str(&response_text)
+                .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))?;
+            
+            Ok(input_channel)
+        }
+    })
+    .await
 }
 
 #[tauri::command]
 async fn upload_photo_create_schema(
-    backend_url: String,
+    app: tauri::AppHandle,
+    previews: tauri::State<'_, PreviewStore>,
+    backend_url: Option<String>,
+    mut photo_create_schema: PhotoCreateSchema,
+    input_channel_id: i32,
+    auth_token: Option<String>,
+    call: Option<invocations::CallOptions>,
+) -> Result<PhotoCreateResponse, ImalinkError> {
+    guest::require_owner(&app, "upload")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let settings = settings::load(&app);
+    // Schemas from process_image_file arrive without previews
+    previews.restore(&mut photo_create_schema)?;
+    let gps_scrub = privacy::apply(&settings.privacy_zones, &mut photo_create_schema);
+    let client = http::client(&app);
+    let stall = stall::StallPolicy::from_settings(&settings);
+    let limit = payload::PayloadLimit::from_settings(&settings);
+    let upload = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url, stall, limit) = (&client, &backend_url, &stall, &limit);
+        let schema = photo_create_schema.clone();
+        async move { upload_schema(client, backend_url, &token, schema, input_channel_id, stall, limit).await }
+    });
+    let mut photo = invocations::run(&app, call, upload).await?;
+    app.state::<existence::ExistenceCache>().record(&backend_url, &photo.hothash, Some(photo.id));
+    photo.gps_scrub = gps_scrub;
+    Ok(photo)
+}
+
+// Upload one PhotoCreateSchema to the backend. A 409 (already exists) is
+// reported as success with `is_duplicate` set.
+async fn upload_schema(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
     photo_create_schema: PhotoCreateSchema,
     input_channel_id: i32,
-    auth_token: String,
-) -> Result<PhotoCreateResponse, String> {
-    let client = reqwest::Client::new();
-    
+    stall: &stall::StallPolicy,
+    limit: &payload::PayloadLimit,
+) -> Result<PhotoCreateResponse, ImalinkError> {
     // PhotoCreateSchema now contains complete image_file_list from frontend
     // No need to build image_file separately - it's already in photo_create_schema.image_file_list
     
-    let request_body = PhotoCreateRequest {
+    let category = photo_create_schema.category.clone();
+    let tags = photo_create_schema.tags.clone();
+    let rating = photo_create_schema.rating.unwrap_or(0);
+    let mut request_body = PhotoCreateRequest {
         photo_create_schema,
         input_channel_id: Some(input_channel_id),
         image_file: None,  // Deprecated - data is now in photo_create_schema.image_file_list
-        rating: Some(0),  // Default rating
+        rating: Some(rating),  // Default rating 0
         visibility: Some("private".to_string()),  // Default visibility
         author_id: None,
-        category: None,
+        category,
+        tags,
+        checksums: None,
     };
     
     // Log upload
@@ -552,24 +1082,45 @@ async fn upload_photo_create_schema(
              request_body.photo_create_schema.hothash, 
              input_channel_id);
     
-    let response = client
-        .post(format!("{}/api/v1/photos/create", backend_url))
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to backend: {}", e))?;
+    // Kept under the size limit, and sent in chunks under the stall watchdog.
+    // Checksums go in before and are redone should the coldpreview shrink.
+    integrity::attach(&mut request_body, limit.checksum_field);
+    let mut body = payload::fit(&mut request_body, limit)?;
+    if integrity::attach(&mut request_body, limit.checksum_field) {
+        body = serde_json::to_vec(&request_body)?;
+    }
+    let sent = integrity::checksums(&request_body.photo_create_schema);
+    let url = format!("{}/api/v1/photos/create", backend_url);
+    let (status, resume, limited, response_text) = stall::run(stall, &url, |heartbeat| {
+        let request = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", auth_token))
+            .header("Content-Type", "application/json")
+            .header("Content-Length", body.len())
+            .body(stall::body(&body, &heartbeat));
+        async move {
+            let response = request.send().await.map_err(|e| ImalinkError::network(backend_url, e))?;
+            let status = response.status();
+            let resume = maintenance::resume_time(response.headers().get(reqwest::header::RETRY_AFTER));
+            let limited = ratelimit::observe(status, response.headers());
+            Ok((status, resume, limited, stall::read_text(response, &heartbeat).await?))
+        }
+    })
+    .await?;
     
-    let status = response.status();
+    if status == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+        if let Some(until) = resume {
+            return Err(maintenance::error(until));
+        }
+    }
+    if let Some(until) = limited {
+        return Err(ratelimit::error(until));
+    }
     
     // Handle 409 Conflict (duplicate) as success
     if status == reqwest::StatusCode::CONFLICT {
-        let response_text = response.text().await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
-        
         let mut photo_response: PhotoCreateResponse = serde_json::from_str(&response_text)
-            .map_err(|e| format!("Failed to parse duplicate response: {} | Response was: {}", e, response_text))?;
+            .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))?;
         
         // Ensure is_duplicate is set to true
         photo_response.is_duplicate = true;
@@ -577,18 +1128,12 @@ async fn upload_photo_create_schema(
     }
     
     if !status.is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Backend returned error {}: {}",
-            status, error_text
-        ));
+        return Err(ImalinkError::from_backend(status, response_text));
     }
-    
-    let response_text = response.text().await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    integrity::verify(&sent, &request_body.photo_create_schema.hothash, &response_text)?;
     
     let photo_response: PhotoCreateResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {} | Response was: {}", e, response_text))?;
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))?;
     
     Ok(photo_response)
 }
@@ -597,11 +1142,15 @@ async fn upload_photo_create_schema(
 
 #[tauri::command]
 async fn login(
+    http: tauri::State<'_, HttpClient>,
+    session: tauri::State<'_, Session>,
+    access: tauri::State<'_, AccessMode>,
     backend_url: String,
     username: String,
     password: String,
-) -> Result<LoginResponse, String> {
-    let client = reqwest::Client::new();
+) -> Result<LoginResponse, ImalinkError> {
+    access.require_owner("log in")?;
+    let client = http.client();
     
     let request_body = LoginRequest {
         username,
@@ -612,37 +1161,45 @@ async fn login(
         .post(format!("{}/api/v1/auth/login/", backend_url))
         .header("Content-Type", "application/json")
         .json(&request_body)
-        .send()
+        .send_paced()
         .await
-        .map_err(|e| format!("Failed to connect to server: {}", e))?;
+        .map_err(|e| ImalinkError::network(&backend_url, e))?;
     
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Login failed ({}): {}",
+        return Err(ImalinkError::from_backend(
             status,
-            if error_text.is_empty() { "Invalid credentials" } else { &error_text }
+            if error_text.is_empty() { "Invalid credentials".to_string() } else { error_text }
         ));
     }
     
     let login_response: LoginResponse = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse login response: {}", e))?;
+        .map_err(ImalinkError::parse)?;
+    if let Some(refresh_token) = &login_response.refresh_token {
+        if let Err(e) = auth::store_refresh_token(&backend_url, refresh_token) {
+            eprintln!("Refresh token not stored, sessions will end when the access token expires: {}", e);
+        }
+    }
+    session.login(&backend_url, &login_response.access_token, Some(login_response.user.clone()));
     
     Ok(login_response)
 }
 
 #[tauri::command]
 async fn register(
+    http: tauri::State<'_, HttpClient>,
+    access: tauri::State<'_, AccessMode>,
     backend_url: String,
     username: String,
     email: String,
     password: String,
     display_name: String,
-) -> Result<User, String> {
-    let client = reqwest::Client::new();
+) -> Result<User, ImalinkError> {
+    access.require_owner("register accounts")?;
+    let client = http.client();
     
     let request_body = RegisterRequest {
         username,
@@ -655,49 +1212,52 @@ async fn register(
         .post(format!("{}/api/v1/auth/register/", backend_url))
         .header("Content-Type", "application/json")
         .json(&request_body)
-        .send()
+        .send_paced()
         .await
-        .map_err(|e| format!("Failed to connect to server: {}", e))?;
+        .map_err(|e| ImalinkError::network(&backend_url, e))?;
     
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Registration failed ({}): {}",
+        return Err(ImalinkError::from_backend(
             status,
-            if error_text.is_empty() { "Registration error" } else { &error_text }
+            if error_text.is_empty() { "Registration error".to_string() } else { error_text }
         ));
     }
     
     let user: User = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse registration response: {}", e))?;
+        .map_err(ImalinkError::parse)?;
     
     Ok(user)
 }
 
 #[tauri::command]
 async fn logout(
-    backend_url: String,
-    auth_token: String,
-) -> Result<(), String> {
-    let client = reqwest::Client::new();
+    http: tauri::State<'_, HttpClient>,
+    session: tauri::State<'_, Session>,
+    access: tauri::State<'_, AccessMode>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<(), ImalinkError> {
+    access.require_owner("log out")?;
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    session.logout();
+    auth::forget_refresh_token(&backend_url);
+    let client = http.client();
     
     let response = client
         .post(format!("{}/api/v1/auth/logout/", backend_url))
         .header("Authorization", format!("Bearer {}", auth_token))
-        .send()
+        .send_paced()
         .await
-        .map_err(|e| format!("Failed to connect to server: {}", e))?;
+        .map_err(|e| ImalinkError::network(&backend_url, e))?;
     
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Logout failed ({}): {}",
-            status, error_text
-        ));
+        return Err(ImalinkError::from_backend(status, error_text));
     }
     
     Ok(())
@@ -705,28 +1265,40 @@ async fn logout(
 
 #[tauri::command]
 async fn validate_token(
-    backend_url: String,
-    auth_token: String,
-) -> Result<User, String> {
-    let client = reqwest::Client::new();
-    
-    let response = client
-        .get(format!("{}/api/v1/auth/me/", backend_url))
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to connect to server: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        return Err(format!("Token validation failed: {}", status));
-    }
-    
-    let user: User = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse user response: {}", e))?;
-    
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<User, ImalinkError> {
+    let session = app.state::<Session>();
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    let client = http::client(&app);
+    
+    let user = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url) = (&client, &backend_url);
+        async move {
+            let response = client
+                .get(format!("{}/api/v1/auth/me/", backend_url))
+                .header("Authorization", format!("Bearer {}", token))
+                .send_paced()
+                .await
+                .map_err(|e| ImalinkError::network(backend_url, e))?;
+            
+            if !response.status().is_success() {
+                let status = response.status();
+                return Err(ImalinkError::from_backend(status, String::new()));
+            }
+            
+            let user: User = response
+                .json()
+                .await
+                .map_err(ImalinkError::parse)?;
+            
+            Ok(user)
+        }
+    })
+    .await?;
+    let auth_token = app.state::<auth::TokenRefresher>().current(&auth_token);
+    session.login(&backend_url, &auth_token, Some(user.clone()));
     Ok(user)
 }
 
@@ -739,14 +1311,70 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(Mutex::new(CoreProcess::new()))
+        .manage(pipeline::ImportSessions::default())
+        .manage(prefetch::Prefetcher::default())
+        .manage(progress::ProgressTrackers::default())
+        .manage(scheduler::Scheduler::default())
+        .manage(slideshow::Slideshows::default())
+        .manage(tether::Tethering::default())
+        .manage(PreviewStore::default())
+        .manage(editor::EditSessions::default())
+        .manage(health::HealthMonitor::default())
+        .manage(operations::Operations::default())
+        .manage(invocations::Invocations::default())
+        .manage(HttpClient::default())
+        .manage(auth::TokenRefresher::default())
+        .manage(Session::default())
+        .register_uri_scheme_protocol(preview_store::SCHEME, |ctx, request| {
+            preview_store::handle_request(ctx.app_handle(), request)
+        })
         .setup(|app| {
-            // Start imalink-core sidecar on app startup
-            let app_handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = start_core_server(app_handle).await {
+            let app_settings = settings::load(app.handle());
+            crash::init(app.handle(), app_settings.crash_reporting_enabled);
+            match app_settings.remote_core.clone().map(|r| remote_core::validate(&r).map(|_| r)).transpose() {
+                Ok(remote) => remote_core::configure(remote),
+                Err(e) => eprintln!("Ignoring remote_core, processing locally: {}", e),
+            }
+            app.manage(schema_cache::SchemaCache::new(
+                app.path().app_cache_dir()?.join("schemas"),
+                app_settings.schema_cache_max_mb * 1024 * 1024,
+            ));
+            app.manage(workspace::Workspaces::new(
+                app.path().app_cache_dir()?.join("workspaces"),
+                app_settings.workspace_max_mb * 1024 * 1024,
+            ));
+            app.manage(hothash::HothashIndex::load(&app.path().app_data_dir()?));
+            app.manage(existence::ExistenceCache::load(&app.path().app_data_dir()?));
+            app.manage(guest::AccessMode::load(&app.path().app_data_dir()?));
+            app.manage(recovery::ImportJournal::new(&app.path().app_data_dir()?));
+            app.manage(quarantine::Quarantine::load(&app.path().app_data_dir()?));
+            app.manage(queue::UploadQueue::load(
+                &app.path().app_data_dir()?,
+                &app.state::<recovery::ImportJournal>(),
+            ));
+            app.manage(history::History::open(&app.path().app_data_dir()?)?);
+            app.manage(offline::OfflinePreviews::new(app.path().app_data_dir()?.join("offline_previews")));
+            scheduler::start(app.handle().clone());
+            health::start(app.handle().clone());
+            low_disk::start(app.handle().clone());
+            queue::start(app.handle().clone());
+            disk_usage::prune_on_startup(app.handle());
+
+            #[cfg(feature = "mock")]
+            if std::env::var_os("IMALINK_MOCK").is_some() {
+                let server = tauri::async_runtime::block_on(mock::start())?;
+                println!("Mock backend and core running at {}", server.url);
+                app.manage(server);
+            }
+            
+            // Start imalink-core sidecar on app startup, unless core is remote
+            if remote_core::is_remote() {
+                println!("Processing on remote imalink-core, sidecar not started");
+            } else {
+                if let Err(e) = start_core_server(app.handle().clone()) {
                     eprintln!("Failed to start imalink-core: {}", e);
                 }
-            });
+            }
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -763,60 +1391,357 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             greet, 
             process_image_file, 
-            scan_directory,
+            scan::scan_directory,
+            scan::scan_directory_paged,
+            scan::scan_directory_grouped,
             get_file_size,
+            get_mock_server_url,
             copy_file_to_storage,
+            copy_files_to_storage,
             list_input_channels,
             create_input_channel,
+            cards::check_card,
+            cards::record_card_import,
+            channel_description::suggest_channel_description,
             upload_photo_create_schema,
+            batch_upload::batch_upload_photos,
             login,
             register,
             logout,
             validate_token,
-            check_core_health,
-            open_web_gallery
+            open_web_gallery,
+            settings::get_settings,
+            settings::update_settings,
+            settings_sync::sync_settings,
+            backup::create_backup,
+            backup::verify_backup,
+            checksums::write_checksum_manifest,
+            compare::compare_photos,
+            backup::restore_backup,
+            crash::list_crash_reports,
+            crash::delete_crash_report,
+            crash::submit_crash_report,
+            disk_usage::get_app_storage_usage,
+            disk_usage::prune_caches,
+            duplicates::propose_duplicate_actions,
+            editor::open_in_editor,
+            editor::get_edit_session,
+            editor::stop_editing,
+            editor::import_edited_version,
+            error::get_error_catalog,
+            events::cluster_pending_events,
+            events::import_events,
+            guest::get_access_mode,
+            guest::create_guest_token,
+            guest::revoke_guest_token,
+            guest::enter_guest_mode,
+            guest::exit_guest_mode,
+            get_core_status,
+            low_disk::get_low_disk_status,
+            low_disk::check_import_space,
+            remote_core::get_core_url,
+            health::get_health_status,
+            health::get_last_health_status,
+            benchmark::benchmark_backend,
+            export::export_photos,
+            history::update_local_metadata,
+            history::get_history_photo,
+            history::get_import_history,
+            history::was_file_imported,
+            history::clear_history,
+            photo_lookup::get_photo_by_hothash,
+            maintenance::get_maintenance_status,
+            ratelimit::get_rate_limit_status,
+            self_author::create_self_author,
+            session::get_session,
+            session::set_session,
+            ios::list_ios_devices,
+            ios::list_ios_camera_roll,
+            ios::import_from_ios,
+            labels::read_cull_marks,
+            legacy::migrate_photo_eggs,
+            offline::sync_offline_previews,
+            offline::get_offline_cache_stats,
+            offline::clear_offline_cache,
+            operations::list_operations,
+            operations::cancel_operation,
+            invocations::cancel_command,
+            originals::locate_original,
+            personal_data::export_my_data,
+            pipeline::start_import,
+            pipeline::get_import_session,
+            pipeline::group_companion_files,
+            progress::get_import_eta,
+            prefetch::prefetch_files,
+            prefetch::cancel_prefetch,
+            prefetch::get_prefetch_status,
+            preflight::check_channel_permission,
+            channels::preview_channel_deletion,
+            channels::delete_input_channel,
+            presets::list_presets,
+            presets::save_preset,
+            presets::delete_preset,
+            recovery::list_interrupted_imports,
+            recovery::verify_interrupted_import,
+            recovery::resume_import,
+            recovery::discard_interrupted_import,
+            quarantine::list_quarantined,
+            quarantine::retry_quarantined,
+            quarantine::dismiss_quarantined,
+            queue::queue_add,
+            queue::queue_status,
+            queue::queue_retry_failed,
+            queue::queue_clear,
+            reimport::reimport_folder,
+            remote::list_remote_sources,
+            remote::save_remote_source,
+            remote::delete_remote_source,
+            remote::scan_remote_source,
+            remote::import_from_remote,
+            schema_cache::get_schema_cache_stats,
+            schema_cache::clear_schema_cache,
+            scheduler::set_scheduler_token,
+            scheduler::list_schedules,
+            scheduler::save_schedule,
+            scheduler::delete_schedule,
+            scheduler::get_schedule_runs,
+            sequences::detect_sequences,
+            multishot::detect_multishot_captures,
+            session_notes::set_session_note,
+            session_notes::get_session_notes,
+            session_notes::append_session_note_to_channel,
+            metrics::estimate_session,
+            auth::secure_store_token,
+            auth::secure_get_token,
+            slideshow::start_slideshow,
+            slideshow::get_slideshow,
+            snapshot::export_queue_snapshot,
+            snapshot::import_queue_snapshot,
+            stats::get_library_stats,
+            sync::sync_now,
+            tether::start_tethering,
+            tether::stop_tethering,
+            tether::get_tethering_status,
+            undo::undo_session_copies,
+            url_import::import_from_url,
+            email_import::extract_email_attachments,
+            email_import::import_from_email,
+            visibility::preview_visibility_promotion,
+            visibility::promote_visibility,
+            coldpreviews::backfill_coldpreviews,
+            workspace::purge_workspaces
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 // ===== Core Server Management =====
+//
+// When the sidecar dies on its own (exit code other than 0, or killed by a
+// signal), it is started again after CORE_RESTART_DELAY, doubling with every
+// crash in a row up to CORE_RESTART_MAX_DELAY. After CORE_RESTART_ATTEMPTS
+// crashes in a row it is left down; a sidecar that ran for CORE_STABLE_AFTER
+// before crashing starts the count over. Every change goes out as a
+// `core-status-changed` event (CoreStatus), and get_core_status returns the
+// current one, with PID, uptime and the time of the last health check, for
+// the UI to offer a restart or diagnostics from. Stopping the sidecar on
+// purpose (stop_core_server) never triggers a restart.
+//
+// A freshly spawned sidecar takes a moment to bind its port, so it stays
+// Starting until it answers /health; then it is Ready and `core-ready`
+// goes out. Requests to core (process_file) wait for that, for up to
+// CORE_READY_TIMEOUT. A sidecar that hasn't answered by then is marked
+// Unreachable and no longer waited for, but still polled, in case it is
+// only slow. The periodic health check (health.rs) moves a sidecar between
+// Ready and Unreachable later on.
+
+const CORE_RESTART_ATTEMPTS: u32 = 5;
+const CORE_RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+const CORE_RESTART_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+const CORE_STABLE_AFTER: std::time::Duration = std::time::Duration::from_secs(120);
+const CORE_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const CORE_READY_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+// A spawned sidecar hasn't answered /health yet
+static CORE_PENDING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn set_core_status(app: &tauri::AppHandle, state: &mut CoreProcess, status: CoreStatus) {
+    CORE_PENDING.store(status.state == CoreState::Starting, std::sync::atomic::Ordering::SeqCst);
+    state.status = status;
+    let _ = app.emit("core-status-changed", state.snapshot());
+}
+
+#[tauri::command]
+fn get_core_status(core: tauri::State<'_, Mutex<CoreProcess>>) -> CoreStatus {
+    core.lock().map(|state| state.snapshot()).unwrap_or_default()
+}
+
+// A health check of the sidecar came back `up` or not
+pub fn core_health_checked(app: &tauri::AppHandle, up: bool) {
+    let Some(core_state) = app.try_state::<Mutex<CoreProcess>>() else { return };
+    let Ok(mut state) = core_state.lock() else { return };
+    if state.child.is_none() {
+        return;
+    }
+    state.last_health_check = Some(chrono::Utc::now());
+    let next = match state.status.state {
+        CoreState::Ready if !up => CoreState::Unreachable,
+        CoreState::Unreachable if up => CoreState::Ready,
+        _ => return,
+    };
+    eprintln!("imalink-core is {}", if up { "answering again" } else { "not answering /health" });
+    let status = CoreStatus { state: next, ..state.status.clone() };
+    set_core_status(app, &mut state, status);
+}
+
+// Wait until a sidecar being started answers, for at most `timeout`. Returns
+// whether core is up as far as we know; right away when no start is pending.
+pub async fn wait_core_ready(timeout: std::time::Duration) -> bool {
+    let started = std::time::Instant::now();
+    while CORE_PENDING.load(std::sync::atomic::Ordering::SeqCst) {
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        tokio::time::sleep(CORE_READY_POLL).await;
+    }
+    true
+}
+
+// Poll the sidecar with `pid` until it answers /health, then mark it Ready
+async fn watch_core_ready(app: tauri::AppHandle, pid: u32) {
+    let client = http::client(&app);
+    let started = std::time::Instant::now();
+    loop {
+        let health = remote_core::request(&client, reqwest::Method::GET, "", "/health").send().await;
+        let answered = health.is_ok_and(|r| r.status().is_success());
+        if !core_checked(&app, pid, answered, started.elapsed()) {
+            return;
+        }
+        tokio::time::sleep(CORE_READY_POLL).await;
+    }
+}
 
-async fn start_core_server(app: tauri::AppHandle) -> Result<(), String> {
+// Update the status of the sidecar with `pid` after a /health poll. Returns
+// whether to keep polling.
+fn core_checked(app: &tauri::AppHandle, pid: u32, answered: bool, waited: std::time::Duration) -> bool {
+    let Some(core_state) = app.try_state::<Mutex<CoreProcess>>() else { return false };
+    let Ok(mut state) = core_state.lock() else { return false };
+    // Stopped, crashed or replaced meanwhile
+    if state.child.as_ref().map(|child| child.pid()) != Some(pid) {
+        return false;
+    }
+    state.last_health_check = Some(chrono::Utc::now());
+    if answered {
+        println!("✓ imalink-core ready after {} ms", waited.as_millis());
+        let status = CoreStatus { state: CoreState::Ready, ..state.status.clone() };
+        set_core_status(app, &mut state, status);
+        let _ = app.emit("core-ready", state.snapshot());
+        return false;
+    }
+    if state.status.state == CoreState::Starting && waited >= CORE_READY_TIMEOUT {
+        eprintln!("imalink-core did not answer /health within {}s", CORE_READY_TIMEOUT.as_secs());
+        let status = CoreStatus { state: CoreState::Unreachable, ..state.status.clone() };
+        set_core_status(app, &mut state, status);
+    }
+    true
+}
+
+// The sidecar with `pid` terminated. Schedules a restart unless it was
+// stopped on purpose or exited cleanly.
+fn core_terminated(app: &tauri::AppHandle, pid: u32, code: Option<i32>) {
+    let Some(core_state) = app.try_state::<Mutex<CoreProcess>>() else { return };
+    let Ok(mut state) = core_state.lock() else { return };
+    // stop_core_server takes the child out first
+    if state.child.as_ref().map(|child| child.pid()) != Some(pid) {
+        return;
+    }
+    state.child = None;
+    let exit = CoreStatus { pid: Some(pid), exit_code: code, max_attempts: CORE_RESTART_ATTEMPTS, ..Default::default() };
+    if code == Some(0) {
+        set_core_status(app, &mut state, CoreStatus { state: CoreState::Stopped, ..exit });
+        return;
+    }
+
+    if state.started_at.is_some_and(|started| started.elapsed() >= CORE_STABLE_AFTER) {
+        state.crash_restarts = 0;
+    }
+    state.crash_restarts += 1;
+    let attempt = state.crash_restarts;
+    if attempt > CORE_RESTART_ATTEMPTS {
+        eprintln!("[imalink-core] Crashed {} times in a row, giving up", CORE_RESTART_ATTEMPTS);
+        set_core_status(app, &mut state, CoreStatus { state: CoreState::Crashed, attempt: attempt - 1, ..exit });
+        return;
+    }
+    let delay = CORE_RESTART_DELAY.saturating_mul(1 << (attempt - 1)).min(CORE_RESTART_MAX_DELAY);
+    eprintln!("[imalink-core] Restarting in {}s (attempt {}/{})", delay.as_secs(), attempt, CORE_RESTART_ATTEMPTS);
+    set_core_status(
+        app,
+        &mut state,
+        CoreStatus { state: CoreState::Restarting, attempt, retry_in_seconds: Some(delay.as_secs()), ..exit },
+    );
+    drop(state);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        // Started meanwhile by someone else (the stage watchdog)
+        let running = app
+            .try_state::<Mutex<CoreProcess>>()
+            .and_then(|core| core.lock().ok().map(|state| state.child.is_some()))
+            .unwrap_or(false);
+        if !running {
+            if let Err(e) = start_core_server(app) {
+                eprintln!("Failed to restart imalink-core: {}", e);
+            }
+        }
+    });
+}
+
+fn start_core_server(app: tauri::AppHandle) -> Result<(), ImalinkError> {
     use tauri_plugin_shell::process::CommandEvent;
     
-    println!("Starting imalink-core server on port 8765...");
+    let port = remote_core::sidecar_port(settings::load(&app).core_port);
+    println!("Starting imalink-core server on port {}...", port);
     
     let sidecar_command = app.shell()
         .sidecar("imalink-core")
-        .map_err(|e| {
-            let err_msg = format!("Failed to create sidecar command: {}", e);
-            eprintln!("{}", err_msg);
-            err_msg
-        })?;
+        .map_err(|e| ImalinkError::internal(format!("Failed to create sidecar command: {}", e)))?
+        .args(["--port", &port.to_string()])
+        .env("IMALINK_CORE_PORT", port.to_string());
     
     println!("Spawning imalink-core process...");
     let (mut rx, child) = sidecar_command
         .spawn()
-        .map_err(|e| {
-            let err_msg = format!("Failed to spawn imalink-core: {}", e);
-            eprintln!("{}", err_msg);
-            err_msg
-        })?;
+        .map_err(|e| ImalinkError::internal(format!("Failed to spawn imalink-core: {}", e)))?;
     
     println!("imalink-core process spawned with PID: {:?}", child.pid());
+    let pid = child.pid();
     
     // Store child process in global state so we can kill it on exit
     if let Some(core_state) = app.try_state::<Mutex<CoreProcess>>() {
         if let Ok(mut state) = core_state.lock() {
             state.child = Some(child);
+            state.started_at = Some(std::time::Instant::now());
+            state.last_health_check = None;
+            let status = CoreStatus {
+                state: CoreState::Starting,
+                pid: Some(pid),
+                attempt: state.crash_restarts,
+                max_attempts: CORE_RESTART_ATTEMPTS,
+                ..Default::default()
+            };
+            set_core_status(&app, &mut state, status);
             println!("✓ imalink-core process stored in state");
         }
     }
     
+    tauri::async_runtime::spawn(watch_core_ready(app.clone(), pid));
+    
     // Listen to core output in background
     tauri::async_runtime::spawn(async move {
         println!("Starting imalink-core output listener...");
+        // Keep the tail of stderr so a fatal exit can be attached to a crash report
+        let mut recent_stderr: std::collections::VecDeque<String> = std::collections::VecDeque::new();
         while let Some(event) = rx.recv().await {
             match event {
                 CommandEvent::Stdout(line) => {
@@ -826,14 +1751,19 @@ async fn start_core_server(app: tauri::AppHandle) -> Result<(), String> {
                 CommandEvent::Stderr(line) => {
                     let output = String::from_utf8_lossy(&line);
                     eprintln!("[imalink-core stderr] {}", output);
+                    if recent_stderr.len() >= 50 {
+                        recent_stderr.pop_front();
+                    }
+                    recent_stderr.push_back(output.to_string());
                 }
                 CommandEvent::Terminated(payload) => {
                     eprintln!("[imalink-core] Process terminated with code: {:?}", payload.code);
-                    if let Some(code) = payload.code {
-                        if code != 0 {
-                            eprintln!("[imalink-core] Non-zero exit code indicates error!");
-                        }
+                    // No code: killed by a signal
+                    if payload.code != Some(0) {
+                        eprintln!("[imalink-core] Non-zero exit code indicates error!");
+                        crash::record_core_exit(payload.code, recent_stderr.drain(..).collect());
                     }
+                    core_terminated(&app, pid, payload.code);
                     break;
                 }
                 CommandEvent::Error(err) => {
@@ -845,7 +1775,7 @@ async fn start_core_server(app: tauri::AppHandle) -> Result<(), String> {
         println!("imalink-core output listener terminated");
     });
     
-    println!("✓ imalink-core server started successfully on http://localhost:8765");
+    println!("✓ imalink-core server spawned on {}, waiting for it to answer", remote_core::get_core_url());
     Ok(())
 }
 
@@ -854,6 +1784,8 @@ fn stop_core_server(app: &tauri::AppHandle) {
         if let Ok(mut state) = core_state.lock() {
             if let Some(child) = state.child.take() {
                 println!("Stopping imalink-core process (PID: {:?})...", child.pid());
+                let status = CoreStatus { state: CoreState::Stopped, pid: Some(child.pid()), ..Default::default() };
+                set_core_status(app, &mut state, status);
                 match child.kill() {
                     Ok(_) => println!("✓ imalink-core stopped successfully"),
                     Err(e) => eprintln!("Failed to stop imalink-core: {}", e),
@@ -866,38 +1798,7 @@ fn stop_core_server(app: &tauri::AppHandle) {
 // ===== Web Gallery Integration =====
 
 #[tauri::command]
-async fn check_core_health(core_api_url: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let health_url = format!("{}/health", core_api_url);
-    
-    println!("Checking imalink-core health at: {}", health_url);
-    
-    match client.get(&health_url).send().await {
-        Ok(response) => {
-            let status = response.status();
-            println!("Health check response status: {}", status);
-            
-            if status.is_success() {
-                match response.text().await {
-                    Ok(body) => {
-                        println!("Health check response body: {}", body);
-                        Ok(format!("✓ imalink-core is running ({})", body))
-                    }
-                    Err(e) => Err(format!("Failed to read response: {}", e))
-                }
-            } else {
-                Err(format!("Health check failed with status: {}", status))
-            }
-        }
-        Err(e) => {
-            eprintln!("Health check request failed: {}", e);
-            Err(format!("Cannot connect to imalink-core at {}: {}", core_api_url, e))
-        }
-    }
-}
-
-#[tauri::command]
-async fn open_web_gallery(app: tauri::AppHandle, token: Option<String>) -> Result<(), String> {
+async fn open_web_gallery(app: tauri::AppHandle, token: Option<String>) -> Result<(), ImalinkError> {
     let gallery_url = if let Some(auth_token) = token {
         // Pass token as URL fragment (client-side only, not sent to server)
         format!("https://imalink.trollfjell.com/#token={}", auth_token)
@@ -908,12 +1809,12 @@ async fn open_web_gallery(app: tauri::AppHandle, token: Option<String>) -> Resul
     WebviewWindowBuilder::new(
         &app,
         "gallery",
-        WebviewUrl::External(gallery_url.parse().map_err(|e| format!("Invalid URL: {}", e))?)
+        WebviewUrl::External(gallery_url.parse().map_err(|e| ImalinkError::invalid(format!("Invalid URL: {}", e)))?)
     )
     .title("Imalink Gallery")
     .inner_size(800.0, 800.0)
     .build()
-    .map_err(|e| format!("Failed to create gallery window: {}", e))?;
+    .map_err(|e| ImalinkError::internal(format!("Failed to create gallery window: {}", e)))?;
 
     Ok(())
 }
@@ -0,0 +1,200 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::disk_usage::{self, CacheLimit, PrunePolicy};
+use crate::error::ImalinkError;
+use crate::pipeline::ImportOptions;
+use crate::{dng, settings, transcode};
+
+// ===== Low Disk Space =====
+//
+// Every CHECK_INTERVAL the drive holding the app's data is checked; below
+// `low_disk_threshold_mb` (0 = never) the app is in low disk mode: nothing
+// new goes in the schema cache, caches are cut down once, and DNG
+// conversions skip the workspace. Changes go out as `low-disk-changed`.
+// check_import_space estimates what an import needs per drive.
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MB: u64 = 1024 * 1024;
+// Temporary files of this many items can exist at once, per process worker
+const TEMP_ITEMS_PER_WORKER: u64 = 4;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+fn safe_limits() -> PrunePolicy {
+    PrunePolicy {
+        schema_cache: CacheLimit { max_mb: Some(0), max_age_days: None },
+        offline_previews: CacheLimit { max_mb: Some(50), max_age_days: None },
+        ..PrunePolicy::default()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LowDiskStatus {
+    pub active: bool,
+    // Where the app's data is
+    pub path: String,
+    pub free_bytes: Option<u64>,
+    pub threshold_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpaceCheck {
+    pub low_disk: LowDiskStatus,
+    // Size of the files to import
+    pub bytes: u64,
+    // Copy mode: archive copies, on the destination drive
+    pub destination: Option<String>,
+    pub destination_bytes: u64,
+    pub destination_free: Option<u64>,
+    // Derivatives and staged DNGs at the same time, on the system drive
+    pub temp_bytes: u64,
+    // The archive copies fit on the destination drive (or there are none)
+    pub fits: bool,
+}
+
+// Free bytes on the filesystem holding `path`
+#[cfg(unix)]
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid statvfs to fill in
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    let drive = path.to_string_lossy().chars().next().filter(|c| c.is_ascii_alphabetic())?;
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", &format!("(Get-PSDrive {}).Free", drive)])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn free_bytes(_path: &Path) -> Option<u64> {
+    None
+}
+
+// Nearest existing ancestor, for paths that are yet to be created
+fn existing(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, ImalinkError> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| ImalinkError::internal(format!("Failed to resolve data directory: {}", e)))
+}
+
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+// Measure the free space again and enter or leave low disk mode
+pub fn refresh(app: &tauri::AppHandle) -> Result<LowDiskStatus, ImalinkError> {
+    let dir = data_dir(app)?;
+    let threshold_bytes = settings::load(app).low_disk_threshold_mb * MB;
+    let free = existing(&dir).and_then(free_bytes);
+    let status = LowDiskStatus {
+        active: threshold_bytes > 0 && free.is_some_and(|f| f < threshold_bytes),
+        path: dir.display().to_string(),
+        free_bytes: free,
+        threshold_bytes,
+    };
+    if ACTIVE.swap(status.active, Ordering::SeqCst) != status.active {
+        eprintln!(
+            "Low disk mode {} ({} MB free)",
+            if status.active { "on" } else { "off" },
+            free.unwrap_or_default() / MB
+        );
+        if status.active {
+            match disk_usage::prune(app, &safe_limits()) {
+                Ok(pruned) => {
+                    let bytes: u64 = pruned.iter().map(|p| p.bytes).sum();
+                    println!("Freed {} MB of caches for low disk mode", bytes / MB);
+                }
+                Err(e) => eprintln!("Pruning caches for low disk mode failed: {}", e),
+            }
+        }
+        let _ = app.emit("low-disk-changed", &status);
+    }
+    Ok(status)
+}
+
+// Check the free space in the background for as long as the app runs
+pub fn start(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tick.tick().await;
+            let app = app.clone();
+            let checked = tauri::async_runtime::spawn_blocking(move || refresh(&app)).await;
+            if let Ok(Err(e)) = checked {
+                eprintln!("Free space check failed: {}", e);
+            }
+        }
+    });
+}
+
+// What importing `files` with `options` takes
+pub fn estimate(app: &tauri::AppHandle, options: &ImportOptions, files: &[String]) -> Result<SpaceCheck, ImalinkError> {
+    let sizes: Vec<(&String, u64)> = files
+        .iter()
+        .map(|f| (f, fs::metadata(f).map(|m| m.len()).unwrap_or(0)))
+        .collect();
+    let bytes = sizes.iter().map(|(_, size)| size).sum();
+    let converted = |file: &str| options.convert_to_dng && dng::is_proprietary_raw(file);
+    let needs_temp = |file: &str| {
+        transcode::needs_transcode(file) || (converted(file) && options.destination_dir.is_some() && !active())
+    };
+    let largest_temp = sizes.iter().filter(|(f, _)| needs_temp(f)).map(|(_, size)| *size).max().unwrap_or(0);
+    let temp_bytes = largest_temp * TEMP_ITEMS_PER_WORKER * options.workers.process.max(1) as u64;
+
+    let destination = options.destination_dir.as_deref().map(PathBuf::from);
+    // A DNG is about as large as its RAW
+    let destination_bytes = match destination {
+        Some(_) => bytes + sizes.iter().filter(|(f, _)| converted(f)).map(|(_, size)| size).sum::<u64>(),
+        None => 0,
+    };
+    let destination_free = destination.as_deref().and_then(existing).and_then(free_bytes);
+    Ok(SpaceCheck {
+        low_disk: refresh(app)?,
+        bytes,
+        destination: destination.map(|d| d.display().to_string()),
+        destination_bytes,
+        fits: destination_free.is_none_or(|free| free >= destination_bytes),
+        destination_free,
+        temp_bytes,
+    })
+}
+
+#[tauri::command]
+pub fn get_low_disk_status(app: tauri::AppHandle) -> Result<LowDiskStatus, ImalinkError> {
+    refresh(&app)
+}
+
+// Space an import with `options` needs against what is free, for a warning
+// before it is started
+#[tauri::command]
+pub async fn check_import_space(app: tauri::AppHandle, options: ImportOptions) -> Result<SpaceCheck, ImalinkError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let files = match &options.files {
+            Some(files) => files.clone(),
+            None => crate::collect_image_files(Path::new(&options.source_dir))?,
+        };
+        estimate(&app, &options, &files)
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
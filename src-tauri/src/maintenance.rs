@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::error::ImalinkError;
+use crate::ratelimit;
+
+// ===== Backend Maintenance =====
+//
+// A 503 with Retry-After becomes ImalinkError::Maintenance. Uploads wrapped
+// in wait_out pause until the announced time and try again, and the upload
+// queue starts nothing meanwhile. `backend-maintenance` tells the frontend
+// when a pause starts, is extended and ends.
+
+// Don't trust a Retry-After further out than this; ask again then
+const MAX_PAUSE: Duration = Duration::from_secs(6 * 60 * 60);
+const MIN_PAUSE: Duration = Duration::from_secs(5);
+
+static UNTIL: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceStatus {
+    pub active: bool,
+    // RFC 3339; None once over
+    pub until: Option<String>,
+    // Local HH:MM for the status line
+    pub resume_at: Option<String>,
+}
+
+fn status(until: Option<DateTime<Utc>>) -> MaintenanceStatus {
+    MaintenanceStatus {
+        active: until.is_some(),
+        until: until.map(|u| u.to_rfc3339()),
+        resume_at: until.map(|u| u.with_timezone(&chrono::Local).format("%H:%M").to_string()),
+    }
+}
+
+// When a 503 with this Retry-After value says to come back
+pub fn resume_time(retry_after: Option<&reqwest::header::HeaderValue>) -> Option<DateTime<Utc>> {
+    let value = retry_after?.to_str().ok()?.trim();
+    let wait = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => (DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    };
+    Some(Utc::now() + wait.clamp(MIN_PAUSE, MAX_PAUSE))
+}
+
+// Error for a 503 that announced when the backend is back
+pub fn error(until: DateTime<Utc>) -> ImalinkError {
+    ImalinkError::Maintenance { until: until.to_rfc3339() }
+}
+
+pub fn active() -> bool {
+    UNTIL.lock().unwrap().is_some_and(|until| until > Utc::now())
+}
+
+// Pause until `until`, or keep a later pause already in place
+fn begin(app: &tauri::AppHandle, until: DateTime<Utc>) {
+    let mut current = UNTIL.lock().unwrap();
+    if current.is_some_and(|c| c >= until) {
+        return;
+    }
+    let started = current.is_none();
+    *current = Some(until);
+    drop(current);
+    eprintln!("Backend under maintenance, pausing uploads until {}", until.to_rfc3339());
+    let _ = app.emit("backend-maintenance", status(Some(until)));
+
+    // One task ends the pause, however often it is extended
+    if started {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                wait().await;
+                let mut current = UNTIL.lock().unwrap();
+                if current.is_none_or(|u| u <= Utc::now()) {
+                    *current = None;
+                    break;
+                }
+            }
+            let _ = app.emit("backend-maintenance", status(None));
+        });
+    }
+}
+
+// Sleep through the current pause, if any
+async fn wait() {
+    loop {
+        let until = *UNTIL.lock().unwrap();
+        match until.and_then(|u| (u - Utc::now()).to_std().ok()) {
+            Some(left) if !left.is_zero() => tokio::time::sleep(left).await,
+            _ => return,
+        }
+    }
+}
+
+// Run a backend call, waiting out maintenance and rate limit pauses before
+// it and whenever it runs into one
+pub async fn wait_out<T, F, Fut>(app: &tauri::AppHandle, call: F) -> Result<T, ImalinkError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, ImalinkError>>,
+{
+    loop {
+        wait().await;
+        ratelimit::wait().await;
+        match call().await {
+            // The pause is in place already, see ratelimit::observe
+            Err(ImalinkError::RateLimited { .. }) => {}
+            Err(ImalinkError::Maintenance { until }) => {
+                let until = DateTime::parse_from_rfc3339(&until)
+                    .map(|u| u.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now() + MIN_PAUSE);
+                begin(app, until);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_maintenance_status() -> MaintenanceStatus {
+    status(*UNTIL.lock().unwrap())
+}
@@ -0,0 +1,174 @@
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::pipeline;
+use crate::presets;
+use crate::progress::Stage;
+use crate::remote_core;
+
+// ===== Processing Metrics =====
+//
+// The import pipeline adds the time every photo spent in core processing
+// and in upload to the `file_metrics` table of the history database, per
+// extension of its master: photos, bytes (the source files for processing,
+// the request body for upload) and milliseconds. Summed over all imports,
+// this gives a rate per extension that estimate_session applies to a set of
+// files before importing them with a preset:
+//
+//   processing  source bytes × ms per byte, spread over the process workers
+//   upload      photos × bytes per photo, × ms per byte, over upload workers
+//
+// The stages run side by side, so the slower one is the expected duration.
+// Extensions never imported before use the rate of all extensions together;
+// with no history at all there is no duration to give.
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Rate {
+    files: i64,
+    bytes: i64,
+    millis: i64,
+}
+
+impl Rate {
+    fn add(&mut self, other: Rate) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+        self.millis += other.millis;
+    }
+
+    fn millis_per_byte(&self) -> Option<f64> {
+        (self.bytes > 0).then(|| self.millis as f64 / self.bytes as f64)
+    }
+
+    fn bytes_per_file(&self) -> Option<f64> {
+        (self.files > 0).then(|| self.bytes as f64 / self.files as f64)
+    }
+}
+
+fn stage_name(stage: Stage) -> &'static str {
+    match stage {
+        Stage::Hash => "hash",
+        Stage::Lookup => "lookup",
+        Stage::Process => "process",
+        Stage::Upload => "upload",
+        Stage::Copy => "copy",
+    }
+}
+
+// Add one photo to the rate of `stage` for `extension`
+pub fn record(app: &tauri::AppHandle, stage: Stage, extension: &str, bytes: u64, elapsed: Duration) {
+    let history = app.state::<History>();
+    let recorded = history.with(|conn| {
+        conn.execute(
+            "INSERT INTO file_metrics (extension, stage, files, bytes, millis, updated_at)
+             VALUES (?1, ?2, 1, ?3, ?4, ?5)
+             ON CONFLICT (extension, stage) DO UPDATE SET files = files + 1,
+                 bytes = bytes + ?3, millis = millis + ?4, updated_at = ?5",
+            params![
+                extension,
+                stage_name(stage),
+                bytes as i64,
+                elapsed.as_millis() as i64,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+        .map(|_| ())
+    });
+    if let Err(e) = recorded {
+        eprintln!("Failed to record {} metrics for .{}: {}", stage_name(stage), extension, e);
+    }
+}
+
+fn rates(history: &History, stage: Stage) -> Result<HashMap<String, Rate>, ImalinkError> {
+    history.with(|conn| {
+        let mut stmt = conn.prepare("SELECT extension, files, bytes, millis FROM file_metrics WHERE stage = ?1")?;
+        let rows = stmt
+            .query_map([stage_name(stage)], |row| {
+                Ok((row.get(0)?, Rate { files: row.get(1)?, bytes: row.get(2)?, millis: row.get(3)? }))
+            })?
+            .collect::<rusqlite::Result<HashMap<String, Rate>>>();
+        rows
+    })
+}
+
+// Rate for `extension`, or of everything when it was never imported
+fn rate_for(rates: &HashMap<String, Rate>, overall: &Rate, extension: &str) -> Option<Rate> {
+    rates.get(extension).copied().filter(|r| r.files > 0).or((overall.files > 0).then_some(*overall))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionEstimate {
+    pub preset: String,
+    pub files: usize,
+    pub photos: usize,
+    pub source_bytes: u64,
+    // Photo create requests sent to the backend
+    pub upload_bytes: u64,
+    // Copied into the archive; 0 when the preset registers files in place
+    pub archive_bytes: u64,
+    pub process_seconds: Option<u64>,
+    pub upload_seconds: Option<u64>,
+    // The slower of the two stages; None without any history
+    pub duration_seconds: Option<u64>,
+    // Master extensions estimated from the overall rate
+    pub unknown_extensions: Vec<String>,
+}
+
+// Expected duration and sizes of importing `paths` with `preset`
+#[tauri::command]
+pub fn estimate_session(app: tauri::AppHandle, paths: Vec<String>, preset: String) -> Result<SessionEstimate, ImalinkError> {
+    let preset = presets::find(&app, &preset)?;
+    let history = app.state::<History>();
+    let process_rates = rates(&history, Stage::Process)?;
+    let upload_rates = rates(&history, Stage::Upload)?;
+    let overall = |rates: &HashMap<String, Rate>| {
+        let mut total = Rate::default();
+        rates.values().for_each(|r| total.add(*r));
+        total
+    };
+    let (process_overall, upload_overall) = (overall(&process_rates), overall(&upload_rates));
+
+    let groups = pipeline::group_companions(&paths, &preset.master_order);
+    let mut source_bytes = 0;
+    let (mut process_millis, mut upload_millis, mut upload_bytes) = (Some(0.0), Some(0.0), 0.0);
+    let mut unknown_extensions = BTreeSet::new();
+    for group in &groups {
+        let size: u64 = group.all_files().iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum();
+        source_bytes += size;
+        let extension = pipeline::extension_of(&group.master_file);
+        if !process_rates.contains_key(&extension) {
+            unknown_extensions.insert(extension.clone());
+        }
+
+        let process = rate_for(&process_rates, &process_overall, &extension).and_then(|r| r.millis_per_byte());
+        process_millis = process_millis.zip(process).map(|(total, per_byte)| total + per_byte * size as f64);
+        let upload = rate_for(&upload_rates, &upload_overall, &extension);
+        let body = upload.and_then(|r| r.bytes_per_file()).unwrap_or_default();
+        upload_bytes += body;
+        upload_millis = upload_millis
+            .zip(upload.and_then(|r| r.millis_per_byte()))
+            .map(|(total, per_byte)| total + per_byte * body);
+    }
+
+    let seconds = |millis: Option<f64>, workers: usize| millis.map(|m| (m / workers.max(1) as f64 / 1000.0).ceil() as u64);
+    let process_seconds = seconds(process_millis, remote_core::process_workers(preset.workers.process));
+    let upload_seconds = seconds(upload_millis, preset.workers.upload);
+    Ok(SessionEstimate {
+        preset: preset.name.clone(),
+        files: paths.len(),
+        photos: groups.len(),
+        source_bytes,
+        upload_bytes: upload_bytes as u64,
+        archive_bytes: if preset.destination_dir.is_some() { source_bytes } else { 0 },
+        process_seconds,
+        upload_seconds,
+        duration_seconds: process_seconds.zip(upload_seconds).map(|(p, u)| p.max(u)),
+        unknown_extensions: unknown_extensions.into_iter().collect(),
+    })
+}
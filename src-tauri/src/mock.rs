@@ -0,0 +1,462 @@
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
+use axum::{Json, Router};
+use base64::Engine;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::error::ImalinkError;
+
+// ===== Mock Backend and Core =====
+//
+// Only built with the `mock` feature. An in-process HTTP server that answers
+// both the imalink-core endpoints (/health, /v1/process, /v1/hothash) and the
+// backend endpoints the app uses (/api/v1/...) from in-memory state, so the
+// whole import pipeline can be exercised - by the frontend during
+// development or by the integration tests in tests/ (through harness.rs) -
+// without a live deployment. Point
+// both core_api_url and backend_url at `MockServer::url`.
+//
+// Responses are canned but consistent: the hothash is derived from the file
+// content, uploads of a known hothash get 409, and edits show up in the
+// change feed. Any username/password logs in. Run the app with
+// IMALINK_MOCK=1 to start it; get_mock_server_url returns its address.
+
+const MOCK_TOKEN: &str = "mock-token";
+
+#[derive(Default)]
+struct MockState {
+    photos: Vec<Value>,
+    channels: Vec<Value>,
+    // Change feed for /photos/changes; the cursor is an index into it
+    changes: Vec<Value>,
+    next_id: i64,
+    // Synced settings document and its version, see settings_sync.rs
+    settings: Value,
+    settings_version: i64,
+    authors: Vec<Value>,
+    // The mock user's self-author, see self_author.rs
+    default_author_id: Option<i64>,
+}
+
+type Shared = Arc<Mutex<MockState>>;
+
+pub struct MockServer {
+    pub url: String,
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn mock_user(state: &MockState) -> Value {
+    json!({
+        "id": 1,
+        "username": "mock",
+        "email": "mock@example.com",
+        "display_name": "Mock User",
+        "is_active": true,
+        "default_author_id": state.default_author_id,
+        "created_at": now(),
+    })
+}
+
+// 1x1 grey JPEG used for every preview
+fn pixel_jpeg() -> &'static [u8] {
+    static PIXEL: OnceLock<Vec<u8>> = OnceLock::new();
+    PIXEL.get_or_init(|| {
+        let mut out = Vec::new();
+        let pixel = image::RgbImage::from_pixel(1, 1, image::Rgb([128, 128, 128]));
+        let _ = pixel.write_to(&mut std::io::Cursor::new(&mut out), image::ImageFormat::Jpeg);
+        out
+    })
+}
+
+fn pixel_jpeg_base64() -> String {
+    base64::engine::general_purpose::STANDARD.encode(pixel_jpeg())
+}
+
+fn not_found(detail: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(json!({ "detail": detail }))).into_response()
+}
+
+impl MockState {
+    // Like the real backend, start out with the protected Quick Channel
+    fn seeded() -> Self {
+        let mut state = MockState::default();
+        let id = state.next_id();
+        state.channels.push(json!({
+            "id": id,
+            "imported_at": now(),
+            "title": "Quick Channel",
+            "description": null,
+            "default_author_id": null,
+            "images_count": 0,
+        }));
+        state
+    }
+
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn photo_response(photo: &Value, is_duplicate: bool) -> Value {
+        let mut response = photo.clone();
+        response["is_duplicate"] = json!(is_duplicate);
+        response
+    }
+
+    fn record_change(&mut self, photo: &Value) {
+        self.changes.push(json!({
+            "id": photo["id"],
+            "hothash": photo["hothash"],
+            "rating": photo["rating"],
+            "visibility": photo["visibility"],
+            "updated_at": photo["updated_at"],
+        }));
+    }
+}
+
+// ===== Core =====
+
+// Content of the multipart "file" field
+async fn file_field(mut multipart: Multipart) -> Result<(String, Vec<u8>), Response> {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("file") {
+            let name = field.file_name().unwrap_or("upload").to_string();
+            let bytes = field.bytes().await.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()).into_response())?;
+            return Ok((name, bytes.to_vec()));
+        }
+    }
+    Err((StatusCode::UNPROCESSABLE_ENTITY, "Missing file field").into_response())
+}
+
+// Stable fake hothash: first 32 hex chars of the content's BLAKE3 hash
+fn mock_hothash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex()[..32].to_string()
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": "mock" }))
+}
+
+async fn process(multipart: Multipart) -> Response {
+    let (filename, bytes) = match file_field(multipart).await {
+        Ok(file) => file,
+        Err(response) => return response,
+    };
+    let (width, height) = image::load_from_memory(&bytes).map(|img| (img.width(), img.height())).unwrap_or((0, 0));
+    Json(json!({
+        "hothash": mock_hothash(&bytes),
+        "hotpreview_base64": pixel_jpeg_base64(),
+        "hotpreview_width": 1,
+        "hotpreview_height": 1,
+        "coldpreview_base64": pixel_jpeg_base64(),
+        "coldpreview_width": 1,
+        "coldpreview_height": 1,
+        "width": width,
+        "height": height,
+        "taken_at": null,
+        "exif_dict": { "Make": "Mock", "Model": "Camera" },
+        "image_file_list": [{
+            "filename": filename,
+            "file_size": bytes.len(),
+            "format": "jpeg",
+            "is_raw": false,
+        }],
+    }))
+    .into_response()
+}
+
+async fn hothash(multipart: Multipart) -> Response {
+    match file_field(multipart).await {
+        Ok((_, bytes)) => Json(json!({ "hothash": mock_hothash(&bytes) })).into_response(),
+        Err(response) => response,
+    }
+}
+
+// ===== Backend: auth =====
+
+async fn login(State(state): State<Shared>) -> Json<Value> {
+    Json(json!({
+        "access_token": MOCK_TOKEN,
+        "token_type": "bearer",
+        "refresh_token": "mock-refresh-token",
+        "user": mock_user(&state.lock().unwrap()),
+    }))
+}
+
+async fn refresh() -> Json<Value> {
+    Json(json!({ "access_token": MOCK_TOKEN, "token_type": "bearer" }))
+}
+
+async fn me(State(state): State<Shared>) -> Json<Value> {
+    Json(mock_user(&state.lock().unwrap()))
+}
+
+async fn update_me(State(state): State<Shared>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    if let Some(author_id) = body.get("default_author_id") {
+        let author_id = author_id.as_i64();
+        if author_id.is_some_and(|id| !state.authors.iter().any(|a| a["id"].as_i64() == Some(id))) {
+            return not_found("Author not found");
+        }
+        state.default_author_id = author_id;
+    }
+    Json(mock_user(&state)).into_response()
+}
+
+// ===== Backend: authors =====
+
+async fn create_author(State(state): State<Shared>, Json(body): Json<Value>) -> Json<Value> {
+    let mut state = state.lock().unwrap();
+    let author = json!({
+        "id": state.next_id(),
+        "name": body["name"],
+        "is_self": body["is_self"].as_bool().unwrap_or(false),
+    });
+    state.authors.push(author.clone());
+    Json(author)
+}
+
+async fn logout() -> Json<Value> {
+    Json(json!({ "message": "Logged out" }))
+}
+
+// ===== Backend: input channels =====
+
+async fn list_channels(State(state): State<Shared>, Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let offset = query.get("offset").and_then(|o| o.parse().ok()).unwrap_or(0);
+    let limit = query.get("limit").and_then(|l| l.parse().ok()).unwrap_or(usize::MAX);
+    let channels: Vec<Value> = state
+        .channels
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .map(|c| {
+            let mut channel = c.clone();
+            channel["images_count"] = json!(state.photos.iter().filter(|p| p["input_channel_id"] == c["id"]).count());
+            channel
+        })
+        .collect();
+    Json(json!({ "total": state.channels.len(), "channels": channels }))
+}
+
+async fn get_channel(State(state): State<Shared>, Path(id): Path<i64>) -> Response {
+    let state = state.lock().unwrap();
+    match state.channels.iter().find(|c| c["id"].as_i64() == Some(id)) {
+        Some(channel) => {
+            let mut channel = channel.clone();
+            channel["images_count"] = json!(state.photos.iter().filter(|p| p["input_channel_id"] == id).count());
+            Json(channel).into_response()
+        }
+        None => not_found("Input channel not found"),
+    }
+}
+
+async fn update_channel(State(state): State<Shared>, Path(id): Path<i64>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(channel) = state.channels.iter_mut().find(|c| c["id"].as_i64() == Some(id)) else {
+        return not_found("Input channel not found");
+    };
+    for key in ["title", "description", "default_author_id"] {
+        if let Some(value) = body.get(key) {
+            channel[key] = value.clone();
+        }
+    }
+    Json(channel.clone()).into_response()
+}
+
+async fn delete_channel(State(state): State<Shared>, Path(id): Path<i64>) -> Response {
+    let mut state = state.lock().unwrap();
+    let before = state.channels.len();
+    state.channels.retain(|c| c["id"].as_i64() != Some(id));
+    if state.channels.len() == before {
+        return not_found("Input channel not found");
+    }
+    state.photos.retain(|p| p["input_channel_id"].as_i64() != Some(id));
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn create_channel(State(state): State<Shared>, Json(body): Json<Value>) -> Json<Value> {
+    let mut state = state.lock().unwrap();
+    let channel = json!({
+        "id": state.next_id(),
+        "imported_at": now(),
+        "title": body["title"],
+        "description": body["description"],
+        "default_author_id": body["default_author_id"],
+        "images_count": 0,
+    });
+    state.channels.push(channel.clone());
+    Json(channel)
+}
+
+// ===== Backend: photos =====
+
+async fn create_photo(State(state): State<Shared>, Json(body): Json<Value>) -> Response {
+    let schema = &body["photo_create_schema"];
+    let hothash = schema["hothash"].as_str().unwrap_or_default().to_string();
+    let mut state = state.lock().unwrap();
+
+    if let Some(existing) = state.photos.iter().find(|p| p["hothash"] == hothash.as_str()) {
+        return (StatusCode::CONFLICT, Json(MockState::photo_response(existing, true))).into_response();
+    }
+    let created_at = now();
+    let photo = json!({
+        "id": state.next_id(),
+        "hothash": hothash,
+        "user_id": 1,
+        "width": schema["width"],
+        "height": schema["height"],
+        "taken_at": schema["taken_at"],
+        "gps_latitude": schema["gps_latitude"],
+        "gps_longitude": schema["gps_longitude"],
+        "rating": body["rating"].as_i64().unwrap_or(0),
+        "visibility": body["visibility"].as_str().unwrap_or("private"),
+        "category": body["category"],
+        "tags": body.get("tags").cloned().unwrap_or_else(|| json!([])),
+        "input_channel_id": body["input_channel_id"],
+        "stack_id": schema["stack_id"],
+        "image_files": schema["image_file_list"],
+        "exif_dict": schema["exif_dict"],
+        "checksums": body["checksums"],
+        "created_at": created_at,
+        "updated_at": created_at,
+    });
+    state.photos.push(photo.clone());
+    (StatusCode::CREATED, Json(MockState::photo_response(&photo, false))).into_response()
+}
+
+async fn list_photos(State(state): State<Shared>, Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let offset = query.get("offset").and_then(|o| o.parse().ok()).unwrap_or(0);
+    let limit = query.get("limit").and_then(|l| l.parse().ok()).unwrap_or(100);
+    let channel = query.get("input_channel_id").and_then(|c| c.parse::<i64>().ok());
+    let matching: Vec<&Value> = state
+        .photos
+        .iter()
+        .filter(|p| channel.is_none_or(|c| p["input_channel_id"].as_i64() == Some(c)))
+        .collect();
+    let page: Vec<&Value> = matching.iter().skip(offset).take(limit).copied().collect();
+    Json(json!({ "data": page, "meta": { "total": matching.len() } }))
+}
+
+async fn photo_by_hothash(State(state): State<Shared>, Path(hothash): Path<String>) -> Response {
+    let state = state.lock().unwrap();
+    match state.photos.iter().find(|p| p["hothash"] == hothash.as_str()) {
+        Some(photo) => Json(photo.clone()).into_response(),
+        None => not_found("Photo not found"),
+    }
+}
+
+async fn update_photo(State(state): State<Shared>, Path(id): Path<i64>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(photo) = state.photos.iter_mut().find(|p| p["id"].as_i64() == Some(id)) else {
+        return not_found("Photo not found");
+    };
+    for key in ["rating", "visibility", "category", "stack_id", "coldpreview_base64", "coldpreview_width", "coldpreview_height"] {
+        if let Some(value) = body.get(key) {
+            photo[key] = value.clone();
+        }
+    }
+    photo["updated_at"] = json!(now());
+    let photo = photo.clone();
+    state.record_change(&photo);
+    Json(photo).into_response()
+}
+
+async fn changes(State(state): State<Shared>, Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let since = query.get("since").and_then(|s| s.parse().ok()).unwrap_or(0);
+    let changes: Vec<&Value> = state.changes.iter().skip(since).collect();
+    Json(json!({ "changes": changes, "cursor": state.changes.len().to_string() }))
+}
+
+async fn preview(State(state): State<Shared>, Path((id, _kind)): Path<(i64, String)>) -> Response {
+    if !state.lock().unwrap().photos.iter().any(|p| p["id"].as_i64() == Some(id)) {
+        return not_found("Photo not found");
+    }
+    ([(header::CONTENT_TYPE, "image/jpeg")], pixel_jpeg()).into_response()
+}
+
+async fn create_stack(State(state): State<Shared>, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let id = state.lock().unwrap().next_id();
+    let stack = json!({
+        "id": id,
+        "stack_type": body["stack_type"],
+        "description": body["description"],
+        "created_at": now(),
+    });
+    (StatusCode::CREATED, Json(stack))
+}
+
+async fn get_settings(State(state): State<Shared>) -> Response {
+    let state = state.lock().unwrap();
+    if state.settings_version == 0 {
+        return not_found("No settings stored");
+    }
+    Json(json!({ "settings": state.settings, "version": state.settings_version })).into_response()
+}
+
+async fn put_settings(State(state): State<Shared>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    if body["base_version"].as_i64().unwrap_or(0) != state.settings_version {
+        return (StatusCode::CONFLICT, Json(json!({ "detail": "Settings changed since base_version" }))).into_response();
+    }
+    state.settings = body["settings"].clone();
+    state.settings_version += 1;
+    Json(json!({ "settings": state.settings, "version": state.settings_version })).into_response()
+}
+
+async fn crash_report() -> StatusCode {
+    StatusCode::CREATED
+}
+
+fn router() -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/v1/process", post(process))
+        .route("/v1/hothash", post(hothash))
+        .route("/api/v1/auth/login/", post(login))
+        .route("/api/v1/auth/register/", post(me))
+        .route("/api/v1/auth/logout/", post(logout))
+        .route("/api/v1/auth/refresh/", post(refresh))
+        .route("/api/v1/auth/me/", get(me))
+        .route("/api/v1/authors/", post(create_author))
+        .route("/api/v1/input-channels/", get(list_channels).post(create_channel))
+        .route("/api/v1/input-channels/{id}/", get(get_channel).patch(update_channel).delete(delete_channel))
+        .route("/api/v1/photos/", get(list_photos))
+        .route("/api/v1/photos/create", post(create_photo))
+        .route("/api/v1/photos/changes", get(changes))
+        .route("/api/v1/photos/hothash/{hothash}", get(photo_by_hothash))
+        .route("/api/v1/photos/{id}", patch(update_photo))
+        .route("/api/v1/photos/{id}/{kind}", get(preview))
+        .route("/api/v1/photo-stacks/", post(create_stack))
+        .route("/api/v1/users/me/", patch(update_me))
+        .route("/api/v1/users/me/settings/", get(get_settings).put(put_settings))
+        .route("/api/v1/crash-reports/", post(crash_report))
+        .with_state(Arc::new(Mutex::new(MockState::seeded())))
+}
+
+// Start the mock server on a free local port
+pub async fn start() -> Result<MockServer, ImalinkError> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Failed to bind mock server: {}", e)))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| ImalinkError::internal(format!("Failed to bind mock server: {}", e)))?;
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = axum::serve(listener, router()).await {
+            eprintln!("Mock server stopped: {}", e);
+        }
+    });
+    Ok(MockServer { url: format!("http://{}", addr) })
+}
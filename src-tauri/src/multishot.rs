@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::ImalinkError;
+use crate::events;
+use crate::pipeline::{self, CompanionGroup, MasterOrder};
+
+// ===== Multi-shot Captures =====
+//
+// Sony pixel shift, Olympus/Panasonic high-resolution mode and Canon dual
+// pixel RAW write several RAW files for what is one photo; imported as is,
+// each frame becomes a photo of its own. detect_multishot_captures finds
+// these sets from the EXIF the camera writes:
+//
+// - a drive mode naming the technique (DriveMode, ReleaseMode, ...), and
+// - a shared group id, or otherwise a shot number counting up from frame to
+//   frame at most MAX_FRAME_GAP_SECONDS apart, from the same camera.
+//
+// The captures the user accepts go into ImportOptions::multishot, and the
+// pipeline merges the groups of each into one: the first frame is the
+// master, and every other frame (with its own companions) is a companion in
+// the same image_file_list, so the backend gets a single PhotoCreateSchema.
+
+const MAX_FRAME_GAP_SECONDS: i64 = 2;
+
+// EXIF tags naming the shooting technique, and the markers in their values
+const DRIVE_MODE_TAGS: &[&str] = &["DriveMode", "ReleaseMode", "ShootingMode", "MultiShotMode"];
+const MARKERS: &[(&str, MultiShotKind)] = &[
+    ("pixel shift", MultiShotKind::PixelShift),
+    ("pixel-shift", MultiShotKind::PixelShift),
+    ("pixelshift", MultiShotKind::PixelShift),
+    ("high res", MultiShotKind::HighResolution),
+    ("hi-res", MultiShotKind::HighResolution),
+    ("high-res", MultiShotKind::HighResolution),
+    ("dual pixel", MultiShotKind::DualPixel),
+    ("dual-pixel", MultiShotKind::DualPixel),
+];
+// Canon flags dual pixel RAW on its own instead of in the drive mode
+const DUAL_PIXEL_TAG: &str = "DualPixelRaw";
+const GROUP_ID_TAGS: &[&str] = &["PixelShiftGroupID", "ShotGroupID", "MultiShotGroupID"];
+const SHOT_NUMBER_TAGS: &[&str] = &["PixelShiftShotNumber", "ShotNumberInSequence", "SequenceNumber"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiShotKind {
+    PixelShift,
+    HighResolution,
+    DualPixel,
+}
+
+// A set of frames that make one photo; passed back in ImportOptions::multishot
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MultiShotCapture {
+    pub kind: MultiShotKind,
+    // Master files of the frames, in shot order; the first becomes the master
+    pub masters: Vec<String>,
+    #[serde(default)]
+    pub camera: Option<String>,
+    #[serde(default)]
+    pub taken_at: Option<String>,
+}
+
+struct Frame {
+    group: CompanionGroup,
+    kind: MultiShotKind,
+    camera: Option<String>,
+    taken_at: Option<chrono::NaiveDateTime>,
+    group_id: Option<String>,
+    shot: Option<u32>,
+}
+
+// First of `tags` present in the EXIF, as text
+fn tag_text(exif_dict: &serde_json::Value, tags: &[&str]) -> Option<String> {
+    tags.iter().find_map(|tag| match exif_dict.get(*tag)? {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(true) => Some(tag.to_string()),
+        _ => None,
+    })
+}
+
+fn kind_of(exif_dict: &serde_json::Value) -> Option<MultiShotKind> {
+    let drive_mode = DRIVE_MODE_TAGS.iter().find_map(|tag| {
+        let value = tag_text(exif_dict, &[tag])?.to_lowercase();
+        MARKERS.iter().find(|(marker, _)| value.contains(marker)).map(|(_, kind)| *kind)
+    });
+    drive_mode.or_else(|| {
+        tag_text(exif_dict, &[DUAL_PIXEL_TAG])
+            .filter(|v| !matches!(v.to_lowercase().as_str(), "0" | "off" | "false"))
+            .map(|_| MultiShotKind::DualPixel)
+    })
+}
+
+// Whether `next` continues the capture that `prev` is the last frame of
+fn continues(prev: &Frame, next: &Frame) -> bool {
+    if next.kind != prev.kind || next.camera != prev.camera {
+        return false;
+    }
+    if let (Some(a), Some(b)) = (&prev.group_id, &next.group_id) {
+        return a == b;
+    }
+    let close = match (prev.taken_at, next.taken_at) {
+        (Some(a), Some(b)) => (b - a).num_seconds().abs() <= MAX_FRAME_GAP_SECONDS,
+        _ => false,
+    };
+    let counting = match (prev.shot, next.shot) {
+        (Some(a), Some(b)) => b > a,
+        _ => true,
+    };
+    close && counting
+}
+
+fn capture(run: &[Frame]) -> MultiShotCapture {
+    MultiShotCapture {
+        kind: run[0].kind,
+        masters: run.iter().map(|f| f.group.master_file.clone()).collect(),
+        camera: run[0].camera.clone(),
+        taken_at: run[0].taken_at.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+    }
+}
+
+// Find multi-shot captures among `files` (processed through the schema cache)
+#[tauri::command]
+pub async fn detect_multishot_captures(
+    app: tauri::AppHandle,
+    core_api_url: String,
+    files: Vec<String>,
+    master_order: Option<MasterOrder>,
+) -> Result<Vec<MultiShotCapture>, ImalinkError> {
+    let mut frames: Vec<Frame> = events::process_groups(&app, &core_api_url, &files, &master_order.unwrap_or_default())
+        .await
+        .into_iter()
+        .filter_map(|(group, schema)| {
+            let schema = schema?;
+            Some(Frame {
+                kind: kind_of(&schema.exif_dict)?,
+                camera: pipeline::camera_of(&schema.exif_dict),
+                taken_at: events::capture_time(schema.taken_at.as_deref()),
+                group_id: tag_text(&schema.exif_dict, GROUP_ID_TAGS),
+                shot: tag_text(&schema.exif_dict, SHOT_NUMBER_TAGS).and_then(|s| s.parse().ok()),
+                group,
+            })
+        })
+        .collect();
+    frames.sort_by(|a, b| {
+        (&a.camera, a.taken_at, a.shot, &a.group.master_file).cmp(&(&b.camera, b.taken_at, b.shot, &b.group.master_file))
+    });
+
+    let mut captures = Vec::new();
+    let mut start = 0;
+    for end in 1..=frames.len() {
+        if end < frames.len() && continues(&frames[end - 1], &frames[end]) {
+            continue;
+        }
+        if end - start >= 2 {
+            captures.push(capture(&frames[start..end]));
+        }
+        start = end;
+    }
+    captures.sort_by(|a, b| a.taken_at.cmp(&b.taken_at));
+    Ok(captures)
+}
+
+// Merge the groups of each capture into the group of its first frame. Groups
+// keep their order; captures with fewer than two frames present are ignored.
+pub fn merge(groups: Vec<CompanionGroup>, captures: &[MultiShotCapture]) -> Vec<CompanionGroup> {
+    let mut groups: Vec<Option<CompanionGroup>> = groups.into_iter().map(Some).collect();
+    for capture in captures {
+        let members: Vec<usize> = capture
+            .masters
+            .iter()
+            .filter_map(|m| groups.iter().position(|g| g.as_ref().is_some_and(|g| &g.master_file == m)))
+            .collect();
+        let Some((&first, rest)) = members.split_first() else { continue };
+        if rest.is_empty() {
+            continue;
+        }
+        let frames: Vec<String> = rest.iter().filter_map(|&i| groups[i].take()).flat_map(|g| g.all_files()).collect();
+        if let Some(master) = groups[first].as_mut() {
+            master.companion_files.extend(frames);
+        }
+    }
+    groups.into_iter().flatten().collect()
+}
@@ -0,0 +1,272 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::{Emitter, Manager};
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+use crate::http::SendPaced;
+use crate::operations::{self, OperationKind};
+use crate::session::Session;
+
+// ===== Offline Previews =====
+//
+// Coldpreviews downloaded ahead of time for browsing without a connection,
+// e.g. on a laptop before a trip. The user picks channels and/or a capture
+// date range; matching photos are listed from the backend and their
+// coldpreviews stored on disk as <hothash>.jpg. The imalink-preview:// scheme
+// falls back to this cache for cold previews not held in memory, so the
+// offline gallery uses the same URLs as the online one.
+
+const PAGE_SIZE: usize = 100;
+const CONCURRENT_DOWNLOADS: usize = 4;
+
+pub struct OfflinePreviews {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OfflineCacheStats {
+    pub entries: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OfflineSelection {
+    // Empty means all channels
+    #[serde(default)]
+    pub input_channel_ids: Vec<i32>,
+    // Capture time range (RFC 3339 or YYYY-MM-DD), inclusive
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OfflineSyncProgress {
+    pub total: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PhotoRef {
+    pub id: i64,
+    pub hothash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PhotoPage {
+    data: Vec<PhotoRef>,
+}
+
+impl OfflinePreviews {
+    pub fn new(dir: PathBuf) -> Self {
+        OfflinePreviews { dir }
+    }
+
+    fn entry_path(&self, hothash: &str) -> Option<PathBuf> {
+        if hothash.is_empty() || !hothash.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(self.dir.join(format!("{}.jpg", hothash)))
+    }
+
+    pub fn get(&self, hothash: &str) -> Option<Vec<u8>> {
+        fs::read(self.entry_path(hothash)?).ok()
+    }
+
+    pub fn contains(&self, hothash: &str) -> bool {
+        self.entry_path(hothash).is_some_and(|p| p.is_file())
+    }
+
+    pub fn put(&self, hothash: &str, bytes: &[u8]) -> Result<(), ImalinkError> {
+        let path = self
+            .entry_path(hothash)
+            .ok_or_else(|| ImalinkError::invalid(format!("Invalid hothash: {}", hothash)))?;
+        fs::create_dir_all(&self.dir).map_err(|e| ImalinkError::io(self.dir.display(), e))?;
+
+        // Write to a temp file first so readers never see a partial preview
+        let tmp = path.with_extension("jpg.tmp");
+        fs::write(&tmp, bytes).map_err(|e| ImalinkError::io(tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| ImalinkError::io(path.display(), e))
+    }
+
+    // Cached previews as (hothash, bytes)
+    pub fn entries(&self) -> Vec<(String, u64)> {
+        fs::read_dir(&self.dir)
+            .map(|read_dir| {
+                read_dir
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jpg"))
+                    .filter_map(|entry| {
+                        let hothash = entry.path().file_stem()?.to_string_lossy().to_string();
+                        Some((hothash, entry.metadata().ok()?.len()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> OfflineCacheStats {
+        let entries = self.entries();
+        OfflineCacheStats {
+            entries: entries.len(),
+            total_bytes: entries.iter().map(|(_, bytes)| bytes).sum(),
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), ImalinkError> {
+        if Path::new(&self.dir).exists() {
+            fs::remove_dir_all(&self.dir).map_err(|e| ImalinkError::io(self.dir.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+// Every photo matching the selection, paging through the backend listing
+pub async fn list_photos(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    selection: &OfflineSelection,
+) -> Result<Vec<PhotoRef>, ImalinkError> {
+    // One listing per channel, or a single unfiltered one
+    let channels: Vec<Option<i32>> = if selection.input_channel_ids.is_empty() {
+        vec![None]
+    } else {
+        selection.input_channel_ids.iter().copied().map(Some).collect()
+    };
+
+    let mut photos = Vec::new();
+    for channel in channels {
+        let mut offset = 0;
+        loop {
+            let mut query: Vec<(&str, String)> = vec![("offset", offset.to_string()), ("limit", PAGE_SIZE.to_string())];
+            if let Some(channel) = channel {
+                query.push(("input_channel_id", channel.to_string()));
+            }
+            if let Some(from) = &selection.from {
+                query.push(("taken_after", from.clone()));
+            }
+            if let Some(to) = &selection.to {
+                query.push(("taken_before", to.clone()));
+            }
+
+            let response = client
+                .get(format!("{}/api/v1/photos/", backend_url))
+                .header("Authorization", format!("Bearer {}", auth_token))
+                .query(&query)
+                .send_paced()
+                .await
+                .map_err(|e| ImalinkError::network(backend_url, e))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(ImalinkError::from_backend(status, error_text));
+            }
+
+            let page: PhotoPage = response.json().await?;
+            let count = page.data.len();
+            photos.extend(page.data);
+            if count < PAGE_SIZE {
+                break;
+            }
+            offset += count;
+        }
+    }
+    Ok(photos)
+}
+
+pub async fn download_coldpreview(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    photo: &PhotoRef,
+) -> Result<Vec<u8>, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/photos/{}/coldpreview", backend_url, photo.id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+// Download coldpreviews for the selection into the offline cache. Emits
+// `offline-sync-progress` as previews complete; photos already cached are
+// reported as skipped.
+#[tauri::command]
+pub async fn sync_offline_previews(
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    selection: OfflineSelection,
+) -> Result<BatchResult<String>, ImalinkError> {
+    app.state::<AccessMode>().require_owner("sync offline previews")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = crate::http::client(&app);
+    let mut photos = list_photos(&client, &backend_url, &auth_token, &selection).await?;
+    // A photo can show up under several channels
+    photos.sort_by(|a, b| a.hothash.cmp(&b.hothash));
+    photos.dedup_by(|a, b| a.hothash == b.hothash);
+
+    let total = photos.len();
+    let done = AtomicUsize::new(0);
+    let cache = app.state::<OfflinePreviews>();
+    let operation = operations::register(&app, OperationKind::OfflineSync, "Download offline previews");
+    operation.set_total(total as u64);
+
+    let outcomes: Vec<(String, Option<Result<(), ImalinkError>>)> = futures_util::stream::iter(photos)
+        .map(|photo| {
+            let (client, backend_url, auth_token, cache, done, app, operation) =
+                (&client, &backend_url, &auth_token, &cache, &done, &app, &operation);
+            async move {
+                let outcome = if cache.contains(&photo.hothash) {
+                    None
+                } else {
+                    let downloaded = download_coldpreview(client, backend_url, auth_token, &photo).await;
+                    Some(downloaded.and_then(|bytes| cache.put(&photo.hothash, &bytes)))
+                };
+                let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                operation.advance();
+                let _ = app.emit("offline-sync-progress", OfflineSyncProgress { total, done });
+                (photo.hothash, outcome)
+            }
+        })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    let mut result = BatchResult::new();
+    for (hothash, outcome) in outcomes {
+        match outcome {
+            None => result.skip(hothash, "Already cached"),
+            Some(stored) => result.record(hothash.clone(), stored.map(|_| hothash)),
+        }
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn get_offline_cache_stats(cache: tauri::State<'_, OfflinePreviews>) -> OfflineCacheStats {
+    cache.stats()
+}
+
+#[tauri::command]
+pub fn clear_offline_cache(
+    access: tauri::State<'_, AccessMode>,
+    cache: tauri::State<'_, OfflinePreviews>,
+) -> Result<(), ImalinkError> {
+    access.require_owner("delete cached previews")?;
+    cache.clear()
+}
@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+
+// ===== Background Operations =====
+//
+// Long-running work registers an `Operation` here while it runs;
+// list_operations shows them with progress and cancel_operation stops one.
+// Cancellation is cooperative: `cancellable()` operations check
+// `is_cancelled()`, `register_with_cancel` ones get a callback. Dropping the
+// handle unregisters the operation.
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Import,
+    Prefetch,
+    Sync,
+    OfflineSync,
+    Export,
+    Tether,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OperationInfo {
+    pub id: String,
+    pub kind: OperationKind,
+    pub label: String,
+    pub started_at: String,
+    pub done: u64,
+    // None while the amount of work is not known yet
+    pub total: Option<u64>,
+    pub cancellable: bool,
+    pub cancelling: bool,
+}
+
+type CancelCallback = Box<dyn Fn() + Send + Sync>;
+
+struct Entry {
+    info: Mutex<OperationInfo>,
+    cancelled: AtomicBool,
+    on_cancel: Option<CancelCallback>,
+}
+
+// Managed state
+#[derive(Default)]
+pub struct Operations(Arc<Mutex<HashMap<String, Arc<Entry>>>>);
+
+// Handle held by the running operation
+pub struct Operation {
+    entry: Arc<Entry>,
+    registry: Arc<Mutex<HashMap<String, Arc<Entry>>>>,
+}
+
+impl Operations {
+    pub fn register(&self, kind: OperationKind, label: impl Into<String>) -> Operation {
+        self.insert(kind, label.into(), false, None)
+    }
+
+    fn insert(&self, kind: OperationKind, label: String, cancellable: bool, on_cancel: Option<CancelCallback>) -> Operation {
+        let entry = Arc::new(Entry {
+            info: Mutex::new(OperationInfo {
+                id: uuid::Uuid::new_v4().to_string(),
+                kind,
+                label,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                done: 0,
+                total: None,
+                cancellable: cancellable || on_cancel.is_some(),
+                cancelling: false,
+            }),
+            cancelled: AtomicBool::new(false),
+            on_cancel,
+        });
+        let id = entry.info.lock().unwrap().id.clone();
+        self.0.lock().unwrap().insert(id, entry.clone());
+        Operation { entry, registry: self.0.clone() }
+    }
+
+    // Running operations, oldest first
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let mut list: Vec<OperationInfo> = self
+            .0
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.lock().unwrap().clone())
+            .collect();
+        list.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        list
+    }
+}
+
+pub fn register(app: &tauri::AppHandle, kind: OperationKind, label: impl Into<String>) -> Operation {
+    app.state::<Operations>().register(kind, label)
+}
+
+// Register an operation stopped by `callback` on cancel
+pub fn register_with_cancel(
+    app: &tauri::AppHandle,
+    kind: OperationKind,
+    label: impl Into<String>,
+    callback: impl Fn() + Send + Sync + 'static,
+) -> Operation {
+    app.state::<Operations>().insert(kind, label.into(), true, Some(Box::new(callback)))
+}
+
+impl Operation {
+    // The operation checks is_cancelled() itself
+    pub fn cancellable(self) -> Self {
+        self.entry.info.lock().unwrap().cancellable = true;
+        self
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.entry.info.lock().unwrap().total = Some(total);
+    }
+
+    pub fn advance(&self) {
+        self.entry.info.lock().unwrap().done += 1;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.entry.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        let id = self.entry.info.lock().unwrap().id.clone();
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.remove(&id);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_operations(operations: tauri::State<'_, Operations>) -> Vec<OperationInfo> {
+    operations.list()
+}
+
+#[tauri::command]
+pub fn cancel_operation(
+    operations: tauri::State<'_, Operations>,
+    access: tauri::State<'_, AccessMode>,
+    id: String,
+) -> Result<OperationInfo, ImalinkError> {
+    access.require_owner("cancel operations")?;
+    let entry = operations
+        .0
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| ImalinkError::invalid(format!("No running operation {}", id)))?;
+    {
+        let mut info = entry.info.lock().unwrap();
+        if !info.cancellable {
+            return Err(ImalinkError::invalid(format!("{} can't be cancelled", info.label)));
+        }
+        info.cancelling = true;
+    }
+    if !entry.cancelled.swap(true, Ordering::SeqCst) {
+        if let Some(callback) = &entry.on_cancel {
+            callback();
+        }
+    }
+    let info = entry.info.lock().unwrap().clone();
+    Ok(info)
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+
+// ===== Local Originals =====
+//
+// Resolves a hothash to the files on disk, so any view that shows a photo can
+// offer "open original" without knowing where it was imported from. The
+// candidates come from local history: the stored files recorded by the import
+// (archive copies in copy mode) first, then the path the master was imported
+// from.
+//
+// Archives often live on external drives. For each path the volume it sits
+// on is reported along with whether that volume is mounted, so the UI can say
+// "connect the drive Photos2019" instead of just "file missing". Volumes are
+// recognised by their usual mount locations:
+//
+//   macOS     /Volumes/<name>
+//   Linux     /media/<user>/<name>, /run/media/<user>/<name>, /mnt/<name>
+//   Windows   drive letters and UNC shares
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OriginalSource {
+    // Recorded as stored by the import
+    Stored,
+    // Where the master was imported from
+    Imported,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OriginalFile {
+    pub path: String,
+    pub source: OriginalSource,
+    pub exists: bool,
+    // Mount point of the removable/network volume holding the file, if any
+    pub volume: Option<String>,
+    pub volume_mounted: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OriginalLocations {
+    pub hothash: String,
+    pub files: Vec<OriginalFile>,
+    // First image file that exists - the one to open
+    pub available: Option<String>,
+}
+
+// Mount point of the volume `path` is on, for paths on removable or network
+// volumes
+pub fn volume_root(path: &Path) -> Option<PathBuf> {
+    let components: Vec<Component> = path.components().collect();
+    if let Some(Component::Prefix(prefix)) = components.first() {
+        return Some(PathBuf::from(prefix.as_os_str()).join(std::path::MAIN_SEPARATOR_STR));
+    }
+    let names: Vec<&str> = components
+        .iter()
+        .filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    let depth = match names.as_slice() {
+        ["Volumes", _, ..] | ["mnt", _, ..] => 2,
+        ["media", _, _, ..] => 3,
+        ["run", "media", _, _, ..] => 4,
+        _ => return None,
+    };
+    Some(Path::new("/").join(names[..depth].iter().collect::<PathBuf>()))
+}
+
+// Whether something is mounted at `root`. An unmounted drive often leaves
+// an empty directory behind, so on Unix the root must also be on a different
+// device than its parent.
+pub fn is_mounted(root: &Path) -> bool {
+    let Ok(meta) = std::fs::metadata(root) else { return false };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let Some(parent) = root.parent().and_then(|p| std::fs::metadata(p).ok()) {
+            return meta.dev() != parent.dev();
+        }
+    }
+    meta.is_dir()
+}
+
+// Not a sidecar (XMP, AAE, ...)
+fn is_image(path: &str) -> bool {
+    let ext = Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    crate::pipeline::master_priority(&ext) < 99
+}
+
+fn describe(path: String, source: OriginalSource) -> OriginalFile {
+    let volume = volume_root(Path::new(&path));
+    OriginalFile {
+        exists: Path::new(&path).is_file(),
+        volume_mounted: volume.as_deref().is_none_or(is_mounted),
+        volume: volume.map(|v| v.display().to_string()),
+        path,
+        source,
+    }
+}
+
+pub fn locate(history: &History, hothash: &str) -> Result<OriginalLocations, ImalinkError> {
+    let photo = history
+        .get(hothash)?
+        .ok_or_else(|| ImalinkError::invalid(format!("Photo {} is not in local history", hothash)))?;
+
+    let mut files: Vec<OriginalFile> = history
+        .stored_files(hothash)?
+        .into_iter()
+        .map(|path| describe(path, OriginalSource::Stored))
+        .collect();
+    if let Some(path) = photo.file_path.filter(|p| !files.iter().any(|f| &f.path == p)) {
+        files.push(describe(path, OriginalSource::Imported));
+    }
+
+    Ok(OriginalLocations {
+        hothash: hothash.to_string(),
+        available: files.iter().find(|f| f.exists && is_image(&f.path)).map(|f| f.path.clone()),
+        files,
+    })
+}
+
+#[tauri::command]
+pub async fn locate_original(app: tauri::AppHandle, hothash: String) -> Result<OriginalLocations, ImalinkError> {
+    tauri::async_runtime::spawn_blocking(move || locate(&app.state::<History>(), &hothash))
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Original lookup failed: {}", e)))?
+}
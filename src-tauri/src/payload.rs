@@ -0,0 +1,161 @@
+use base64::Engine;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::error::ImalinkError;
+use crate::settings::AppSettings;
+use crate::PhotoCreateRequest;
+
+// ===== Upload Payload Size =====
+//
+// The photo create request carries the coldpreview inline as base64, and for
+// some files (large panoramas, PNG previews) that alone pushes the request
+// past the request size limit of the backend or a proxy in front of it,
+// which answers with a bare 413. The serialized request is measured before
+// sending; when it is over `max_upload_body_kb` (settings) the coldpreview
+// is downscaled and recompressed until the request fits, and as a last
+// resort left out - it is optional, the backend can do without it. Every
+// shrink is logged and reported through the `on_shrink` callback (the
+// pipeline counts them in the upload stage stats).
+
+const KB: u64 = 1024;
+pub const DEFAULT_MAX_KB: u64 = 10 * 1024;
+
+// Don't shrink coldpreviews further than this before dropping them
+const MIN_COLDPREVIEW_PX: u32 = 256;
+const JPEG_QUALITY: u8 = 80;
+const MAX_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PayloadShrink {
+    pub hothash: String,
+    pub original_bytes: u64,
+    pub final_bytes: u64,
+    // Longer edge of the coldpreview before and after; None after = dropped
+    pub coldpreview_from: Option<u32>,
+    pub coldpreview_to: Option<u32>,
+}
+
+type ShrinkCallback = Arc<dyn Fn(&PayloadShrink) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct PayloadLimit {
+    // 0 = no limit
+    pub max_bytes: u64,
+    // Checksums in the request's own field, not exif_dict (integrity.rs)
+    pub checksum_field: bool,
+    on_shrink: Option<ShrinkCallback>,
+}
+
+impl PayloadLimit {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        PayloadLimit {
+            max_bytes: settings.max_upload_body_kb * KB,
+            checksum_field: settings.upload_checksum_field,
+            on_shrink: None,
+        }
+    }
+
+    pub fn on_shrink(mut self, callback: impl Fn(&PayloadShrink) + Send + Sync + 'static) -> Self {
+        self.on_shrink = Some(Arc::new(callback));
+        self
+    }
+}
+
+fn longer_edge(request: &PhotoCreateRequest) -> Option<u32> {
+    let schema = &request.photo_create_schema;
+    schema.coldpreview_base64.as_ref()?;
+    Some(schema.coldpreview_width.unwrap_or(0).max(schema.coldpreview_height.unwrap_or(0)).max(0) as u32)
+}
+
+// Re-encode the coldpreview with its longer edge at most `max_edge`
+fn shrink_coldpreview(request: &mut PhotoCreateRequest, max_edge: u32) -> Result<(), ImalinkError> {
+    let schema = &mut request.photo_create_schema;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let decoded = schema
+        .coldpreview_base64
+        .as_deref()
+        .and_then(|b| engine.decode(b).ok())
+        .and_then(|bytes| image::load_from_memory(&bytes).ok());
+    let Some(img) = decoded else {
+        // Nothing to shrink - leave it out instead
+        drop_coldpreview(request);
+        return Ok(());
+    };
+    let resized = img.thumbnail(max_edge, max_edge).to_rgb8();
+
+    let mut out = Vec::new();
+    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY);
+    resized
+        .write_with_encoder(encoder)
+        .map_err(|e| ImalinkError::internal(format!("Failed to encode coldpreview: {}", e)))?;
+    schema.coldpreview_base64 = Some(engine.encode(out));
+    schema.coldpreview_width = Some(resized.width() as i32);
+    schema.coldpreview_height = Some(resized.height() as i32);
+    Ok(())
+}
+
+fn drop_coldpreview(request: &mut PhotoCreateRequest) {
+    let schema = &mut request.photo_create_schema;
+    schema.coldpreview_base64 = None;
+    schema.coldpreview_width = None;
+    schema.coldpreview_height = None;
+}
+
+// Serialize the request, shrinking its coldpreview as needed to stay under
+// the limit. Fails when even the request without coldpreview is too large.
+pub fn fit(request: &mut PhotoCreateRequest, limit: &PayloadLimit) -> Result<Vec<u8>, ImalinkError> {
+    let body = serde_json::to_vec(request)?;
+    let original_bytes = body.len() as u64;
+    if limit.max_bytes == 0 || original_bytes <= limit.max_bytes {
+        return Ok(body);
+    }
+
+    let coldpreview_from = longer_edge(request);
+    let mut body = body;
+    let mut attempts = 0;
+    while body.len() as u64 > limit.max_bytes {
+        let Some(edge) = longer_edge(request) else { break };
+        let cold_len = request.photo_create_schema.coldpreview_base64.as_ref().map_or(0, |c| c.len()) as f64;
+        let budget = limit.max_bytes as f64 - (body.len() as f64 - cold_len);
+        // Bytes scale with area, so the edge with the square root; aim a bit low
+        let scale = if budget > 0.0 { (budget / cold_len).sqrt() * 0.9 } else { 0.0 };
+        let target = (edge as f64 * scale.min(0.9)) as u32;
+        attempts += 1;
+        if target < MIN_COLDPREVIEW_PX || attempts > MAX_ATTEMPTS {
+            drop_coldpreview(request);
+        } else {
+            shrink_coldpreview(request, target)?;
+        }
+        body = serde_json::to_vec(request)?;
+    }
+
+    if body.len() as u64 > limit.max_bytes {
+        return Err(ImalinkError::invalid(format!(
+            "Photo {} is {} KB to upload, over the {} KB limit (max_upload_body_kb) even without coldpreview",
+            request.photo_create_schema.hothash,
+            body.len() as u64 / KB,
+            limit.max_bytes / KB
+        )));
+    }
+
+    let shrink = PayloadShrink {
+        hothash: request.photo_create_schema.hothash.clone(),
+        original_bytes,
+        final_bytes: body.len() as u64,
+        coldpreview_from,
+        coldpreview_to: longer_edge(request),
+    };
+    println!(
+        "Shrunk upload of {} from {} KB to {} KB (coldpreview {:?} -> {:?})",
+        shrink.hothash,
+        shrink.original_bytes / KB,
+        shrink.final_bytes / KB,
+        shrink.coldpreview_from,
+        shrink.coldpreview_to
+    );
+    if let Some(callback) = &limit.on_shrink {
+        callback(&shrink);
+    }
+    Ok(body)
+}
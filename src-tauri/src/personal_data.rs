@@ -0,0 +1,178 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::{History, HistoryPhoto};
+use crate::http::SendPaced;
+use crate::session::Session;
+
+// ===== Personal Data Export =====
+//
+// export_my_data writes everything the user has in imalink into one zip, in
+// formats any tool can read - for a data access request, or to move away
+// from a hosted instance:
+//
+//   user.json               the account
+//   input_channels.json     the user's input channels
+//   photos.json             full metadata of every photo on the backend
+//   photos.csv              the main photo fields, one row per photo
+//   local_history.json/csv  the desktop app's import history
+//
+// Previews and originals are not included; originals are already on the
+// user's disk and export_photos can write previews.
+
+const PAGE_SIZE: usize = 100;
+
+const PHOTO_COLUMNS: &[&str] = &[
+    "id",
+    "hothash",
+    "taken_at",
+    "width",
+    "height",
+    "rating",
+    "visibility",
+    "category",
+    "input_channel_id",
+    "gps_latitude",
+    "gps_longitude",
+    "created_at",
+    "updated_at",
+];
+
+const HISTORY_COLUMNS: &[&str] = &[
+    "hothash",
+    "photo_id",
+    "file_path",
+    "input_channel_id",
+    "rating",
+    "visibility",
+    "imported_at",
+    "taken_at",
+    "camera",
+    "bytes",
+    "archived_bytes",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PersonalDataExport {
+    pub path: String,
+    pub photos: usize,
+    pub history_photos: usize,
+    pub input_channels: usize,
+}
+
+// Every photo of the user, with all the metadata the listing returns
+async fn fetch_photos(client: &reqwest::Client, backend_url: &str, auth_token: &str) -> Result<Vec<Value>, ImalinkError> {
+    let mut photos = Vec::new();
+    let mut offset = 0;
+    loop {
+        let response = client
+            .get(format!("{}/api/v1/photos/", backend_url))
+            .header("Authorization", format!("Bearer {}", auth_token))
+            .query(&[("offset", offset.to_string()), ("limit", PAGE_SIZE.to_string())])
+            .send_paced()
+            .await
+            .map_err(|e| ImalinkError::network(backend_url, e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ImalinkError::from_backend(status, error_text));
+        }
+
+        let page: Value = response.json().await?;
+        let data = page["data"].as_array().cloned().unwrap_or_default();
+        let count = data.len();
+        photos.extend(data);
+        if count < PAGE_SIZE {
+            break;
+        }
+        offset += count;
+    }
+    Ok(photos)
+}
+
+// RFC 4180 field: quoted when it contains a separator, quote or newline
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+// CSV with the given columns of each JSON object
+fn to_csv(columns: &[&str], rows: &[Value]) -> String {
+    let mut csv = columns.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = columns.iter().map(|c| csv_field(&row[*c])).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn write_package(dest_dir: &Path, files: Vec<(&str, Vec<u8>)>) -> Result<PathBuf, ImalinkError> {
+    fs::create_dir_all(dest_dir).map_err(|e| ImalinkError::io(dest_dir.display(), e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let path = dest_dir.join(format!("imalink-my-data-{}.zip", stamp));
+
+    let file = fs::File::create(&path).map_err(|e| ImalinkError::io(path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, bytes) in files {
+        zip.start_file(name, options).map_err(|e| ImalinkError::io(path.display(), e))?;
+        zip.write_all(&bytes).map_err(|e| ImalinkError::io(path.display(), e))?;
+    }
+    zip.finish().map_err(|e| ImalinkError::io(path.display(), e))?;
+    Ok(path)
+}
+
+// Export the user's backend data and local history as a zip in `dest`
+#[tauri::command]
+pub async fn export_my_data(
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    dest: String,
+) -> Result<PersonalDataExport, ImalinkError> {
+    guest::require_owner(&app, "export")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = crate::http::client(&app);
+    let user = crate::validate_token(app.clone(), Some(backend_url.clone()), Some(auth_token.clone())).await?;
+    let channels =
+        crate::list_input_channels(app.clone(), Some(backend_url.clone()), Some(auth_token.clone()), None, None, None).await?;
+    let photos = fetch_photos(&client, &backend_url, &auth_token).await?;
+    let history: Vec<HistoryPhoto> = app.state::<History>().all_photos()?;
+    let history_rows: Vec<Value> = history.iter().map(serde_json::to_value).collect::<Result<_, _>>()?;
+
+    let files = vec![
+        ("user.json", serde_json::to_vec_pretty(&user)?),
+        ("input_channels.json", serde_json::to_vec_pretty(&channels)?),
+        ("photos.json", serde_json::to_vec_pretty(&photos)?),
+        ("photos.csv", to_csv(PHOTO_COLUMNS, &photos).into_bytes()),
+        ("local_history.json", serde_json::to_vec_pretty(&history_rows)?),
+        ("local_history.csv", to_csv(HISTORY_COLUMNS, &history_rows).into_bytes()),
+    ];
+    let dest = PathBuf::from(dest);
+    let path = tauri::async_runtime::spawn_blocking(move || write_package(&dest, files))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))??;
+
+    Ok(PersonalDataExport {
+        path: path.to_string_lossy().to_string(),
+        photos: photos.len(),
+        history_photos: history.len(),
+        input_channels: channels.len(),
+    })
+}
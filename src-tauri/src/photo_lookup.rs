@@ -0,0 +1,97 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::{History, HistoryPhoto};
+use crate::originals::{self, OriginalLocations};
+use crate::{auth, hothash};
+
+// ===== Photo Lookup =====
+//
+// Everything known about a hothash in one answer, for views that start from
+// a hothash (duplicate resolution, reveal/open original): the photo on the
+// backend and the row in local history with the files on disk.
+//
+// Rating and visibility come from the backend, except while a local edit
+// hasn't been synced yet (`dirty`). Without a login, or when the backend
+// can't be reached, the local side is returned on its own with the reason in
+// `backend_error`; only a hothash neither side knows is an error.
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PhotoLookup {
+    pub hothash: String,
+    pub photo_id: Option<i32>,
+    pub rating: Option<i32>,
+    pub visibility: Option<String>,
+    // Photo as the backend returned it
+    pub backend: Option<Value>,
+    pub backend_error: Option<String>,
+    pub local: Option<HistoryPhoto>,
+    pub originals: Option<OriginalLocations>,
+}
+
+fn merge(hothash: String, backend: Option<Value>, local: Option<HistoryPhoto>, originals: Option<OriginalLocations>) -> PhotoLookup {
+    let remote_id = backend.as_ref().and_then(|b| b.get("id")?.as_i64()).map(|id| id as i32);
+    let remote_rating = backend.as_ref().and_then(|b| b.get("rating")?.as_i64()).map(|r| r as i32);
+    let remote_visibility = backend.as_ref().and_then(|b| Some(b.get("visibility")?.as_str()?.to_string()));
+    let local_first = local.as_ref().is_some_and(|l| l.dirty);
+    let (local_rating, local_visibility) = match &local {
+        Some(l) => (l.rating, l.visibility.clone()),
+        None => (None, None),
+    };
+    PhotoLookup {
+        photo_id: remote_id.or(local.as_ref().and_then(|l| l.photo_id)),
+        rating: if local_first { local_rating.or(remote_rating) } else { remote_rating.or(local_rating) },
+        visibility: if local_first {
+            local_visibility.or(remote_visibility)
+        } else {
+            remote_visibility.or(local_visibility)
+        },
+        hothash,
+        backend,
+        backend_error: None,
+        local,
+        originals,
+    }
+}
+
+// The photo with `hothash` on the backend (when logged in) and in local history
+#[tauri::command]
+pub async fn get_photo_by_hothash(
+    app: tauri::AppHandle,
+    hothash: String,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<PhotoLookup, ImalinkError> {
+    let history = app.state::<History>();
+    let local = history.get(&hothash)?;
+    let originals = match &local {
+        Some(_) => Some(originals::locate(&history, &hothash)?),
+        None => None,
+    };
+
+    let (backend, backend_error) = match (&backend_url, &auth_token) {
+        (Some(backend_url), Some(auth_token)) => {
+            let client = crate::http::client(&app);
+            let fetched = auth::with_refresh(&app, backend_url, auth_token, |token| {
+                let (client, backend_url, hothash) = (&client, backend_url, &hothash);
+                async move { hothash::fetch_backend_photo(client, backend_url, &token, hothash).await }
+            })
+            .await;
+            match fetched {
+                Ok(photo) => (photo, None),
+                Err(e) if local.is_some() => (None, Some(e.to_string())),
+                Err(e) => return Err(e),
+            }
+        }
+        _ => (None, Some("Not logged in".to_string())),
+    };
+    if backend.is_none() && local.is_none() {
+        return Err(ImalinkError::invalid(format!("No photo with hothash {}", hothash)));
+    }
+
+    let mut lookup = merge(hothash, backend, local, originals);
+    lookup.backend_error = backend_error;
+    Ok(lookup)
+}
@@ -0,0 +1,1521 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tauri::{Emitter, Manager};
+use tokio::sync::mpsc;
+
+use crate::assets;
+use crate::auth;
+use crate::authors::{self, AuthorRule};
+use crate::batch::BatchResult;
+use crate::cards;
+use crate::coldpreviews;
+use crate::dng;
+use crate::duplicates::{self, DuplicatePolicy};
+use crate::email_import::EmailSource;
+use crate::error::ImalinkError;
+use crate::existence::{self, ExistenceCache};
+use crate::guest;
+use crate::history::{History, ImportedFile, UploadRecord};
+use crate::labels::{self, CullMarks};
+use crate::low_disk;
+use crate::hothash::{self, HothashIndex};
+use crate::hotpreview;
+use crate::maintenance;
+use crate::metrics;
+use crate::multishot::{self, MultiShotCapture};
+use crate::operations::{self, Operation, OperationKind};
+use crate::payload::PayloadLimit;
+use crate::plugins::PluginSet;
+use crate::privacy::{self, GpsScrub, PrivacyZone};
+use crate::progress::{ProgressAggregator, Stage};
+use crate::quarantine::Quarantine;
+use crate::{preflight, recovery, remote_core, rename};
+use crate::schema_cache::SchemaCache;
+use crate::sequences::{self, SequencePlan};
+use crate::session::Session;
+use crate::session_notes;
+use crate::stall::StallPolicy;
+use crate::transcode::{self, Transcoder};
+use crate::watchdog::{self, StageTimeouts};
+use crate::{streaming, undo, workspace};
+use crate::{ImageFileSchema, PhotoCreateResponse, PhotoCreateSchema};
+
+// ===== Import Pipeline =====
+//
+// Staged, bounded-concurrency import: scan → hash → lookup → process (core)
+// → upload → copy. Each stage has its own worker pool fed by a bounded channel, so a
+// slow stage (usually core processing) applies backpressure upstream instead
+// of buffering the whole card in memory, while the other stages keep working
+// on items that are already through.
+//
+// The lookup stage resolves the hothash without full processing where
+// possible (see hothash.rs) and finishes photos the backend already has as
+// duplicates, skipping both core and upload for them.
+//
+// Copy runs after upload so files are only archived once the backend has
+// accepted them; the destination path is computed up front and recorded in
+// `local_storage_info` before upload.
+//
+// Files the user rejected while culling (`rejected`) never enter the stages.
+// They are reported as skipped and counted in the session; in copy mode they
+// can still be archived under their original names (`archive_rejected`).
+// Time-lapse frames outside the keyframes of a keyframes_only plan are
+// skipped the same way; stack plans get their backend stack before the first
+// item is fed in (see sequences.rs).
+
+// Number of workers per stage
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StageWorkers {
+    pub hash: usize,
+    pub lookup: usize,
+    pub process: usize,
+    pub upload: usize,
+    pub copy: usize,
+}
+
+impl Default for StageWorkers {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        StageWorkers {
+            hash: cpus.clamp(2, 8),
+            lookup: 4,
+            process: 4,
+            upload: 4,
+            copy: 2,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportOptions {
+    pub source_dir: String,
+    // Explicit selection; when absent the whole source_dir is scanned
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+    pub core_api_url: String,
+    // Taken from the login session when empty
+    #[serde(default)]
+    pub backend_url: String,
+    #[serde(default)]
+    pub auth_token: String,
+    pub input_channel_id: i32,
+    // Copy mode when set, register mode (files stay in place) otherwise
+    #[serde(default)]
+    pub destination_dir: Option<String>,
+    #[serde(default)]
+    pub preserve_structure: bool,
+    // Archive as YYYYMMDD_HHMMSS_camera_seq.ext (copy mode only, see rename.rs)
+    #[serde(default)]
+    pub rename_originals: bool,
+    // What to do with near duplicates of photos already in history
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+    // User-defined category set on every photo
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub workers: StageWorkers,
+    // Culled files; a group is rejected when any of its files is
+    #[serde(default)]
+    pub rejected: Vec<String>,
+    // Still copy rejected groups into storage (copy mode only)
+    #[serde(default)]
+    pub archive_rejected: bool,
+    // Detected sequences to stack or thin out, see sequences.rs
+    #[serde(default)]
+    pub sequences: Vec<SequencePlan>,
+    // Multi-shot RAW captures to import as one photo each, see multishot.rs
+    #[serde(default)]
+    pub multishot: Vec<MultiShotCapture>,
+    // Note and labels for the session, see session_notes.rs
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // Where downloaded files came from (file path → URL), see url_import.rs
+    #[serde(default)]
+    pub source_urls: HashMap<String, String>,
+    // Messages attachments were extracted from (file path → sender, date,
+    // subject), see email_import.rs
+    #[serde(default)]
+    pub source_emails: HashMap<String, EmailSource>,
+    // Also archive proprietary RAWs as DNG, see dng.rs
+    #[serde(default)]
+    pub convert_to_dng: bool,
+    // Which file of a companion group becomes the master
+    #[serde(default)]
+    pub master_order: MasterOrder,
+    // Temporary workspace holding the files, released when the import
+    // completes (see workspace.rs)
+    #[serde(default)]
+    pub workspace: Option<String>,
+    // Color labels, flags and ratings set while culling (file → marks);
+    // they take precedence over XMP sidecars, see labels.rs
+    #[serde(default)]
+    pub marks: HashMap<String, CullMarks>,
+    // Backend stack for every photo not stacked by a sequence plan
+    #[serde(default)]
+    pub stack_id: Option<i32>,
+    // Upload without coldpreviews; backfill_coldpreviews adds them later for
+    // the photos that get viewed (see coldpreviews.rs)
+    #[serde(default)]
+    pub omit_coldpreviews: bool,
+    // Send small non-image companions (voice memos, logs) inline with the
+    // photo, see assets.rs
+    #[serde(default)]
+    pub upload_assets: bool,
+    // Ask the backend about photos local history has as imported too, e.g.
+    // after deleting them on the web
+    #[serde(default)]
+    pub recheck_imported: bool,
+}
+
+// Master file plus companions sharing directory and basename (IMG_0001.JPG + IMG_0001.CR3)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompanionGroup {
+    pub basename: String,
+    pub master_file: String,
+    pub companion_files: Vec<String>,
+    pub master_priority: u32,
+}
+
+impl CompanionGroup {
+    pub fn all_files(&self) -> Vec<String> {
+        std::iter::once(self.master_file.clone())
+            .chain(self.companion_files.iter().cloned())
+            .collect()
+    }
+}
+
+// Successful result for one photo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedPhoto {
+    pub file: String,
+    pub hothash: String,
+    pub photo_id: i32,
+    pub is_duplicate: bool,
+    pub companion_count: usize,
+    // Size of master and companions
+    #[serde(default)]
+    pub bytes: u64,
+    // Copied into storage (copy mode)
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub taken_at: Option<String>,
+    #[serde(default)]
+    pub camera: Option<String>,
+    // Set when GPS was removed or coarsened by a privacy zone
+    #[serde(default)]
+    pub gps_scrubbed: Option<GpsScrub>,
+    #[serde(default)]
+    pub phash: Option<String>,
+    #[serde(default)]
+    pub width: i32,
+    #[serde(default)]
+    pub height: i32,
+    // Where the files are kept: storage copies in copy mode, the sources in
+    // register mode. Empty for photos the backend already had.
+    #[serde(default)]
+    pub stored_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Running,
+    Completed,
+    // Stopped with cancel_operation; the rest can be resumed (recovery.rs)
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportSession {
+    pub id: String,
+    pub status: SessionStatus,
+    pub source_dir: String,
+    pub total: usize,
+    pub completed: usize,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub result: BatchResult<ImportedPhoto>,
+    // Groups the user rejected, and how many of those were archived anyway
+    #[serde(default)]
+    pub rejected: usize,
+    #[serde(default)]
+    pub rejected_archived: usize,
+    // Cameras no author rule matched, see authors.rs
+    #[serde(default)]
+    pub unmatched_cameras: Vec<String>,
+    // Groups core could not process, see quarantine.rs
+    #[serde(default)]
+    pub quarantined: usize,
+    #[serde(default)]
+    pub input_channel_id: i32,
+    // The user's note and labels, see session_notes.rs
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+// Managed state: all import sessions started during this app run
+#[derive(Default)]
+pub struct ImportSessions(Mutex<HashMap<String, ImportSession>>);
+
+impl ImportSessions {
+    pub fn get(&self, session_id: &str) -> Option<ImportSession> {
+        self.0.lock().ok()?.get(session_id).cloned()
+    }
+
+    pub fn status(&self, session_id: &str) -> Option<SessionStatus> {
+        self.0.lock().ok()?.get(session_id).map(|s| s.status.clone())
+    }
+
+    pub fn all(&self) -> Vec<ImportSession> {
+        self.0.lock().map(|sessions| sessions.values().cloned().collect()).unwrap_or_default()
+    }
+
+    // Put back a session from a queue snapshot (development only)
+    pub fn restore(&self, session: ImportSession) {
+        if let Ok(mut sessions) = self.0.lock() {
+            sessions.insert(session.id.clone(), session);
+        }
+    }
+
+    pub fn annotate(&self, session_id: &str, note: &str, labels: &[String]) {
+        if let Some(session) = self.0.lock().unwrap().get_mut(session_id) {
+            session.note = Some(note.to_string()).filter(|n| !n.is_empty());
+            session.labels = labels.to_vec();
+        }
+    }
+
+    pub fn has_running(&self) -> bool {
+        self.0
+            .lock()
+            .map(|sessions| sessions.values().any(|s| s.status == SessionStatus::Running))
+            .unwrap_or(false)
+    }
+}
+
+// Default priority of each extension when picking the master file (lower wins)
+pub fn master_priority(ext: &str) -> u32 {
+    match ext {
+        "jpg" | "jpeg" => 1,
+        "heic" | "heif" => 2,
+        "png" => 3,
+        "tif" | "tiff" | "jxl" => 4,
+        "arw" | "cr2" | "cr3" | "nef" | "dng" | "orf" | "raf" | "rw2" | "raw" => 10,
+        _ => 99,
+    }
+}
+
+fn is_raw_extension(ext: &str) -> bool {
+    master_priority(ext) == 10
+}
+
+// User-defined master preference, e.g. ["raw", "jpeg"] for RAW-first
+// workflows. Entries are format classes (jpeg, heic, png, tiff, raw) or single
+// extensions ("cr3"); earlier entries win. Supported formats the order
+// doesn't mention come after, in the default order. Empty = default order.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct MasterOrder(pub Vec<String>);
+
+impl MasterOrder {
+    fn matches(entry: &str, ext: &str) -> bool {
+        match entry.trim_start_matches('.').to_lowercase().as_str() {
+            "jpeg" | "jpg" => ext == "jpg" || ext == "jpeg",
+            "heic" | "heif" => ext == "heic" || ext == "heif",
+            "tiff" | "tif" => ext == "tif" || ext == "tiff",
+            "raw" => is_raw_extension(ext),
+            other => other == ext,
+        }
+    }
+
+    pub fn priority(&self, ext: &str) -> u32 {
+        let default = master_priority(ext);
+        if default == 99 {
+            return default;
+        }
+        match self.0.iter().position(|entry| Self::matches(entry, ext)) {
+            Some(i) => i as u32,
+            None => self.0.len() as u32 + default,
+        }
+    }
+}
+
+pub fn extension_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// Group files by directory + basename and pick a master per group
+pub fn group_companions(files: &[String], order: &MasterOrder) -> Vec<CompanionGroup> {
+    let mut groups: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    let mut index: HashMap<PathBuf, usize> = HashMap::new();
+
+    for file in files {
+        let path = Path::new(file);
+        let Some(stem) = path.file_stem() else { continue };
+        if path.extension().is_none() {
+            continue;
+        }
+        let key = path.with_file_name(stem);
+        match index.get(&key) {
+            Some(&i) => groups[i].1.push(file.clone()),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key, vec![file.clone()]));
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, mut members)| {
+            // Stable: ties keep scan order
+            members.sort_by_key(|f| order.priority(&extension_of(f)));
+            let master_file = members.remove(0);
+            CompanionGroup {
+                basename: key.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                master_priority: order.priority(&extension_of(&master_file)),
+                master_file,
+                companion_files: members,
+            }
+        })
+        .collect()
+}
+
+// Companion groups of `files`, with masters picked by `master_order`
+#[tauri::command]
+pub fn group_companion_files(files: Vec<String>, master_order: Option<MasterOrder>) -> Vec<CompanionGroup> {
+    group_companions(&files, &master_order.unwrap_or_default())
+}
+
+// One photo travelling through the pipeline
+struct WorkItem {
+    group: CompanionGroup,
+    content_hash: Option<String>,
+    // Total size of master and companions, for throughput stats
+    size: u64,
+    schema: Option<PhotoCreateSchema>,
+    // Kept from the schema for history after it has been uploaded
+    taken_at: Option<String>,
+    camera: Option<String>,
+    gps_scrubbed: Option<GpsScrub>,
+    phash: Option<String>,
+    response: Option<PhotoCreateResponse>,
+    // Planned archive location per file (copy mode only)
+    destinations: HashMap<String, PathBuf>,
+    // DNG companions created during import → the RAW they were converted from
+    converted: HashMap<String, String>,
+    // JPEG derivative core processed instead of the master, see transcode.rs
+    transcoded: Option<PathBuf>,
+}
+
+enum Outcome {
+    Succeeded(ImportedPhoto),
+    Skipped(String, String),
+    Failed(String, ImalinkError),
+    Rejected { file: String, archived: bool },
+}
+
+struct PipelineContext {
+    app: tauri::AppHandle,
+    session_id: String,
+    options: ImportOptions,
+    client: reqwest::Client,
+    progress: Arc<ProgressAggregator>,
+    plugins: PluginSet,
+    privacy_zones: Vec<PrivacyZone>,
+    author_rules: Vec<AuthorRule>,
+    destination_claims: rename::Claims,
+    duplicate_threshold: u32,
+    // Backend stack per file, set once the stacks are created
+    stack_ids: OnceLock<HashMap<String, i32>>,
+    dng_converter: dng::DngConverter,
+    transcoder: Transcoder,
+    asset_extensions: Vec<String>,
+    upload_stall: StallPolicy,
+    upload_limit: PayloadLimit,
+    min_hotpreview_px: u32,
+    process_retries: u32,
+    stage_timeouts: StageTimeouts,
+    // Entry in the operations registry; cancelling stops feeding new groups
+    operation: Operation,
+}
+
+// Run `workers` tasks pulling from a shared bounded receiver
+fn spawn_stage<F, Fut>(workers: usize, rx: mpsc::Receiver<WorkItem>, handler: F)
+where
+    F: Fn(WorkItem) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+    for _ in 0..workers.max(1) {
+        let rx = rx.clone();
+        let handler = handler.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let item = rx.lock().await.recv().await;
+                match item {
+                    Some(item) => handler(item).await,
+                    None => break,
+                }
+            }
+        });
+    }
+}
+
+fn file_name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+// Fill in storage/import metadata for the master and append companions to image_file_list
+fn attach_file_info(item: &mut WorkItem, options: &ImportOptions, asset_extensions: &[String]) -> Result<(), ImalinkError> {
+    let Some(schema) = item.schema.as_mut() else {
+        return Ok(());
+    };
+
+    // Name in storage, which differs from the source when renaming or for a staged DNG
+    let stored_name = |source: &str| {
+        let renamed = (options.rename_originals || item.converted.contains_key(source))
+            .then(|| item.destinations.get(source))
+            .flatten();
+        renamed.map(|p| file_name_of(&p.to_string_lossy())).unwrap_or_else(|| file_name_of(source))
+    };
+    let all_filenames: Vec<String> = item.group.all_files().iter().map(|f| stored_name(f)).collect();
+    let imported_at = chrono::Utc::now().to_rfc3339();
+    let import_mode = if options.destination_dir.is_some() { "copy" } else { "register" };
+
+    let storage_info = |source: &str| {
+        let storage_path = item
+            .destinations
+            .get(source)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| source.to_string());
+        let mut info = serde_json::json!({
+            "import_mode": import_mode,
+            "source_path": source,
+            "storage_path": storage_path,
+            "companion_files": all_filenames,
+        });
+        if options.rename_originals && item.destinations.contains_key(source) {
+            info["original_filename"] = serde_json::json!(file_name_of(source));
+        }
+        if let Some(raw) = item.converted.get(source) {
+            info["converted_from"] = serde_json::json!(stored_name(raw));
+        }
+        info
+    };
+    let mut imported_info = serde_json::json!({
+        "imported_at": imported_at,
+        "original_selection": options.source_dir,
+    });
+    if let Some(url) = options.source_urls.get(&item.group.master_file) {
+        imported_info["source_url"] = serde_json::json!(url);
+    }
+    if let Some(email) = options.source_emails.get(&item.group.master_file) {
+        imported_info["email"] = serde_json::json!(email);
+    }
+
+    if let Some(master) = schema.image_file_list.first_mut() {
+        let mut master_info = storage_info(&item.group.master_file);
+        if let Some(hash) = &item.content_hash {
+            master_info["content_hash"] = serde_json::json!(format!("blake3:{}", hash));
+        }
+        // Core described the derivative; the entry is the original's
+        if transcode::needs_transcode(&item.group.master_file) {
+            master_info["transcoded_preview"] = serde_json::json!(true);
+            master.file_size = fs::metadata(&item.group.master_file)
+                .map_err(|e| ImalinkError::io(&item.group.master_file, e))?
+                .len() as i64;
+            master.format = Some(extension_of(&item.group.master_file));
+            master.is_raw = false;
+        }
+        master.local_storage_info = Some(master_info);
+        master.filename = stored_name(&item.group.master_file);
+        master.imported_info = Some(imported_info.clone());
+    }
+
+    for companion in &item.group.companion_files {
+        let size = fs::metadata(companion).map_err(|e| ImalinkError::io(companion, e))?.len();
+        let ext = extension_of(companion);
+        let format = if ext == "jpg" || ext == "jpeg" {
+            "jpeg".to_string()
+        } else if is_raw_extension(&ext) {
+            "raw".to_string()
+        } else {
+            ext.clone()
+        };
+        let mut companion_storage = storage_info(companion);
+        let mut companion_imported = imported_info.clone();
+        if assets::is_asset(companion, asset_extensions) {
+            companion_storage["asset"] = serde_json::json!(true);
+            if let Some(content) = options.upload_assets.then(|| assets::inline_content(companion)).flatten() {
+                companion_imported["content_base64"] = serde_json::json!(content);
+            }
+        }
+        schema.image_file_list.push(ImageFileSchema {
+            filename: stored_name(companion),
+            file_size: size as i64,
+            is_raw: format == "raw",
+            format: Some(format),
+            local_storage_info: Some(companion_storage),
+            imported_info: Some(companion_imported),
+        });
+    }
+
+    schema.input_channel_id = Some(options.input_channel_id);
+    if options.category.is_some() {
+        schema.category = options.category.clone();
+    }
+    Ok(())
+}
+
+// Rating, category and tags belong to the photo, not to whichever file was
+// processed as master. Every file of the group records the same final values
+// in its imported_info, so a RAW and its JPEG never disagree.
+fn record_photo_marks(schema: &mut PhotoCreateSchema) {
+    let marks = serde_json::json!({
+        "rating": schema.rating,
+        "category": schema.category,
+        "tags": schema.tags,
+    });
+    for file in &mut schema.image_file_list {
+        let info = file.imported_info.get_or_insert_with(|| serde_json::json!({}));
+        info["photo_marks"] = marks.clone();
+    }
+}
+
+// With renaming this needs the processed schema (capture time, camera)
+fn plan_destinations(item: &mut WorkItem, ctx: &PipelineContext) -> Result<(), ImalinkError> {
+    let options = &ctx.options;
+    let Some(dest_dir) = options.destination_dir.as_deref() else {
+        return Ok(());
+    };
+    let planned = storage_destinations(&item.group, dest_dir, options.preserve_structure, &options.source_dir)?;
+
+    item.destinations = if options.rename_originals {
+        let schema = item.schema.as_ref().ok_or_else(|| ImalinkError::internal("Renaming before processing"))?;
+        let stem = rename::canonical_stem(schema, &item.group.master_file);
+        ctx.destination_claims
+            .claim(planned, &stem, &item.group.master_file, item.content_hash.as_deref())?
+    } else {
+        ctx.destination_claims.reserve(&planned)?;
+        planned
+    };
+    Ok(())
+}
+
+// Archive location of each file of a group under its original name
+fn storage_destinations(
+    group: &CompanionGroup,
+    dest_dir: &str,
+    preserve_structure: bool,
+    source_dir: &str,
+) -> Result<HashMap<String, PathBuf>, ImalinkError> {
+    group
+        .all_files()
+        .into_iter()
+        .map(|file| {
+            let dest = crate::storage_destination_path(&file, dest_dir, preserve_structure, Some(source_dir))?;
+            Ok((file, dest))
+        })
+        .collect()
+}
+
+// Reserve a group's destinations under their original names and copy it
+// there, like plan_destinations and the copy stage in plain copy mode.
+// `copied` is called for each file once it is in place.
+pub(crate) fn archive_group(
+    claims: &rename::Claims,
+    group: &CompanionGroup,
+    dest_dir: &str,
+    preserve_structure: bool,
+    source_dir: &str,
+    mut copied: impl FnMut(&str, &Path),
+) -> Result<(), ImalinkError> {
+    let planned = storage_destinations(group, dest_dir, preserve_structure, source_dir)?;
+    claims.reserve(&planned)?;
+    for (source, dest) in &planned {
+        streaming::copy_new(source, dest)?;
+        copied(source, dest);
+    }
+    Ok(())
+}
+
+// Make a JPEG derivative of a master core can't read, in the session's workspace
+async fn transcode_master(item: &mut WorkItem, ctx: &PipelineContext) -> Result<(), ImalinkError> {
+    if !transcode::needs_transcode(&item.group.master_file) {
+        return Ok(());
+    }
+    // Processed before: the cached schema is used and no derivative is needed
+    let cache = ctx.app.state::<SchemaCache>();
+    if item.content_hash.as_deref().is_some_and(|hash| cache.get(hash).is_some()) {
+        return Ok(());
+    }
+    let workspace = workspace::open(&ctx.app, &ctx.session_id)?;
+    let output = transcode::output_path(&item.group.master_file, &workspace);
+    transcode::transcode(&ctx.transcoder, &item.group.master_file, &output).await?;
+    item.transcoded = Some(output);
+    Ok(())
+}
+
+// Convert the group's proprietary RAW to DNG (when enabled) and add the DNG
+// as a companion, archived next to the RAW in copy mode
+async fn convert_raw(item: &mut WorkItem, ctx: &PipelineContext) -> Result<(), ImalinkError> {
+    if !ctx.options.convert_to_dng {
+        return Ok(());
+    }
+    let Some(raw) = dng::raw_to_convert(&item.group) else {
+        return Ok(());
+    };
+    let dest = item.destinations.get(&raw).map(|d| d.with_extension("dng"));
+    if let Some(dest) = &dest {
+        ctx.destination_claims.reserve(&HashMap::from([(raw.clone(), dest.clone())]))?;
+    }
+    // Low on space, the DNG is written where it is archived instead of
+    // staged and copied; the copy stage leaves it be
+    let output = match dest.clone() {
+        Some(dest) if low_disk::active() => {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+            }
+            dest
+        }
+        Some(_) => dng::output_path(&raw, Some(&workspace::open(&ctx.app, &ctx.session_id)?)),
+        None => dng::output_path(&raw, None),
+    };
+    // Register mode: a DNG left out of the selection is reused as it is
+    if dest.is_some() || !output.exists() {
+        dng::convert(&ctx.dng_converter, &raw, &output).await?;
+    }
+
+    let dng_file = output.to_string_lossy().to_string();
+    item.size += fs::metadata(&output).map_err(|e| ImalinkError::io(output.display(), e))?.len();
+    if let Some(dest) = dest {
+        item.destinations.insert(dng_file.clone(), dest);
+    }
+    item.group.companion_files.push(dng_file.clone());
+    item.converted.insert(dng_file, raw);
+    Ok(())
+}
+
+// Copy a rejected group into storage under its original names
+fn archive_rejected(ctx: &PipelineContext, group: &CompanionGroup) -> Result<bool, ImalinkError> {
+    let options = &ctx.options;
+    let Some(dest_dir) = options.destination_dir.as_deref().filter(|_| options.archive_rejected) else {
+        return Ok(false);
+    };
+    archive_group(
+        &ctx.destination_claims,
+        group,
+        dest_dir,
+        options.preserve_structure,
+        &options.source_dir,
+        |file, dest| undo::record(&ctx.app, &ctx.session_id, file, dest, Some(dest_dir), false),
+    )?;
+    Ok(true)
+}
+
+// Hothash and backend photo id if the item is already in the library.
+// Lookup failures are not fatal - the item just goes through full processing.
+async fn find_existing(ctx: &PipelineContext, item: &WorkItem) -> Option<(String, i32)> {
+    let content_hash = item.content_hash.as_deref()?;
+    let index = ctx.app.state::<HothashIndex>();
+    let hothash = index
+        .resolve(&ctx.client, &ctx.options.core_api_url, content_hash, &item.group.master_file)
+        .await?;
+    if !ctx.options.recheck_imported {
+        if let Some(photo_id) = hothash::find_imported(&ctx.app.state::<History>(), &hothash) {
+            return Some((hothash, photo_id));
+        }
+    }
+
+    let existing = existence::find_backend_photo(
+        &ctx.app,
+        &ctx.client,
+        &ctx.options.backend_url,
+        &ctx.options.auth_token,
+        &hothash,
+    );
+    match existing.await {
+        Ok(Some(photo_id)) => Some((hothash, photo_id)),
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Backend hothash lookup failed for {}: {}", item.group.master_file, e);
+            None
+        }
+    }
+}
+
+// Processed schema for the item's master file, from the schema cache when
+// the same content has been through core before
+async fn process_cached(ctx: &PipelineContext, item: &WorkItem) -> Result<PhotoCreateSchema, ImalinkError> {
+    let cache = ctx.app.state::<SchemaCache>();
+    if let Some(hash) = &item.content_hash {
+        if let Some(schema) = cache.get(hash) {
+            ctx.app.state::<HothashIndex>().insert(hash, &schema.hothash);
+            return Ok(schema);
+        }
+    }
+
+    let source = match &item.transcoded {
+        Some(derivative) => derivative.to_string_lossy().to_string(),
+        None => item.group.master_file.clone(),
+    };
+    let schema = crate::process_file(&ctx.client, &source, &ctx.options.core_api_url).await?;
+    if let Some(hash) = &item.content_hash {
+        if let Err(e) = cache.put(hash, &schema) {
+            eprintln!("Failed to cache PhotoCreateSchema for {}: {}", item.group.master_file, e);
+        }
+        ctx.app.state::<HothashIndex>().insert(hash, &schema.hothash);
+    }
+    Ok(schema)
+}
+
+// Core may fail once on a busy or restarting sidecar; file problems it
+// reports the same way every time, so those end in quarantine
+fn worth_retrying(e: &ImalinkError) -> bool {
+    matches!(e, ImalinkError::Core { .. } | ImalinkError::Network { .. } | ImalinkError::Parse { .. })
+}
+
+// process_cached, retried up to `process_retries` times. A group that still
+// fails is quarantined before the error is passed on.
+async fn process_or_quarantine(ctx: &PipelineContext, item: &WorkItem) -> Result<PhotoCreateSchema, ImalinkError> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match process_cached(ctx, item).await {
+            Ok(schema) => return Ok(schema),
+            Err(e) if worth_retrying(&e) && attempts <= ctx.process_retries => {
+                eprintln!("Processing {} failed (attempt {}), retrying: {}", item.group.master_file, attempts, e);
+                tokio::time::sleep(std::time::Duration::from_secs(attempts as u64)).await;
+            }
+            Err(e) if worth_retrying(&e) => {
+                ctx.app
+                    .state::<Quarantine>()
+                    .add(&ctx.session_id, &item.group, &ctx.options, &e, attempts);
+                update_session(&ctx.app, &ctx.session_id, |s| s.quarantined += 1);
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Run `group`'s work in `stage` under the watchdog. A group stuck in it is
+// quarantined and the stage recovered, see watchdog.rs.
+async fn watched<T>(
+    ctx: &PipelineContext,
+    stage: Stage,
+    group: &CompanionGroup,
+    work: impl Future<Output = Result<T, ImalinkError>>,
+) -> Result<T, ImalinkError> {
+    let result = watchdog::run(&ctx.stage_timeouts, stage, &group.master_file, work).await;
+    if let Err(e @ ImalinkError::StageStuck { .. }) = &result {
+        ctx.progress.stuck(stage);
+        ctx.app.state::<Quarantine>().add(&ctx.session_id, group, &ctx.options, e, 1);
+        update_session(&ctx.app, &ctx.session_id, |s| s.quarantined += 1);
+        watchdog::recover(&ctx.app, stage).await;
+    }
+    result
+}
+
+// Rebuild a hotpreview that came back too small, see hotpreview.rs
+async fn regenerate_small_hotpreview(
+    ctx: &PipelineContext,
+    item: &WorkItem,
+    mut schema: PhotoCreateSchema,
+) -> Result<PhotoCreateSchema, ImalinkError> {
+    let original = match &item.transcoded {
+        Some(derivative) => derivative.to_string_lossy().to_string(),
+        None => item.group.master_file.clone(),
+    };
+    let min_px = ctx.min_hotpreview_px;
+    tauri::async_runtime::spawn_blocking(move || {
+        if hotpreview::ensure_size(&mut schema, &original, min_px)? {
+            println!("Regenerated undersized hotpreview for {}", original);
+        }
+        Ok(schema)
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(format!("Hotpreview task failed: {}", e)))?
+}
+
+// "Make Model" from the EXIF data, without repeating the make
+pub fn camera_of(exif_dict: &serde_json::Value) -> Option<String> {
+    let field = |key: &str| exif_dict.get(key).and_then(|v| v.as_str()).map(|v| v.trim().to_string());
+    match (field("Make"), field("Model")) {
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    }
+    .filter(|c| !c.is_empty())
+}
+
+// Compute the item's perceptual hash and, unless the policy uploads
+// everything, the reason to skip it as a near duplicate
+fn near_duplicate(ctx: &PipelineContext, item: &mut WorkItem) -> Result<Option<String>, ImalinkError> {
+    let Some(schema) = item.schema.as_ref() else { return Ok(None) };
+    item.phash = duplicates::phash_of(schema);
+    let Some(phash) = item.phash.as_deref() else { return Ok(None) };
+    if ctx.options.duplicate_policy == DuplicatePolicy::Upload {
+        return Ok(None);
+    }
+
+    let history = ctx.app.state::<History>();
+    let nearest = duplicates::nearest(&history, &schema.hothash, phash, ctx.duplicate_threshold)?;
+    Ok(nearest.and_then(|(existing, distance)| {
+        duplicates::skip_reason(ctx.options.duplicate_policy, &existing, distance, schema.width, schema.height)
+    }))
+}
+
+fn stack_id_of(ctx: &PipelineContext, group: &CompanionGroup) -> Option<i32> {
+    let stack_ids = ctx.stack_ids.get()?;
+    group.all_files().iter().find_map(|f| stack_ids.get(f).copied())
+}
+
+fn finished(item: &WorkItem) -> Outcome {
+    let response = item.response.as_ref();
+    Outcome::Succeeded(ImportedPhoto {
+        file: item.group.master_file.clone(),
+        hothash: response.map(|r| r.hothash.clone()).unwrap_or_default(),
+        photo_id: response.map(|r| r.id).unwrap_or_default(),
+        is_duplicate: response.map(|r| r.is_duplicate).unwrap_or(false),
+        companion_count: item.group.companion_files.len(),
+        bytes: item.size,
+        archived: !item.destinations.is_empty(),
+        taken_at: item.taken_at.clone(),
+        camera: item.camera.clone(),
+        gps_scrubbed: item.gps_scrubbed.clone(),
+        phash: item.phash.clone(),
+        width: response.map(|r| r.width).unwrap_or_default(),
+        height: response.map(|r| r.height).unwrap_or_default(),
+        // Master first
+        stored_files: item
+            .group
+            .all_files()
+            .into_iter()
+            .map(|f| match item.destinations.get(&f) {
+                Some(dest) => dest.to_string_lossy().to_string(),
+                None => f,
+            })
+            .collect(),
+    })
+}
+
+// Start an import in the background and return its session id immediately.
+// Progress is reported via rate-limited `import-progress` snapshots (see
+// progress.rs), failures via `import-item-error`, and `import-complete`
+// carries the final session (see get_import_session).
+#[tauri::command]
+pub async fn start_import(app: tauri::AppHandle, options: ImportOptions) -> Result<String, ImalinkError> {
+    spawn_import(&app, options)
+}
+
+// Register a session and run the pipeline for it in the background
+pub fn spawn_import(app: &tauri::AppHandle, mut options: ImportOptions) -> Result<String, ImalinkError> {
+    guest::require_owner(app, "import")?;
+    app.state::<Session>().fill(&mut options.backend_url, &mut options.auth_token)?;
+    let source = PathBuf::from(&options.source_dir);
+    if options.files.is_none() && !source.is_dir() {
+        return Err(ImalinkError::NotADirectory { path: options.source_dir.clone() });
+    }
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let source_dir = options.source_dir.clone();
+    let session = ImportSession {
+        id: session_id.clone(),
+        status: SessionStatus::Running,
+        source_dir: options.source_dir.clone(),
+        total: 0,
+        completed: 0,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        finished_at: None,
+        result: BatchResult::new(),
+        rejected: 0,
+        rejected_archived: 0,
+        unmatched_cameras: Vec::new(),
+        quarantined: 0,
+        input_channel_id: options.input_channel_id,
+        note: None,
+        labels: Vec::new(),
+    };
+    app.state::<ImportSessions>().0.lock().unwrap().insert(session_id.clone(), session);
+    if options.note.is_some() || !options.labels.is_empty() {
+        let note = options.note.as_deref().unwrap_or_default();
+        if let Err(e) = session_notes::record(app, &session_id, note, &options.labels, Some(options.input_channel_id)) {
+            eprintln!("Failed to save note of session {}: {}", session_id, e);
+        }
+    }
+    recovery::journal(app).start(&session_id, &options);
+
+    let settings = crate::settings::load(app);
+    let progress = ProgressAggregator::start(app.clone(), session_id.clone(), settings.progress_events_per_second);
+    let stalled = progress.clone();
+    let shrunk = progress.clone();
+    let ctx = Arc::new(PipelineContext {
+        app: app.clone(),
+        session_id: session_id.clone(),
+        options,
+        client: crate::http::client(app),
+        upload_stall: StallPolicy::from_settings(&settings).on_stall(move || stalled.stalled(Stage::Upload)),
+        upload_limit: PayloadLimit::from_settings(&settings).on_shrink(move |_| shrunk.payload_shrunk(Stage::Upload)),
+        progress,
+        plugins: PluginSet::from_settings(&settings),
+        privacy_zones: settings.privacy_zones,
+        author_rules: settings.author_rules,
+        destination_claims: rename::Claims::default(),
+        duplicate_threshold: settings.near_duplicate_threshold,
+        stack_ids: OnceLock::new(),
+        dng_converter: settings.dng_converter,
+        transcoder: settings.transcoder,
+        asset_extensions: settings.asset_companion_extensions,
+        min_hotpreview_px: settings.min_hotpreview_px,
+        process_retries: settings.process_retries,
+        stage_timeouts: settings.stage_timeouts,
+        operation: operations::register(app, OperationKind::Import, format!("Import {}", source_dir)).cancellable(),
+    });
+    tauri::async_runtime::spawn(run_pipeline(app.clone(), ctx));
+
+    Ok(session_id)
+}
+
+#[tauri::command]
+pub fn get_import_session(
+    sessions: tauri::State<'_, ImportSessions>,
+    session_id: String,
+) -> Result<ImportSession, ImalinkError> {
+    sessions
+        .0
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown import session: {}", session_id)))
+}
+
+async fn run_pipeline(app: tauri::AppHandle, ctx: Arc<PipelineContext>) {
+    let workers = StageWorkers {
+        process: remote_core::process_workers(ctx.options.workers.process),
+        ..ctx.options.workers.clone()
+    };
+    let (results_tx, mut results_rx) = mpsc::unbounded_channel::<Outcome>();
+
+    // Stage channels, bounded for backpressure
+    let (hash_tx, hash_rx) = mpsc::channel::<WorkItem>(workers.hash.max(1) * 2);
+    let (lookup_tx, lookup_rx) = mpsc::channel::<WorkItem>(workers.lookup.max(1) * 2);
+    let (process_tx, process_rx) = mpsc::channel::<WorkItem>(workers.process.max(1) * 2);
+    let (upload_tx, upload_rx) = mpsc::channel::<WorkItem>(workers.upload.max(1) * 2);
+    let (copy_tx, copy_rx) = mpsc::channel::<WorkItem>(workers.copy.max(1) * 2);
+
+    // Hash: content hash of the master file
+    {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        spawn_stage(workers.hash, hash_rx, move |mut item| {
+            let next = lookup_tx.clone();
+            let results = results.clone();
+            let ctx = ctx.clone();
+            async move {
+                let files = item.group.all_files();
+                let app = ctx.app.clone();
+                let hashing = tauri::async_runtime::spawn_blocking(move || {
+                    let hash = hothash::content_hash(&app.state::<History>(), &files[0])?;
+                    let size = files.iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum::<u64>();
+                    Ok::<_, ImalinkError>((hash, size))
+                });
+                let hashed = watched(&ctx, Stage::Hash, &item.group, async {
+                    hashing.await.map_err(|e| ImalinkError::internal(e.to_string()))?
+                })
+                .await;
+                match hashed {
+                    Ok((hash, size)) => {
+                        ctx.progress.stage_done(Stage::Hash, size);
+                        item.content_hash = Some(hash);
+                        item.size = size;
+                        let _ = next.send(item).await;
+                    }
+                    Err(e) => { let _ = results.send(Outcome::Failed(item.group.master_file, e)); }
+                }
+            }
+        });
+    }
+
+    // Lookup: finish photos the backend already has without processing them
+    {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        spawn_stage(workers.lookup, lookup_rx, move |item| {
+            let next = process_tx.clone();
+            let results = results.clone();
+            let ctx = ctx.clone();
+            async move {
+                let existing = watched(&ctx, Stage::Lookup, &item.group, async { Ok(find_existing(&ctx, &item).await) }).await;
+                if existing.is_ok() {
+                    ctx.progress.stage_done(Stage::Lookup, item.size);
+                }
+                match existing {
+                    Err(e) => { let _ = results.send(Outcome::Failed(item.group.master_file, e)); }
+                    Ok(Some((hothash, photo_id))) => {
+                        let _ = results.send(Outcome::Succeeded(ImportedPhoto {
+                            file: item.group.master_file.clone(),
+                            hothash,
+                            photo_id,
+                            is_duplicate: true,
+                            companion_count: item.group.companion_files.len(),
+                            bytes: item.size,
+                            archived: false,
+                            taken_at: None,
+                            camera: None,
+                            gps_scrubbed: None,
+                            phash: None,
+                            width: 0,
+                            height: 0,
+                            stored_files: Vec::new(),
+                        }));
+                    }
+                    Ok(None) => { let _ = next.send(item).await; }
+                }
+            }
+        });
+    }
+
+    // Process: send master to imalink-core (unless cached), attach companion/storage info
+    {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        spawn_stage(workers.process, process_rx, move |mut item| {
+            let next = upload_tx.clone();
+            let results = results.clone();
+            let ctx = ctx.clone();
+            async move {
+                let started = Instant::now();
+                let group = item.group.clone();
+                let processed = watched(&ctx, Stage::Process, &group, async {
+                    // Plain copy checks destinations before the expensive core step
+                    let renaming = ctx.options.rename_originals;
+                    if !renaming {
+                        plan_destinations(&mut item, &ctx)?;
+                    }
+                    transcode_master(&mut item, &ctx).await?;
+                    let schema = process_or_quarantine(&ctx, &item).await?;
+                    item.schema = Some(regenerate_small_hotpreview(&ctx, &item, schema).await?);
+                    if let Some(derivative) = &item.transcoded {
+                        let _ = fs::remove_file(derivative);
+                    }
+                    if let Some(reason) = near_duplicate(&ctx, &mut item)? {
+                        return Ok(Some(reason));
+                    }
+                    if renaming {
+                        plan_destinations(&mut item, &ctx)?;
+                    }
+                    convert_raw(&mut item, &ctx).await?;
+                    attach_file_info(&mut item, &ctx.options, &ctx.asset_extensions)?;
+                    let mut schema = item.schema.take().unwrap_or_default();
+                    labels::apply(&mut schema, &labels::marks_for(&item.group, &ctx.options.marks));
+                    schema.stack_id = stack_id_of(&ctx, &item.group)
+                        .or(ctx.options.stack_id)
+                        .or(schema.stack_id);
+                    if let Some(camera) = authors::apply(&ctx.author_rules, &mut schema) {
+                        update_session(&ctx.app, &ctx.session_id, |s| {
+                            if !s.unmatched_cameras.contains(&camera) {
+                                s.unmatched_cameras.push(camera);
+                            }
+                        });
+                    }
+                    let mut schema = ctx.plugins.mutate(&item.group, schema).await?;
+                    // Last, so plugins can't put a scrubbed position back
+                    item.gps_scrubbed = privacy::apply(&ctx.privacy_zones, &mut schema);
+                    if ctx.options.omit_coldpreviews {
+                        coldpreviews::omit(&mut schema);
+                    }
+                    record_photo_marks(&mut schema);
+                    item.taken_at = schema.taken_at.clone();
+                    item.camera = camera_of(&schema.exif_dict);
+                    item.schema = Some(schema);
+                    Ok::<_, ImalinkError>(None)
+                })
+                .await;
+                match processed {
+                    Ok(Some(reason)) => {
+                        let _ = results.send(Outcome::Skipped(item.group.master_file, reason));
+                    }
+                    Ok(None) => {
+                        ctx.progress.stage_done(Stage::Process, item.size);
+                        let extension = extension_of(&item.group.master_file);
+                        metrics::record(&ctx.app, Stage::Process, &extension, item.size, started.elapsed());
+                        let _ = next.send(item).await;
+                    }
+                    Err(ImalinkError::DestinationExists { path }) => {
+                        let _ = results.send(Outcome::Skipped(
+                            item.group.master_file,
+                            format!("Destination file already exists: {}", path),
+                        ));
+                    }
+                    Err(e) => { let _ = results.send(Outcome::Failed(item.group.master_file, e)); }
+                }
+            }
+        });
+    }
+
+    // Upload: PhotoCreateSchema to backend
+    {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        spawn_stage(workers.upload, upload_rx, move |mut item| {
+            let next = copy_tx.clone();
+            let results = results.clone();
+            let ctx = ctx.clone();
+            async move {
+                let schema = item.schema.take().unwrap_or_default();
+                let body_bytes = serde_json::to_vec(&schema).map(|b| b.len() as u64).unwrap_or_default();
+                let started = Instant::now();
+                let options = &ctx.options;
+                // Maintenance and rate limit pauses don't count against the
+                // stage timeout, only the upload itself
+                let uploaded = maintenance::wait_out(&ctx.app, || {
+                    auth::with_refresh(&ctx.app, &options.backend_url, &options.auth_token, |token| {
+                        let (ctx, schema, group) = (&ctx, schema.clone(), &item.group);
+                        watched(ctx, Stage::Upload, group, async move {
+                            crate::upload_schema(
+                                &ctx.client,
+                                &ctx.options.backend_url,
+                                &token,
+                                schema,
+                                ctx.options.input_channel_id,
+                                &ctx.upload_stall,
+                                &ctx.upload_limit,
+                            )
+                            .await
+                        })
+                    })
+                })
+                .await;
+                match uploaded {
+                    Ok(response) => {
+                        ctx.progress.stage_done(Stage::Upload, item.size);
+                        let extension = extension_of(&item.group.master_file);
+                        metrics::record(&ctx.app, Stage::Upload, &extension, body_bytes, started.elapsed());
+                        item.response = Some(response);
+                        if item.destinations.is_empty() {
+                            let _ = results.send(finished(&item));
+                        } else {
+                            let _ = next.send(item).await;
+                        }
+                    }
+                    Err(e) => { let _ = results.send(Outcome::Failed(item.group.master_file, e)); }
+                }
+            }
+        });
+    }
+
+    // Copy: archive master and companions to their planned destinations
+    {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        spawn_stage(workers.copy, copy_rx, move |item| {
+            let results = results.clone();
+            let ctx = ctx.clone();
+            async move {
+                let destinations = item.destinations.clone();
+                let staged_dngs: Vec<String> = item
+                    .converted
+                    .keys()
+                    .filter(|dng| destinations.get(*dng).is_none_or(|dest| dest != Path::new(dng)))
+                    .cloned()
+                    .collect();
+                let copy_ctx = ctx.clone();
+                let copying = tauri::async_runtime::spawn_blocking(move || {
+                    let root = copy_ctx.options.destination_dir.as_deref();
+                    for (source, dest) in &destinations {
+                        // Converted in place, see convert_raw
+                        if dest != Path::new(source) {
+                            streaming::copy_new(source, dest)?;
+                        }
+                        undo::record(&copy_ctx.app, &copy_ctx.session_id, source, dest, root, false);
+                    }
+                    for staged in &staged_dngs {
+                        let _ = fs::remove_file(staged);
+                    }
+                    Ok::<(), ImalinkError>(())
+                });
+                let copied = watched(&ctx, Stage::Copy, &item.group, async {
+                    copying.await.unwrap_or_else(|e| Err(ImalinkError::internal(e.to_string())))
+                })
+                .await;
+                match copied {
+                    Ok(()) => {
+                        ctx.progress.stage_done(Stage::Copy, item.size);
+                        let _ = results.send(finished(&item));
+                    }
+                    Err(e) => { let _ = results.send(Outcome::Failed(item.group.master_file, e)); }
+                }
+            }
+        });
+    }
+
+    // Preflight: one failure for the whole import if the channel can't be
+    // written to, instead of one per photo (see preflight.rs)
+    let preflight = preflight::check_channel(
+        &ctx.client,
+        &ctx.options.backend_url,
+        &ctx.options.auth_token,
+        ctx.options.input_channel_id,
+    )
+    .await;
+
+    // Scan: feed groups into the hash stage
+    let scan_ctx = ctx.clone();
+    let scanned = tauri::async_runtime::spawn_blocking(move || match &scan_ctx.options.files {
+        Some(files) => Ok(files.clone()),
+        None => crate::collect_image_files(&PathBuf::from(&scan_ctx.options.source_dir)),
+    })
+    .await
+    .unwrap_or_else(|e| Err(ImalinkError::internal(e.to_string())));
+
+    // Archive copies that won't fit fail the import up front, see low_disk.rs
+    let space_ctx = ctx.clone();
+    let scanned = scanned.and_then(|files| {
+        let space = low_disk::estimate(&space_ctx.app, &space_ctx.options, &files)?;
+        if space.fits {
+            return Ok(files);
+        }
+        Err(ImalinkError::LowDiskSpace {
+            path: space.destination.unwrap_or_default(),
+            free_mb: space.destination_free.unwrap_or_default() / (1024 * 1024),
+            needed_mb: space.destination_bytes / (1024 * 1024),
+        })
+    });
+    let groups = match preflight.and(scanned) {
+        Ok(files) => {
+            let mut groups = multishot::merge(
+                group_companions(&files, &ctx.options.master_order),
+                &ctx.options.multishot,
+            );
+            assets::attach(&mut groups, &ctx.asset_extensions);
+            groups
+        }
+        Err(e) => {
+            let _ = results_tx.send(Outcome::Failed(ctx.options.source_dir.clone(), e));
+            Vec::new()
+        }
+    };
+    let total = groups.len();
+    let groups = match ctx.plugins.filter(&ctx.options.source_dir, groups).await {
+        Ok((kept, skipped)) => {
+            for (group, reason) in skipped {
+                let _ = results_tx.send(Outcome::Skipped(group.master_file, reason));
+            }
+            kept
+        }
+        Err(e) => {
+            let _ = results_tx.send(Outcome::Failed(ctx.options.source_dir.clone(), e));
+            Vec::new()
+        }
+    };
+    let (rejected, groups): (Vec<_>, Vec<_>) = groups
+        .into_iter()
+        .partition(|g| g.all_files().iter().any(|f| ctx.options.rejected.contains(f)));
+    let groups: Vec<CompanionGroup> = groups
+        .into_iter()
+        .filter(|group| match sequences::dropped_frame(&ctx.options.sequences, group) {
+            Some(reason) => {
+                let _ = results_tx.send(Outcome::Skipped(group.master_file.clone(), reason));
+                false
+            }
+            None => true,
+        })
+        .collect();
+    let stack_ids = sequences::create_stacks(
+        &ctx.client,
+        &ctx.options.backend_url,
+        &ctx.options.auth_token,
+        &ctx.options.sequences,
+    )
+    .await;
+    let _ = ctx.stack_ids.set(stack_ids);
+    update_session(&app, &ctx.session_id, |s| s.total = total);
+    ctx.progress.set_total(total);
+    ctx.operation.set_total(total as u64);
+
+    if !rejected.is_empty() {
+        let results = results_tx.clone();
+        let ctx = ctx.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            for group in rejected {
+                let outcome = match archive_rejected(&ctx, &group) {
+                    Ok(archived) => Outcome::Rejected { file: group.master_file, archived },
+                    Err(e) => Outcome::Failed(group.master_file, e),
+                };
+                let _ = results.send(outcome);
+            }
+        });
+    }
+
+    let feed_ctx = ctx.clone();
+    tauri::async_runtime::spawn(async move {
+        for group in groups {
+            if feed_ctx.operation.is_cancelled() {
+                break;
+            }
+            let item = WorkItem {
+                group,
+                content_hash: None,
+                size: 0,
+                taken_at: None,
+                camera: None,
+                gps_scrubbed: None,
+                phash: None,
+                schema: None,
+                response: None,
+                destinations: HashMap::new(),
+                converted: HashMap::new(),
+                transcoded: None,
+            };
+            if hash_tx.send(item).await.is_err() {
+                break;
+            }
+        }
+    });
+    drop(results_tx);
+
+    // Collect outcomes until every stage has shut down
+    while let Some(outcome) = results_rx.recv().await {
+        match &outcome {
+            Outcome::Succeeded(photo) => {
+                ctx.progress.succeeded();
+                let recorded = app.state::<History>().record_upload(&UploadRecord {
+                    hothash: photo.hothash.clone(),
+                    photo_id: photo.photo_id,
+                    file_path: photo.file.clone(),
+                    input_channel_id: ctx.options.input_channel_id,
+                    taken_at: photo.taken_at.clone(),
+                    camera: photo.camera.clone(),
+                    bytes: photo.bytes,
+                    archived_bytes: if photo.archived { photo.bytes } else { 0 },
+                    is_duplicate: photo.is_duplicate,
+                    phash: photo.phash.clone(),
+                    width: Some(photo.width).filter(|w| *w > 0),
+                    height: Some(photo.height).filter(|h| *h > 0),
+                    stored_files: photo.stored_files.clone(),
+                });
+                if let Err(e) = recorded {
+                    eprintln!("Failed to record {} in history: {}", photo.file, e);
+                }
+                app.state::<ExistenceCache>().record(&ctx.options.backend_url, &photo.hothash, Some(photo.photo_id));
+                if ctx.options.omit_coldpreviews && !photo.is_duplicate {
+                    coldpreviews::mark_pending(&app, &photo.hothash, photo.photo_id, &photo.file);
+                }
+            }
+            Outcome::Skipped(_, _) | Outcome::Rejected { .. } => ctx.progress.skipped(),
+            Outcome::Failed(file, e) => ctx.progress.failed(file, e),
+        }
+        let logged = app.state::<History>().record_file(&logged_file(&ctx, &outcome));
+        if let Err(e) = logged {
+            eprintln!("Failed to log import of a file in history: {}", e);
+        }
+        // Failed items stay out of the journal so a resumed import retries them
+        match &outcome {
+            Outcome::Succeeded(ImportedPhoto { file, .. })
+            | Outcome::Skipped(file, _)
+            | Outcome::Rejected { file, .. } => recovery::journal(&app).mark_done(&ctx.session_id, file),
+            Outcome::Failed(_, _) => {}
+        }
+
+        update_session(&app, &ctx.session_id, |s| {
+            match outcome {
+                Outcome::Succeeded(photo) => s.result.succeed(photo),
+                Outcome::Skipped(file, reason) => s.result.skip(file, reason),
+                Outcome::Failed(file, e) => s.result.fail(file, e),
+                Outcome::Rejected { file, archived } => {
+                    s.rejected += 1;
+                    if archived {
+                        s.rejected_archived += 1;
+                        s.result.skip(file, "Rejected (archived)");
+                    } else {
+                        s.result.skip(file, "Rejected");
+                    }
+                }
+            }
+            s.completed += 1;
+        });
+        ctx.operation.advance();
+    }
+
+    let cancelled = ctx.operation.is_cancelled();
+    update_session(&app, &ctx.session_id, |s| {
+        s.status = if cancelled { SessionStatus::Cancelled } else { SessionStatus::Completed };
+        s.finished_at = Some(chrono::Utc::now().to_rfc3339());
+    });
+    ctx.progress.finish();
+    let imported: Vec<String> = app
+        .state::<ImportSessions>()
+        .get(&ctx.session_id)
+        .map(|s| s.result.succeeded.iter().map(|p| p.file.clone()).collect())
+        .unwrap_or_default();
+    if !imported.is_empty() {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = cards::record(&app.state::<History>(), &imported) {
+                eprintln!("Failed to record the memory card imported from: {}", e);
+            }
+        });
+    }
+    // A cancelled import keeps its journal so the rest can be resumed
+    if !cancelled {
+        recovery::journal(&app).finish(&ctx.session_id);
+    }
+    workspace::release(&app, &ctx.session_id);
+    if let Some(id) = &ctx.options.workspace {
+        workspace::release(&app, id);
+    }
+    if let Some(session) = app.state::<ImportSessions>().0.lock().unwrap().get(&ctx.session_id) {
+        let _ = app.emit("import-complete", session.clone());
+        crate::hooks::fire(&app, session);
+    }
+}
+
+// Entry in the import log of history for an outcome
+fn logged_file(ctx: &PipelineContext, outcome: &Outcome) -> ImportedFile {
+    let (path, status, detail) = match outcome {
+        Outcome::Succeeded(photo) => (&photo.file, if photo.is_duplicate { "duplicate" } else { "imported" }, None),
+        Outcome::Skipped(file, reason) => (file, "skipped", Some(reason.clone())),
+        Outcome::Failed(file, e) => (file, "failed", Some(e.to_string())),
+        Outcome::Rejected { file, .. } => (file, "rejected", None),
+    };
+    let photo = match outcome {
+        Outcome::Succeeded(photo) => Some(photo),
+        _ => None,
+    };
+    ImportedFile {
+        path: path.clone(),
+        session_id: ctx.session_id.clone(),
+        hothash: photo.map(|p| p.hothash.clone()),
+        photo_id: photo.map(|p| p.photo_id),
+        input_channel_id: Some(ctx.options.input_channel_id),
+        status: status.to_string(),
+        detail,
+        recorded_at: String::new(),
+    }
+}
+
+fn update_session(app: &tauri::AppHandle, session_id: &str, f: impl FnOnce(&mut ImportSession)) {
+    if let Some(session) = app.state::<ImportSessions>().0.lock().unwrap().get_mut(session_id) {
+        f(session);
+    }
+}
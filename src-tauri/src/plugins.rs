@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::ImalinkError;
+use crate::pipeline::CompanionGroup;
+use crate::settings::AppSettings;
+use crate::PhotoCreateSchema;
+
+// ===== Pipeline Plugins =====
+//
+// External processors declared in settings.json that hook into the import
+// pipeline at two points:
+//
+// - post_scan_filter: called once per session with every scanned group and
+//   answers with the master files to keep; the rest are skipped.
+// - pre_upload: called per photo with its PhotoCreateSchema (after file and
+//   storage info are attached) and answers with the schema to upload.
+//
+// A plugin is an executable, or a WASI module run through `wasm_runtime`
+// (wasmtime by default). Both speak JSON: one request object on stdin, one
+// response object on stdout. Plugins of the same step run in settings
+// order, each seeing the previous one's output. Nothing runs unless
+// `plugins_enabled` is set.
+//
+//   post_scan_filter  in:  { "step", "source_dir", "groups": [{ "master_file", "companion_files" }] }
+//                     out: { "keep": ["<master_file>", ...] }
+//   pre_upload        in:  { "step", "master_file", "companion_files", "schema" }
+//                     out: { "schema": { ... } }
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginStep {
+    PostScanFilter,
+    PreUpload,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginRuntime {
+    Executable {
+        program: String,
+    },
+    Wasm {
+        module: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelinePlugin {
+    pub name: String,
+    pub step: PluginStep,
+    #[serde(flatten)]
+    pub runtime: PluginRuntime,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+}
+
+#[derive(Serialize)]
+struct GroupRef<'a> {
+    master_file: &'a str,
+    companion_files: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct FilterResponse {
+    keep: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MutateResponse {
+    schema: PhotoCreateSchema,
+}
+
+// Plugins active for one import session
+#[derive(Debug, Clone, Default)]
+pub struct PluginSet {
+    plugins: Vec<PipelinePlugin>,
+    wasm_runtime: String,
+}
+
+impl PluginSet {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        if !settings.plugins_enabled {
+            return PluginSet::default();
+        }
+        PluginSet {
+            plugins: settings.plugins.iter().filter(|p| p.enabled).cloned().collect(),
+            wasm_runtime: settings.wasm_runtime.clone(),
+        }
+    }
+
+    fn for_step(&self, step: PluginStep) -> impl Iterator<Item = &PipelinePlugin> {
+        self.plugins.iter().filter(move |p| p.step == step)
+    }
+
+    // Split groups into kept and (group, reason) skipped
+    pub async fn filter(
+        &self,
+        source_dir: &str,
+        groups: Vec<CompanionGroup>,
+    ) -> Result<(Vec<CompanionGroup>, Vec<(CompanionGroup, String)>), ImalinkError> {
+        let mut kept = groups;
+        let mut skipped = Vec::new();
+        for plugin in self.for_step(PluginStep::PostScanFilter) {
+            let request = serde_json::json!({
+                "step": PluginStep::PostScanFilter,
+                "source_dir": source_dir,
+                "groups": kept
+                    .iter()
+                    .map(|g| GroupRef { master_file: &g.master_file, companion_files: &g.companion_files })
+                    .collect::<Vec<_>>(),
+            });
+            let response: FilterResponse = self.call(plugin, &request).await?;
+            let keep: HashSet<String> = response.keep.into_iter().collect();
+            let (stay, drop): (Vec<_>, Vec<_>) = kept.into_iter().partition(|g| keep.contains(&g.master_file));
+            skipped.extend(drop.into_iter().map(|g| (g, format!("Filtered out by plugin {}", plugin.name))));
+            kept = stay;
+        }
+        Ok((kept, skipped))
+    }
+
+    pub async fn mutate(&self, group: &CompanionGroup, schema: PhotoCreateSchema) -> Result<PhotoCreateSchema, ImalinkError> {
+        let mut schema = schema;
+        for plugin in self.for_step(PluginStep::PreUpload) {
+            let request = serde_json::json!({
+                "step": PluginStep::PreUpload,
+                "master_file": group.master_file,
+                "companion_files": group.companion_files,
+                "schema": schema,
+            });
+            let response: MutateResponse = self.call(plugin, &request).await?;
+            schema = response.schema;
+        }
+        Ok(schema)
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        plugin: &PipelinePlugin,
+        request: &serde_json::Value,
+    ) -> Result<T, ImalinkError> {
+        let (program, args) = match &plugin.runtime {
+            PluginRuntime::Executable { program } => (program.clone(), plugin.args.clone()),
+            PluginRuntime::Wasm { module } => (
+                self.wasm_runtime.clone(),
+                std::iter::once(module.clone()).chain(plugin.args.iter().cloned()).collect(),
+            ),
+        };
+        let input = serde_json::to_vec(request)?;
+        let timeout = Duration::from_secs(plugin.timeout_seconds.max(1));
+        let output = tokio::time::timeout(timeout, run(&program, &args, &input))
+            .await
+            .map_err(|_| ImalinkError::internal(format!("Plugin {} timed out after {}s", plugin.name, plugin.timeout_seconds)))??;
+        serde_json::from_slice(&output)
+            .map_err(|e| ImalinkError::parse(format!("Invalid response from plugin {}: {}", plugin.name, e)))
+    }
+}
+
+// Run a plugin process and return its stdout
+async fn run(program: &str, args: &[String], input: &[u8]) -> Result<Vec<u8>, ImalinkError> {
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ImalinkError::io(program, e))?;
+
+    // Write and read concurrently so a large request can't deadlock on full pipes;
+    // stdin is closed once written
+    let stdin = child.stdin.take();
+    let write = async move {
+        match stdin {
+            Some(mut stdin) => stdin.write_all(input).await,
+            None => Ok(()),
+        }
+    };
+    let (written, output) = tokio::join!(write, child.wait_with_output());
+    let output = output.map_err(|e| ImalinkError::io(program, e))?;
+    written.map_err(|e| ImalinkError::io(program, e))?;
+    if !output.status.success() {
+        return Err(ImalinkError::internal(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::History;
+use crate::hothash::{self, HothashIndex};
+use crate::operations::{self, OperationKind};
+use crate::pipeline::{self, ImportSessions, MasterOrder};
+use crate::preview_store::PreviewStore;
+use crate::schema_cache::SchemaCache;
+use crate::PhotoCreateSchema;
+
+// ===== Background Pre-processing =====
+//
+// While the user is still culling in the review grid, the frontend hands us
+// the files it expects to be imported. They are hashed and sent through core
+// one at a time, filling the schema cache, hothash index and preview store,
+// so the real import finds everything cached and only has to upload.
+//
+// Prefetching is low priority: a single worker, paused while an import is
+// running, and abandoned as soon as a newer file list arrives.
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PrefetchStatus {
+    pub total: usize,
+    pub done: usize,
+    pub cached: usize,
+    pub failed: usize,
+    pub running: bool,
+}
+
+#[derive(Default)]
+pub struct Prefetcher {
+    // Bumped on every new request; stale workers notice and stop
+    generation: AtomicU64,
+    status: Mutex<PrefetchStatus>,
+}
+
+impl Prefetcher {
+    pub fn status(&self) -> PrefetchStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    // Stale workers notice the new generation and stop
+    fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut status) = self.status.lock() {
+            status.running = false;
+        }
+    }
+
+    fn update(&self, generation: u64, f: impl FnOnce(&mut PrefetchStatus)) {
+        if !self.is_current(generation) {
+            return;
+        }
+        if let Ok(mut status) = self.status.lock() {
+            f(&mut status);
+        }
+    }
+}
+
+// Start prefetching `files`, replacing any prefetch already in progress
+#[tauri::command]
+pub fn prefetch_files(
+    app: tauri::AppHandle,
+    prefetcher: tauri::State<'_, Prefetcher>,
+    core_api_url: String,
+    files: Vec<String>,
+    master_order: Option<MasterOrder>,
+) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "prefetch")?;
+    let groups = pipeline::group_companions(&files, &master_order.unwrap_or_default());
+    let generation = prefetcher.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut status) = prefetcher.status.lock() {
+        *status = PrefetchStatus {
+            total: groups.len(),
+            running: !groups.is_empty(),
+            ..Default::default()
+        };
+    }
+
+    let cancel_app = app.clone();
+    let operation = operations::register_with_cancel(
+        &app,
+        OperationKind::Prefetch,
+        format!("Prefetch {} files", groups.len()),
+        move || cancel_app.state::<Prefetcher>().cancel(),
+    );
+    operation.set_total(groups.len() as u64);
+
+    tauri::async_runtime::spawn(async move {
+        let client = crate::http::client(&app);
+        let prefetcher = app.state::<Prefetcher>();
+
+        for group in groups {
+            // Imports take precedence; wait until they're done
+            while app.state::<ImportSessions>().has_running() {
+                if !prefetcher.is_current(generation) {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            if !prefetcher.is_current(generation) {
+                return;
+            }
+
+            match prefetch_one(&app, &client, &core_api_url, &group.master_file).await {
+                Ok((_, was_cached)) => prefetcher.update(generation, |s| {
+                    s.done += 1;
+                    if was_cached {
+                        s.cached += 1;
+                    }
+                }),
+                Err(e) => {
+                    eprintln!("Prefetch failed for {}: {}", group.master_file, e);
+                    prefetcher.update(generation, |s| {
+                        s.done += 1;
+                        s.failed += 1;
+                    });
+                }
+            }
+            operation.advance();
+        }
+        prefetcher.update(generation, |s| s.running = false);
+    });
+
+    Ok(())
+}
+
+// Hash and process one master file. Returns the schema (previews stripped)
+// and whether it was already cached.
+pub async fn prefetch_one(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    core_api_url: &str,
+    file_path: &str,
+) -> Result<(PhotoCreateSchema, bool), ImalinkError> {
+    let (app_handle, path) = (app.clone(), file_path.to_string());
+    let content_hash = tauri::async_runtime::spawn_blocking(move || hothash::content_hash(&app_handle.state::<History>(), &path))
+        .await
+        .map_err(|e| ImalinkError::internal(e.to_string()))??;
+
+    let cache = app.state::<SchemaCache>();
+    let (mut schema, was_cached) = match cache.get(&content_hash) {
+        Some(schema) => (schema, true),
+        None => {
+            let schema = crate::process_file(client, file_path, core_api_url).await?;
+            cache.put(&content_hash, &schema)?;
+            (schema, false)
+        }
+    };
+
+    app.state::<HothashIndex>().insert(&content_hash, &schema.hothash);
+    // Make previews available to the review grid right away
+    app.state::<PreviewStore>().strip(&mut schema);
+    Ok((schema, was_cached))
+}
+
+#[tauri::command]
+pub fn cancel_prefetch(app: tauri::AppHandle, prefetcher: tauri::State<'_, Prefetcher>) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "prefetch")?;
+    prefetcher.cancel();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_prefetch_status(prefetcher: tauri::State<'_, Prefetcher>) -> PrefetchStatus {
+    prefetcher.status()
+}
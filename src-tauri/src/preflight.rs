@@ -0,0 +1,45 @@
+use crate::error::ImalinkError;
+use crate::http::{HttpClient, SendPaced};
+use crate::session::Session;
+
+// ===== Upload Preflight =====
+//
+// Before an import starts, the target channel is fetched once with its
+// token: 401 is `unauthorized`, 403/404 `channel_forbidden`, other errors a
+// backend error. check_channel_permission runs the same check for the UI.
+
+pub async fn check_channel(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    input_channel_id: i32,
+) -> Result<(), ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/input-channels/{}/", backend_url, input_channel_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::NOT_FOUND {
+        return Err(ImalinkError::ChannelForbidden { channel_id: input_channel_id });
+    }
+    let error_text = response.text().await.unwrap_or_default();
+    Err(ImalinkError::from_backend(status, error_text))
+}
+
+#[tauri::command]
+pub async fn check_channel_permission(
+    session: tauri::State<'_, Session>,
+    http: tauri::State<'_, HttpClient>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    input_channel_id: i32,
+) -> Result<(), ImalinkError> {
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    check_channel(&http.client(), &backend_url, &auth_token, input_channel_id).await
+}
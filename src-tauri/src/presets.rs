@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::duplicates::DuplicatePolicy;
+use crate::error::ImalinkError;
+use crate::pipeline::{ImportOptions, MasterOrder, StageWorkers};
+use crate::{guest, settings};
+
+// ===== Import Presets =====
+//
+// A preset is a named set of import options (where to upload, which channel,
+// copy or register, worker counts) without the source, so the same setup can
+// be reused by scheduled imports, URL imports and the UI. Presets are stored
+// in settings.json.
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportPreset {
+    pub name: String,
+    pub core_api_url: String,
+    pub backend_url: String,
+    pub input_channel_id: i32,
+    #[serde(default)]
+    pub destination_dir: Option<String>,
+    #[serde(default)]
+    pub preserve_structure: bool,
+    #[serde(default)]
+    pub rename_originals: bool,
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+    #[serde(default)]
+    pub workers: StageWorkers,
+    #[serde(default)]
+    pub convert_to_dng: bool,
+    #[serde(default)]
+    pub master_order: MasterOrder,
+    #[serde(default)]
+    pub omit_coldpreviews: bool,
+    #[serde(default)]
+    pub upload_assets: bool,
+}
+
+impl ImportPreset {
+    // Full import options for a run of this preset
+    pub fn import_options(&self, source_dir: &str, files: Option<Vec<String>>, auth_token: &str) -> ImportOptions {
+        ImportOptions {
+            source_dir: source_dir.to_string(),
+            files,
+            core_api_url: self.core_api_url.clone(),
+            backend_url: self.backend_url.clone(),
+            auth_token: auth_token.to_string(),
+            input_channel_id: self.input_channel_id,
+            destination_dir: self.destination_dir.clone(),
+            preserve_structure: self.preserve_structure,
+            rename_originals: self.rename_originals,
+            duplicate_policy: self.duplicate_policy,
+            category: None,
+            workers: self.workers.clone(),
+            rejected: Vec::new(),
+            archive_rejected: false,
+            sequences: Vec::new(),
+            multishot: Vec::new(),
+            note: None,
+            labels: Vec::new(),
+            source_urls: Default::default(),
+            source_emails: Default::default(),
+            convert_to_dng: self.convert_to_dng,
+            master_order: self.master_order.clone(),
+            workspace: None,
+            marks: Default::default(),
+            stack_id: None,
+            omit_coldpreviews: self.omit_coldpreviews,
+            upload_assets: self.upload_assets,
+            recheck_imported: false,
+        }
+    }
+}
+
+pub fn find(app: &tauri::AppHandle, name: &str) -> Result<ImportPreset, ImalinkError> {
+    settings::load(app)
+        .presets
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown preset: {}", name)))
+}
+
+#[tauri::command]
+pub fn list_presets(app: tauri::AppHandle) -> Vec<ImportPreset> {
+    settings::load(&app).presets
+}
+
+// Create or replace the preset with the same name
+#[tauri::command]
+pub fn save_preset(app: tauri::AppHandle, preset: ImportPreset) -> Result<Vec<ImportPreset>, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    if preset.name.trim().is_empty() {
+        return Err(ImalinkError::invalid("Preset name cannot be empty"));
+    }
+    let mut settings = settings::load(&app);
+    match settings.presets.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => settings.presets.push(preset),
+    }
+    settings::save(&app, &settings)?;
+    Ok(settings.presets)
+}
+
+#[tauri::command]
+pub fn delete_preset(app: tauri::AppHandle, name: String) -> Result<Vec<ImportPreset>, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    let mut settings = settings::load(&app);
+    if settings.schedules.iter().any(|s| s.preset == name) {
+        return Err(ImalinkError::invalid(format!("Preset {} is used by a scheduled import", name)));
+    }
+    settings.presets.retain(|p| p.name != name);
+    settings::save(&app, &settings)?;
+    Ok(settings.presets)
+}
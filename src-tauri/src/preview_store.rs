@@ -0,0 +1,143 @@
+use base64::Engine;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::PhotoCreateSchema;
+
+// ===== Preview Store =====
+//
+// Hot/cold previews stay in Rust instead of travelling to the webview as
+// base64 inside JSON. Commands strip the base64 fields from schemas they
+// return and the frontend loads the images through the `imalink-preview`
+// URI scheme, addressed by hothash:
+//
+//   imalink-preview://localhost/hot/<hothash>
+//   imalink-preview://localhost/cold/<hothash>
+//
+// (use `convertFileSrc("hot/<hothash>", "imalink-preview")` to get the right
+// URL form per platform). When a stripped schema comes back for upload, the
+// previews are restored from the store by hothash.
+
+pub const SCHEME: &str = "imalink-preview";
+
+// Memory budget for decoded previews; oldest entries are dropped first
+const MAX_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PreviewKind {
+    Hot,
+    Cold,
+}
+
+impl PreviewKind {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "hot" => Some(PreviewKind::Hot),
+            "cold" => Some(PreviewKind::Cold),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<(PreviewKind, String), Arc<Vec<u8>>>,
+    order: VecDeque<(PreviewKind, String)>,
+    total_bytes: usize,
+}
+
+#[derive(Default)]
+pub struct PreviewStore(Mutex<Inner>);
+
+impl PreviewStore {
+    pub fn insert(&self, kind: PreviewKind, hothash: &str, bytes: Vec<u8>) {
+        let Ok(mut inner) = self.0.lock() else { return };
+        let key = (kind, hothash.to_string());
+        if let Some(old) = inner.entries.insert(key.clone(), Arc::new(bytes)) {
+            inner.total_bytes -= old.len();
+            inner.order.retain(|k| k != &key);
+        }
+        inner.total_bytes += inner.entries[&key].len();
+        inner.order.push_back(key);
+
+        while inner.total_bytes > MAX_BYTES {
+            let Some(oldest) = inner.order.pop_front() else { break };
+            if let Some(old) = inner.entries.remove(&oldest) {
+                inner.total_bytes -= old.len();
+            }
+        }
+    }
+
+    pub fn get(&self, kind: PreviewKind, hothash: &str) -> Option<Arc<Vec<u8>>> {
+        self.0.lock().ok()?.entries.get(&(kind, hothash.to_string())).cloned()
+    }
+
+    // Move base64 previews out of the schema into the store
+    pub fn strip(&self, schema: &mut PhotoCreateSchema) {
+        let engine = base64::engine::general_purpose::STANDARD;
+        if !schema.hotpreview_base64.is_empty() {
+            if let Ok(bytes) = engine.decode(&schema.hotpreview_base64) {
+                self.insert(PreviewKind::Hot, &schema.hothash, bytes);
+                schema.hotpreview_base64.clear();
+            }
+        }
+        if let Some(cold) = schema.coldpreview_base64.as_deref() {
+            if let Ok(bytes) = engine.decode(cold) {
+                self.insert(PreviewKind::Cold, &schema.hothash, bytes);
+                schema.coldpreview_base64 = None;
+            }
+        }
+    }
+
+    // Put previews back into a stripped schema before sending it to the backend
+    pub fn restore(&self, schema: &mut PhotoCreateSchema) -> Result<(), ImalinkError> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        if schema.hotpreview_base64.is_empty() {
+            let bytes = self.get(PreviewKind::Hot, &schema.hothash).ok_or_else(|| {
+                ImalinkError::invalid(format!(
+                    "Hotpreview for {} is no longer available, process the file again",
+                    schema.hothash
+                ))
+            })?;
+            schema.hotpreview_base64 = engine.encode(bytes.as_slice());
+        }
+        if schema.coldpreview_base64.is_none() && schema.coldpreview_width.is_some() {
+            if let Some(bytes) = self.get(PreviewKind::Cold, &schema.hothash) {
+                schema.coldpreview_base64 = Some(engine.encode(bytes.as_slice()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Handler for the imalink-preview:// URI scheme
+pub fn handle_request<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let path = request.uri().path().trim_start_matches('/');
+    let found = path.split_once('/').and_then(|(kind, hothash)| {
+        let kind = PreviewKind::parse(kind)?;
+        app.state::<PreviewStore>().get(kind, hothash).or_else(|| {
+            // Cold previews downloaded for offline use (see offline.rs)
+            let offline = app.try_state::<crate::offline::OfflinePreviews>()?;
+            (kind == PreviewKind::Cold).then(|| offline.get(hothash)).flatten().map(Arc::new)
+        })
+    });
+
+    match found {
+        Some(bytes) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "image/jpeg")
+            .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+            .body(bytes.as_ref().clone())
+            .unwrap_or_default(),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new())
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PhotoCreateSchema;
+
+// ===== GPS Privacy Zones =====
+//
+// Zones (center + radius, e.g. home) configured in settings.json. Photos taken
+// inside a zone have their position removed or coarsened before upload:
+//
+// - strip: gps_latitude/gps_longitude are cleared
+// - fuzz:  coordinates are snapped to a grid of `fuzz_meters`, so the photo
+//          still lands in the right area on a map. Snapping (rather than
+//          random noise) means repeated uploads can't be averaged back to
+//          the real position.
+//
+// In both cases the GPS* tags are removed from exif_dict, which would
+// otherwise carry the precise position. The action taken is returned so it
+// can be shown in the import report.
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+fn default_fuzz_meters() -> f64 {
+    1000.0
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneAction {
+    #[default]
+    Strip,
+    Fuzz,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrivacyZone {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_meters: f64,
+    #[serde(default)]
+    pub action: ZoneAction,
+    #[serde(default = "default_fuzz_meters")]
+    pub fuzz_meters: f64,
+}
+
+// What was done to a photo's position
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GpsScrub {
+    pub zone: String,
+    pub action: ZoneAction,
+}
+
+// Great-circle distance in meters
+pub fn distance_meters(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+// Snap a position to a grid of roughly `cell` meters
+fn snap(lat: f64, lon: f64, cell: f64) -> (f64, f64) {
+    let cell = cell.max(1.0);
+    let lat_step = (cell / EARTH_RADIUS_M).to_degrees();
+    let snapped_lat = (lat / lat_step).round() * lat_step;
+    // Longitude degrees shrink towards the poles
+    let lon_step = lat_step / snapped_lat.to_radians().cos().abs().max(0.01);
+    let snapped_lon = (lon / lon_step).round() * lon_step;
+    (snapped_lat, snapped_lon)
+}
+
+fn strip_exif_gps(exif_dict: &mut serde_json::Value) {
+    if let Some(exif) = exif_dict.as_object_mut() {
+        exif.retain(|key, _| !key.starts_with("GPS"));
+    }
+}
+
+// Apply the first zone containing the photo's position, if any
+pub fn apply(zones: &[PrivacyZone], schema: &mut PhotoCreateSchema) -> Option<GpsScrub> {
+    let (lat, lon) = (schema.gps_latitude?, schema.gps_longitude?);
+    let zone = zones
+        .iter()
+        .find(|z| distance_meters(lat, lon, z.latitude, z.longitude) <= z.radius_meters)?;
+
+    match zone.action {
+        ZoneAction::Strip => {
+            schema.gps_latitude = None;
+            schema.gps_longitude = None;
+        }
+        ZoneAction::Fuzz => {
+            let (lat, lon) = snap(lat, lon, zone.fuzz_meters);
+            schema.gps_latitude = Some(lat);
+            schema.gps_longitude = Some(lon);
+        }
+    }
+    strip_exif_gps(&mut schema.exif_dict);
+
+    Some(GpsScrub {
+        zone: zone.name.clone(),
+        action: zone.action,
+    })
+}
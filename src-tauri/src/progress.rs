@@ -0,0 +1,328 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::error::ImalinkError;
+use crate::ratelimit::{self, RateLimitStatus};
+
+// ===== Progress Aggregation =====
+//
+// A large import finishes thousands of items per stage; emitting an event for
+// each floods the IPC bridge. The aggregator keeps cumulative counters and a
+// ticker emits one `import-progress` snapshot at most N times per second, and
+// only when something changed. Per-item `import-item-error` events are still
+// sent immediately since errors are rare and need to be shown individually.
+//
+// Every tick also records a sample of the counters; per-stage throughput and
+// the ETA are computed over the samples from the last RATE_WINDOW, so they
+// follow the current speed rather than the average since the start.
+
+const RATE_WINDOW: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Hash,
+    Lookup,
+    Process,
+    Upload,
+    Copy,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct StageStats {
+    pub files: usize,
+    pub bytes: u64,
+    pub files_per_second: f64,
+    pub mb_per_second: f64,
+    // Transfer attempts aborted for making no progress (upload only)
+    pub stalls: usize,
+    // Requests whose coldpreview was shrunk to fit the size limit (upload only)
+    pub payloads_shrunk: usize,
+    // Items abandoned for taking longer than the stage timeout (see
+    // watchdog.rs)
+    pub stuck: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct StageCounters {
+    pub hash: StageStats,
+    pub lookup: StageStats,
+    pub process: StageStats,
+    pub upload: StageStats,
+    pub copy: StageStats,
+}
+
+impl StageCounters {
+    fn get_mut(&mut self, stage: Stage) -> &mut StageStats {
+        match stage {
+            Stage::Hash => &mut self.hash,
+            Stage::Lookup => &mut self.lookup,
+            Stage::Process => &mut self.process,
+            Stage::Upload => &mut self.upload,
+            Stage::Copy => &mut self.copy,
+        }
+    }
+
+    fn all_mut(&mut self) -> [&mut StageStats; 5] {
+        [&mut self.hash, &mut self.lookup, &mut self.process, &mut self.upload, &mut self.copy]
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ProgressSnapshot {
+    pub session_id: String,
+    pub total: usize,
+    pub completed: usize,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub stages: StageCounters,
+    // Finished photos per second over the recent window
+    pub items_per_second: f64,
+    pub elapsed_seconds: u64,
+    // None until there is enough progress to estimate from
+    pub eta_seconds: Option<u64>,
+    pub finished: bool,
+    // Backend rate limiting, for all uploads at once (see ratelimit.rs)
+    pub rate_limit: RateLimitStatus,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportEta {
+    pub session_id: String,
+    pub remaining: usize,
+    pub items_per_second: f64,
+    pub eta_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ItemError {
+    session_id: String,
+    file: String,
+    code: String,
+    error: String,
+    retryable: bool,
+}
+
+// Counter values at one tick
+struct Sample {
+    at: Instant,
+    completed: usize,
+    stages: [(usize, u64); 5],
+}
+
+struct State {
+    snapshot: ProgressSnapshot,
+    samples: VecDeque<Sample>,
+}
+
+pub struct ProgressAggregator {
+    app: tauri::AppHandle,
+    started: Instant,
+    state: Mutex<State>,
+    dirty: AtomicBool,
+}
+
+// Managed state: aggregators by session id, for get_import_eta
+#[derive(Default)]
+pub struct ProgressTrackers(Mutex<HashMap<String, Arc<ProgressAggregator>>>);
+
+impl ProgressTrackers {
+    pub fn snapshots(&self) -> Vec<ProgressSnapshot> {
+        self.0
+            .lock()
+            .map(|trackers| trackers.values().map(|t| t.snapshot()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl ProgressAggregator {
+    // Create the aggregator, register it and start its ticker
+    pub fn start(app: tauri::AppHandle, session_id: String, events_per_second: u32) -> Arc<Self> {
+        let aggregator = Arc::new(ProgressAggregator {
+            app: app.clone(),
+            started: Instant::now(),
+            state: Mutex::new(State {
+                snapshot: ProgressSnapshot {
+                    session_id: session_id.clone(),
+                    ..Default::default()
+                },
+                samples: VecDeque::new(),
+            }),
+            dirty: AtomicBool::new(false),
+        });
+        if let Ok(mut trackers) = app.state::<ProgressTrackers>().0.lock() {
+            trackers.insert(session_id, aggregator.clone());
+        }
+
+        let interval = Duration::from_millis(1000 / events_per_second.clamp(1, 60) as u64);
+        let ticker = aggregator.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            loop {
+                tick.tick().await;
+                if ticker.tick() {
+                    break;
+                }
+            }
+        });
+
+        aggregator
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ProgressSnapshot)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state.snapshot);
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.update(|s| s.total = total);
+    }
+
+    // Record one item through `stage`; `bytes` is the size of its files
+    pub fn stage_done(&self, stage: Stage, bytes: u64) {
+        self.update(|s| {
+            let stats = s.stages.get_mut(stage);
+            stats.files += 1;
+            stats.bytes += bytes;
+        });
+    }
+
+    // Record one attempt of `stage` aborted as stalled
+    pub fn stalled(&self, stage: Stage) {
+        self.update(|s| s.stages.get_mut(stage).stalls += 1);
+    }
+
+    // Record one request of `stage` shrunk to fit the upload size limit
+    pub fn payload_shrunk(&self, stage: Stage) {
+        self.update(|s| s.stages.get_mut(stage).payloads_shrunk += 1);
+    }
+
+    // Record one item of `stage` given up on by the watchdog
+    pub fn stuck(&self, stage: Stage) {
+        self.update(|s| s.stages.get_mut(stage).stuck += 1);
+    }
+
+    pub fn succeeded(&self) {
+        self.update(|s| {
+            s.succeeded += 1;
+            s.completed += 1;
+        });
+    }
+
+    pub fn skipped(&self) {
+        self.update(|s| {
+            s.skipped += 1;
+            s.completed += 1;
+        });
+    }
+
+    pub fn failed(&self, file: &str, error: &ImalinkError) {
+        let mut session_id = String::new();
+        self.update(|s| {
+            s.failed += 1;
+            s.completed += 1;
+            session_id = s.session_id.clone();
+        });
+        let _ = self.app.emit("import-item-error", ItemError {
+            session_id,
+            file: file.to_string(),
+            code: error.code().to_string(),
+            error: error.message(),
+            retryable: error.retryable(),
+        });
+    }
+
+    // Mark the session finished; the next tick emits the final snapshot and stops
+    pub fn finish(&self) {
+        self.update(|s| {
+            s.finished = true;
+            s.eta_seconds = Some(0);
+        });
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        self.state.lock().map(|s| s.snapshot.clone()).unwrap_or_default()
+    }
+
+    // Sample counters, refresh rates and emit a snapshot if anything changed.
+    // Returns true once the final snapshot is out.
+    fn tick(&self) -> bool {
+        let now = Instant::now();
+        let snapshot = {
+            let Ok(mut state) = self.state.lock() else { return true };
+            let State { snapshot, samples } = &mut *state;
+
+            let mut stages = [(0, 0); 5];
+            for (slot, stats) in stages.iter_mut().zip(snapshot.stages.all_mut()) {
+                *slot = (stats.files, stats.bytes);
+            }
+            samples.push_back(Sample { at: now, completed: snapshot.completed, stages });
+            // Keep one sample older than the window as the baseline
+            while samples.len() > 2 && now.duration_since(samples[1].at) > RATE_WINDOW {
+                samples.pop_front();
+            }
+
+            let oldest = &samples[0];
+            let seconds = now.duration_since(oldest.at).as_secs_f64();
+            if seconds > 0.0 {
+                for (stats, (files, bytes)) in snapshot.stages.all_mut().into_iter().zip(oldest.stages) {
+                    stats.files_per_second = (stats.files - files) as f64 / seconds;
+                    stats.mb_per_second = (stats.bytes - bytes) as f64 / seconds / (1024.0 * 1024.0);
+                }
+                snapshot.items_per_second = (snapshot.completed - oldest.completed) as f64 / seconds;
+            }
+
+            snapshot.elapsed_seconds = self.started.elapsed().as_secs();
+            if !snapshot.finished {
+                let remaining = snapshot.total.saturating_sub(snapshot.completed);
+                snapshot.eta_seconds = (snapshot.items_per_second > 0.0)
+                    .then(|| (remaining as f64 / snapshot.items_per_second).ceil() as u64);
+            }
+
+            // A pause starting or ending is news even without progress
+            let rate_limit = ratelimit::status();
+            if snapshot.rate_limit != rate_limit {
+                snapshot.rate_limit = rate_limit;
+                self.dirty.store(true, Ordering::SeqCst);
+            }
+
+            if !self.dirty.swap(false, Ordering::SeqCst) {
+                return false;
+            }
+            snapshot.clone()
+        };
+
+        let _ = self.app.emit("import-progress", &snapshot);
+        snapshot.finished
+    }
+}
+
+// Live ETA for an import session, from remaining work and recent rates
+#[tauri::command]
+pub fn get_import_eta(
+    trackers: tauri::State<'_, ProgressTrackers>,
+    session_id: String,
+) -> Result<ImportEta, ImalinkError> {
+    let aggregator = trackers
+        .0
+        .lock()
+        .unwrap()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown import session: {}", session_id)))?;
+
+    let snapshot = aggregator.snapshot();
+    Ok(ImportEta {
+        remaining: snapshot.total.saturating_sub(snapshot.completed),
+        session_id: snapshot.session_id,
+        items_per_second: snapshot.items_per_second,
+        eta_seconds: snapshot.eta_seconds,
+    })
+}
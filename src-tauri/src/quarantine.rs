@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::pipeline::{self, CompanionGroup, ImportOptions};
+use crate::session::Session;
+
+// ===== Processing Quarantine =====
+//
+// A group core still fails on after `process_retries`, or that gets stuck in
+// a stage (watchdog.rs), is recorded in <app data>/quarantine.json with the
+// error and the import moves on. list_quarantined, retry_quarantined and
+// dismiss_quarantined manage the entries; files are never moved.
+
+const QUARANTINE_FILE: &str = "quarantine.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuarantinedItem {
+    pub id: String,
+    pub session_id: String,
+    pub master_file: String,
+    // Master first
+    pub files: Vec<String>,
+    pub code: String,
+    pub error: String,
+    // Response body of imalink-core, when core was the one failing
+    pub core_output: Option<String>,
+    pub attempts: u32,
+    pub quarantined_at: String,
+    // Options of the session, without the auth token
+    pub options: ImportOptions,
+}
+
+// Managed state
+pub struct Quarantine {
+    path: PathBuf,
+    items: Mutex<Vec<QuarantinedItem>>,
+}
+
+impl Quarantine {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(QUARANTINE_FILE);
+        let items = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Quarantine { path, items: Mutex::new(items) }
+    }
+
+    fn save(&self, items: &[QuarantinedItem]) {
+        let written = (|| {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+            }
+            let text = serde_json::to_string_pretty(items)?;
+            fs::write(&self.path, text).map_err(|e| ImalinkError::io(self.path.display(), e))
+        })();
+        if let Err(e) = written {
+            eprintln!("Failed to save quarantine: {}", e);
+        }
+    }
+
+    // Quarantine a group that failed processing. A group already in
+    // quarantine (from an earlier attempt) is replaced.
+    pub fn add(
+        &self,
+        session_id: &str,
+        group: &CompanionGroup,
+        options: &ImportOptions,
+        error: &ImalinkError,
+        attempts: u32,
+    ) -> QuarantinedItem {
+        let item = QuarantinedItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            master_file: group.master_file.clone(),
+            files: group.all_files(),
+            code: error.code().to_string(),
+            error: error.message(),
+            core_output: match error {
+                ImalinkError::Core { detail, .. } => Some(detail.clone()),
+                _ => None,
+            },
+            attempts,
+            quarantined_at: chrono::Utc::now().to_rfc3339(),
+            options: ImportOptions { auth_token: String::new(), files: None, ..options.clone() },
+        };
+        let mut items = self.items.lock().unwrap();
+        items.retain(|i| i.master_file != item.master_file);
+        items.push(item.clone());
+        self.save(&items);
+        item
+    }
+
+    pub fn list(&self) -> Vec<QuarantinedItem> {
+        self.items.lock().unwrap().clone()
+    }
+
+    // Remove the items with these ids (all when None) and return them
+    fn take(&self, ids: Option<&[String]>) -> Vec<QuarantinedItem> {
+        let mut items = self.items.lock().unwrap();
+        let (taken, kept): (Vec<_>, Vec<_>) =
+            items.drain(..).partition(|i| ids.is_none_or(|ids| ids.contains(&i.id)));
+        *items = kept;
+        if !taken.is_empty() {
+            self.save(&items);
+        }
+        taken
+    }
+}
+
+#[tauri::command]
+pub fn list_quarantined(quarantine: tauri::State<'_, Quarantine>) -> Vec<QuarantinedItem> {
+    quarantine.list()
+}
+
+// Import the quarantined groups with `ids` (all when None) again. Returns the
+// new session ids; groups that fail again go back into quarantine.
+#[tauri::command]
+pub fn retry_quarantined(
+    app: tauri::AppHandle,
+    ids: Option<Vec<String>>,
+    auth_token: Option<String>,
+) -> Result<Vec<String>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let items = app.state::<Quarantine>().take(ids.as_deref());
+
+    let mut by_session: HashMap<String, (ImportOptions, Vec<String>)> = HashMap::new();
+    for item in items {
+        let (_, files) = by_session.entry(item.session_id).or_insert_with(|| (item.options, Vec::new()));
+        files.extend(item.files.into_iter().filter(|f| Path::new(f).is_file()));
+    }
+
+    let mut sessions = Vec::new();
+    for (options, files) in by_session.into_values().filter(|(_, files)| !files.is_empty()) {
+        let options = ImportOptions {
+            files: Some(files),
+            auth_token: auth_token.clone(),
+            ..options
+        };
+        sessions.push(pipeline::spawn_import(&app, options)?);
+    }
+    Ok(sessions)
+}
+
+// Forget the quarantined groups with `ids`; their files are left alone
+#[tauri::command]
+pub fn dismiss_quarantined(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+) -> Result<Vec<QuarantinedItem>, ImalinkError> {
+    guest::require_owner(&app, "dismiss quarantined files")?;
+    Ok(app.state::<Quarantine>().take(Some(&ids)))
+}
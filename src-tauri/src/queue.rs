@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::pipeline::{self, ImportOptions, ImportSessions, SessionStatus};
+use crate::scheduler::Scheduler;
+use crate::session::Session;
+use crate::{guest, maintenance, multishot, ratelimit, recovery};
+
+// ===== Upload Queue =====
+//
+// Imports added with queue_add run one after the other, and the queue is
+// kept in <app data>/upload_queue.json so it outlives the app: entries still
+// queued when the app is closed or crashes start again on the next launch,
+// and the one that was running is queued again from the start - photos it
+// already uploaded are found by the lookup stage and reported as duplicates
+// without being processed a second time.
+//
+// The file holds no tokens. Queued imports run with the token of the
+// queue_add call that added them while the app is running, and otherwise
+// with the login token the frontend hands the scheduler
+// (set_scheduler_token) or that of the login session; without any, the
+// queue waits.
+//
+// queue_retry_failed queues the failed groups of finished entries again,
+// and queue_clear removes finished entries (and with `all`, queued ones).
+// While the backend is under maintenance no new entry is started (see
+// maintenance.rs); the running one waits inside its upload stage.
+
+const QUEUE_FILE: &str = "upload_queue.json";
+const PUMP_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueEntryStatus {
+    Queued,
+    Running,
+    Completed,
+    // Could not be started, see `error`
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueEntry {
+    pub id: String,
+    pub status: QueueEntryStatus,
+    // Without the auth token
+    pub options: ImportOptions,
+    pub added_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub session_id: Option<String>,
+    pub uploaded: usize,
+    // Master files that failed (or the source folder, when scanning did)
+    pub failed: Vec<String>,
+    pub error: Option<String>,
+    // Times it was queued again after an interruption or by queue_retry_failed
+    pub attempts: u32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueStatus {
+    pub entries: Vec<QueueEntry>,
+    pub queued: usize,
+    pub running: Option<String>,
+    // Entries are waiting and there is no token to run them with
+    pub waiting_for_login: bool,
+}
+
+// Managed state
+pub struct UploadQueue {
+    path: PathBuf,
+    entries: Mutex<Vec<QueueEntry>>,
+    auth_token: Mutex<Option<String>>,
+}
+
+impl UploadQueue {
+    // Load the queue; an entry that was running when the app went away is
+    // queued again, and its interrupted-import journal dropped since the
+    // queue takes care of it
+    pub fn load(dir: &Path, journal: &recovery::ImportJournal) -> Self {
+        let path = dir.join(QUEUE_FILE);
+        let mut entries: Vec<QueueEntry> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        for entry in entries.iter_mut().filter(|e| e.status == QueueEntryStatus::Running) {
+            if let Some(session_id) = entry.session_id.take() {
+                journal.finish(&session_id);
+            }
+            entry.status = QueueEntryStatus::Queued;
+            entry.attempts += 1;
+        }
+        UploadQueue { path, entries: Mutex::new(entries), auth_token: Mutex::new(None) }
+    }
+
+    fn save(&self, entries: &[QueueEntry]) {
+        let written = (|| {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+            }
+            let text = serde_json::to_string_pretty(entries)?;
+            fs::write(&self.path, text).map_err(|e| ImalinkError::io(self.path.display(), e))
+        })();
+        if let Err(e) = written {
+            eprintln!("Failed to save upload queue: {}", e);
+        }
+    }
+
+    fn status(&self, has_token: bool) -> QueueStatus {
+        let entries = self.entries.lock().unwrap().clone();
+        let queued = entries.iter().filter(|e| e.status == QueueEntryStatus::Queued).count();
+        QueueStatus {
+            running: entries.iter().find(|e| e.status == QueueEntryStatus::Running).map(|e| e.id.clone()),
+            waiting_for_login: queued > 0 && !has_token,
+            queued,
+            entries,
+        }
+    }
+}
+
+fn token(app: &tauri::AppHandle) -> Option<String> {
+    let own = app.state::<UploadQueue>().auth_token.lock().unwrap().clone();
+    own.or_else(|| app.state::<Scheduler>().auth_token())
+        .or_else(|| app.state::<Session>().auth_token())
+}
+
+// Finish the running entry once its session is done, then start the next
+fn pump(app: &tauri::AppHandle) {
+    let queue = app.state::<UploadQueue>();
+    let sessions = app.state::<ImportSessions>();
+    let mut entries = queue.entries.lock().unwrap();
+    let mut changed = false;
+
+    if let Some(entry) = entries.iter_mut().find(|e| e.status == QueueEntryStatus::Running) {
+        let session = entry.session_id.as_deref().and_then(|id| sessions.get(id));
+        match session {
+            Some(session) if session.status == SessionStatus::Running => return,
+            Some(session) => {
+                entry.status = QueueEntryStatus::Completed;
+                entry.uploaded = session.result.succeeded.len();
+                entry.failed = session.result.failed.into_iter().map(|f| f.item).collect();
+            }
+            // Restored from a snapshot or otherwise lost
+            None => entry.status = QueueEntryStatus::Completed,
+        }
+        entry.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        changed = true;
+    }
+
+    let next = entries.iter_mut().find(|e| e.status == QueueEntryStatus::Queued);
+    if let Some(entry) = next.filter(|_| !maintenance::active() && !ratelimit::active()) {
+        if let Some(auth_token) = token(app) {
+            let options = ImportOptions { auth_token, ..entry.options.clone() };
+            entry.started_at = Some(chrono::Utc::now().to_rfc3339());
+            match pipeline::spawn_import(app, options) {
+                Ok(session_id) => {
+                    entry.status = QueueEntryStatus::Running;
+                    entry.session_id = Some(session_id);
+                    entry.error = None;
+                }
+                Err(e) => {
+                    entry.status = QueueEntryStatus::Failed;
+                    entry.finished_at = entry.started_at.clone();
+                    entry.error = Some(e.message());
+                }
+            }
+            changed = true;
+        }
+    }
+
+    if changed {
+        queue.save(&entries);
+    }
+}
+
+// Start the background loop that works through the queue
+pub fn start(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(PUMP_INTERVAL);
+        loop {
+            tick.tick().await;
+            pump(&app);
+        }
+    });
+}
+
+// Options to import only the failed groups of an entry again
+fn retry_options(entry: &QueueEntry) -> Result<ImportOptions, ImalinkError> {
+    if entry.failed.contains(&entry.options.source_dir) {
+        return Ok(entry.options.clone());
+    }
+    let files = match &entry.options.files {
+        Some(files) => files.clone(),
+        None => crate::collect_image_files(&PathBuf::from(&entry.options.source_dir))?,
+    };
+    let groups = pipeline::group_companions(&files, &entry.options.master_order);
+    let files = multishot::merge(groups, &entry.options.multishot)
+        .into_iter()
+        .filter(|g| entry.failed.contains(&g.master_file))
+        .flat_map(|g| g.all_files())
+        .collect();
+    Ok(ImportOptions { files: Some(files), ..entry.options.clone() })
+}
+
+// ===== Commands =====
+
+#[tauri::command]
+pub fn queue_add(app: tauri::AppHandle, options: ImportOptions) -> Result<QueueEntry, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let queue = app.state::<UploadQueue>();
+    if !options.auth_token.is_empty() {
+        *queue.auth_token.lock().unwrap() = Some(options.auth_token.clone());
+    }
+    let entry = QueueEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        status: QueueEntryStatus::Queued,
+        options: ImportOptions { auth_token: String::new(), ..options },
+        added_at: chrono::Utc::now().to_rfc3339(),
+        started_at: None,
+        finished_at: None,
+        session_id: None,
+        uploaded: 0,
+        failed: Vec::new(),
+        error: None,
+        attempts: 0,
+    };
+    {
+        let mut entries = queue.entries.lock().unwrap();
+        entries.push(entry.clone());
+        queue.save(&entries);
+    }
+    pump(&app);
+    Ok(entry)
+}
+
+#[tauri::command]
+pub fn queue_status(app: tauri::AppHandle) -> QueueStatus {
+    app.state::<UploadQueue>().status(token(&app).is_some())
+}
+
+// Queue the failed groups of finished entries, and entries that could not
+// start, again. Returns the entries queued.
+#[tauri::command]
+pub async fn queue_retry_failed(app: tauri::AppHandle) -> Result<Vec<QueueEntry>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let worker = app.clone();
+    let retried = tauri::async_runtime::spawn_blocking(move || {
+        let queue = worker.state::<UploadQueue>();
+        let mut entries = queue.entries.lock().unwrap();
+        let mut retried = Vec::new();
+        for entry in entries.iter_mut() {
+            let retry = match entry.status {
+                QueueEntryStatus::Failed => true,
+                QueueEntryStatus::Completed => !entry.failed.is_empty(),
+                _ => false,
+            };
+            if !retry {
+                continue;
+            }
+            if entry.status == QueueEntryStatus::Completed {
+                entry.options = retry_options(entry)?;
+            }
+            entry.status = QueueEntryStatus::Queued;
+            entry.session_id = None;
+            entry.failed.clear();
+            entry.error = None;
+            entry.attempts += 1;
+            retried.push(entry.clone());
+        }
+        queue.save(&entries);
+        Ok::<_, ImalinkError>(retried)
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(format!("Queue retry failed: {}", e)))??;
+    pump(&app);
+    Ok(retried)
+}
+
+// Remove finished entries; with `all`, queued ones too. The running entry stays.
+#[tauri::command]
+pub fn queue_clear(app: tauri::AppHandle, all: Option<bool>) -> Result<QueueStatus, ImalinkError> {
+    guest::require_owner(&app, "change the upload queue")?;
+    let all = all.unwrap_or(false);
+    let queue = app.state::<UploadQueue>();
+    {
+        let mut entries = queue.entries.lock().unwrap();
+        entries.retain(|e| match e.status {
+            QueueEntryStatus::Running => true,
+            QueueEntryStatus::Queued => !all,
+            QueueEntryStatus::Completed | QueueEntryStatus::Failed => false,
+        });
+        queue.save(&entries);
+    }
+    Ok(queue.status(token(&app).is_some()))
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::ImalinkError;
+
+// ===== Backend Rate Limits =====
+//
+// A 429 (or RateLimit-*/X-RateLimit-* headers showing none left) pauses all
+// backend traffic until the Retry-After, PAUSE_DEFAULT when missing.
+// Requests sent with http::SendPaced wait out the pause and retry a 429;
+// upload_schema returns ImalinkError::RateLimited. The state is reported in
+// import-progress and by get_rate_limit_status.
+
+const PAUSE_DEFAULT: Duration = Duration::from_secs(5);
+const PAUSE_MIN: Duration = Duration::from_secs(1);
+const PAUSE_MAX: Duration = Duration::from_secs(15 * 60);
+
+struct Limits {
+    until: Option<DateTime<Utc>>,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    hits: u64,
+}
+
+static LIMITS: Mutex<Limits> = Mutex::new(Limits { until: None, limit: None, remaining: None, hits: 0 });
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct RateLimitStatus {
+    // Backend traffic is paused
+    pub limited: bool,
+    // RFC 3339 end of the pause
+    pub until: Option<String>,
+    // From the last answer with rate limit headers
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    // 429s since the app started
+    pub hits: u64,
+}
+
+fn header<'a>(headers: &'a HeaderMap, names: &[&str]) -> Option<&'a str> {
+    names.iter().find_map(|name| headers.get(*name)?.to_str().ok()).map(str::trim)
+}
+
+fn number(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    header(headers, names)?.parse().ok()
+}
+
+// Seconds to wait, or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = header(headers, &["retry-after"])?;
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => (DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc) - Utc::now()).to_std().ok(),
+    }
+}
+
+// Reset as seconds from now; X-RateLimit-Reset is often an epoch time instead
+fn reset_in(headers: &HeaderMap) -> Option<Duration> {
+    let reset = number(headers, &["ratelimit-reset", "x-ratelimit-reset"])?;
+    let now = Utc::now().timestamp() as u64;
+    Some(Duration::from_secs(if reset > now { reset - now } else { reset }))
+}
+
+fn pause(limits: &mut Limits, wait: Duration) -> DateTime<Utc> {
+    let until = Utc::now() + wait.clamp(PAUSE_MIN, PAUSE_MAX);
+    if limits.until.is_none_or(|current| current < until) {
+        limits.until = Some(until);
+    }
+    until
+}
+
+// Take note of a backend answer. Returns when to try again for a 429.
+pub fn observe(status: reqwest::StatusCode, headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    let mut limits = LIMITS.lock().unwrap();
+    if let Some(limit) = number(headers, &["ratelimit-limit", "x-ratelimit-limit"]) {
+        limits.limit = Some(limit);
+    }
+    let remaining = number(headers, &["ratelimit-remaining", "x-ratelimit-remaining"]);
+    if remaining.is_some() {
+        limits.remaining = remaining;
+    }
+
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        limits.hits += 1;
+        let until = pause(&mut limits, retry_after(headers).or_else(|| reset_in(headers)).unwrap_or(PAUSE_DEFAULT));
+        eprintln!("Rate limited by the backend, pausing requests until {}", until.to_rfc3339());
+        return Some(until);
+    }
+    // Used up: stop before the backend has to say so
+    if remaining == Some(0) {
+        if let Some(wait) = reset_in(headers) {
+            pause(&mut limits, wait);
+        }
+    }
+    None
+}
+
+pub fn error(until: DateTime<Utc>) -> ImalinkError {
+    ImalinkError::RateLimited { until: until.to_rfc3339() }
+}
+
+pub fn active() -> bool {
+    LIMITS.lock().unwrap().until.is_some_and(|until| until > Utc::now())
+}
+
+// Sleep through the current pause, if any
+pub async fn wait() {
+    loop {
+        let until = LIMITS.lock().unwrap().until;
+        match until.and_then(|u| (u - Utc::now()).to_std().ok()) {
+            Some(left) if !left.is_zero() => tokio::time::sleep(left).await,
+            _ => return,
+        }
+    }
+}
+
+pub fn status() -> RateLimitStatus {
+    let limits = LIMITS.lock().unwrap();
+    let until = limits.until.filter(|u| *u > Utc::now());
+    RateLimitStatus {
+        limited: until.is_some(),
+        until: until.map(|u| u.to_rfc3339()),
+        limit: limits.limit,
+        remaining: limits.remaining,
+        hits: limits.hits,
+    }
+}
+
+#[tauri::command]
+pub fn get_rate_limit_status() -> RateLimitStatus {
+    status()
+}
@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::hothash::{self, HothashIndex};
+use crate::pipeline::{self, CompanionGroup, ImportOptions, ImportSessions};
+use crate::session::Session;
+use crate::{guest, streaming};
+
+// ===== Interrupted Import Recovery =====
+//
+// Each session journals to <app data>/import_journal: <id>.json holds the
+// options (no auth token), <id>.done the master files finished. Journals of
+// sessions not running belong to interrupted imports: list_interrupted_imports
+// reports them, verify_interrupted_import checks what made it (journal,
+// history, backend) and resume_import imports the rest.
+
+const JOURNAL_DIR: &str = "import_journal";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct JournalHeader {
+    session_id: String,
+    started_at: String,
+    options: ImportOptions,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InterruptedImport {
+    pub session_id: String,
+    pub started_at: String,
+    pub source_dir: String,
+    // Files named in the options; None means the whole source_dir
+    pub file_count: Option<usize>,
+    pub done: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RecoveryReport {
+    pub session_id: String,
+    pub source_dir: String,
+    pub total: usize,
+    // Masters already imported, found in the journal, history or backend
+    pub completed: Vec<String>,
+    // Masters still to import
+    pub remaining: Vec<String>,
+}
+
+// Managed state
+pub struct ImportJournal {
+    dir: PathBuf,
+}
+
+impl ImportJournal {
+    pub fn new(app_data_dir: &Path) -> Self {
+        ImportJournal { dir: app_data_dir.join(JOURNAL_DIR) }
+    }
+
+    fn header_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", session_id))
+    }
+
+    fn done_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.done", session_id))
+    }
+
+    // Failures are logged, not returned: a missing journal only costs
+    // recovery, never the import itself
+    pub fn start(&self, session_id: &str, options: &ImportOptions) {
+        let header = JournalHeader {
+            session_id: session_id.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            options: ImportOptions { auth_token: String::new(), ..options.clone() },
+        };
+        let written = fs::create_dir_all(&self.dir)
+            .map_err(|e| ImalinkError::io(self.dir.display(), e))
+            .and_then(|_| Ok(serde_json::to_string_pretty(&header)?))
+            .and_then(|text| {
+                let path = self.header_path(session_id);
+                fs::write(&path, text).map_err(|e| ImalinkError::io(path.display(), e))
+            });
+        if let Err(e) = written {
+            eprintln!("Failed to write import journal for {}: {}", session_id, e);
+        }
+    }
+
+    pub fn mark_done(&self, session_id: &str, master_file: &str) {
+        let path = self.done_path(session_id);
+        let appended = fs::File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", master_file));
+        if let Err(e) = appended {
+            eprintln!("Failed to update import journal {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn finish(&self, session_id: &str) {
+        for path in [self.header_path(session_id), self.done_path(session_id)] {
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("Failed to remove import journal {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    fn header(&self, session_id: &str) -> Result<JournalHeader, ImalinkError> {
+        let path = self.header_path(session_id);
+        let text = fs::read_to_string(&path)
+            .map_err(|_| ImalinkError::invalid(format!("No interrupted import {}", session_id)))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    fn done(&self, session_id: &str) -> HashSet<String> {
+        fs::read_to_string(self.done_path(session_id))
+            .map(|text| text.lines().filter(|l| !l.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    fn session_ids(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else { return Vec::new() };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .collect()
+    }
+}
+
+// A journal as a whole, for queue snapshots (snapshot.rs)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalSnapshot {
+    pub session_id: String,
+    pub started_at: String,
+    pub options: ImportOptions,
+    pub done: Vec<String>,
+}
+
+impl ImportJournal {
+    pub fn snapshots(&self) -> Vec<JournalSnapshot> {
+        self.session_ids()
+            .into_iter()
+            .filter_map(|id| {
+                let header = self.header(&id).ok()?;
+                let mut done: Vec<String> = self.done(&id).into_iter().collect();
+                done.sort();
+                Some(JournalSnapshot {
+                    session_id: header.session_id,
+                    started_at: header.started_at,
+                    options: header.options,
+                    done,
+                })
+            })
+            .collect()
+    }
+
+    // Write a journal back from a snapshot (development only)
+    pub fn restore(&self, snapshot: &JournalSnapshot) -> Result<(), ImalinkError> {
+        let header = JournalHeader {
+            session_id: snapshot.session_id.clone(),
+            started_at: snapshot.started_at.clone(),
+            options: ImportOptions { auth_token: String::new(), ..snapshot.options.clone() },
+        };
+        fs::create_dir_all(&self.dir).map_err(|e| ImalinkError::io(self.dir.display(), e))?;
+        let path = self.header_path(&snapshot.session_id);
+        fs::write(&path, serde_json::to_string_pretty(&header)?).map_err(|e| ImalinkError::io(path.display(), e))?;
+        let path = self.done_path(&snapshot.session_id);
+        let done: String = snapshot.done.iter().map(|f| format!("{}\n", f)).collect();
+        fs::write(&path, done).map_err(|e| ImalinkError::io(path.display(), e))
+    }
+}
+
+pub fn journal(app: &tauri::AppHandle) -> tauri::State<'_, ImportJournal> {
+    app.state::<ImportJournal>()
+}
+
+fn groups_of(options: &ImportOptions) -> Result<Vec<CompanionGroup>, ImalinkError> {
+    let files = match &options.files {
+        Some(files) => files.clone(),
+        None => crate::collect_image_files(&PathBuf::from(&options.source_dir))?,
+    };
+    Ok(pipeline::group_companions(&files, &options.master_order))
+}
+
+// Whether the backend has the photo of `master_file`, by its hothash
+async fn on_backend(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    options: &ImportOptions,
+    auth_token: &str,
+    master_file: &str,
+) -> bool {
+    let file = master_file.to_string();
+    let Ok(Ok(content_hash)) = tauri::async_runtime::spawn_blocking(move || streaming::hash_file(&file)).await else {
+        return false;
+    };
+    let Some(hothash) = app
+        .state::<HothashIndex>()
+        .resolve(client, &options.core_api_url, &content_hash, master_file)
+        .await
+    else {
+        return false;
+    };
+    matches!(
+        hothash::find_backend_photo(client, &options.backend_url, auth_token, &hothash).await,
+        Ok(Some(_))
+    )
+}
+
+// The journal header, the groups still to import and the report
+async fn verify(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    auth_token: &str,
+) -> Result<(JournalHeader, Vec<CompanionGroup>, RecoveryReport), ImalinkError> {
+    let journal = journal(app);
+    let header = journal.header(session_id)?;
+    let done = journal.done(session_id);
+
+    let options = header.options.clone();
+    let groups = tauri::async_runtime::spawn_blocking(move || groups_of(&options))
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Scan failed: {}", e)))??;
+
+    let client = crate::http::client(app);
+    let history = app.state::<History>();
+    let mut report = RecoveryReport {
+        session_id: session_id.to_string(),
+        source_dir: header.options.source_dir.clone(),
+        total: groups.len(),
+        completed: Vec::new(),
+        remaining: Vec::new(),
+    };
+    let mut remaining = Vec::new();
+    for group in groups {
+        let master = group.master_file.clone();
+        let completed = done.contains(&master)
+            || history.find_by_file(&master)?.is_some()
+            || on_backend(app, &client, &header.options, auth_token, &master).await;
+        if completed {
+            report.completed.push(master);
+        } else {
+            report.remaining.push(master);
+            remaining.push(group);
+        }
+    }
+    Ok((header, remaining, report))
+}
+
+// Imports left unfinished by an earlier run of the app
+#[tauri::command]
+pub fn list_interrupted_imports(app: tauri::AppHandle) -> Vec<InterruptedImport> {
+    let journal = journal(&app);
+    let sessions = app.state::<ImportSessions>();
+    let mut interrupted: Vec<InterruptedImport> = journal
+        .session_ids()
+        .into_iter()
+        .filter(|id| sessions.status(id) != Some(pipeline::SessionStatus::Running))
+        .filter_map(|id| {
+            let header = journal.header(&id).ok()?;
+            Some(InterruptedImport {
+                done: journal.done(&id).len(),
+                session_id: header.session_id,
+                started_at: header.started_at,
+                source_dir: header.options.source_dir,
+                file_count: header.options.files.as_ref().map(|f| f.len()),
+            })
+        })
+        .collect();
+    interrupted.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    interrupted
+}
+
+// Check which photos of an interrupted import actually completed
+#[tauri::command]
+pub async fn verify_interrupted_import(
+    app: tauri::AppHandle,
+    session_id: String,
+    auth_token: Option<String>,
+) -> Result<RecoveryReport, ImalinkError> {
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    Ok(verify(&app, &session_id, &auth_token).await?.2)
+}
+
+// Import what an interrupted session didn't get to, with its options, as a
+// new session. Returns the new session id (None when nothing was left).
+#[tauri::command]
+pub async fn resume_import(
+    app: tauri::AppHandle,
+    session_id: String,
+    auth_token: Option<String>,
+) -> Result<Option<String>, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let (header, remaining, _) = verify(&app, &session_id, &auth_token).await?;
+    journal(&app).finish(&session_id);
+    if remaining.is_empty() {
+        return Ok(None);
+    }
+
+    let files = remaining.iter().flat_map(|g| g.all_files()).collect();
+    let options = ImportOptions {
+        files: Some(files),
+        auth_token,
+        ..header.options
+    };
+    pipeline::spawn_import(&app, options).map(Some)
+}
+
+#[tauri::command]
+pub fn discard_interrupted_import(app: tauri::AppHandle, session_id: String) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "discard imports")?;
+    let journal = journal(&app);
+    journal.header(&session_id)?;
+    journal.finish(&session_id);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::History;
+use crate::pipeline::{self, CompanionGroup, ImportOptions};
+
+// ===== Differential Re-import =====
+//
+// reimport_folder compares a folder with local history and imports only
+// groups that are new or changed (modified after import, so a new photo).
+// `flag_deletions` lists photos whose file is gone, deleting nothing;
+// `dry_run` returns the comparison without importing.
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ReimportPlan {
+    pub folder: String,
+    // Master files of the groups to import
+    pub new: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    // Imported from this folder, but no longer there (with flag_deletions)
+    pub deleted: Vec<String>,
+    // Import session for new and changed groups, unless dry run or nothing to do
+    pub session_id: Option<String>,
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Whether any file of the group was written after `imported_at`
+fn changed_since(group: &CompanionGroup, imported_at: Option<&str>) -> bool {
+    let Some(imported_at) = imported_at.and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()) else {
+        return false;
+    };
+    let imported_at: SystemTime = imported_at.into();
+    group.all_files().iter().filter_map(|f| modified(f)).any(|m| m > imported_at)
+}
+
+// The plan, and all files of the groups to import
+fn plan(
+    history: &History,
+    folder: &Path,
+    options: &ImportOptions,
+    flag_deletions: bool,
+) -> Result<(ReimportPlan, Vec<String>), ImalinkError> {
+    let files = crate::collect_image_files(folder)?;
+    let groups = pipeline::group_companions(&files, &options.master_order);
+
+    let mut plan = ReimportPlan { folder: folder.display().to_string(), ..Default::default() };
+    let mut to_import = Vec::new();
+    for group in groups {
+        let imported = match history.find_by_file(&group.master_file)? {
+            Some(hothash) => history.get(&hothash)?,
+            None => None,
+        };
+        match imported {
+            None => plan.new.push(group.master_file.clone()),
+            Some(photo) if changed_since(&group, photo.imported_at.as_deref()) => {
+                plan.changed.push(group.master_file.clone())
+            }
+            Some(_) => {
+                plan.unchanged += 1;
+                continue;
+            }
+        }
+        to_import.extend(group.all_files());
+    }
+
+    if flag_deletions {
+        let present: HashSet<&String> = files.iter().collect();
+        plan.deleted = history
+            .all_photos()?
+            .into_iter()
+            .filter_map(|photo| photo.file_path)
+            .filter(|path| Path::new(path).starts_with(folder) && !present.contains(path))
+            .collect();
+        plan.deleted.sort();
+    }
+    Ok((plan, to_import))
+}
+
+// Import the new and changed files of a folder imported before, with
+// `session_options` (its source_dir and files are replaced)
+#[tauri::command]
+pub async fn reimport_folder(
+    app: tauri::AppHandle,
+    path: String,
+    session_options: ImportOptions,
+    flag_deletions: Option<bool>,
+    dry_run: Option<bool>,
+) -> Result<ReimportPlan, ImalinkError> {
+    let folder = PathBuf::from(&path);
+    if !folder.is_dir() {
+        return Err(ImalinkError::NotADirectory { path });
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        guest::require_owner(&app, "import")?;
+    }
+
+    let scan_app = app.clone();
+    let scan_options = session_options.clone();
+    let (mut plan, files) = tauri::async_runtime::spawn_blocking(move || {
+        plan(&scan_app.state::<History>(), &folder, &scan_options, flag_deletions.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(format!("Folder comparison failed: {}", e)))??;
+
+    if !dry_run && !files.is_empty() {
+        let options = ImportOptions {
+            source_dir: path,
+            files: Some(files),
+            ..session_options
+        };
+        plan.session_id = Some(pipeline::spawn_import(&app, options)?);
+    }
+    Ok(plan)
+}
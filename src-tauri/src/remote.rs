@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+
+use crate::auth;
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::session::Session;
+use crate::staging::{self, StagedFile};
+use crate::{pipeline, presets, settings};
+
+// ===== Remote Sources (SFTP/FTP) =====
+//
+// Named servers to import from - e.g. an FTP drop box a drone uploads to -
+// configured in settings.json with their own credentials, the passwords in
+// the OS credential store (see auth.rs). Transfers go through the curl
+// command line tool, which must be on PATH (and built with SFTP support for
+// sftp sources). Credentials are handed to curl as a config file on stdin,
+// never as arguments, so they don't show up in process listings.
+//
+// scan_remote_source lists the image files under the source's path
+// (recursively); import_from_remote downloads a selection to a staging
+// folder and imports it through the normal pipeline with a preset.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteProtocol {
+    Sftp,
+    Ftp,
+    // FTP with explicit TLS
+    Ftps,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteSource {
+    pub name: String,
+    pub protocol: RemoteProtocol,
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    // Directory on the server to scan
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    // Only passed to save_remote_source; kept in the credential store (see
+    // auth.rs), never in settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    // Whether the credential store has a password for this source
+    #[serde(default)]
+    pub password_stored: bool,
+    // SFTP private key file; preferred over a password
+    #[serde(default)]
+    pub private_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteFile {
+    // Path on the server
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteImport {
+    // None when nothing could be downloaded
+    pub session_id: Option<String>,
+    pub downloads: BatchResult<StagedFile>,
+}
+
+// Entry of a directory listing
+struct ListEntry {
+    name: String,
+    size: u64,
+    is_dir: bool,
+}
+
+impl RemoteSource {
+    fn url(&self, path: &str) -> Result<reqwest::Url, ImalinkError> {
+        let scheme = match self.protocol {
+            RemoteProtocol::Sftp => "sftp",
+            RemoteProtocol::Ftp | RemoteProtocol::Ftps => "ftp",
+        };
+        let mut url = reqwest::Url::parse(&format!("{}://{}", scheme, self.host))
+            .map_err(|e| ImalinkError::invalid(format!("Invalid host {}: {}", self.host, e)))?;
+        url.set_port(self.port)
+            .map_err(|_| ImalinkError::invalid(format!("Invalid port for {}", self.host)))?;
+        url.set_path(path);
+        Ok(url)
+    }
+
+    // curl config with the credentials, fed through stdin
+    fn curl_config(&self) -> String {
+        let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut config = String::new();
+        if let Some(username) = &self.username {
+            let password = self.password.as_deref().unwrap_or_default();
+            config.push_str(&format!("user = {}\n", quote(&format!("{}:{}", username, password))));
+        }
+        if let Some(key) = self.private_key.as_deref().filter(|_| self.protocol == RemoteProtocol::Sftp) {
+            config.push_str(&format!("key = {}\n", quote(key)));
+        }
+        if self.protocol == RemoteProtocol::Ftps {
+            config.push_str("ssl-reqd\n");
+        }
+        config
+    }
+}
+
+// Run curl with the source's credentials and return its stdout
+async fn run_curl(source: &RemoteSource, args: &[&str]) -> Result<String, ImalinkError> {
+    let mut child = tokio::process::Command::new("curl")
+        .args(["--config", "-", "--silent", "--show-error", "--fail"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImalinkError::invalid("curl not found - install curl to use remote sources"),
+            _ => ImalinkError::io("curl", e),
+        })?;
+
+    let config = source.curl_config();
+    let mut stdin = child.stdin.take().ok_or_else(|| ImalinkError::internal("curl has no stdin"))?;
+    stdin.write_all(config.as_bytes()).await.map_err(|e| ImalinkError::io("curl", e))?;
+    drop(stdin);
+
+    let output = child.wait_with_output().await.map_err(|e| ImalinkError::io("curl", e))?;
+    if !output.status.success() {
+        return Err(ImalinkError::network(&source.host, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// `ls -l` style line, as both FTP servers and curl's SFTP listing produce:
+// drwxr-xr-x 2 user group 4096 Jan 01 12:00 name with spaces
+fn parse_list_line(line: &str) -> Option<ListEntry> {
+    let mut rest = line.trim_end();
+    let mut fields = Vec::new();
+    for _ in 0..8 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    let name = rest.trim_start();
+    if name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+    Some(ListEntry {
+        name: name.to_string(),
+        size: fields[4].parse().ok()?,
+        is_dir: fields[0].starts_with('d'),
+    })
+}
+
+fn is_image(name: &str) -> bool {
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    pipeline::master_priority(&ext) < 99
+}
+
+async fn list_recursive(source: &RemoteSource, root: &str) -> Result<Vec<RemoteFile>, ImalinkError> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.trim_end_matches('/').to_string()];
+    while let Some(dir) = pending.pop() {
+        // A trailing slash makes curl list the directory instead of fetching it
+        let url = source.url(&format!("{}/", dir))?;
+        let listing = run_curl(source, &[url.as_str()]).await?;
+        for entry in listing.lines().filter_map(parse_list_line) {
+            let path = format!("{}/{}", dir, entry.name);
+            if entry.is_dir {
+                pending.push(path);
+            } else if is_image(&entry.name) {
+                files.push(RemoteFile { path, name: entry.name, size: entry.size });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+// Move passwords in settings to the credential store - ones passed to
+// save_remote_source, and plain text written by older versions. Returns
+// whether any were moved.
+fn secure_passwords(sources: &mut [RemoteSource]) -> Result<bool, ImalinkError> {
+    let mut moved = false;
+    for source in sources.iter_mut() {
+        if let Some(password) = source.password.take() {
+            auth::store_remote_password(&source.name, &password)?;
+            source.password_stored = true;
+            moved = true;
+        }
+    }
+    Ok(moved)
+}
+
+fn load_sources(app: &tauri::AppHandle) -> Result<Vec<RemoteSource>, ImalinkError> {
+    let mut settings = settings::load(app);
+    if secure_passwords(&mut settings.remote_sources)? {
+        settings::save(app, &settings)?;
+    }
+    Ok(settings.remote_sources)
+}
+
+// The source with its password, ready for curl
+fn find_source(app: &tauri::AppHandle, name: &str) -> Result<RemoteSource, ImalinkError> {
+    let mut source = load_sources(app)?
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown remote source: {}", name)))?;
+    if source.password_stored {
+        source.password = auth::remote_password(&source.name)?;
+    }
+    Ok(source)
+}
+
+// Folder name for the source under staging/remote
+fn staging_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_remote_sources(app: tauri::AppHandle) -> Result<Vec<RemoteSource>, ImalinkError> {
+    load_sources(&app)
+}
+
+// Add a source, or replace the one with the same name. A password of None
+// keeps the stored one, an empty one removes it.
+#[tauri::command]
+pub fn save_remote_source(app: tauri::AppHandle, mut source: RemoteSource) -> Result<Vec<RemoteSource>, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    if source.name.trim().is_empty() || source.host.trim().is_empty() {
+        return Err(ImalinkError::invalid("Remote source needs a name and a host"));
+    }
+    let mut settings = settings::load(&app);
+    let existing = settings.remote_sources.iter().position(|s| s.name == source.name);
+    match source.password.as_deref() {
+        Some("") => {
+            source.password = None;
+            source.password_stored = false;
+            auth::forget_remote_password(&source.name);
+        }
+        Some(_) => {}
+        None => source.password_stored = existing.is_some_and(|i| settings.remote_sources[i].password_stored),
+    }
+    match existing {
+        Some(i) => settings.remote_sources[i] = source,
+        None => settings.remote_sources.push(source),
+    }
+    secure_passwords(&mut settings.remote_sources)?;
+    settings::save(&app, &settings)?;
+    Ok(settings.remote_sources)
+}
+
+#[tauri::command]
+pub fn delete_remote_source(app: tauri::AppHandle, name: String) -> Result<Vec<RemoteSource>, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    let mut settings = settings::load(&app);
+    settings.remote_sources.retain(|s| s.name != name);
+    auth::forget_remote_password(&name);
+    settings::save(&app, &settings)?;
+    Ok(settings.remote_sources)
+}
+
+// Image files under the source's path
+#[tauri::command]
+pub async fn scan_remote_source(app: tauri::AppHandle, name: String) -> Result<Vec<RemoteFile>, ImalinkError> {
+    let source = find_source(&app, &name)?;
+    list_recursive(&source, &source.path).await
+}
+
+async fn download(source: &RemoteSource, remote: &str, workspace: &Path) -> Result<StagedFile, ImalinkError> {
+    let root = source.path.trim_end_matches('/');
+    let relative = remote.strip_prefix(root).unwrap_or(remote).trim_start_matches('/');
+    if relative.is_empty() || relative.split('/').any(|part| part == "..") {
+        return Err(ImalinkError::invalid(format!("Not a file under {}: {}", source.path, remote)));
+    }
+    // Keep the folder structure so same-named files in different folders don't collide
+    let local = workspace.join(relative);
+    let local_str = local.to_string_lossy().to_string();
+    let url = source.url(remote)?;
+    run_curl(source, &["--create-dirs", "--output", &local_str, url.as_str()]).await?;
+
+    let bytes = std::fs::metadata(&local).map_err(|e| ImalinkError::io(local.display(), e))?.len();
+    Ok(StagedFile {
+        source: remote.to_string(),
+        path: local_str,
+        bytes,
+    })
+}
+
+// Download `files` (all image files under the source's path when absent)
+// and import them with a preset
+#[tauri::command]
+pub async fn import_from_remote(
+    app: tauri::AppHandle,
+    name: String,
+    files: Option<Vec<String>>,
+    preset: String,
+    auth_token: Option<String>,
+) -> Result<RemoteImport, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let source = find_source(&app, &name)?;
+    let preset = presets::find(&app, &preset)?;
+    let files = match files {
+        Some(files) => files,
+        None => list_recursive(&source, &source.path).await?.into_iter().map(|f| f.path).collect(),
+    };
+
+    let batch = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let workspace = staging::workspace(&app, &format!("remote/{}", staging_name(&source.name)), &batch)?;
+    let mut downloads = BatchResult::new();
+    for remote in &files {
+        downloads.record(remote.clone(), download(&source, remote, &workspace).await);
+    }
+    if downloads.succeeded.is_empty() {
+        let _ = std::fs::remove_dir_all(&workspace);
+        return Ok(RemoteImport { session_id: None, downloads });
+    }
+
+    let staged: Vec<String> = downloads.succeeded.iter().map(|f| f.path.clone()).collect();
+    let options = preset.import_options(&workspace.to_string_lossy(), Some(staged), &auth_token);
+    let session_id = pipeline::spawn_import(&app, options)?;
+    Ok(RemoteImport {
+        session_id: Some(session_id),
+        downloads,
+    })
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
+
+use crate::error::ImalinkError;
+
+// ===== Remote Processing =====
+//
+// Studios with a dedicated server can run imalink-core there instead of
+// next to each desktop. With `remote_core` set in settings.json:
+//
+// - every request to core (/v1/process, /v1/hothash, /health) goes to
+//   `url` instead of the core_api_url the frontend passes, with `api_token`
+//   as a bearer token; the URL must be https unless it is on this machine
+// - the import pipeline runs `process_workers` processing workers instead
+//   of ImportOptions::workers.process, since a server takes many more files
+//   at once than the local sidecar
+// - the imalink-core sidecar is not spawned when the app starts
+//
+// Switching modes in settings applies to requests right away; the sidecar
+// follows on the next start.
+//
+// The local sidecar listens on `core_port` (settings) when set, otherwise on
+// DEFAULT_SIDECAR_PORT if that is free and on any free port if not - other
+// services on some machines hold 8765. The port is picked once per app run
+// and handed to core as `--port` and IMALINK_CORE_PORT. get_core_url tells
+// the frontend where core is; a core_api_url that is empty or still points at
+// localhost:8765 is taken to mean the sidecar, wherever it ended up.
+
+static REMOTE: Mutex<Option<RemoteCore>> = Mutex::new(None);
+
+const DEFAULT_PROCESS_WORKERS: usize = 16;
+
+pub const DEFAULT_SIDECAR_PORT: u16 = 8765;
+
+// 0 until the sidecar has been started
+static SIDECAR_PORT: AtomicU16 = AtomicU16::new(0);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteCore {
+    pub url: String,
+    #[serde(default)]
+    pub api_token: Option<String>,
+    #[serde(default = "default_process_workers")]
+    pub process_workers: usize,
+}
+
+fn default_process_workers() -> usize {
+    DEFAULT_PROCESS_WORKERS
+}
+
+// Refuse plain http to anything but a loopback address
+pub fn validate(remote: &RemoteCore) -> Result<(), ImalinkError> {
+    let url = reqwest::Url::parse(&remote.url)
+        .map_err(|e| ImalinkError::invalid(format!("Remote core URL {}: {}", remote.url, e)))?;
+    let local = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if url.scheme() != "https" && !local {
+        return Err(ImalinkError::invalid(format!("Remote core URL must use https: {}", remote.url)));
+    }
+    Ok(())
+}
+
+pub fn configure(remote: Option<RemoteCore>) {
+    *REMOTE.lock().unwrap() = remote;
+}
+
+pub fn is_remote() -> bool {
+    REMOTE.lock().unwrap().is_some()
+}
+
+fn port_free(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
+
+// Port for the sidecar: the one already picked during this run, `configured`,
+// or the default when free and whatever the OS hands out otherwise
+pub fn sidecar_port(configured: Option<u16>) -> u16 {
+    let picked = SIDECAR_PORT.load(Ordering::SeqCst);
+    if picked != 0 {
+        return picked;
+    }
+    let port = match configured {
+        Some(port) => port,
+        None if port_free(DEFAULT_SIDECAR_PORT) => DEFAULT_SIDECAR_PORT,
+        None => TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .unwrap_or(DEFAULT_SIDECAR_PORT),
+    };
+    SIDECAR_PORT.store(port, Ordering::SeqCst);
+    port
+}
+
+fn sidecar_url() -> String {
+    let port = match SIDECAR_PORT.load(Ordering::SeqCst) {
+        0 => DEFAULT_SIDECAR_PORT,
+        port => port,
+    };
+    format!("http://localhost:{}", port)
+}
+
+// `core_api_url`, or the sidecar's URL when it means the sidecar
+fn local_url(core_api_url: &str) -> String {
+    let core_api_url = core_api_url.trim_end_matches('/');
+    let default = reqwest::Url::parse(core_api_url).is_ok_and(|url| {
+        matches!(url.host_str(), Some("localhost" | "127.0.0.1")) && url.port() == Some(DEFAULT_SIDECAR_PORT)
+    });
+    if core_api_url.is_empty() || default {
+        sidecar_url()
+    } else {
+        core_api_url.to_string()
+    }
+}
+
+// Base URL of core: the remote one when configured, otherwise `core_api_url`
+pub fn url(core_api_url: &str) -> String {
+    match &*REMOTE.lock().unwrap() {
+        Some(remote) => remote.url.trim_end_matches('/').to_string(),
+        None => local_url(core_api_url),
+    }
+}
+
+// Where core is, for the frontend to stop assuming localhost:8765
+#[tauri::command]
+pub fn get_core_url() -> String {
+    url("")
+}
+
+// Request to `path` on core, authenticated when remote
+pub fn request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    core_api_url: &str,
+    path: &str,
+) -> reqwest::RequestBuilder {
+    let remote = REMOTE.lock().unwrap().clone();
+    match remote {
+        Some(remote) => {
+            let request = client.request(method, format!("{}{}", remote.url.trim_end_matches('/'), path));
+            match &remote.api_token {
+                Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+                None => request,
+            }
+        }
+        None => client.request(method, format!("{}{}", local_url(core_api_url), path)),
+    }
+}
+
+// Processing workers for an import that asked for `local`
+pub fn process_workers(local: usize) -> usize {
+    match &*REMOTE.lock().unwrap() {
+        Some(remote) => remote.process_workers.max(1),
+        None => local,
+    }
+}
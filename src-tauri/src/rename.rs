@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::ImalinkError;
+use crate::PhotoCreateSchema;
+
+// ===== Archive Renaming =====
+//
+// Optional step in copy mode: originals are archived as
+// `YYYYMMDD_HHMMSS_camera_seq.ext` instead of their card names, so
+// IMG_0001.JPG from two cards don't collide. The timestamp is the capture
+// time (file modification time when the photo has none), and companions get
+// the master's new name with their own extension so pairs stay together.
+// The original filename is kept in `local_storage_info.original_filename`.
+//
+// `seq` starts at 001 and is bumped while any file of the group would
+// collide with an existing file or one claimed by another worker. An
+// existing master with identical content means the photo is already
+// archived and is reported as DestinationExists like in plain copy mode.
+//
+// Plain copy mode reserves its destinations in the same Claims, so two
+// same-named files from different folders in a flat archive fail the second
+// one instead of overwriting the first.
+
+const MAX_SEQ: u32 = 9999;
+
+fn camera_slug(schema: &PhotoCreateSchema) -> String {
+    let camera = crate::pipeline::camera_of(&schema.exif_dict).unwrap_or_default();
+    let mut slug = String::new();
+    for c in camera.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() { "unknown".to_string() } else { slug }
+}
+
+// YYYYMMDD_HHMMSS from the first 14 digits of taken_at (ISO or EXIF format)
+fn timestamp(schema: &PhotoCreateSchema, master_file: &str) -> String {
+    let digits: String = schema
+        .taken_at
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .take(14)
+        .collect();
+    if digits.len() == 14 {
+        return format!("{}_{}", &digits[..8], &digits[8..]);
+    }
+
+    let modified = std::fs::metadata(master_file)
+        .and_then(|m| m.modified())
+        .map(chrono::DateTime::<chrono::Local>::from)
+        .unwrap_or_else(|_| chrono::Local::now());
+    modified.format("%Y%m%d_%H%M%S").to_string()
+}
+
+// Canonical name without sequence number and extension
+pub fn canonical_stem(schema: &PhotoCreateSchema, master_file: &str) -> String {
+    format!("{}_{}", timestamp(schema, master_file), camera_slug(schema))
+}
+
+fn renamed(dest: &Path, source: &str, stem: &str, seq: u32) -> PathBuf {
+    let ext = Path::new(source)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    dest.with_file_name(format!("{}_{:03}.{}", stem, seq, ext))
+}
+
+// Destinations handed out during one import session
+#[derive(Default)]
+pub struct Claims(Mutex<HashSet<PathBuf>>);
+
+impl Claims {
+    // Rename planned destinations (source → dest) to the first free sequence number
+    pub fn claim(
+        &self,
+        planned: HashMap<String, PathBuf>,
+        stem: &str,
+        master_file: &str,
+        content_hash: Option<&str>,
+    ) -> Result<HashMap<String, PathBuf>, ImalinkError> {
+        let mut claimed = self.0.lock().map_err(|e| ImalinkError::internal(e.to_string()))?;
+
+        for seq in 1..=MAX_SEQ {
+            let candidates: HashMap<String, PathBuf> = planned
+                .iter()
+                .map(|(source, dest)| (source.clone(), renamed(dest, source, stem, seq)))
+                .collect();
+            if candidates.values().any(|p| claimed.contains(p)) {
+                continue;
+            }
+            if let Some(existing) = candidates.get(master_file).filter(|p| p.exists()) {
+                let same = content_hash.is_some_and(|hash| {
+                    crate::streaming::hash_file(&existing.to_string_lossy()).is_ok_and(|h| h == hash)
+                });
+                if same {
+                    return Err(ImalinkError::DestinationExists { path: existing.display().to_string() });
+                }
+                continue;
+            }
+            if candidates.values().any(|p| p.exists()) {
+                continue;
+            }
+
+            claimed.extend(candidates.values().cloned());
+            return Ok(candidates);
+        }
+        Err(ImalinkError::invalid(format!("No free archive name for {}_NNN", stem)))
+    }
+
+    // Reserve planned destinations as they are, without renaming: one already
+    // handed out this session or already on disk is DestinationExists
+    pub fn reserve(&self, planned: &HashMap<String, PathBuf>) -> Result<(), ImalinkError> {
+        let mut claimed = self.0.lock().map_err(|e| ImalinkError::internal(e.to_string()))?;
+        if let Some(taken) = planned.values().find(|p| claimed.contains(*p) || p.exists()) {
+            return Err(ImalinkError::DestinationExists { path: taken.display().to_string() });
+        }
+        claimed.extend(planned.values().cloned());
+        Ok(())
+    }
+}
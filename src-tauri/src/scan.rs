@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::error::ImalinkError;
+use crate::pipeline;
+
+// ===== Directory Scan =====
+//
+// Walks run on a blocking thread and report `scan-progress` at most every
+// PROGRESS_INTERVAL. scan_directory_paged sends the paths in `scan-page`
+// events of `page_size` files (sorted within a page) and returns the totals.
+// Events carry the scanned directory, so concurrent scans can be told apart.
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanProgress {
+    pub dir_path: String,
+    pub files_found: usize,
+    pub current_dir: String,
+    pub done: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanPage {
+    pub dir_path: String,
+    // 0-based
+    pub page: usize,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanSummary {
+    pub dir_path: String,
+    pub files: usize,
+    pub pages: usize,
+}
+
+fn check_dir(dir_path: &str) -> Result<PathBuf, ImalinkError> {
+    let path = PathBuf::from(dir_path);
+    if !path.exists() {
+        return Err(ImalinkError::FileNotFound { path: dir_path.to_string() });
+    }
+    if !path.is_dir() {
+        return Err(ImalinkError::NotADirectory { path: dir_path.to_string() });
+    }
+    Ok(path)
+}
+
+// Walk `dir_path` on a blocking thread, reporting progress. With a page
+// size, full pages are emitted and taken out of the list as they fill up.
+async fn walk(app: tauri::AppHandle, dir_path: String, page_size: Option<usize>) -> Result<(Vec<String>, ScanSummary), ImalinkError> {
+    let path = check_dir(&dir_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut last_report = Instant::now();
+        let (mut emitted, mut pages) = (0, 0);
+        let progress = |files_found: usize, current_dir: &Path, done: bool| ScanProgress {
+            dir_path: dir_path.clone(),
+            files_found,
+            current_dir: current_dir.display().to_string(),
+            done,
+        };
+        let emit_page = |mut files: Vec<String>, pages: &mut usize| {
+            files.sort();
+            let _ = app.emit("scan-page", ScanPage { dir_path: dir_path.clone(), page: *pages, files });
+            *pages += 1;
+        };
+
+        let mut files = crate::walk_image_files(&path, &mut |dir, files| {
+            if let Some(size) = page_size.filter(|s| *s > 0) {
+                while files.len() >= size {
+                    let rest = files.split_off(size);
+                    emitted += size;
+                    emit_page(std::mem::replace(files, rest), &mut pages);
+                }
+            }
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = Instant::now();
+                let _ = app.emit("scan-progress", progress(emitted + files.len(), dir, false));
+            }
+        })?;
+
+        let found = emitted + files.len();
+        if page_size.is_some_and(|s| s > 0) && !files.is_empty() {
+            emit_page(std::mem::take(&mut files), &mut pages);
+        }
+        let _ = app.emit("scan-progress", progress(found, &path, true));
+        Ok((files, ScanSummary { dir_path: dir_path.clone(), files: found, pages }))
+    })
+    .await
+    .map_err(|e| ImalinkError::internal(e.to_string()))?
+}
+
+// Every supported image file under `dir_path`, sorted
+#[tauri::command]
+pub async fn scan_directory(app: tauri::AppHandle, dir_path: String) -> Result<Vec<String>, ImalinkError> {
+    let (mut files, _) = walk(app, dir_path, None).await?;
+    files.sort();
+    Ok(files)
+}
+
+// scan_directory for trees too large to return in one go: the paths arrive
+// as `scan-page` events
+#[tauri::command]
+pub async fn scan_directory_paged(
+    app: tauri::AppHandle,
+    dir_path: String,
+    page_size: usize,
+) -> Result<ScanSummary, ImalinkError> {
+    if page_size == 0 {
+        return Err(ImalinkError::invalid("Page size must be at least 1"));
+    }
+    let (_, summary) = walk(app, dir_path, Some(page_size)).await?;
+    Ok(summary)
+}
+
+// scan_directory, with RAW+JPEG (and other same-name) files grouped into one
+// photo each and a master picked by `master_order`
+#[tauri::command]
+pub async fn scan_directory_grouped(
+    app: tauri::AppHandle,
+    dir_path: String,
+    master_order: Option<pipeline::MasterOrder>,
+) -> Result<Vec<pipeline::CompanionGroup>, ImalinkError> {
+    let files = scan_directory(app, dir_path).await?;
+    Ok(pipeline::group_companions(&files, &master_order.unwrap_or_default()))
+}
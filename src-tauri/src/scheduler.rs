@@ -0,0 +1,405 @@
+use chrono::{Datelike, Local, Timelike};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::pipeline::{self, ImportSessions, SessionStatus};
+use crate::session::Session;
+use crate::{guest, presets, settings};
+
+// ===== Scheduled Imports =====
+//
+// Rules like "import \\NAS\incoming every night at 02:00 with preset X" are
+// stored in settings.json with a cron expression (minute hour day-of-month
+// month day-of-week, local time; `*`, lists, ranges and `*/n` steps, plus
+// @hourly/@daily/@weekly/@monthly). A background loop checks the rules twice
+// a minute and starts an import through the normal pipeline when one is due.
+//
+// If the previous run of a rule is still going when it fires again, its
+// overlap policy decides: skip this run, queue one run for when the current
+// one finishes, or run in parallel. Every trigger is logged to the
+// `schedule_runs` table in the history database.
+//
+// Runs authenticate with the token the frontend hands over after login
+// (set_scheduler_token), or else that of the login session; while logged
+// out, due runs are logged as failed.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    #[default]
+    Skip,
+    Queue,
+    Allow,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub source_dir: String,
+    pub preset: String,
+    pub cron: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScheduleRun {
+    pub id: i64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub triggered_at: String,
+    pub status: String,  // started | completed | skipped | queued | failed
+    pub session_id: Option<String>,
+    pub detail: Option<String>,
+}
+
+// ===== Cron Expressions =====
+
+// One cron field as a bitmask of allowed values
+#[derive(Debug, Clone, Copy)]
+struct CronField {
+    bits: u64,
+    any: bool,
+}
+
+impl CronField {
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self, ImalinkError> {
+        let invalid = || ImalinkError::invalid(format!("Invalid cron field '{}' (expected {}-{})", field, min, max));
+        let number = |s: &str| s.parse::<u32>().ok().filter(|n| (min..=max).contains(n)).ok_or_else(invalid);
+
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(invalid)?),
+                None => (part, 1),
+            };
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                (number(a)?, number(b)?)
+            } else {
+                let n = number(range)?;
+                // "5/15" means from 5 to the end in steps of 15
+                (n, if step > 1 { max } else { n })
+            };
+            if start > end {
+                return Err(invalid());
+            }
+            for n in (start..=end).step_by(step as usize) {
+                bits |= 1 << n;
+            }
+        }
+        Ok(CronField { bits, any: field == "*" })
+    }
+
+    fn contains(&self, n: u32) -> bool {
+        self.bits & (1 << n) != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CronSpec {
+    minutes: CronField,
+    hours: CronField,
+    days: CronField,
+    months: CronField,
+    weekdays: CronField,
+}
+
+impl CronSpec {
+    pub fn parse(expr: &str) -> Result<Self, ImalinkError> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(ImalinkError::invalid(format!(
+                "Cron expression needs 5 fields (minute hour day month weekday): {}",
+                expr
+            )));
+        };
+
+        let mut weekdays = CronField::parse(weekdays, 0, 7)?;
+        // 7 is Sunday too
+        if weekdays.contains(7) {
+            weekdays.bits |= 1;
+        }
+        Ok(CronSpec {
+            minutes: CronField::parse(minutes, 0, 59)?,
+            hours: CronField::parse(hours, 0, 23)?,
+            days: CronField::parse(days, 1, 31)?,
+            months: CronField::parse(months, 1, 12)?,
+            weekdays,
+        })
+    }
+
+    pub fn matches<T: Datelike + Timelike>(&self, t: &T) -> bool {
+        let day_match = self.days.contains(t.day());
+        let weekday_match = self.weekdays.contains(t.weekday().num_days_from_sunday());
+        // Like cron: when both day fields are restricted, either may match
+        let day_ok = match (self.days.any, self.weekdays.any) {
+            (false, false) => day_match || weekday_match,
+            _ => day_match && weekday_match,
+        };
+        self.minutes.contains(t.minute()) && self.hours.contains(t.hour()) && self.months.contains(t.month()) && day_ok
+    }
+}
+
+// ===== Scheduler =====
+
+#[derive(Default)]
+struct RuleState {
+    // Minute the rule last fired, so it fires once per matching minute
+    last_fired: Option<String>,
+    session_id: Option<String>,
+    run_id: Option<i64>,
+    queued: bool,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    auth_token: Mutex<Option<String>>,
+    rules: Mutex<HashMap<String, RuleState>>,
+}
+
+// Run state of one rule, for queue snapshots (snapshot.rs)
+#[derive(Debug, Serialize, Clone)]
+pub struct RuleRunState {
+    pub rule_id: String,
+    pub last_fired: Option<String>,
+    pub session_id: Option<String>,
+    pub queued: bool,
+}
+
+impl Scheduler {
+    // Login token handed over by the frontend, also used by the upload queue
+    pub fn auth_token(&self) -> Option<String> {
+        self.auth_token.lock().ok().and_then(|t| t.clone())
+    }
+
+    pub fn run_states(&self) -> Vec<RuleRunState> {
+        let Ok(states) = self.rules.lock() else { return Vec::new() };
+        states
+            .iter()
+            .map(|(rule_id, state)| RuleRunState {
+                rule_id: rule_id.clone(),
+                last_fired: state.last_fired.clone(),
+                session_id: state.session_id.clone(),
+                queued: state.queued,
+            })
+            .collect()
+    }
+}
+
+fn log_run(
+    app: &tauri::AppHandle,
+    rule: &ScheduleRule,
+    status: &str,
+    session_id: Option<&str>,
+    detail: Option<&str>,
+) -> Option<i64> {
+    let logged = app.state::<History>().with(|conn| {
+        conn.execute(
+            "INSERT INTO schedule_runs (rule_id, rule_name, triggered_at, status, session_id, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![rule.id, rule.name, Local::now().to_rfc3339(), status, session_id, detail],
+        )?;
+        Ok(conn.last_insert_rowid())
+    });
+    match logged {
+        Ok(id) => Some(id),
+        Err(e) => {
+            eprintln!("Failed to log scheduled run of {}: {}", rule.name, e);
+            None
+        }
+    }
+}
+
+fn mark_completed(app: &tauri::AppHandle, run_id: i64) {
+    let updated = app.state::<History>().with(|conn| {
+        conn.execute("UPDATE schedule_runs SET status = 'completed' WHERE id = ?1", [run_id])
+            .map(|_| ())
+    });
+    if let Err(e) = updated {
+        eprintln!("Failed to update scheduled run {}: {}", run_id, e);
+    }
+}
+
+// Start an import for the rule and remember its session in `state`
+fn launch(app: &tauri::AppHandle, rule: &ScheduleRule, state: &mut RuleState) {
+    let token = app.state::<Scheduler>().auth_token().or_else(|| app.state::<Session>().auth_token());
+    let started = token
+        .ok_or_else(|| ImalinkError::Unauthorized { detail: "No login for scheduled imports".to_string() })
+        .and_then(|token| {
+            let preset = presets::find(app, &rule.preset)?;
+            pipeline::spawn_import(app, preset.import_options(&rule.source_dir, None, &token))
+        });
+
+    match started {
+        Ok(session_id) => {
+            println!("Scheduled import '{}' started: {}", rule.name, session_id);
+            state.run_id = log_run(app, rule, "started", Some(&session_id), None);
+            state.session_id = Some(session_id);
+        }
+        Err(e) => {
+            eprintln!("Scheduled import '{}' failed to start: {}", rule.name, e);
+            log_run(app, rule, "failed", None, Some(&e.message()));
+        }
+    }
+}
+
+fn check_rules(app: &tauri::AppHandle) {
+    let now = Local::now();
+    let minute = now.format("%Y-%m-%d %H:%M").to_string();
+    let rules = settings::load(app).schedules;
+    let scheduler = app.state::<Scheduler>();
+    let sessions = app.state::<ImportSessions>();
+    let Ok(mut states) = scheduler.rules.lock() else { return };
+
+    for rule in rules.iter().filter(|r| r.enabled) {
+        let Ok(cron) = CronSpec::parse(&rule.cron) else { continue };
+        let state = states.entry(rule.id.clone()).or_default();
+
+        let running = state
+            .session_id
+            .as_deref()
+            .is_some_and(|id| sessions.status(id) == Some(SessionStatus::Running));
+        if !running {
+            if let Some(run_id) = state.run_id.take() {
+                mark_completed(app, run_id);
+            }
+            state.session_id = None;
+            if state.queued {
+                state.queued = false;
+                launch(app, rule, state);
+                continue;
+            }
+        }
+
+        if !cron.matches(&now) || state.last_fired.as_deref() == Some(minute.as_str()) {
+            continue;
+        }
+        state.last_fired = Some(minute.clone());
+
+        if !running {
+            launch(app, rule, state);
+            continue;
+        }
+        match rule.overlap {
+            OverlapPolicy::Skip => {
+                log_run(app, rule, "skipped", None, Some("Previous run still in progress"));
+            }
+            OverlapPolicy::Queue => {
+                state.queued = true;
+                log_run(app, rule, "queued", None, Some("Waiting for previous run to finish"));
+            }
+            OverlapPolicy::Allow => launch(app, rule, state),
+        }
+    }
+}
+
+// Start the background loop that fires due rules
+pub fn start(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            tick.tick().await;
+            check_rules(&app);
+        }
+    });
+}
+
+// ===== Commands =====
+
+// Token used by scheduled runs; set after login, cleared (None) on logout
+#[tauri::command]
+pub fn set_scheduler_token(
+    app: tauri::AppHandle,
+    scheduler: tauri::State<'_, Scheduler>,
+    auth_token: Option<String>,
+) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "change scheduled imports")?;
+    if let Ok(mut token) = scheduler.auth_token.lock() {
+        *token = auth_token;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_schedules(app: tauri::AppHandle) -> Vec<ScheduleRule> {
+    settings::load(&app).schedules
+}
+
+// Create (empty id) or update a rule
+#[tauri::command]
+pub fn save_schedule(app: tauri::AppHandle, mut rule: ScheduleRule) -> Result<ScheduleRule, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    CronSpec::parse(&rule.cron)?;
+    presets::find(&app, &rule.preset)?;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let mut settings = settings::load(&app);
+    match settings.schedules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => settings.schedules.push(rule.clone()),
+    }
+    settings::save(&app, &settings)?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn delete_schedule(app: tauri::AppHandle, rule_id: String) -> Result<(), ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    let mut settings = settings::load(&app);
+    settings.schedules.retain(|r| r.id != rule_id);
+    settings::save(&app, &settings)
+}
+
+// Execution history, newest first
+#[tauri::command]
+pub fn get_schedule_runs(
+    history: tauri::State<'_, History>,
+    rule_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ScheduleRun>, ImalinkError> {
+    history.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, rule_id, rule_name, triggered_at, status, session_id, detail
+             FROM schedule_runs
+             WHERE ?1 IS NULL OR rule_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![rule_id, limit.unwrap_or(100)], |row| {
+            Ok(ScheduleRun {
+                id: row.get(0)?,
+                rule_id: row.get(1)?,
+                rule_name: row.get(2)?,
+                triggered_at: row.get(3)?,
+                status: row.get(4)?,
+                session_id: row.get(5)?,
+                detail: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+use crate::PhotoCreateSchema;
+
+// ===== Processed Schema Cache =====
+//
+// PhotoCreateSchemas returned by imalink-core (previews included) are cached
+// on disk keyed by the BLAKE3 content hash of the master file, so a retry
+// after a failed upload or a re-import skips the expensive core step. Each
+// entry is one JSON file; the file mtime is bumped on every hit and the
+// oldest entries are evicted once the cache exceeds its size limit (LRU).
+
+pub struct SchemaCache {
+    dir: PathBuf,
+    max_bytes: AtomicU64,
+    evict_lock: Mutex<()>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SchemaCacheStats {
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+}
+
+impl SchemaCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        SchemaCache {
+            dir,
+            max_bytes: AtomicU64::new(max_bytes),
+            evict_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::SeqCst);
+        self.evict();
+    }
+
+    fn entry_path(&self, content_hash: &str) -> Option<PathBuf> {
+        // Content hashes are hex; anything else is not a valid key
+        if content_hash.is_empty() || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.dir.join(format!("{}.json", content_hash)))
+    }
+
+    pub fn get(&self, content_hash: &str) -> Option<PhotoCreateSchema> {
+        let path = self.entry_path(content_hash)?;
+        let text = fs::read_to_string(&path).ok()?;
+        let schema = serde_json::from_str(&text).ok()?;
+
+        // Mark as recently used
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(schema)
+    }
+
+    pub fn put(&self, content_hash: &str, schema: &PhotoCreateSchema) -> Result<(), ImalinkError> {
+        // Every MB counts then, see low_disk.rs
+        if crate::low_disk::active() {
+            return Ok(());
+        }
+        let path = self
+            .entry_path(content_hash)
+            .ok_or_else(|| ImalinkError::invalid(format!("Invalid cache key: {}", content_hash)))?;
+        fs::create_dir_all(&self.dir).map_err(|e| ImalinkError::io(self.dir.display(), e))?;
+
+        // Write to a temp file first so readers never see a partial entry
+        let tmp = path.with_extension("json.tmp");
+        let text = serde_json::to_string(schema)?;
+        fs::write(&tmp, text).map_err(|e| ImalinkError::io(tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| ImalinkError::io(path.display(), e))?;
+
+        self.evict();
+        Ok(())
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                Some((entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+            })
+            .collect()
+    }
+
+    // Remove least recently used entries until the cache fits its limit
+    fn evict(&self) {
+        let Ok(_guard) = self.evict_lock.lock() else { return };
+        let max_bytes = self.max_bytes.load(Ordering::SeqCst);
+
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= max_bytes {
+            return;
+        }
+
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> SchemaCacheStats {
+        let entries = self.entries();
+        SchemaCacheStats {
+            entries: entries.len(),
+            total_bytes: entries.iter().map(|(_, size, _)| size).sum(),
+            max_bytes: self.max_bytes.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), ImalinkError> {
+        if Path::new(&self.dir).exists() {
+            fs::remove_dir_all(&self.dir).map_err(|e| ImalinkError::io(self.dir.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_schema_cache_stats(cache: tauri::State<'_, SchemaCache>) -> SchemaCacheStats {
+    cache.stats()
+}
+
+#[tauri::command]
+pub fn clear_schema_cache(
+    access: tauri::State<'_, AccessMode>,
+    cache: tauri::State<'_, SchemaCache>,
+) -> Result<(), ImalinkError> {
+    access.require_owner("delete cached schemas")?;
+    cache.clear()
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::http::{self, SendPaced};
+use crate::session::Session;
+use crate::{auth, User};
+
+// ===== Self-Author =====
+//
+// Photos without an author_id of their own get the default author of their
+// input channel, and channels created by the app get the user's
+// `default_author_id` - the author standing for the user themself. Accounts
+// created before the backend made that author, or by tools that skip it,
+// have none, and their uploads ended up without any author at all.
+//
+// The frontend checks the user returned from login and validate_token; with
+// no default_author_id it offers create_self_author, which creates the
+// author (POST /api/v1/authors/) and points the user at it (PATCH
+// /api/v1/users/me/). The session's user is updated, so the author picker
+// and new channels have a default right away.
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Author {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub is_self: bool,
+}
+
+// Whether `user` still lacks the author standing for themself
+pub fn missing(user: &User) -> bool {
+    user.default_author_id.is_none()
+}
+
+async fn create_author(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    name: &str,
+) -> Result<Author, ImalinkError> {
+    let response = client
+        .post(format!("{}/api/v1/authors/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&json!({ "name": name, "is_self": true }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let response_text = response.text().await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))
+}
+
+async fn set_default_author(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    author_id: i32,
+) -> Result<User, ImalinkError> {
+    let response = client
+        .patch(format!("{}/api/v1/users/me/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&json!({ "default_author_id": author_id }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let response_text = response.text().await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))
+}
+
+// Create the author standing for the logged in user, named `display_name`
+// (the user's display name or username when empty), and make it their
+// default. Returns the updated user; one that already has a default author
+// is returned as it is.
+#[tauri::command]
+pub async fn create_self_author(
+    app: tauri::AppHandle,
+    display_name: Option<String>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<User, ImalinkError> {
+    guest::require_owner(&app, "create authors")?;
+    let session = app.state::<Session>();
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    let current = session.info().user;
+    if let Some(user) = current.as_ref().filter(|u| !missing(u)) {
+        return Ok(user.clone());
+    }
+    let name = display_name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| current.as_ref().and_then(|u| u.display_name.clone()).filter(|n| !n.trim().is_empty()))
+        .or_else(|| current.as_ref().map(|u| u.username.clone()))
+        .ok_or_else(|| ImalinkError::invalid("A name for the author is required"))?;
+    let client = http::client(&app);
+
+    // Separate calls, so a refresh between them doesn't create a second author
+    let author = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url, name) = (&client, &backend_url, &name);
+        async move { create_author(client, backend_url, &token, name).await }
+    })
+    .await?;
+    println!("Created self-author {} ({})", author.name, author.id);
+    let user = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url) = (&client, &backend_url);
+        async move { set_default_author(client, backend_url, &token, author.id).await }
+    })
+    .await?;
+    let auth_token = app.state::<auth::TokenRefresher>().current(&auth_token);
+    session.login(&backend_url, &auth_token, Some(user.clone()));
+    Ok(user)
+}
@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::ImalinkError;
+use crate::events;
+use crate::http::SendPaced;
+use crate::pipeline::{self, CompanionGroup, MasterOrder};
+
+// ===== Sequence Detection =====
+//
+// Finds runs of frames that belong together so they don't flood the photo
+// timeline one by one:
+//
+// - time-lapse: at least `min_frames` (default 30) consecutive frames from
+//   the same camera, evenly spaced at one second or more. EXIF times only
+//   have whole seconds, so each step may differ from the first by a second
+//   or 10% of the interval, whichever is larger.
+// - focus bracket: at least `min_bracket_frames` (default 3) frames from the
+//   same camera at most a second apart, whose EXIF focus distance steps in
+//   one direction. A jump back starts the next bracket. Frames in a focus
+//   bracket are not considered for time-lapses.
+//
+// detect_sequences reports what it found; the user then picks, per sequence,
+// a SequencePlan that goes into ImportOptions::sequences: `stack` creates a
+// backend photo stack and uploads every frame into it, `keyframes_only`
+// uploads just the keyframes and skips the rest.
+
+const DEFAULT_MIN_FRAMES: usize = 30;
+const DEFAULT_KEYFRAME_EVERY: usize = 50;
+const DEFAULT_MIN_BRACKET_FRAMES: usize = 3;
+
+// EXIF tags carrying the focus distance, most specific first
+const FOCUS_DISTANCE_TAGS: &[&str] = &["FocusDistance", "SubjectDistance", "ApproximateFocusDistance"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceKind {
+    Timelapse,
+    FocusBracket,
+}
+
+impl SequenceKind {
+    fn frame_label(self) -> &'static str {
+        match self {
+            SequenceKind::Timelapse => "Time-lapse frame",
+            SequenceKind::FocusBracket => "Focus-bracket frame",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceAction {
+    #[default]
+    Stack,
+    KeyframesOnly,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DetectedSequence {
+    pub kind: SequenceKind,
+    // Master files in capture order
+    pub masters: Vec<String>,
+    // Masters and companions
+    pub files: Vec<String>,
+    pub frame_count: usize,
+    pub interval_seconds: f64,
+    pub start: String,
+    pub end: String,
+    pub camera: Option<String>,
+    // Suggested masters to keep with keyframes_only
+    pub keyframes: Vec<String>,
+}
+
+// What to do with a detected sequence during import
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SequencePlan {
+    pub kind: SequenceKind,
+    #[serde(default)]
+    pub action: SequenceAction,
+    // Files of the sequence; a group belongs to it when any of its files does
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub keyframes: Vec<String>,
+    // Stack description, e.g. "Sunset over Bergen"
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl SequencePlan {
+    pub fn contains(&self, group: &CompanionGroup) -> bool {
+        group.all_files().iter().any(|f| self.files.contains(f))
+    }
+}
+
+// A frame that is left out under a keyframes_only plan, with the reason
+pub fn dropped_frame(plans: &[SequencePlan], group: &CompanionGroup) -> Option<String> {
+    plans
+        .iter()
+        .filter(|p| p.action == SequenceAction::KeyframesOnly && p.contains(group))
+        .find(|p| !group.all_files().iter().any(|f| p.keyframes.contains(f)))
+        .map(|p| format!("{} (keyframes only)", p.kind.frame_label()))
+}
+
+// Create a backend photo stack and return its id
+pub async fn create_stack(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    stack_type: impl Serialize,
+    description: Option<&str>,
+) -> Result<i32, ImalinkError> {
+    let response = client
+        .post(format!("{}/api/v1/photo-stacks/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({
+            "stack_type": stack_type,
+            "description": description,
+        }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let body: serde_json::Value = response.json().await?;
+    body["id"]
+        .as_i64()
+        .map(|id| id as i32)
+        .ok_or_else(|| ImalinkError::parse(format!("Stack response without id: {}", body)))
+}
+
+struct Frame {
+    group: CompanionGroup,
+    taken_at: chrono::NaiveDateTime,
+    camera: Option<String>,
+    focus_distance: Option<f64>,
+}
+
+// Focus distance from EXIF, as a number or a string like "0.35 m"
+fn focus_distance(exif_dict: &serde_json::Value) -> Option<f64> {
+    FOCUS_DISTANCE_TAGS.iter().find_map(|tag| match exif_dict.get(*tag)? {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.split_whitespace().next()?.parse().ok(),
+        _ => None,
+    })
+}
+
+fn seconds_between(a: &Frame, b: &Frame) -> i64 {
+    (b.taken_at - a.taken_at).num_seconds()
+}
+
+fn sequence(kind: SequenceKind, run: &[Frame], keyframe_every: usize) -> DetectedSequence {
+    let (first, last) = (&run[0], &run[run.len() - 1]);
+    let keyframes = match kind {
+        SequenceKind::Timelapse => {
+            let keyframe_every = keyframe_every.max(1);
+            run.iter()
+                .enumerate()
+                .filter(|(i, _)| i % keyframe_every == 0 || *i == run.len() - 1)
+                .map(|(_, f)| f.group.master_file.clone())
+                .collect()
+        }
+        // The middle frame is the best single stand-in for a bracket
+        SequenceKind::FocusBracket => vec![run[run.len() / 2].group.master_file.clone()],
+    };
+    DetectedSequence {
+        kind,
+        masters: run.iter().map(|f| f.group.master_file.clone()).collect(),
+        files: run.iter().flat_map(|f| f.group.all_files()).collect(),
+        frame_count: run.len(),
+        interval_seconds: seconds_between(first, last) as f64 / (run.len() - 1).max(1) as f64,
+        start: first.taken_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        end: last.taken_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        camera: first.camera.clone(),
+        keyframes,
+    }
+}
+
+// Evenly spaced runs in `frames` (sorted by capture time)
+fn find_timelapses(frames: &[Frame], min_frames: usize, keyframe_every: usize) -> Vec<DetectedSequence> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start + 1 < frames.len() {
+        let interval = seconds_between(&frames[start], &frames[start + 1]);
+        let tolerance = (interval / 10).max(1);
+        let mut end = start + 1;
+        while end + 1 < frames.len()
+            && frames[end + 1].camera == frames[start].camera
+            && (seconds_between(&frames[end], &frames[end + 1]) - interval).abs() <= tolerance
+        {
+            end += 1;
+        }
+
+        let run = &frames[start..=end];
+        // Bursts have sub-second spacing even when the rounded steps look even
+        let mean_interval = seconds_between(&run[0], &run[run.len() - 1]) as f64 / (run.len() - 1) as f64;
+        if interval >= 1 && run.len() >= min_frames && mean_interval >= 1.0 {
+            found.push(sequence(SequenceKind::Timelapse, run, keyframe_every));
+            start = end + 1;
+        } else {
+            start += 1;
+        }
+    }
+    found
+}
+
+// Runs of frames with stepped focus distance, indices into `frames` (sorted by capture time)
+fn find_focus_brackets(frames: &[Frame], min_frames: usize) -> Vec<std::ops::Range<usize>> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start + 1 < frames.len() {
+        let Some(first_distance) = frames[start].focus_distance else {
+            start += 1;
+            continue;
+        };
+        let mut end = start;
+        let mut last_distance = first_distance;
+        let mut direction = 0.0;
+        while end + 1 < frames.len() {
+            let (prev, next) = (&frames[end], &frames[end + 1]);
+            let Some(distance) = next.focus_distance else { break };
+            let step = distance - last_distance;
+            let stepped = step != 0.0 && (direction == 0.0 || step.signum() == direction);
+            if next.camera != prev.camera || seconds_between(prev, next) > 1 || !stepped {
+                break;
+            }
+            direction = step.signum();
+            last_distance = distance;
+            end += 1;
+        }
+
+        if end + 1 - start >= min_frames {
+            found.push(start..end + 1);
+            start = end + 1;
+        } else {
+            start += 1;
+        }
+    }
+    found
+}
+
+// Detect sequences among `files` (processed through the schema cache)
+#[tauri::command]
+pub async fn detect_sequences(
+    app: tauri::AppHandle,
+    core_api_url: String,
+    files: Vec<String>,
+    min_frames: Option<usize>,
+    keyframe_every: Option<usize>,
+    min_bracket_frames: Option<usize>,
+    master_order: Option<MasterOrder>,
+) -> Result<Vec<DetectedSequence>, ImalinkError> {
+    let mut frames: Vec<Frame> = events::process_groups(&app, &core_api_url, &files, &master_order.unwrap_or_default())
+        .await
+        .into_iter()
+        .filter_map(|(group, schema)| {
+            let schema = schema?;
+            Some(Frame {
+                taken_at: events::capture_time(schema.taken_at.as_deref())?,
+                camera: pipeline::camera_of(&schema.exif_dict),
+                focus_distance: focus_distance(&schema.exif_dict),
+                group,
+            })
+        })
+        .collect();
+    frames.sort_by(|a, b| (a.taken_at, &a.group.master_file).cmp(&(b.taken_at, &b.group.master_file)));
+
+    let keyframe_every = keyframe_every.unwrap_or(DEFAULT_KEYFRAME_EVERY);
+    let brackets = find_focus_brackets(&frames, min_bracket_frames.unwrap_or(DEFAULT_MIN_BRACKET_FRAMES).max(2));
+    let mut sequences: Vec<DetectedSequence> = brackets
+        .iter()
+        .map(|range| sequence(SequenceKind::FocusBracket, &frames[range.clone()], keyframe_every))
+        .collect();
+
+    let remaining: Vec<Frame> = frames
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !brackets.iter().any(|range| range.contains(i)))
+        .map(|(_, frame)| frame)
+        .collect();
+    sequences.extend(find_timelapses(
+        &remaining,
+        min_frames.unwrap_or(DEFAULT_MIN_FRAMES).max(2),
+        keyframe_every,
+    ));
+    sequences.sort_by(|a, b| a.start.cmp(&b.start));
+    Ok(sequences)
+}
+
+// Stack id per file for the stack plans, creating the stacks on the backend.
+// A stack that can't be created is logged and its frames import unstacked.
+pub async fn create_stacks(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    plans: &[SequencePlan],
+) -> HashMap<String, i32> {
+    let mut stack_ids = HashMap::new();
+    for plan in plans.iter().filter(|p| p.action == SequenceAction::Stack) {
+        match create_stack(client, backend_url, auth_token, plan.kind, plan.label.as_deref()).await {
+            Ok(id) => stack_ids.extend(plan.files.iter().map(|f| (f.clone(), id))),
+            Err(e) => eprintln!("Failed to create {:?} stack: {}", plan.kind, e),
+        }
+    }
+    stack_ids
+}
@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::sync::RwLock;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+use crate::User;
+
+// ===== Login Session =====
+//
+// The backend, access token and user of the current login, kept here so
+// commands don't need them passed in on every call. login and validate_token
+// fill it in, logout clears it, and a token refresh (auth.rs) swaps the
+// token in place. set_session picks the backend and core to work against
+// (the active profile); switching backend logs out of the previous one.
+//
+// Commands still accept `backend_url`/`auth_token` and use them when given;
+// when left out they come from here. Import, export and legacy options fill
+// empty fields the same way. get_session never returns the token.
+
+#[derive(Debug, Default, Clone)]
+struct SessionState {
+    backend_url: Option<String>,
+    core_api_url: Option<String>,
+    auth_token: Option<String>,
+    user: Option<User>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionInfo {
+    pub backend_url: Option<String>,
+    pub core_api_url: Option<String>,
+    pub user: Option<User>,
+    pub logged_in: bool,
+}
+
+// Managed state
+#[derive(Default)]
+pub struct Session(RwLock<SessionState>);
+
+impl Session {
+    pub fn info(&self) -> SessionInfo {
+        let state = self.0.read().unwrap();
+        SessionInfo {
+            backend_url: state.backend_url.clone(),
+            core_api_url: state.core_api_url.clone(),
+            user: state.user.clone(),
+            logged_in: state.auth_token.is_some(),
+        }
+    }
+
+    pub fn auth_token(&self) -> Option<String> {
+        self.0.read().unwrap().auth_token.clone()
+    }
+
+    pub fn login(&self, backend_url: &str, auth_token: &str, user: Option<User>) {
+        let mut state = self.0.write().unwrap();
+        state.backend_url = Some(backend_url.to_string());
+        state.auth_token = Some(auth_token.to_string());
+        if user.is_some() {
+            state.user = user;
+        }
+    }
+
+    pub fn logout(&self) {
+        let mut state = self.0.write().unwrap();
+        state.auth_token = None;
+        state.user = None;
+    }
+
+    // A refresh replaced `stale` with `fresh`
+    pub fn replace_token(&self, stale: &str, fresh: &str) {
+        let mut state = self.0.write().unwrap();
+        if state.auth_token.as_deref() == Some(stale) {
+            state.auth_token = Some(fresh.to_string());
+        }
+    }
+
+    pub fn backend_url(&self, given: Option<String>) -> Result<String, ImalinkError> {
+        given
+            .filter(|u| !u.is_empty())
+            .or_else(|| self.0.read().unwrap().backend_url.clone())
+            .ok_or_else(|| ImalinkError::invalid("No backend selected"))
+    }
+
+    pub fn token(&self, given: Option<String>) -> Result<String, ImalinkError> {
+        given
+            .filter(|t| !t.is_empty())
+            .or_else(|| self.auth_token())
+            .ok_or_else(|| ImalinkError::Unauthorized { detail: "Not logged in".to_string() })
+    }
+
+    // Backend URL and token: the given ones, or those of the session
+    pub fn credentials(
+        &self,
+        backend_url: Option<String>,
+        auth_token: Option<String>,
+    ) -> Result<(String, String), ImalinkError> {
+        Ok((self.backend_url(backend_url)?, self.token(auth_token)?))
+    }
+
+    // Fill empty backend URL and token fields of an options struct
+    pub fn fill(&self, backend_url: &mut String, auth_token: &mut String) -> Result<(), ImalinkError> {
+        if backend_url.is_empty() {
+            *backend_url = self.backend_url(None)?;
+        }
+        if auth_token.is_empty() {
+            *auth_token = self.token(None)?;
+        }
+        Ok(())
+    }
+}
+
+// ===== Commands =====
+
+#[tauri::command]
+pub fn get_session(session: tauri::State<'_, Session>) -> SessionInfo {
+    session.info()
+}
+
+// Select the backend and core to work against. A different backend ends the
+// login to the previous one.
+#[tauri::command]
+pub fn set_session(
+    session: tauri::State<'_, Session>,
+    access: tauri::State<'_, AccessMode>,
+    backend_url: Option<String>,
+    core_api_url: Option<String>,
+) -> Result<SessionInfo, ImalinkError> {
+    access.require_owner("change the session")?;
+    {
+        let mut state = session.0.write().unwrap();
+        if let Some(backend_url) = backend_url.filter(|u| !u.is_empty()) {
+            if state.backend_url.as_deref() != Some(backend_url.as_str()) {
+                state.auth_token = None;
+                state.user = None;
+            }
+            state.backend_url = Some(backend_url);
+        }
+        if let Some(core_api_url) = core_api_url.filter(|u| !u.is_empty()) {
+            state.core_api_url = Some(core_api_url);
+        }
+    }
+    Ok(session.info())
+}
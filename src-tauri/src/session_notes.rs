@@ -0,0 +1,205 @@
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::http::SendPaced;
+use crate::pipeline::ImportSessions;
+use crate::session::Session;
+use crate::{auth, channels, guest, InputChannel};
+
+// ===== Session Notes =====
+//
+// A free-text note and labels per import session ("Client X wedding, card 2
+// of 3"; "wedding", "client-x"), set with ImportOptions::note/labels when the
+// import starts or later with set_session_note. They are kept on the
+// in-memory session and in the `session_notes` table of the history
+// database, so they outlive the app run.
+//
+// append_session_note_to_channel adds the note as a new paragraph to the
+// description of the channel the session imported into. It does so once per
+// session; the note can still be edited locally afterwards.
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionNote {
+    pub session_id: String,
+    pub note: String,
+    pub labels: Vec<String>,
+    pub input_channel_id: Option<i32>,
+    pub updated_at: String,
+    // When the note was added to the channel description
+    pub appended_at: Option<String>,
+}
+
+fn read(history: &History, session_id: &str) -> Result<Option<SessionNote>, ImalinkError> {
+    history.with(|conn| {
+        conn.query_row(
+            "SELECT session_id, note, labels, input_channel_id, updated_at, appended_at
+             FROM session_notes WHERE session_id = ?1",
+            [session_id],
+            |row| {
+                let labels: String = row.get(2)?;
+                Ok(SessionNote {
+                    session_id: row.get(0)?,
+                    note: row.get(1)?,
+                    labels: serde_json::from_str(&labels).unwrap_or_default(),
+                    input_channel_id: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    appended_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+    })
+}
+
+// Store the note of a session, keeping its appended_at
+pub fn record(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    note: &str,
+    labels: &[String],
+    input_channel_id: Option<i32>,
+) -> Result<SessionNote, ImalinkError> {
+    let labels: Vec<String> = labels.iter().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let history = app.state::<History>();
+    history.with(|conn| {
+        conn.execute(
+            "INSERT INTO session_notes (session_id, note, labels, input_channel_id, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (session_id) DO UPDATE SET note = ?2, labels = ?3,
+                 input_channel_id = COALESCE(?4, input_channel_id), updated_at = ?5",
+            params![
+                session_id,
+                note.trim(),
+                serde_json::to_string(&labels).unwrap_or_default(),
+                input_channel_id,
+                updated_at
+            ],
+        )
+        .map(|_| ())
+    })?;
+    app.state::<ImportSessions>().annotate(session_id, note.trim(), &labels);
+    read(&history, session_id)?.ok_or_else(|| ImalinkError::internal("Session note vanished after saving"))
+}
+
+// Set or replace the note and labels of an import session. The channel is
+// taken from the session when it ran during this app run.
+#[tauri::command]
+pub fn set_session_note(
+    app: tauri::AppHandle,
+    session_id: String,
+    note: String,
+    labels: Option<Vec<String>>,
+    input_channel_id: Option<i32>,
+) -> Result<SessionNote, ImalinkError> {
+    guest::require_owner(&app, "edit session notes")?;
+    let input_channel_id =
+        input_channel_id.or_else(|| app.state::<ImportSessions>().get(&session_id).map(|s| s.input_channel_id));
+    record(&app, &session_id, &note, &labels.unwrap_or_default(), input_channel_id)
+}
+
+// Notes of the given sessions, or of all sessions, newest first
+#[tauri::command]
+pub fn get_session_notes(
+    history: tauri::State<'_, History>,
+    session_ids: Option<Vec<String>>,
+) -> Result<Vec<SessionNote>, ImalinkError> {
+    let ids: Vec<String> = match session_ids {
+        Some(ids) => ids,
+        None => history.with(|conn| {
+            let mut stmt = conn.prepare("SELECT session_id FROM session_notes ORDER BY updated_at DESC")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>();
+            ids
+        })?,
+    };
+    let mut notes = Vec::new();
+    for id in ids {
+        if let Some(note) = read(&history, &id)? {
+            notes.push(note);
+        }
+    }
+    Ok(notes)
+}
+
+// The paragraph added to the channel description
+fn paragraph(note: &SessionNote) -> String {
+    if note.labels.is_empty() {
+        note.note.clone()
+    } else {
+        format!("{} [{}]", note.note, note.labels.join(", "))
+    }
+}
+
+async fn patch_description(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    input_channel_id: i32,
+    description: &str,
+) -> Result<InputChannel, ImalinkError> {
+    let response = client
+        .patch(format!("{}/api/v1/input-channels/{}/", backend_url, input_channel_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({ "description": description }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return Err(ImalinkError::invalid("The backend does not support editing channel descriptions"));
+    }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let response_text = response.text().await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ImalinkError::parse(format!("{} | Response was: {}", e, response_text)))
+}
+
+// Add the session's note to the description of its channel
+#[tauri::command]
+pub async fn append_session_note_to_channel(
+    app: tauri::AppHandle,
+    session_id: String,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<InputChannel, ImalinkError> {
+    guest::require_owner(&app, "edit input channels")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let history = app.state::<History>();
+    let note = read(&history, &session_id)?
+        .ok_or_else(|| ImalinkError::invalid(format!("Session {} has no note", session_id)))?;
+    if note.appended_at.is_some() {
+        return Err(ImalinkError::invalid(format!("The note of session {} is already in the channel description", session_id)));
+    }
+    let input_channel_id = note
+        .input_channel_id
+        .ok_or_else(|| ImalinkError::invalid(format!("Channel of session {} is not known", session_id)))?;
+
+    let client = crate::http::client(&app);
+    let channel = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url, note) = (&client, &backend_url, &note);
+        async move {
+            let channel = channels::fetch_channel(client, backend_url, &token, input_channel_id).await?;
+            let description = match channel.description.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+                Some(existing) => format!("{}\n\n{}", existing, paragraph(note)),
+                None => paragraph(note),
+            };
+            patch_description(client, backend_url, &token, input_channel_id, &description).await
+        }
+    })
+    .await?;
+
+    history.with(|conn| {
+        conn.execute(
+            "UPDATE session_notes SET appended_at = ?2 WHERE session_id = ?1",
+            params![session_id, chrono::Utc::now().to_rfc3339()],
+        )
+        .map(|_| ())
+    })?;
+    Ok(channel)
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::authors::AuthorRule;
+use crate::disk_usage::PrunePolicy;
+use crate::dng::DngConverter;
+use crate::editor::ExternalEditor;
+use crate::error::ImalinkError;
+use crate::guest::GuestToken;
+use crate::hooks::PostImportHook;
+use crate::plugins::PipelinePlugin;
+use crate::presets::ImportPreset;
+use crate::privacy::PrivacyZone;
+use crate::remote::RemoteSource;
+use crate::remote_core::{self, RemoteCore};
+use crate::scheduler::ScheduleRule;
+use crate::transcode::Transcoder;
+use crate::watchdog::StageTimeouts;
+
+// ===== Application Settings =====
+//
+// Persisted as JSON in the app config directory. Every field has a default so
+// settings files written by older versions keep loading.
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    // Opt-in: crash reports are only written when enabled, and only
+    // uploaded through an explicit submit_crash_report call
+    pub crash_reporting_enabled: bool,
+    // Size limit of the processed PhotoCreateSchema cache
+    pub schema_cache_max_mb: u64,
+    // Upper bound on import-progress events sent to the UI per second
+    pub progress_events_per_second: u32,
+    pub presets: Vec<ImportPreset>,
+    pub schedules: Vec<ScheduleRule>,
+    // Opt-in: post-import hooks only run when enabled
+    pub hooks_enabled: bool,
+    pub hooks: Vec<PostImportHook>,
+    // Opt-in: pipeline plugins only load when enabled
+    pub plugins_enabled: bool,
+    pub plugins: Vec<PipelinePlugin>,
+    // Command used to run WASM plugins
+    pub wasm_runtime: String,
+    // Areas where GPS is stripped or coarsened before upload
+    pub privacy_zones: Vec<PrivacyZone>,
+    // Max differing dHash bits for two photos to count as near duplicates
+    pub near_duplicate_threshold: u32,
+    // SFTP/FTP servers to import from, with their credentials
+    pub remote_sources: Vec<RemoteSource>,
+    // External tool for ImportOptions::convert_to_dng
+    pub dng_converter: DngConverter,
+    // Abort an upload after this long without progress, and retry it this
+    // many times (see stall.rs)
+    pub upload_stall_timeout_seconds: u64,
+    pub upload_stall_retries: u32,
+    // Size limit of all temporary import workspaces together
+    pub workspace_max_mb: u64,
+    // Hotpreviews with a shorter longest edge are rebuilt before upload
+    // (see hotpreview.rs); 0 turns the check off
+    pub min_hotpreview_px: u32,
+    // Tokens for read-only guest use and the passcode that ends it (see
+    // guest.rs), both hashed
+    pub guest_tokens: Vec<GuestToken>,
+    pub owner_passcode_hash: Option<String>,
+    // Editors offered by open_in_editor
+    pub editors: Vec<ExternalEditor>,
+    // Request size limit of the backend (or its proxy) for photo uploads;
+    // larger requests get a smaller coldpreview, see payload.rs. 0 = none
+    pub max_upload_body_kb: u64,
+    // Send upload checksums in a request field of their own rather than in
+    // exif_dict; for backends that accept it (see integrity.rs)
+    pub upload_checksum_field: bool,
+    // Description of channels created for an import (see
+    // channel_description.rs); empty leaves it to the user
+    pub channel_description_template: String,
+    // Camera serial/model → author, see authors.rs
+    pub author_rules: Vec<AuthorRule>,
+    // Age/size limits for the prunable caches, applied at startup when
+    // enabled (see disk_usage.rs)
+    pub cache_limits: PrunePolicy,
+    pub prune_caches_on_startup: bool,
+    // Extra attempts at core processing before a group is quarantined
+    // (see quarantine.rs)
+    pub process_retries: u32,
+    // Longest an item may take in each pipeline stage before it is
+    // quarantined (see watchdog.rs)
+    pub stage_timeouts: StageTimeouts,
+    // External tool making JPEG derivatives of TIFF/JXL masters (see
+    // transcode.rs)
+    pub transcoder: Transcoder,
+    // Files next to a photo that join its group, by extension (see
+    // assets.rs); empty turns this off
+    pub asset_companion_extensions: Vec<String>,
+    // imalink-core on a server instead of the local sidecar (see
+    // remote_core.rs)
+    pub remote_core: Option<RemoteCore>,
+    // Port of the local imalink-core sidecar; None picks a free one (see
+    // remote_core.rs). Applies on the next start.
+    pub core_port: Option<u16>,
+    // Free space on the system drive below which the app saves space where
+    // it can (see low_disk.rs); 0 turns this off
+    pub low_disk_threshold_mb: u64,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            crash_reporting_enabled: false,
+            schema_cache_max_mb: 512,
+            progress_events_per_second: 4,
+            presets: Vec::new(),
+            schedules: Vec::new(),
+            hooks_enabled: false,
+            hooks: Vec::new(),
+            plugins_enabled: false,
+            plugins: Vec::new(),
+            wasm_runtime: "wasmtime".to_string(),
+            privacy_zones: Vec::new(),
+            near_duplicate_threshold: crate::duplicates::DEFAULT_THRESHOLD,
+            remote_sources: Vec::new(),
+            dng_converter: DngConverter::default(),
+            upload_stall_timeout_seconds: crate::stall::DEFAULT_TIMEOUT_SECONDS,
+            upload_stall_retries: crate::stall::DEFAULT_RETRIES,
+            workspace_max_mb: 20 * 1024,
+            min_hotpreview_px: crate::hotpreview::DEFAULT_MIN_PX,
+            guest_tokens: Vec::new(),
+            owner_passcode_hash: None,
+            editors: Vec::new(),
+            max_upload_body_kb: crate::payload::DEFAULT_MAX_KB,
+            upload_checksum_field: false,
+            channel_description_template: crate::channel_description::DEFAULT_TEMPLATE.to_string(),
+            author_rules: Vec::new(),
+            cache_limits: PrunePolicy::default(),
+            prune_caches_on_startup: true,
+            process_retries: 2,
+            stage_timeouts: StageTimeouts::default(),
+            transcoder: Transcoder::default(),
+            asset_companion_extensions: crate::assets::default_extensions(),
+            remote_core: None,
+            core_port: None,
+            low_disk_threshold_mb: 2048,
+        }
+    }
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, ImalinkError> {
+    let dir = app.path().app_config_dir()
+        .map_err(|e| ImalinkError::internal(format!("Failed to resolve config directory: {}", e)))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+pub fn load(app: &tauri::AppHandle) -> AppSettings {
+    let Ok(path) = settings_path(app) else {
+        return AppSettings::default();
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("Invalid settings file {}, using defaults: {}", path.display(), e);
+            AppSettings::default()
+        }),
+        Err(_) => AppSettings::default(),
+    }
+}
+
+pub fn save(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), ImalinkError> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| ImalinkError::io(parent.display(), e))?;
+    }
+
+    let text = serde_json::to_string_pretty(settings)?;
+    fs::write(&path, text)
+        .map_err(|e| ImalinkError::io(path.display(), e))
+}
+
+#[tauri::command]
+pub fn get_settings(app: tauri::AppHandle) -> AppSettings {
+    load(&app)
+}
+
+#[tauri::command]
+pub fn update_settings(app: tauri::AppHandle, mut settings: AppSettings) -> Result<AppSettings, ImalinkError> {
+    crate::guest::require_owner(&app, "change settings")?;
+    // Guest tokens and the owner passcode only change through guest.rs
+    let stored = load(&app);
+    settings.guest_tokens = stored.guest_tokens;
+    settings.owner_passcode_hash = stored.owner_passcode_hash;
+    if let Some(remote) = &settings.remote_core {
+        remote_core::validate(remote)?;
+    }
+    save(&app, &settings)?;
+    crate::crash::set_enabled(settings.crash_reporting_enabled);
+    remote_core::configure(settings.remote_core.clone());
+    app.state::<crate::schema_cache::SchemaCache>()
+        .set_max_bytes(settings.schema_cache_max_mb * 1024 * 1024);
+    app.state::<crate::workspace::Workspaces>()
+        .set_max_bytes(settings.workspace_max_mb * 1024 * 1024);
+    Ok(settings)
+}
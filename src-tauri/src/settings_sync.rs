@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::http::SendPaced;
+use crate::presets::ImportPreset;
+use crate::scheduler::ScheduleRule;
+use crate::session::Session;
+use crate::settings::{self, AppSettings};
+use crate::{auth, guest};
+
+// ===== Settings Sync =====
+//
+// Keeps import presets and scheduled folder imports the same on every
+// machine the user runs the app on, through a small per-user settings
+// document on the backend (GET/PUT /api/v1/users/me/settings/). Only run
+// when asked for, with sync_settings.
+//
+// The document is a map of items - `preset:<name>` and `schedule:<id>` -
+// and the sync is a three-way merge per item against the state of the last
+// sync with that backend (kept in the history database):
+//
+//   changed here only          sent up
+//   changed there only         taken over
+//   changed on both sides      a conflict, unless both made the same change
+//
+// A removed item counts as changed. Conflicts stay as they are on both sides
+// until sync_settings is called again with a resolution for them, picking
+// this machine's or the backend's version. The backend bumps the document's
+// version on every write and refuses a PUT based on an older one (409); the
+// merge is then redone against the newer document.
+
+const PUT_ATTEMPTS: usize = 3;
+
+fn base_key(backend_url: &str) -> String {
+    format!("settings_sync_base:{}", backend_url)
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictChoice {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SettingsConflict {
+    pub key: String,
+    // None = removed on that side
+    pub local: Option<Value>,
+    pub remote: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SettingsSyncReport {
+    pub pulled: Vec<String>,
+    pub pushed: Vec<String>,
+    pub conflicts: Vec<SettingsConflict>,
+    pub version: i64,
+    pub synced_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct RemoteSettings {
+    #[serde(default)]
+    settings: BTreeMap<String, Value>,
+    #[serde(default)]
+    version: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct SyncBase {
+    items: BTreeMap<String, Value>,
+    version: i64,
+}
+
+type Items = BTreeMap<String, Value>;
+
+fn local_items(settings: &AppSettings) -> Items {
+    let presets = settings
+        .presets
+        .iter()
+        .map(|p| (format!("preset:{}", p.name), serde_json::to_value(p).unwrap_or_default()));
+    let schedules = settings
+        .schedules
+        .iter()
+        .map(|s| (format!("schedule:{}", s.id), serde_json::to_value(s).unwrap_or_default()));
+    presets.chain(schedules).collect()
+}
+
+// Put `items` into the settings, keeping the order of what stays
+fn apply_items(settings: &mut AppSettings, items: &Items) -> Result<(), ImalinkError> {
+    fn merge<T: serde::de::DeserializeOwned>(
+        current: &mut Vec<T>,
+        items: &Items,
+        prefix: &str,
+        key_of: impl Fn(&T) -> String,
+    ) -> Result<(), ImalinkError> {
+        let mut wanted: BTreeMap<&str, &Value> = items
+            .iter()
+            .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?, v)))
+            .collect();
+        let mut merged = Vec::new();
+        for item in current.drain(..) {
+            if let Some(value) = wanted.remove(key_of(&item).as_str()) {
+                merged.push(serde_json::from_value(value.clone())?);
+            }
+        }
+        for value in wanted.into_values() {
+            merged.push(serde_json::from_value(value.clone())?);
+        }
+        *current = merged;
+        Ok(())
+    }
+    merge(&mut settings.presets, items, "preset:", |p: &ImportPreset| p.name.clone())?;
+    merge(&mut settings.schedules, items, "schedule:", |s: &ScheduleRule| s.id.clone())
+}
+
+async fn fetch_remote(client: &reqwest::Client, backend_url: &str, auth_token: &str) -> Result<RemoteSettings, ImalinkError> {
+    let response = client
+        .get(format!("{}/api/v1/users/me/settings/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    // Nothing synced from any machine yet
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(RemoteSettings::default());
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(response.json().await?)
+}
+
+// Write the document; None when another machine wrote a newer version first
+async fn put_remote(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    items: &Items,
+    base_version: i64,
+) -> Result<Option<i64>, ImalinkError> {
+    let response = client
+        .put(format!("{}/api/v1/users/me/settings/", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({ "settings": items, "base_version": base_version }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let written: RemoteSettings = response.json().await?;
+    Ok(Some(written.version))
+}
+
+struct Merge {
+    local: Items,
+    remote: Items,
+    base: Items,
+    report: SettingsSyncReport,
+}
+
+fn merge(local: &Items, remote: &Items, base: &Items, resolutions: &HashMap<String, ConflictChoice>) -> Merge {
+    let mut merge = Merge {
+        local: Items::new(),
+        remote: Items::new(),
+        base: Items::new(),
+        report: SettingsSyncReport::default(),
+    };
+    let keys: BTreeSet<&String> = local.keys().chain(remote.keys()).chain(base.keys()).collect();
+    for key in keys {
+        let (l, r, b) = (local.get(key), remote.get(key), base.get(key));
+        let take = if l == r {
+            l
+        } else if l == b {
+            merge.report.pulled.push(key.clone());
+            r
+        } else if r == b {
+            merge.report.pushed.push(key.clone());
+            l
+        } else {
+            match resolutions.get(key) {
+                Some(ConflictChoice::Local) => {
+                    merge.report.pushed.push(key.clone());
+                    l
+                }
+                Some(ConflictChoice::Remote) => {
+                    merge.report.pulled.push(key.clone());
+                    r
+                }
+                None => {
+                    // Both sides keep their version, the base stays behind
+                    merge.report.conflicts.push(SettingsConflict {
+                        key: key.clone(),
+                        local: l.cloned(),
+                        remote: r.cloned(),
+                    });
+                    for (side, value) in [(&mut merge.local, l), (&mut merge.remote, r), (&mut merge.base, b)] {
+                        if let Some(value) = value {
+                            side.insert(key.clone(), value.clone());
+                        }
+                    }
+                    continue;
+                }
+            }
+        };
+        if let Some(value) = take {
+            for side in [&mut merge.local, &mut merge.remote, &mut merge.base] {
+                side.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    merge
+}
+
+// Merge presets and scheduled imports with the backend's copy. `resolutions`
+// settles conflicts reported by an earlier call, by item key.
+#[tauri::command]
+pub async fn sync_settings(
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    resolutions: Option<HashMap<String, ConflictChoice>>,
+) -> Result<SettingsSyncReport, ImalinkError> {
+    guest::require_owner(&app, "change settings")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let resolutions = resolutions.unwrap_or_default();
+    let history = app.state::<History>();
+    let base: SyncBase = history
+        .sync_value(&base_key(&backend_url))?
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+
+    let client = crate::http::client(&app);
+    let mut settings = settings::load(&app);
+    let local = local_items(&settings);
+    let (merged, version) = auth::with_refresh(&app, &backend_url, &auth_token, |token| {
+        let (client, backend_url, local, base, resolutions) = (&client, &backend_url, &local, &base, &resolutions);
+        async move {
+            for _ in 0..PUT_ATTEMPTS {
+                let remote = fetch_remote(client, backend_url, &token).await?;
+                let merged = merge(local, &remote.settings, &base.items, resolutions);
+                if merged.remote == remote.settings {
+                    return Ok((merged, remote.version));
+                }
+                if let Some(version) = put_remote(client, backend_url, &token, &merged.remote, remote.version).await? {
+                    return Ok((merged, version));
+                }
+            }
+            Err(ImalinkError::Backend {
+                status: 409,
+                detail: "Settings kept changing on the backend while syncing; try again".to_string(),
+            })
+        }
+    })
+    .await?;
+
+    if merged.local != local {
+        apply_items(&mut settings, &merged.local)?;
+        settings::save(&app, &settings)?;
+    }
+    let base = SyncBase { items: merged.base, version };
+    history.set_sync_value(&base_key(&backend_url), &serde_json::to_string(&base)?)?;
+
+    let mut report = merged.report;
+    report.version = version;
+    report.synced_at = chrono::Utc::now().to_rfc3339();
+    Ok(report)
+}
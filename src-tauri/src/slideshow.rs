@@ -0,0 +1,185 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::existence;
+use crate::history::History;
+use crate::offline::{self, OfflinePreviews, OfflineSelection, PhotoRef};
+use crate::preview_store::{PreviewKind, PreviewStore};
+use crate::session::Session;
+
+// ===== Slideshow =====
+//
+// start_slideshow opens a borderless fullscreen window (slideshow.html) that
+// cycles the coldpreviews of a selection - explicit hothashes, or channels
+// and a capture date range - e.g. to review yesterday's import on the big
+// screen. The window fetches its slide list with get_slideshow and loads
+// each slide from imalink-preview://localhost/cold/<hothash>.
+//
+// Previews already in memory or in the offline cache are shown as they are;
+// the rest are downloaded from the backend in the background, in slide
+// order, so the first slides are ready first.
+
+const CONCURRENT_DOWNLOADS: usize = 4;
+
+fn default_interval() -> u32 {
+    5
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SlideshowSelection {
+    // Takes precedence over the channel/date selection when not empty
+    #[serde(default)]
+    pub hothashes: Vec<String>,
+    #[serde(flatten)]
+    pub range: OfflineSelection,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlideshowOptions {
+    #[serde(default = "default_interval")]
+    pub interval_seconds: u32,
+    #[serde(default)]
+    pub shuffle: bool,
+    // Index into the list of available monitors; the current one when absent
+    #[serde(default)]
+    pub monitor: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Slideshow {
+    pub id: String,
+    pub hothashes: Vec<String>,
+    pub interval_seconds: u32,
+}
+
+// Managed state: slideshows by id, for their windows to look up
+#[derive(Default)]
+pub struct Slideshows(Mutex<HashMap<String, Slideshow>>);
+
+// Photos to show, with backend ids for downloading previews
+async fn resolve_photos(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    selection: &SlideshowSelection,
+) -> Result<Vec<PhotoRef>, ImalinkError> {
+    if selection.hothashes.is_empty() {
+        let mut photos = offline::list_photos(client, backend_url, auth_token, &selection.range).await?;
+        let mut seen = std::collections::HashSet::new();
+        photos.retain(|p| seen.insert(p.hothash.clone()));
+        return Ok(photos);
+    }
+
+    let history = app.state::<History>();
+    let mut photos = Vec::new();
+    for hothash in &selection.hothashes {
+        let known = history.get(hothash)?.and_then(|p| p.photo_id);
+        let photo_id = match known {
+            Some(id) => Some(id),
+            None => existence::find_backend_photo(app, client, backend_url, auth_token, hothash).await?,
+        };
+        match photo_id {
+            Some(id) => photos.push(PhotoRef { id: id as i64, hothash: hothash.clone() }),
+            None => eprintln!("Slideshow: {} is not on the backend, leaving it out", hothash),
+        }
+    }
+    Ok(photos)
+}
+
+fn has_coldpreview(app: &tauri::AppHandle, hothash: &str) -> bool {
+    app.state::<PreviewStore>().get(PreviewKind::Cold, hothash).is_some()
+        || app.state::<OfflinePreviews>().contains(hothash)
+}
+
+// Shuffle by sorting on a hash of the hothash and a random per-show seed
+fn shuffle(photos: &mut [PhotoRef]) {
+    let seed = uuid::Uuid::new_v4();
+    photos.sort_by_cached_key(|p| {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(p.hothash.as_bytes());
+        *hasher.finalize().as_bytes()
+    });
+}
+
+fn open_window(app: &tauri::AppHandle, id: &str, monitor: Option<usize>) -> Result<(), ImalinkError> {
+    let window_error = |e: tauri::Error| ImalinkError::internal(format!("Failed to open slideshow window: {}", e));
+    let url = tauri::WebviewUrl::App(format!("slideshow.html?id={}", id).into());
+    let window = tauri::WebviewWindowBuilder::new(app, format!("slideshow-{}", id), url)
+        .title("Imalink Slideshow")
+        .decorations(false)
+        .build()
+        .map_err(window_error)?;
+
+    if let Some(index) = monitor {
+        let monitors = app.available_monitors().map_err(window_error)?;
+        let monitor = monitors
+            .get(index)
+            .ok_or_else(|| ImalinkError::invalid(format!("No monitor {} ({} available)", index, monitors.len())))?;
+        window.set_position(*monitor.position()).map_err(window_error)?;
+    }
+    window.set_fullscreen(true).map_err(window_error)
+}
+
+// Open a slideshow window for the selection and return the show
+#[tauri::command]
+pub async fn start_slideshow(
+    app: tauri::AppHandle,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+    selection: SlideshowSelection,
+    options: SlideshowOptions,
+) -> Result<Slideshow, ImalinkError> {
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = crate::http::client(&app);
+    let mut photos = resolve_photos(&app, &client, &backend_url, &auth_token, &selection).await?;
+    if photos.is_empty() {
+        return Err(ImalinkError::invalid("Nothing to show - the selection has no photos"));
+    }
+    if options.shuffle {
+        shuffle(&mut photos);
+    }
+
+    let show = Slideshow {
+        id: uuid::Uuid::new_v4().to_string(),
+        hothashes: photos.iter().map(|p| p.hothash.clone()).collect(),
+        interval_seconds: options.interval_seconds.max(1),
+    };
+    app.state::<Slideshows>().0.lock().unwrap().insert(show.id.clone(), show.clone());
+
+    let missing: Vec<PhotoRef> = photos.into_iter().filter(|p| !has_coldpreview(&app, &p.hothash)).collect();
+    let download_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = &download_app;
+        let (client, backend_url, auth_token) = (&client, &backend_url, &auth_token);
+        futures_util::stream::iter(missing)
+            .map(|photo| async move {
+                match offline::download_coldpreview(client, backend_url, auth_token, &photo).await {
+                    Ok(bytes) => app.state::<PreviewStore>().insert(PreviewKind::Cold, &photo.hothash, bytes),
+                    Err(e) => eprintln!("Slideshow: coldpreview of {} unavailable: {}", photo.hothash, e),
+                }
+            })
+            .buffered(CONCURRENT_DOWNLOADS)
+            .collect::<Vec<()>>()
+            .await;
+    });
+
+    open_window(&app, &show.id, options.monitor)?;
+    Ok(show)
+}
+
+#[tauri::command]
+pub fn get_slideshow(slideshows: tauri::State<'_, Slideshows>, id: String) -> Result<Slideshow, ImalinkError> {
+    slideshows
+        .0
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| ImalinkError::invalid(format!("Unknown slideshow: {}", id)))
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::operations::{OperationInfo, Operations};
+use crate::pipeline::{ImportSession, ImportSessions};
+use crate::prefetch::{PrefetchStatus, Prefetcher};
+use crate::progress::{ProgressSnapshot, ProgressTrackers};
+use crate::quarantine::{Quarantine, QuarantinedItem};
+use crate::recovery::{self, JournalSnapshot};
+use crate::scheduler::{RuleRunState, Scheduler};
+
+// ===== Queue Snapshots =====
+//
+// When a user reports a stuck import, export_queue_snapshot writes what the
+// app knows about its work to a JSON file they can attach to the support
+// case: import sessions with their per-file results, progress per stage,
+// running operations, the prefetch status, scheduler state, the journals of
+// interrupted imports and the processing quarantine. Nothing in it carries
+// previews, and tokens are left out (journals and quarantine entries never
+// hold one).
+//
+// import_queue_snapshot loads the sessions and journals from such a file
+// into a development build, so the UI can be looked at in the same state.
+// Sessions come back exactly as exported - one that was running stays
+// "running" until the app restarts. Release builds refuse it.
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct QueueSnapshot {
+    pub snapshot_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub sessions: Vec<ImportSession>,
+    pub progress: Vec<ProgressSnapshot>,
+    pub operations: Vec<OperationInfo>,
+    pub prefetch: PrefetchStatus,
+    pub schedules: Vec<RuleRunState>,
+    pub interrupted: Vec<JournalSnapshot>,
+    pub quarantined: Vec<QuarantinedItem>,
+}
+
+// The parts of a snapshot that can be restored
+#[derive(Debug, Deserialize)]
+struct RestorableSnapshot {
+    snapshot_version: u32,
+    sessions: Vec<ImportSession>,
+    interrupted: Vec<JournalSnapshot>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoredSnapshot {
+    pub sessions: usize,
+    pub interrupted: usize,
+}
+
+fn collect(app: &tauri::AppHandle) -> QueueSnapshot {
+    let mut sessions = app.state::<ImportSessions>().all();
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    QueueSnapshot {
+        snapshot_version: SNAPSHOT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        sessions,
+        progress: app.state::<ProgressTrackers>().snapshots(),
+        operations: app.state::<Operations>().list(),
+        prefetch: app.state::<Prefetcher>().status(),
+        schedules: app.state::<Scheduler>().run_states(),
+        interrupted: recovery::journal(app).snapshots(),
+        quarantined: app.state::<Quarantine>().list(),
+    }
+}
+
+// Write the snapshot to `dest` (which must not exist) and return its path
+#[tauri::command]
+pub async fn export_queue_snapshot(app: tauri::AppHandle, dest: String) -> Result<String, ImalinkError> {
+    guest::require_owner(&app, "export snapshots")?;
+    let path = PathBuf::from(&dest);
+    if path.exists() {
+        return Err(ImalinkError::DestinationExists { path: dest });
+    }
+    let snapshot = collect(&app);
+    let text = serde_json::to_string_pretty(&snapshot)?;
+    fs::write(&path, text).map_err(|e| ImalinkError::io(path.display(), e))?;
+    Ok(dest)
+}
+
+#[tauri::command]
+pub async fn import_queue_snapshot(app: tauri::AppHandle, path: String) -> Result<RestoredSnapshot, ImalinkError> {
+    guest::require_owner(&app, "restore snapshots")?;
+    if !cfg!(debug_assertions) {
+        return Err(ImalinkError::invalid("Queue snapshots can only be imported in development builds"));
+    }
+    let text = fs::read_to_string(&path).map_err(|e| ImalinkError::io(&path, e))?;
+    let snapshot: RestorableSnapshot = serde_json::from_str(&text)?;
+    if snapshot.snapshot_version > SNAPSHOT_VERSION {
+        return Err(ImalinkError::invalid(format!(
+            "Snapshot version {} is newer than this app supports ({})",
+            snapshot.snapshot_version, SNAPSHOT_VERSION
+        )));
+    }
+
+    let journal = recovery::journal(&app);
+    for interrupted in &snapshot.interrupted {
+        journal.restore(interrupted)?;
+    }
+    let restored = RestoredSnapshot {
+        sessions: snapshot.sessions.len(),
+        interrupted: snapshot.interrupted.len(),
+    };
+    let sessions = app.state::<ImportSessions>();
+    for session in snapshot.sessions {
+        sessions.restore(session);
+    }
+    Ok(restored)
+}
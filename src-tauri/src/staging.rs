@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+
+// ===== Staging =====
+//
+// Files pulled from devices (iPhone, tethered camera) and remote servers
+// (SFTP/FTP) are staged under <app data>/staging/<source>/<id> and imported
+// from there. Staged files are kept after import: in register mode that is
+// where they live.
+
+const STAGING_DIR: &str = "staging";
+
+// A file copied into staging, and where it came from
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StagedFile {
+    pub source: String,
+    pub path: String,
+    pub bytes: u64,
+}
+
+// Create (if needed) and return the staging folder for one source and batch
+pub fn workspace(app: &tauri::AppHandle, source: &str, id: &str) -> Result<PathBuf, ImalinkError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ImalinkError::internal(format!("Failed to resolve data directory: {}", e)))?
+        .join(STAGING_DIR)
+        .join(source)
+        .join(id);
+    std::fs::create_dir_all(&dir).map_err(|e| ImalinkError::io(dir.display(), e))?;
+    Ok(dir)
+}
@@ -0,0 +1,134 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+
+use crate::error::ImalinkError;
+use crate::settings::AppSettings;
+
+// ===== Upload Stall Detection =====
+//
+// A request whose connection silently stops moving data never errors out on
+// its own. Uploads therefore run under a watchdog: the request body is sent
+// in small chunks and every chunk handed to the connection - as well as the
+// response headers and each chunk of the response body - counts as a
+// heartbeat. When no heartbeat arrives for `upload_stall_timeout_seconds`
+// (settings), the attempt is dropped, which aborts the connection, and the
+// upload is retried up to `upload_stall_retries` times.
+//
+// The timeout also covers the backend's own processing time between the last
+// request chunk and the response, so it shouldn't be set much below a minute.
+// A retried photo create is safe: if the stalled attempt did reach the
+// backend, the retry gets a 409 and is reported as a duplicate.
+
+// Size of the request body chunks, i.e. the heartbeat granularity
+const HEARTBEAT_CHUNK: usize = 64 * 1024;
+// How often the watchdog checks for a heartbeat
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 60;
+pub const DEFAULT_RETRIES: u32 = 2;
+
+#[derive(Clone)]
+pub struct StallPolicy {
+    pub timeout: Duration,
+    pub retries: u32,
+    // Called for every stalled attempt, e.g. to count it in transfer stats
+    on_stall: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl StallPolicy {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        StallPolicy {
+            timeout: Duration::from_secs(settings.upload_stall_timeout_seconds.max(1)),
+            retries: settings.upload_stall_retries,
+            on_stall: None,
+        }
+    }
+
+    pub fn on_stall(mut self, callback: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_stall = Some(Arc::new(callback));
+        self
+    }
+}
+
+// Time of the last progress of one transfer attempt
+#[derive(Clone)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    fn new() -> Self {
+        Heartbeat(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    pub fn beat(&self) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    fn idle(&self) -> Duration {
+        self.0.lock().map(|last| last.elapsed()).unwrap_or_default()
+    }
+}
+
+// Request body that beats the heartbeat as each chunk is sent
+pub fn body(bytes: &[u8], heartbeat: &Heartbeat) -> reqwest::Body {
+    let chunks: Vec<Vec<u8>> = bytes.chunks(HEARTBEAT_CHUNK).map(|c| c.to_vec()).collect();
+    let heartbeat = heartbeat.clone();
+    reqwest::Body::wrap_stream(futures_util::stream::iter(chunks).map(move |chunk| {
+        heartbeat.beat();
+        Ok::<_, std::io::Error>(chunk)
+    }))
+}
+
+// Read a response body as text, beating the heartbeat per chunk
+pub async fn read_text(mut response: reqwest::Response, heartbeat: &Heartbeat) -> Result<String, ImalinkError> {
+    heartbeat.beat();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        heartbeat.beat();
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+// Run `attempt` under the watchdog, starting it over when it stalls
+pub async fn run<T, F, Fut>(policy: &StallPolicy, url: &str, mut attempt: F) -> Result<T, ImalinkError>
+where
+    F: FnMut(Heartbeat) -> Fut,
+    Fut: Future<Output = Result<T, ImalinkError>>,
+{
+    let mut stalls = 0;
+    loop {
+        let heartbeat = Heartbeat::new();
+        let watchdog = async {
+            while heartbeat.idle() < policy.timeout {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        };
+        tokio::select! {
+            result = attempt(heartbeat.clone()) => return result,
+            _ = watchdog => {}
+        }
+
+        stalls += 1;
+        eprintln!(
+            "Transfer to {} stalled: no progress for {}s (attempt {} of {})",
+            url,
+            policy.timeout.as_secs(),
+            stalls,
+            policy.retries + 1
+        );
+        if let Some(callback) = &policy.on_stall {
+            callback();
+        }
+        if stalls > policy.retries {
+            return Err(ImalinkError::Stalled {
+                url: url.to_string(),
+                seconds: policy.timeout.as_secs(),
+            });
+        }
+    }
+}
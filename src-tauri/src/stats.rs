@@ -0,0 +1,163 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ImalinkError;
+use crate::history::History;
+
+// ===== Library Statistics =====
+//
+// Aggregates for the dashboard view. Everything the desktop app recorded
+// itself comes from the local history database; when a backend login is
+// given, per-channel photo counts are added from the backend so photos
+// imported from other machines or the web show up too.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatsScope {
+    // Restrict local figures to one input channel
+    #[serde(default)]
+    pub input_channel_id: Option<i32>,
+    // Capture time range (RFC 3339 or YYYY-MM-DD), inclusive
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub backend_url: Option<String>,
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PeriodCount {
+    pub period: String,  // "2024" or "2024-06"
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CameraCount {
+    pub camera: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChannelStats {
+    pub input_channel_id: i32,
+    pub title: Option<String>,
+    pub local_count: i64,
+    // None when the backend wasn't queried
+    pub backend_count: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct LibraryStats {
+    pub total_photos: i64,
+    pub per_year: Vec<PeriodCount>,
+    pub per_month: Vec<PeriodCount>,
+    pub per_camera: Vec<CameraCount>,
+    pub per_channel: Vec<ChannelStats>,
+    pub duplicates_skipped: i64,
+    pub bytes_imported: i64,
+    pub bytes_archived: i64,
+    pub backend_total: Option<i64>,
+}
+
+// WHERE clause shared by all local queries; parameters are ?1..?3
+const SCOPE_FILTER: &str = "photo_id IS NOT NULL
+    AND (?1 IS NULL OR input_channel_id = ?1)
+    AND (?2 IS NULL OR taken_at >= ?2)
+    AND (?3 IS NULL OR taken_at <= ?3)";
+
+fn local_stats(history: &History, scope: &StatsScope) -> Result<LibraryStats, ImalinkError> {
+    // A bare date as upper bound should include the whole day
+    let to = scope.to.as_ref().map(|to| if to.len() == 10 { format!("{}T23:59:59", to) } else { to.clone() });
+    let filter = params![scope.input_channel_id, scope.from, to];
+
+    history.with(|conn| {
+        let mut stats = LibraryStats::default();
+        (stats.total_photos, stats.duplicates_skipped, stats.bytes_imported, stats.bytes_archived) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(duplicate_count), 0), COALESCE(SUM(bytes), 0),
+                        COALESCE(SUM(archived_bytes), 0)
+                 FROM photos WHERE {}",
+                SCOPE_FILTER
+            ),
+            filter,
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+
+        let periods = |len: usize| -> rusqlite::Result<Vec<PeriodCount>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT substr(taken_at, 1, {len}) AS period, COUNT(*) FROM photos
+                 WHERE taken_at IS NOT NULL AND {SCOPE_FILTER}
+                 GROUP BY period ORDER BY period"
+            ))?;
+            let rows = stmt.query_map(filter, |row| Ok(PeriodCount { period: row.get(0)?, count: row.get(1)? }))?;
+            rows.collect()
+        };
+        stats.per_year = periods(4)?;
+        stats.per_month = periods(7)?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT COALESCE(camera, 'Unknown') AS cam, COUNT(*) FROM photos
+             WHERE {} GROUP BY cam ORDER BY COUNT(*) DESC",
+            SCOPE_FILTER
+        ))?;
+        stats.per_camera = stmt
+            .query_map(filter, |row| Ok(CameraCount { camera: row.get(0)?, count: row.get(1)? }))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT input_channel_id, COUNT(*) FROM photos
+             WHERE input_channel_id IS NOT NULL AND {} GROUP BY input_channel_id ORDER BY input_channel_id",
+            SCOPE_FILTER
+        ))?;
+        stats.per_channel = stmt
+            .query_map(filter, |row| {
+                Ok(ChannelStats {
+                    input_channel_id: row.get(0)?,
+                    title: None,
+                    local_count: row.get(1)?,
+                    backend_count: None,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(stats)
+    })
+}
+
+// Photo statistics for the dashboard, optionally restricted to a channel
+// and/or capture date range
+#[tauri::command]
+pub async fn get_library_stats(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, History>,
+    scope: Option<StatsScope>,
+) -> Result<LibraryStats, ImalinkError> {
+    let scope = scope.unwrap_or_default();
+    let mut stats = local_stats(&history, &scope)?;
+
+    if let (Some(backend_url), Some(auth_token)) = (&scope.backend_url, &scope.auth_token) {
+        let channels = crate::list_input_channels(app, Some(backend_url.clone()), Some(auth_token.clone()), None, None, None).await?;
+        for channel in channels {
+            if scope.input_channel_id.is_some_and(|id| id != channel.id) {
+                continue;
+            }
+            match stats.per_channel.iter_mut().find(|c| c.input_channel_id == channel.id) {
+                Some(local) => {
+                    local.title = channel.title.clone();
+                    local.backend_count = Some(channel.images_count);
+                }
+                None => stats.per_channel.push(ChannelStats {
+                    input_channel_id: channel.id,
+                    title: channel.title.clone(),
+                    local_count: 0,
+                    backend_count: Some(channel.images_count),
+                }),
+            }
+        }
+        stats.backend_total = Some(stats.per_channel.iter().filter_map(|c| c.backend_count).map(i64::from).sum());
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,209 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::error::ImalinkError;
+
+// ===== Streaming File I/O =====
+//
+// Hashing, copying and uploading work on fixed-size chunks instead of reading
+// whole files into memory, so importing 100 MB RAWs with several workers per
+// stage keeps RSS flat. Chunk buffers come from a small shared pool and go
+// back to it when dropped, so steady-state imports don't allocate per file.
+
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+// Buffers kept around for reuse; anything beyond this is freed
+const MAX_POOLED: usize = 16;
+
+static POOL: OnceLock<Mutex<Vec<Vec<u8>>>> = OnceLock::new();
+
+fn pool() -> &'static Mutex<Vec<Vec<u8>>> {
+    POOL.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// A CHUNK_SIZE buffer borrowed from the pool
+pub struct PooledBuffer(Vec<u8>);
+
+impl PooledBuffer {
+    pub fn acquire() -> Self {
+        let buffer = pool().lock().ok().and_then(|mut buffers| buffers.pop());
+        PooledBuffer(buffer.unwrap_or_else(|| vec![0; CHUNK_SIZE]))
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Ok(mut buffers) = pool().lock() {
+            if buffers.len() < MAX_POOLED {
+                buffers.push(std::mem::take(&mut self.0));
+            }
+        }
+    }
+}
+
+// BLAKE3 content hash of a file, read chunk by chunk
+pub fn hash_file(path: &str) -> Result<String, ImalinkError> {
+    let mut file = fs::File::open(path).map_err(|e| ImalinkError::io(path, e))?;
+    let mut buffer = PooledBuffer::acquire();
+    let mut hasher = blake3::Hasher::new();
+    loop {
+        let n = file.read(&mut buffer).map_err(|e| ImalinkError::io(path, e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+// Copy a file chunk by chunk, keeping its permissions. A partially written
+// destination is removed on failure. Returns the number of bytes copied.
+pub fn copy_file(source: &str, dest: &Path) -> Result<u64, ImalinkError> {
+    copy_hashing(source, dest, None)
+}
+
+// copy_file into a destination that must not exist yet, so an archived
+// original is never overwritten: an existing one is DestinationExists
+pub fn copy_new(source: &str, dest: &Path) -> Result<u64, ImalinkError> {
+    let writer = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dest)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => ImalinkError::DestinationExists { path: dest.display().to_string() },
+            _ => ImalinkError::io(dest.display(), e),
+        })?;
+    write_copy(source, dest, writer, None)
+}
+
+// copy_file, feeding the source bytes to `hasher` on the way
+fn copy_hashing(source: &str, dest: &Path, hasher: Option<&mut blake3::Hasher>) -> Result<u64, ImalinkError> {
+    let writer = fs::File::create(dest).map_err(|e| ImalinkError::io(source, e))?;
+    write_copy(source, dest, writer, hasher)
+}
+
+fn write_copy(
+    source: &str,
+    dest: &Path,
+    mut writer: fs::File,
+    mut hasher: Option<&mut blake3::Hasher>,
+) -> Result<u64, ImalinkError> {
+    let mut copy = || -> std::io::Result<u64> {
+        let mut reader = fs::File::open(source)?;
+        let mut buffer = PooledBuffer::acquire();
+        let mut copied = 0u64;
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..n])?;
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.update(&buffer[..n]);
+            }
+            copied += n as u64;
+        }
+        writer.flush()?;
+        fs::set_permissions(dest, reader.metadata()?.permissions())?;
+        Ok(copied)
+    };
+
+    copy().map_err(|e| {
+        let _ = fs::remove_file(dest);
+        ImalinkError::io(source, e)
+    })
+}
+
+// Copy a file and read the copy back: the BLAKE3 hash of the source (taken
+// while copying) must match that of the destination. A differing copy is
+// removed and reported as CopyMismatch. Returns the hash.
+pub fn copy_verified(source: &str, dest: &Path) -> Result<String, ImalinkError> {
+    let mut hasher = blake3::Hasher::new();
+    copy_hashing(source, dest, Some(&mut hasher))?;
+    let source_hash = hasher.finalize().to_hex().to_string();
+    let dest_hash = hash_file(&dest.to_string_lossy()).inspect_err(|_| {
+        let _ = fs::remove_file(dest);
+    })?;
+    if dest_hash != source_hash {
+        let _ = fs::remove_file(dest);
+        return Err(ImalinkError::CopyMismatch {
+            source: source.to_string(),
+            destination: dest.display().to_string(),
+        });
+    }
+    Ok(source_hash)
+}
+
+// Outcome of move_file
+pub struct Moved {
+    // Taken when the file had to be copied
+    pub hash: Option<String>,
+    // False when the source could not be deleted after copying it
+    pub source_removed: bool,
+}
+
+// Move a file: a rename when source and destination share a filesystem,
+// else copy_verified before the source is deleted. A source that can't be
+// deleted (write-protected card) stays, reported as not removed; the copy is
+// good either way.
+pub fn move_file(source: &str, dest: &Path) -> Result<Moved, ImalinkError> {
+    if fs::rename(source, dest).is_ok() {
+        return Ok(Moved { hash: None, source_removed: true });
+    }
+
+    let hash = copy_verified(source, dest)?;
+    let source_removed = match fs::remove_file(source) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Copied {} to {} but could not remove the source: {}", source, dest.display(), e);
+            false
+        }
+    };
+    Ok(Moved { hash: Some(hash), source_removed })
+}
+
+// Multipart part that streams the file from disk as the request is sent
+pub async fn file_part(path: &str) -> Result<reqwest::multipart::Part, ImalinkError> {
+    let file = tokio::fs::File::open(path).await.map_err(|e| ImalinkError::io(path, e))?;
+    let length = file.metadata().await.map_err(|e| ImalinkError::io(path, e))?.len();
+    let file_name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| ImalinkError::invalid(format!("Invalid filename: {}", path)))?
+        .to_string();
+
+    let stream = futures_util::stream::unfold(
+        (file, PooledBuffer::acquire()),
+        |(mut file, mut buffer)| async move {
+            use tokio::io::AsyncReadExt;
+            match file.read(&mut buffer).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    let chunk = buffer[..n].to_vec();
+                    Some((Ok::<_, std::io::Error>(chunk), (file, buffer)))
+                }
+                Err(e) => Some((Err(e), (file, buffer))),
+            }
+        },
+    );
+
+    Ok(reqwest::multipart::Part::stream_with_length(reqwest::Body::wrap_stream(stream), length)
+        .file_name(file_name))
+}
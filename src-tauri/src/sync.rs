@@ -0,0 +1,244 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::existence::ExistenceCache;
+use crate::guest;
+use crate::http::{HttpClient, SendPaced};
+use crate::history::{History, HistoryPhoto};
+use crate::operations::{OperationKind, Operations};
+use crate::session::Session;
+
+// ===== Metadata Sync =====
+//
+// Two-way sync of rating/visibility between the backend and local history.
+//
+// Remote → local: the backend's change feed is read from the cursor saved
+// after the previous sync, so only photos edited since then are fetched.
+// Local → remote: rows marked dirty by local edits are PATCHed up; rows
+// without a backend photo id (edited before upload) stay dirty until the
+// photo has been uploaded.
+//
+// When a photo changed on both sides, the most recent edit wins and the
+// conflict is listed in the report so the user can see what was overwritten.
+
+const REMOTE_CURSOR: &str = "remote_cursor";
+const LAST_SYNC: &str = "last_sync_at";
+
+#[derive(Debug, Deserialize)]
+struct RemoteChange {
+    id: i32,
+    hothash: String,
+    rating: Option<i32>,
+    visibility: Option<String>,
+    updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangesResponse {
+    changes: Vec<RemoteChange>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictWinner {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SyncConflict {
+    pub hothash: String,
+    pub winner: ConflictWinner,
+    pub local_rating: Option<i32>,
+    pub remote_rating: Option<i32>,
+    pub local_visibility: Option<String>,
+    pub remote_visibility: Option<String>,
+    pub local_modified_at: Option<String>,
+    pub remote_modified_at: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SyncReport {
+    // Remote changes applied locally
+    pub pulled: usize,
+    // Local edits sent to the backend; skipped ones aren't uploaded yet
+    pub pushed: BatchResult<String>,
+    pub conflicts: Vec<SyncConflict>,
+    pub synced_at: String,
+}
+
+async fn fetch_changes(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    cursor: Option<&str>,
+) -> Result<ChangesResponse, ImalinkError> {
+    let mut request = client
+        .get(format!("{}/api/v1/photos/changes", backend_url))
+        .header("Authorization", format!("Bearer {}", auth_token));
+    if let Some(cursor) = cursor {
+        request = request.query(&[("since", cursor)]);
+    }
+
+    let response = request.send_paced().await.map_err(|e| ImalinkError::network(backend_url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    Ok(response.json().await?)
+}
+
+// Apply one remote change, returning the conflict if the photo was also edited locally
+fn apply_remote(history: &History, change: &RemoteChange) -> Result<Option<SyncConflict>, ImalinkError> {
+    let local = history.get(&change.hothash)?;
+
+    let conflict = local.as_ref().filter(|l| l.dirty).map(|l| {
+        // Compared as instants: the two sides write different offsets and
+        // precisions. A local time that can't be read counts as older.
+        let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+        let local_newer = match (l.local_modified_at.as_deref().and_then(parse), parse(&change.updated_at)) {
+            (Some(local), Some(remote)) => local > remote,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        SyncConflict {
+            hothash: change.hothash.clone(),
+            winner: if local_newer { ConflictWinner::Local } else { ConflictWinner::Remote },
+            local_rating: l.rating,
+            remote_rating: change.rating,
+            local_visibility: l.visibility.clone(),
+            remote_visibility: change.visibility.clone(),
+            local_modified_at: l.local_modified_at.clone(),
+            remote_modified_at: change.updated_at.clone(),
+        }
+    });
+
+    let local_wins = conflict.as_ref().is_some_and(|c| c.winner == ConflictWinner::Local);
+    history.with(|conn| {
+        if local_wins {
+            // Keep the local values (still dirty, pushed below) but remember the backend id
+            conn.execute(
+                "UPDATE photos SET photo_id = ?2, remote_modified_at = ?3 WHERE hothash = ?1",
+                params![change.hothash, change.id, change.updated_at],
+            )
+        } else {
+            conn.execute(
+                "INSERT INTO photos (hothash, photo_id, rating, visibility, remote_modified_at, dirty)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 0)
+                 ON CONFLICT(hothash) DO UPDATE SET
+                    photo_id = excluded.photo_id,
+                    rating = excluded.rating,
+                    visibility = excluded.visibility,
+                    remote_modified_at = excluded.remote_modified_at,
+                    dirty = 0",
+                params![change.hothash, change.id, change.rating, change.visibility, change.updated_at],
+            )
+        }
+        .map(|_| ())
+    })?;
+
+    Ok(conflict)
+}
+
+async fn push_local(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    photo: &HistoryPhoto,
+    photo_id: i32,
+) -> Result<Option<String>, ImalinkError> {
+    let response = client
+        .patch(format!("{}/api/v1/photos/{}", backend_url, photo_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({
+            "rating": photo.rating,
+            "visibility": photo.visibility,
+        }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    Ok(body.get("updated_at").and_then(|u| u.as_str()).map(|u| u.to_string()))
+}
+
+// Pull remote metadata changes since the last sync, then push local edits
+#[tauri::command]
+pub async fn sync_now(
+    app: tauri::AppHandle,
+    session: tauri::State<'_, Session>,
+    http: tauri::State<'_, HttpClient>,
+    history: tauri::State<'_, History>,
+    operations: tauri::State<'_, Operations>,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<SyncReport, ImalinkError> {
+    guest::require_owner(&app, "upload metadata changes")?;
+    let existence = app.state::<ExistenceCache>();
+    let (backend_url, auth_token) = session.credentials(backend_url, auth_token)?;
+    let operation = operations.register(OperationKind::Sync, "Sync metadata").cancellable();
+    let client = http.client();
+    let mut report = SyncReport::default();
+
+    // Remote → local
+    let cursor = history.sync_value(REMOTE_CURSOR)?;
+    let changes = fetch_changes(&client, &backend_url, &auth_token, cursor.as_deref()).await?;
+    for change in &changes.changes {
+        if let Some(conflict) = apply_remote(&history, change)? {
+            report.conflicts.push(conflict);
+        }
+        existence.record(&backend_url, &change.hothash, Some(change.id));
+        report.pulled += 1;
+    }
+    if let Some(cursor) = &changes.cursor {
+        history.set_sync_value(REMOTE_CURSOR, cursor)?;
+    }
+
+    // Local → remote; what a cancel leaves out stays dirty for the next sync
+    let dirty = history.dirty_photos()?;
+    operation.set_total(dirty.len() as u64);
+    for photo in dirty {
+        if operation.is_cancelled() {
+            break;
+        }
+        operation.advance();
+        let Some(photo_id) = photo.photo_id else {
+            report.pushed.skip(photo.hothash.clone(), "Not uploaded yet");
+            continue;
+        };
+        match push_local(&client, &backend_url, &auth_token, &photo, photo_id).await {
+            Ok(updated_at) => {
+                history.with(|conn| {
+                    conn.execute(
+                        "UPDATE photos SET dirty = 0, remote_modified_at = COALESCE(?2, remote_modified_at)
+                         WHERE hothash = ?1 AND local_modified_at IS ?3",
+                        params![photo.hothash, updated_at, photo.local_modified_at],
+                    )
+                    .map(|_| ())
+                })?;
+                report.pushed.succeed(photo.hothash);
+            }
+            Err(e) => {
+                // Removed on the backend since it was last seen there
+                if matches!(e, ImalinkError::Backend { status: 404, .. }) {
+                    existence.forget(std::slice::from_ref(&photo.hothash));
+                }
+                report.pushed.fail(photo.hothash, e)
+            }
+        }
+    }
+
+    report.synced_at = chrono::Utc::now().to_rfc3339();
+    history.set_sync_value(LAST_SYNC, &report.synced_at)?;
+    Ok(report)
+}
@@ -0,0 +1,238 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+use tokio::io::AsyncBufReadExt;
+
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::operations::{self, OperationKind};
+use crate::session::Session;
+use crate::{pipeline, prefetch, presets, staging};
+
+// ===== Tethered Capture =====
+//
+// Listens to a camera connected over USB in tethered (PTP) mode through the
+// gphoto2 command line tool, which must be installed and on PATH:
+//
+//   gphoto2 --capture-tethered --keep --filename <staging>/%f.%C
+//
+// gphoto2 downloads every frame as soon as the shutter fires and prints
+// "Saving file as <path>". Each frame is sent through core right away so its
+// previews are in the preview store, announced with a "tether-frame" event
+// (the UI can show imalink-preview://localhost/hot/<hothash> immediately),
+// and then imported with the session's preset on its own.
+//
+// One tethering session at a time; stop_tethering ends it.
+
+const SAVED_PREFIX: &str = "Saving file as ";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TetherStatus {
+    pub id: String,
+    pub preset: String,
+    pub staging_dir: String,
+    pub started_at: String,
+    pub frames: usize,
+    pub failed: usize,
+    pub running: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TetherFrame {
+    pub tether_id: String,
+    // 1-based frame number within the tethering session
+    pub seq: usize,
+    pub file_path: String,
+    pub hothash: Option<String>,
+    // Import session uploading the frame
+    pub session_id: Option<String>,
+    pub error: Option<String>,
+}
+
+struct TetherSession {
+    status: Arc<Mutex<TetherStatus>>,
+    stop: tokio::sync::oneshot::Sender<()>,
+}
+
+// End the running session, if any
+fn end_session(tethering: &Tethering) -> Result<Option<TetherStatus>, ImalinkError> {
+    let Some(session) = tethering.lock()?.take() else { return Ok(None) };
+    let _ = session.stop.send(());
+    if let Ok(mut s) = session.status.lock() {
+        s.running = false;
+    }
+    Ok(snapshot(&session.status))
+}
+
+#[derive(Default)]
+pub struct Tethering(Mutex<Option<TetherSession>>);
+
+impl Tethering {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Option<TetherSession>>, ImalinkError> {
+        self.0
+            .lock()
+            .map_err(|_| ImalinkError::internal("Tethering lock poisoned"))
+    }
+}
+
+fn snapshot(status: &Arc<Mutex<TetherStatus>>) -> Option<TetherStatus> {
+    status.lock().ok().map(|s| s.clone())
+}
+
+// Process and import one downloaded frame
+async fn ingest_frame(
+    app: &tauri::AppHandle,
+    preset: &presets::ImportPreset,
+    auth_token: &str,
+    tether_id: &str,
+    seq: usize,
+    file_path: String,
+) -> TetherFrame {
+    let mut frame = TetherFrame {
+        tether_id: tether_id.to_string(),
+        seq,
+        file_path: file_path.clone(),
+        hothash: None,
+        session_id: None,
+        error: None,
+    };
+
+    let client = crate::http::client(app);
+    let processed = prefetch::prefetch_one(app, &client, &preset.core_api_url, &file_path).await;
+    let imported = processed.and_then(|(schema, _)| {
+        frame.hothash = Some(schema.hothash);
+        // Already cached by the step above, so the import only uploads
+        let source_dir = std::path::Path::new(&file_path)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let options = preset.import_options(&source_dir, Some(vec![file_path.clone()]), auth_token);
+        pipeline::spawn_import(app, options)
+    });
+    match imported {
+        Ok(session_id) => frame.session_id = Some(session_id),
+        Err(e) => frame.error = Some(e.to_string()),
+    }
+    frame
+}
+
+// Start listening for captures from the connected camera (or the one on
+// `port`, e.g. "usb:001,004", when several are attached)
+#[tauri::command]
+pub async fn start_tethering(
+    app: tauri::AppHandle,
+    tethering: tauri::State<'_, Tethering>,
+    preset: String,
+    auth_token: Option<String>,
+    port: Option<String>,
+) -> Result<TetherStatus, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let mut current = tethering.lock()?;
+    if current.as_ref().is_some_and(|s| !s.stop.is_closed()) {
+        return Err(ImalinkError::invalid("A tethering session is already running"));
+    }
+
+    let preset = presets::find(&app, &preset)?;
+    let id = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let workspace = staging::workspace(&app, "tether", &id)?;
+    let pattern = workspace.join("%f.%C").to_string_lossy().to_string();
+
+    let mut command = tokio::process::Command::new("gphoto2");
+    command.args(["--capture-tethered", "--keep", "--filename", &pattern]);
+    if let Some(port) = &port {
+        command.args(["--port", port]);
+    }
+    let mut child = command
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                ImalinkError::invalid("gphoto2 not found - install gphoto2 to use tethered capture")
+            }
+            _ => ImalinkError::io("gphoto2", e),
+        })?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ImalinkError::internal("gphoto2 has no stdout"))?;
+
+    let status = Arc::new(Mutex::new(TetherStatus {
+        id: id.clone(),
+        preset: preset.name.clone(),
+        staging_dir: workspace.to_string_lossy().to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+        frames: 0,
+        failed: 0,
+        running: true,
+    }));
+    let (stop, mut stopped) = tokio::sync::oneshot::channel();
+    *current = Some(TetherSession { status: status.clone(), stop });
+    let initial = snapshot(&status).ok_or_else(|| ImalinkError::internal("Tethering status lock poisoned"))?;
+
+    let stop_app = app.clone();
+    let operation = operations::register_with_cancel(&app, OperationKind::Tether, format!("Tethering ({})", preset.name), move || {
+        let _ = end_session(&stop_app.state::<Tethering>());
+    });
+
+    let task_status = status.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        loop {
+            let line = tokio::select! {
+                _ = &mut stopped => break,
+                line = lines.next_line() => line,
+            };
+            let Ok(Some(line)) = line else { break };
+            let Some(path) = line.trim().strip_prefix(SAVED_PREFIX) else { continue };
+
+            let seq = match task_status.lock() {
+                Ok(mut s) => {
+                    s.frames += 1;
+                    s.frames
+                }
+                Err(_) => break,
+            };
+            operation.advance();
+            // Frames are ingested concurrently so a slow upload never holds up the next shot
+            let (app, preset, auth_token, id, status) =
+                (app.clone(), preset.clone(), auth_token.clone(), id.clone(), task_status.clone());
+            let path = path.to_string();
+            tauri::async_runtime::spawn(async move {
+                let frame = ingest_frame(&app, &preset, &auth_token, &id, seq, path).await;
+                if frame.error.is_some() {
+                    if let Ok(mut s) = status.lock() {
+                        s.failed += 1;
+                    }
+                }
+                let _ = app.emit("tether-frame", frame);
+            });
+        }
+
+        let _ = child.kill().await;
+        if let Ok(mut s) = task_status.lock() {
+            s.running = false;
+        }
+        if let Some(status) = snapshot(&task_status) {
+            let _ = app.emit("tether-stopped", status);
+        }
+    });
+
+    Ok(initial)
+}
+
+#[tauri::command]
+pub fn stop_tethering(
+    app: tauri::AppHandle,
+    tethering: tauri::State<'_, Tethering>,
+) -> Result<Option<TetherStatus>, ImalinkError> {
+    guest::require_owner(&app, "tether")?;
+    end_session(&tethering)
+}
+
+#[tauri::command]
+pub fn get_tethering_status(tethering: tauri::State<'_, Tethering>) -> Result<Option<TetherStatus>, ImalinkError> {
+    Ok(tethering.lock()?.as_ref().and_then(|s| snapshot(&s.status)))
+}
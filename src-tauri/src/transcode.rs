@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::ImalinkError;
+
+// ===== Preview Transcoding =====
+//
+// Scans of prints and exports from editors often come as 16-bit TIFF or JPEG
+// XL, with no JPEG next to them. Core and the web gallery can't make
+// previews of those, so a group whose master is such a file gets a JPEG
+// derivative first: core processes the derivative, and the hotpreview,
+// coldpreview and hothash of the photo come from it. The original stays the
+// master in image_file_list - with its own name, size and format, and
+// `transcoded_preview` set in its storage info - and is archived as usual.
+// The derivative only lives in the session's workspace and goes with it.
+//
+// Transcoding runs an external tool configured in settings.json
+// (`transcoder`), ImageMagick by default. Its arguments may use {input} and
+// {output}.
+
+const DERIVATIVE_EXTENSION: &str = "jpg";
+
+// Masters the gallery can't display
+const TRANSCODE_EXTENSIONS: &[&str] = &["tif", "tiff", "jxl"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Transcoder {
+    pub program: String,
+    #[serde(default = "default_args")]
+    pub args: Vec<String>,
+}
+
+fn default_args() -> Vec<String> {
+    ["{input}", "-auto-orient", "-colorspace", "sRGB", "-depth", "8", "-quality", "92", "{output}"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+impl Default for Transcoder {
+    fn default() -> Self {
+        Transcoder {
+            program: "magick".to_string(),
+            args: default_args(),
+        }
+    }
+}
+
+pub fn needs_transcode(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| TRANSCODE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// Path of the derivative of `original` in `workspace`, unique so same-named
+// files from different folders don't collide
+pub fn output_path(original: &str, workspace: &Path) -> PathBuf {
+    let stem = Path::new(original)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    workspace.join(format!("{}_{}.{}", stem, uuid::Uuid::new_v4().simple(), DERIVATIVE_EXTENSION))
+}
+
+// Run the transcoder on `original`, writing the JPEG `output`
+pub async fn transcode(transcoder: &Transcoder, original: &str, output: &Path) -> Result<(), ImalinkError> {
+    let output_str = output.to_string_lossy().to_string();
+    let args: Vec<String> = transcoder
+        .args
+        .iter()
+        .map(|arg| arg.replace("{input}", original).replace("{output}", &output_str))
+        .collect();
+
+    let result = tokio::process::Command::new(&transcoder.program)
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ImalinkError::invalid(format!(
+                "{} not found - install ImageMagick or fix transcoder in settings",
+                transcoder.program
+            )),
+            _ => ImalinkError::io(&transcoder.program, e),
+        })?;
+    if !result.status.success() {
+        return Err(ImalinkError::invalid(format!(
+            "Transcoding {} failed: {}",
+            original,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+    if !output.is_file() {
+        return Err(ImalinkError::invalid(format!(
+            "{} did not write {} - check the transcoder arguments",
+            transcoder.program, output_str
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use rusqlite::params;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::history::History;
+use crate::pipeline::{ImportSessions, SessionStatus};
+use crate::{guest, streaming};
+
+// ===== Undo Archive Copies =====
+//
+// Every file an import session writes into storage is recorded in the
+// `session_files` table of the history database, with the BLAKE3 checksum
+// of the copy and whether the source was moved rather than copied. When a
+// destination template turns out to be wrong, undo_session_copies takes the
+// session's files back out of storage, newest first:
+//
+//   - a copy whose checksum no longer matches was changed after the import
+//     and is kept (reported as skipped), as is one that is already gone
+//   - a moved file is first put back at its source, unless something else
+//     has taken that place since
+//   - directories left empty are removed, up to the destination root
+//
+// The photos stay on the backend; only the local archive is rolled back.
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UndoneFile {
+    pub path: String,
+    // Where a moved file was put back
+    pub restored_to: Option<String>,
+}
+
+struct SessionFile {
+    id: i64,
+    source: String,
+    dest: String,
+    checksum: String,
+    moved: bool,
+    root: Option<String>,
+}
+
+// Record a file the session has written into storage. Failures are logged:
+// an unrecorded copy can't be undone, but the import itself is fine.
+pub fn record(app: &tauri::AppHandle, session_id: &str, source: &str, dest: &Path, root: Option<&str>, moved: bool) {
+    let dest = dest.to_string_lossy().to_string();
+    let recorded = streaming::hash_file(&dest).and_then(|checksum| {
+        app.state::<History>().with(|conn| {
+            conn.execute(
+                "INSERT INTO session_files (session_id, source, dest, checksum, moved, root, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![session_id, source, dest, checksum, moved, root, chrono::Utc::now().to_rfc3339()],
+            )
+        })
+    });
+    if let Err(e) = recorded {
+        eprintln!("Failed to record {} for undo: {}", dest, e);
+    }
+}
+
+fn session_files(history: &History, session_id: &str) -> Result<Vec<SessionFile>, ImalinkError> {
+    history.with(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, source, dest, checksum, moved, root FROM session_files
+             WHERE session_id = ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([session_id], |row| {
+            Ok(SessionFile {
+                id: row.get(0)?,
+                source: row.get(1)?,
+                dest: row.get(2)?,
+                checksum: row.get(3)?,
+                moved: row.get(4)?,
+                root: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+// Remove the directories `removed` leaves empty, stopping at `root`
+fn prune_empty_dirs(removed: &Path, root: Option<&str>) {
+    let Some(root) = root.map(Path::new) else { return };
+    let mut dir = removed.parent();
+    while let Some(current) = dir.filter(|d| d.starts_with(root) && *d != root) {
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+enum Undo {
+    Done(UndoneFile),
+    Kept(String),
+}
+
+fn undo_file(file: &SessionFile) -> Result<Undo, ImalinkError> {
+    let dest = Path::new(&file.dest);
+    if !dest.is_file() {
+        return Ok(Undo::Kept("No longer in storage".to_string()));
+    }
+    if streaming::hash_file(&file.dest)? != file.checksum {
+        return Ok(Undo::Kept("Changed since the import".to_string()));
+    }
+
+    let mut restored_to = None;
+    if file.moved {
+        let source = Path::new(&file.source);
+        if source.exists() {
+            return Ok(Undo::Kept(format!("{} exists again, not restoring over it", file.source)));
+        }
+        if let Some(parent) = source.parent() {
+            fs::create_dir_all(parent).map_err(|e| ImalinkError::io(parent.display(), e))?;
+        }
+        streaming::copy_file(&file.dest, source)?;
+        restored_to = Some(file.source.clone());
+    }
+    fs::remove_file(dest).map_err(|e| ImalinkError::io(dest.display(), e))?;
+    prune_empty_dirs(dest, file.root.as_deref());
+    Ok(Undo::Done(UndoneFile { path: file.dest.clone(), restored_to }))
+}
+
+fn undo(history: &History, session_id: &str) -> Result<BatchResult<UndoneFile>, ImalinkError> {
+    let files = session_files(history, session_id)?;
+    if files.is_empty() {
+        return Err(ImalinkError::invalid(format!("Import session {} has no archive copies to undo", session_id)));
+    }
+
+    let mut result = BatchResult::new();
+    for file in files {
+        match undo_file(&file) {
+            Ok(Undo::Done(undone)) => {
+                history.with(|conn| {
+                    conn.execute("DELETE FROM session_files WHERE id = ?1", [file.id])?;
+                    conn.execute("DELETE FROM stored_files WHERE path = ?1", [&file.dest])
+                })?;
+                result.succeed(undone);
+            }
+            Ok(Undo::Kept(reason)) => result.skip(file.dest, reason),
+            Err(e) => result.fail(file.dest, e),
+        }
+    }
+    Ok(result)
+}
+
+// Remove the files an import session copied into storage and put moved
+// sources back
+#[tauri::command]
+pub async fn undo_session_copies(
+    app: tauri::AppHandle,
+    session_id: String,
+) -> Result<BatchResult<UndoneFile>, ImalinkError> {
+    guest::require_owner(&app, "undo imports")?;
+    if app.state::<ImportSessions>().status(&session_id) == Some(SessionStatus::Running) {
+        return Err(ImalinkError::invalid(format!("Import session {} is still running", session_id)));
+    }
+    tauri::async_runtime::spawn_blocking(move || undo(&app.state::<History>(), &session_id))
+        .await
+        .map_err(|e| ImalinkError::internal(format!("Undo failed: {}", e)))?
+}
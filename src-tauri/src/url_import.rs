@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::invocations::{self, CallOptions};
+use crate::session::Session;
+use crate::{pipeline, presets, workspace};
+
+// ===== Import from URL =====
+//
+// Images are downloaded into a per-import folder and then imported through
+// the normal pipeline with the given preset. In register mode that folder is
+// under the app data dir and kept, since it is where the files live; in copy
+// mode it is a temporary workspace (workspace.rs), removed once the import
+// completes.
+// Only image content types are accepted, and the first bytes must match the
+// declared type, so an HTML error page served with status 200 isn't
+// imported as a photo. The source URL is recorded in `imported_info`.
+
+const WORKSPACE_DIR: &str = "url_imports";
+
+// Largest download accepted
+const MAX_DOWNLOAD_BYTES: u64 = 500 * 1024 * 1024;
+
+// Bytes looks_like needs to tell the types apart
+const SNIFF_BYTES: usize = 12;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DownloadedFile {
+    pub url: String,
+    pub path: String,
+    pub content_type: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UrlImport {
+    // None when no URL could be downloaded
+    pub session_id: Option<String>,
+    pub downloads: BatchResult<DownloadedFile>,
+}
+
+// File extension for an accepted image content type
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/heic" | "image/heic-sequence" => Some("heic"),
+        "image/heif" | "image/heif-sequence" => Some("heif"),
+        "image/x-adobe-dng" | "image/dng" => Some("dng"),
+        _ => None,
+    }
+}
+
+// Check the first bytes against the declared type
+fn looks_like(extension: &str, head: &[u8]) -> bool {
+    match extension {
+        "jpg" => head.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "png" => head.starts_with(&[0x89, b'P', b'N', b'G']),
+        "heic" | "heif" => head.len() >= 12 && &head[4..8] == b"ftyp",
+        "dng" => head.starts_with(b"II*\0") || head.starts_with(b"MM\0*"),
+        _ => false,
+    }
+}
+
+// Safe local file name from the URL's last path segment
+fn file_name_for(url: &reqwest::Url, extension: &str, index: usize) -> String {
+    let stem = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .map(|name| Path::new(name).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default())
+        .map(|name| {
+            name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect::<String>()
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "download".to_string());
+    // Prefix keeps names unique when several URLs end in the same file name
+    format!("{:03}_{}.{}", index + 1, stem, extension)
+}
+
+async fn download(
+    client: &reqwest::Client,
+    url: &str,
+    workspace: &Path,
+    index: usize,
+) -> Result<DownloadedFile, ImalinkError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| ImalinkError::invalid(format!("Invalid URL {}: {}", url, e)))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(ImalinkError::invalid(format!("Only http(s) URLs can be imported: {}", url)));
+    }
+
+    let mut response = client.get(parsed.clone()).send().await.map_err(|e| ImalinkError::network(url, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::Backend { status: status.as_u16(), detail: error_text });
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or("").trim().to_lowercase())
+        .unwrap_or_default();
+    let extension = extension_for(&content_type)
+        .ok_or_else(|| ImalinkError::invalid(format!("Not a supported image type ({}): {}", content_type, url)))?;
+    if response.content_length().is_some_and(|len| len > MAX_DOWNLOAD_BYTES) {
+        return Err(ImalinkError::invalid(format!("Download too large: {}", url)));
+    }
+
+    let path = workspace.join(file_name_for(&parsed, extension, index));
+    let mut file = std::fs::File::create(&path).map_err(|e| ImalinkError::io(path.display(), e))?;
+    let mut bytes = 0u64;
+    let written = async {
+        // The start is held back until there is enough of it to check
+        let mut head = Some(Vec::with_capacity(SNIFF_BYTES));
+        let mismatch = || ImalinkError::invalid(format!("Content does not match {}: {}", content_type, url));
+        while let Some(chunk) = response.chunk().await.map_err(|e| ImalinkError::network(url, e))? {
+            bytes += chunk.len() as u64;
+            if bytes > MAX_DOWNLOAD_BYTES {
+                return Err(ImalinkError::invalid(format!("Download too large: {}", url)));
+            }
+            let Some(start) = head.as_mut() else {
+                file.write_all(&chunk).map_err(|e| ImalinkError::io(path.display(), e))?;
+                continue;
+            };
+            start.extend_from_slice(&chunk);
+            if start.len() >= SNIFF_BYTES {
+                if !looks_like(extension, start) {
+                    return Err(mismatch());
+                }
+                file.write_all(start).map_err(|e| ImalinkError::io(path.display(), e))?;
+                head = None;
+            }
+        }
+        if bytes == 0 {
+            return Err(ImalinkError::invalid(format!("Empty download: {}", url)));
+        }
+        // Shorter than SNIFF_BYTES altogether
+        if let Some(start) = head {
+            if !looks_like(extension, &start) {
+                return Err(mismatch());
+            }
+            file.write_all(&start).map_err(|e| ImalinkError::io(path.display(), e))?;
+        }
+        file.flush().map_err(|e| ImalinkError::io(path.display(), e))
+    }
+    .await;
+
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(DownloadedFile {
+        url: url.to_string(),
+        path: path.to_string_lossy().to_string(),
+        content_type,
+        bytes,
+    })
+}
+
+// Download one or more image URLs and import them with the given preset
+#[tauri::command]
+pub async fn import_from_url(
+    app: tauri::AppHandle,
+    urls: Vec<String>,
+    preset: String,
+    auth_token: Option<String>,
+    call: Option<CallOptions>,
+) -> Result<UrlImport, ImalinkError> {
+    guest::require_owner(&app, "import")?;
+    let auth_token = app.state::<Session>().token(auth_token)?;
+    let preset = presets::find(&app, &preset)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let temporary = preset.destination_dir.is_some();
+    let workspace: PathBuf = if temporary {
+        workspace::open(&app, &id)?
+    } else {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| ImalinkError::internal(format!("Failed to resolve data directory: {}", e)))?
+            .join(WORKSPACE_DIR)
+            .join(&id);
+        std::fs::create_dir_all(&dir).map_err(|e| ImalinkError::io(dir.display(), e))?;
+        dir
+    };
+
+    let discard = || {
+        if temporary {
+            workspace::release(&app, &id);
+        } else {
+            let _ = std::fs::remove_dir_all(&workspace);
+        }
+    };
+
+    let client = crate::http::client(&app);
+    let downloaded = invocations::run(&app, call, async {
+        let mut downloads = BatchResult::new();
+        for (index, url) in urls.iter().enumerate() {
+            downloads.record(url.clone(), download(&client, url, &workspace, index).await);
+        }
+        Ok(downloads)
+    })
+    .await;
+    let downloads = match downloaded {
+        Ok(downloads) => downloads,
+        Err(e) => {
+            discard();
+            return Err(e);
+        }
+    };
+
+    if downloads.succeeded.is_empty() {
+        discard();
+        return Ok(UrlImport { session_id: None, downloads });
+    }
+
+    let files: Vec<String> = downloads.succeeded.iter().map(|d| d.path.clone()).collect();
+    let mut options = preset.import_options(&workspace.to_string_lossy(), Some(files), &auth_token);
+    options.source_urls = downloads.succeeded.iter().map(|d| (d.path.clone(), d.url.clone())).collect();
+    options.workspace = temporary.then_some(id);
+    let session_id = pipeline::spawn_import(&app, options)?;
+
+    Ok(UrlImport {
+        session_id: Some(session_id),
+        downloads,
+    })
+}
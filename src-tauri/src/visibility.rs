@@ -0,0 +1,210 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::Manager;
+
+use crate::batch::BatchResult;
+use crate::error::ImalinkError;
+use crate::guest;
+use crate::history::History;
+use crate::http::SendPaced;
+use crate::pipeline::ImportSessions;
+use crate::session::Session;
+
+// ===== Visibility Promotion =====
+//
+// Imports upload everything as private. Once a batch has been reviewed, the
+// keepers are promoted in one go instead of photo by photo on the web:
+//
+//   1. preview_visibility_promotion resolves the selection - the photos a
+//      session uploaded (duplicates that were already on the backend are
+//      left out), or a list of hothashes - and summarizes what would change
+//      for the confirmation dialog
+//   2. promote_visibility PATCHes each photo on the backend and records the
+//      new visibility in local history, reporting per photo
+//
+// Visibility only goes up (private < space < authenticated < public);
+// photos already at the level or above are skipped, so promoting never
+// hides anything that was shared.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    Private,
+    Space,
+    Authenticated,
+    Public,
+}
+
+impl Visibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Private => "private",
+            Visibility::Space => "space",
+            Visibility::Authenticated => "authenticated",
+            Visibility::Public => "public",
+        }
+    }
+
+    // Unknown or unset counts as private, the upload default
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("space") => Visibility::Space,
+            Some("authenticated") => Visibility::Authenticated,
+            Some("public") => Visibility::Public,
+            _ => Visibility::Private,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PromotionSelection {
+    // Photos uploaded by an import session of this app run
+    Session { session_id: String },
+    Hothashes { hothashes: Vec<String> },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PromotionSummary {
+    pub level: Visibility,
+    // Photos that would change
+    pub to_promote: usize,
+    // Current visibility → count, of the photos that would change
+    pub current: BTreeMap<String, usize>,
+    pub already_visible: usize,
+    // Not uploaded yet, so nothing to promote
+    pub not_uploaded: Vec<String>,
+}
+
+struct Candidate {
+    hothash: String,
+    photo_id: Option<i32>,
+    current: Visibility,
+}
+
+fn candidates(
+    history: &History,
+    sessions: &ImportSessions,
+    selection: &PromotionSelection,
+) -> Result<Vec<Candidate>, ImalinkError> {
+    let hothashes: Vec<(String, Option<i32>)> = match selection {
+        PromotionSelection::Session { session_id } => sessions
+            .get(session_id)
+            .ok_or_else(|| ImalinkError::invalid(format!("Unknown import session: {}", session_id)))?
+            .result
+            .succeeded
+            .into_iter()
+            .filter(|p| !p.is_duplicate)
+            .map(|p| (p.hothash, Some(p.photo_id)))
+            .collect(),
+        PromotionSelection::Hothashes { hothashes } => hothashes.iter().map(|h| (h.clone(), None)).collect(),
+    };
+
+    hothashes
+        .into_iter()
+        .map(|(hothash, photo_id)| {
+            let local = history.get(&hothash)?;
+            Ok(Candidate {
+                photo_id: photo_id.or_else(|| local.as_ref().and_then(|p| p.photo_id)),
+                current: Visibility::parse(local.as_ref().and_then(|p| p.visibility.as_deref())),
+                hothash,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn preview_visibility_promotion(
+    history: tauri::State<'_, History>,
+    sessions: tauri::State<'_, ImportSessions>,
+    selection: PromotionSelection,
+    level: Visibility,
+) -> Result<PromotionSummary, ImalinkError> {
+    let mut summary = PromotionSummary {
+        level,
+        to_promote: 0,
+        current: BTreeMap::new(),
+        already_visible: 0,
+        not_uploaded: Vec::new(),
+    };
+    for candidate in candidates(&history, &sessions, &selection)? {
+        if candidate.photo_id.is_none() {
+            summary.not_uploaded.push(candidate.hothash);
+        } else if candidate.current >= level {
+            summary.already_visible += 1;
+        } else {
+            summary.to_promote += 1;
+            *summary.current.entry(candidate.current.as_str().to_string()).or_default() += 1;
+        }
+    }
+    Ok(summary)
+}
+
+async fn patch_visibility(
+    client: &reqwest::Client,
+    backend_url: &str,
+    auth_token: &str,
+    photo_id: i32,
+    level: Visibility,
+) -> Result<Option<String>, ImalinkError> {
+    let response = client
+        .patch(format!("{}/api/v1/photos/{}", backend_url, photo_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&serde_json::json!({ "visibility": level.as_str() }))
+        .send_paced()
+        .await
+        .map_err(|e| ImalinkError::network(backend_url, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ImalinkError::from_backend(status, error_text));
+    }
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    Ok(body.get("updated_at").and_then(|u| u.as_str()).map(|u| u.to_string()))
+}
+
+// Raise the visibility of the selected photos to `level`, per photo
+#[tauri::command]
+pub async fn promote_visibility(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, History>,
+    sessions: tauri::State<'_, ImportSessions>,
+    selection: PromotionSelection,
+    level: Visibility,
+    backend_url: Option<String>,
+    auth_token: Option<String>,
+) -> Result<BatchResult<String>, ImalinkError> {
+    guest::require_owner(&app, "change visibility")?;
+    let (backend_url, auth_token) = app.state::<Session>().credentials(backend_url, auth_token)?;
+    let client = crate::http::client(&app);
+    let mut result = BatchResult::new();
+
+    for candidate in candidates(&history, &sessions, &selection)? {
+        let Some(photo_id) = candidate.photo_id else {
+            result.skip(candidate.hothash, "Not uploaded yet");
+            continue;
+        };
+        if candidate.current >= level {
+            result.skip(candidate.hothash, format!("Already {}", candidate.current.as_str()));
+            continue;
+        }
+        match patch_visibility(&client, &backend_url, &auth_token, photo_id, level).await {
+            Ok(updated_at) => {
+                // Backend and history agree now, so the row is not dirty
+                history.with(|conn| {
+                    conn.execute(
+                        "UPDATE photos SET visibility = ?2, remote_modified_at = COALESCE(?3, remote_modified_at)
+                         WHERE hothash = ?1",
+                        params![candidate.hothash, level.as_str(), updated_at],
+                    )
+                    .map(|_| ())
+                })?;
+                result.succeed(candidate.hothash);
+            }
+            Err(e) => result.fail(candidate.hothash, e),
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::ImalinkError;
+use crate::progress::Stage;
+use crate::remote_core;
+
+// ===== Stage Watchdog =====
+//
+// Each item gets at most `stage_timeouts` (per stage) in a stage; one that
+// runs over is dropped and quarantined with `stage_stuck`, and the worker
+// moves on. Blocking work is left to finish on its thread. A stuck process
+// stage restarts the local sidecar, at most once per CORE_RESTART_COOLDOWN.
+
+// After a restart, how long to wait for core to answer again
+const CORE_START_WAIT: Duration = Duration::from_secs(30);
+const CORE_RESTART_COOLDOWN: Duration = Duration::from_secs(60);
+
+static LAST_CORE_RESTART: Mutex<Option<Instant>> = Mutex::new(None);
+
+// Seconds an item may spend in each stage; 0 = no limit
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StageTimeouts {
+    pub hash: u64,
+    pub lookup: u64,
+    pub process: u64,
+    // Uploads also have the stall detector (stall.rs); this is the limit
+    // for all of an item's attempts together
+    pub upload: u64,
+    pub copy: u64,
+}
+
+impl Default for StageTimeouts {
+    fn default() -> Self {
+        StageTimeouts {
+            hash: 300,
+            lookup: 120,
+            process: 600,
+            upload: 1800,
+            copy: 1800,
+        }
+    }
+}
+
+impl StageTimeouts {
+    fn of(&self, stage: Stage) -> Option<Duration> {
+        let seconds = match stage {
+            Stage::Hash => self.hash,
+            Stage::Lookup => self.lookup,
+            Stage::Process => self.process,
+            Stage::Upload => self.upload,
+            Stage::Copy => self.copy,
+        };
+        Some(Duration::from_secs(seconds)).filter(|d| !d.is_zero())
+    }
+}
+
+fn stage_name(stage: Stage) -> &'static str {
+    match stage {
+        Stage::Hash => "hash",
+        Stage::Lookup => "lookup",
+        Stage::Process => "process",
+        Stage::Upload => "upload",
+        Stage::Copy => "copy",
+    }
+}
+
+// Run `file`'s work in `stage`, giving up with StageStuck after the stage's
+// timeout
+pub async fn run<T>(
+    timeouts: &StageTimeouts,
+    stage: Stage,
+    file: &str,
+    work: impl Future<Output = Result<T, ImalinkError>>,
+) -> Result<T, ImalinkError> {
+    let Some(limit) = timeouts.of(stage) else {
+        return work.await;
+    };
+    match tokio::time::timeout(limit, work).await {
+        Ok(result) => result,
+        Err(_) => {
+            eprintln!("{} of {} took over {}s, abandoning it", stage_name(stage), file, limit.as_secs());
+            Err(ImalinkError::StageStuck {
+                stage: stage_name(stage).to_string(),
+                file: file.to_string(),
+                seconds: limit.as_secs(),
+            })
+        }
+    }
+}
+
+// Get the stage working again after an item got stuck in it
+pub async fn recover(app: &tauri::AppHandle, stage: Stage) {
+    if !matches!(stage, Stage::Process) || remote_core::is_remote() {
+        return;
+    }
+    {
+        let mut last = LAST_CORE_RESTART.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < CORE_RESTART_COOLDOWN) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+
+    eprintln!("imalink-core looks hung, restarting it");
+    crate::stop_core_server(app);
+    if let Err(e) = crate::start_core_server(app.clone()) {
+        eprintln!("Failed to restart imalink-core: {}", e);
+        return;
+    }
+    if !crate::wait_core_ready(CORE_START_WAIT).await {
+        eprintln!("imalink-core did not come back within {}s", CORE_START_WAIT.as_secs());
+    }
+}
@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::error::ImalinkError;
+use crate::guest::AccessMode;
+
+// ===== Temporary Workspaces =====
+//
+// Scratch space for work an import only needs while it runs - DNG
+// conversions before they are archived, URL downloads in copy mode - under
+// <app cache>/workspaces/<id>. Unlike staging (staging.rs), nothing in a
+// workspace is meant to outlive its import: the pipeline releases the
+// session's workspaces when it completes, and whatever is left over from a
+// crash is removed at the next start.
+//
+// All workspaces together are capped at `workspace_max_mb` (settings); a
+// workspace can't be opened while they use more than that.
+// purge_workspaces removes every workspace not in use by a running import.
+
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WorkspacePurge {
+    pub removed: usize,
+    pub bytes: u64,
+}
+
+// Managed state
+pub struct Workspaces {
+    dir: PathBuf,
+    max_bytes: AtomicU64,
+    // Workspaces handed out and not released yet
+    active: Mutex<HashSet<String>>,
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else { return 0 };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+// Only plain names, so an id can't point outside the workspaces folder
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl Workspaces {
+    // Open the workspaces folder, removing workspaces left behind by a crash
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        let workspaces = Workspaces {
+            dir,
+            max_bytes: AtomicU64::new(max_bytes),
+            active: Mutex::new(HashSet::new()),
+        };
+        let leftover = workspaces.purge();
+        if leftover.removed > 0 {
+            println!(
+                "Removed {} leftover workspaces ({} MB)",
+                leftover.removed,
+                leftover.bytes / MB
+            );
+        }
+        workspaces
+    }
+
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::SeqCst);
+    }
+
+    // Create (if needed) and return the workspace `id`, typically an import
+    // session id
+    pub fn open(&self, id: &str) -> Result<PathBuf, ImalinkError> {
+        if !valid_id(id) {
+            return Err(ImalinkError::invalid(format!("Invalid workspace id: {}", id)));
+        }
+        let max_bytes = self.max_bytes.load(Ordering::SeqCst);
+        let used = dir_size(&self.dir);
+        if used >= max_bytes {
+            return Err(ImalinkError::invalid(format!(
+                "Temporary workspaces use {} MB (limit {} MB) - purge them or raise workspace_max_mb",
+                used / MB,
+                max_bytes / MB
+            )));
+        }
+
+        let path = self.dir.join(id);
+        fs::create_dir_all(&path).map_err(|e| ImalinkError::io(path.display(), e))?;
+        self.active.lock().unwrap().insert(id.to_string());
+        Ok(path)
+    }
+
+    // Delete the workspace `id`, if there is one
+    pub fn release(&self, id: &str) {
+        if !valid_id(id) || !self.active.lock().unwrap().remove(id) {
+            return;
+        }
+        let path = self.dir.join(id);
+        if let Err(e) = fs::remove_dir_all(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Failed to remove workspace {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    // Remove every workspace that isn't active
+    fn purge(&self) -> WorkspacePurge {
+        let active = self.active.lock().unwrap().clone();
+        let mut purge = WorkspacePurge { removed: 0, bytes: 0 };
+        let Ok(entries) = fs::read_dir(&self.dir) else { return purge };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if active.contains(&name) {
+                continue;
+            }
+            let path = entry.path();
+            let bytes = dir_size(&path);
+            let removed = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+            match removed {
+                Ok(()) => {
+                    purge.removed += 1;
+                    purge.bytes += bytes;
+                }
+                Err(e) => eprintln!("Failed to remove workspace {}: {}", path.display(), e),
+            }
+        }
+        purge
+    }
+}
+
+// Workspace for an import session, see Workspaces::open
+pub fn open(app: &tauri::AppHandle, id: &str) -> Result<PathBuf, ImalinkError> {
+    app.state::<Workspaces>().open(id)
+}
+
+pub fn release(app: &tauri::AppHandle, id: &str) {
+    app.state::<Workspaces>().release(id);
+}
+
+// Remove all workspaces not used by a running import
+#[tauri::command]
+pub fn purge_workspaces(
+    access: tauri::State<'_, AccessMode>,
+    workspaces: tauri::State<'_, Workspaces>,
+) -> Result<WorkspacePurge, ImalinkError> {
+    access.require_owner("delete workspaces")?;
+    Ok(workspaces.purge())
+}
@@ -0,0 +1,167 @@
+// Import steps against the in-process mock backend/core (src/mock.rs):
+// scan → process → upload, then a re-import of the same files, which must
+// find them on the backend and come back as duplicates. And archiving into
+// storage, which must not overwrite one original with another, and uploads
+// to core, which must stream the file rather than read it up front.
+//
+//   cargo test --features mock
+
+#![cfg(feature = "mock")]
+
+use imalink_desktop_lib::harness::{self, ImalinkError};
+use std::path::{Path, PathBuf};
+
+// Quick Channel, seeded by the mock like on a fresh backend
+const CHANNEL_ID: i32 = 1;
+
+fn write_jpeg(path: &Path, shade: u8) {
+    let img = image::RgbImage::from_pixel(4, 3, image::Rgb([shade, shade, shade]));
+    img.save(path).unwrap();
+}
+
+// Two photos, one of them in a subfolder, and a file the scan must skip
+fn photo_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("imalink-mock-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("day2")).unwrap();
+    write_jpeg(&dir.join("IMG_0001.jpg"), 40);
+    write_jpeg(&dir.join("day2").join("IMG_0002.jpg"), 200);
+    std::fs::write(dir.join("notes.txt"), "not a photo").unwrap();
+    dir
+}
+
+// Same name, different photos, in two subfolders
+fn same_named_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("imalink-mock-{}", uuid::Uuid::new_v4()));
+    for (folder, shade) in [("card1", 40), ("card2", 200)] {
+        std::fs::create_dir_all(dir.join(folder)).unwrap();
+        write_jpeg(&dir.join(folder).join("IMG_0001.jpg"), shade);
+    }
+    dir
+}
+
+async fn login(client: &reqwest::Client, backend_url: &str) -> String {
+    let body: serde_json::Value = client
+        .post(format!("{}/api/v1/auth/login/", backend_url))
+        .json(&serde_json::json!({ "username": "test", "password": "test" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    body["access_token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn import_then_reimport_finds_duplicates() {
+    let server = harness::start_mock().await.unwrap();
+    let client = reqwest::Client::new();
+    let token = login(&client, &server.url).await;
+    let dir = photo_dir();
+
+    let files = harness::scan(&dir).unwrap();
+    assert_eq!(files.len(), 2, "scanned {:?}", files);
+    let groups = harness::group(files);
+    assert_eq!(groups.len(), 2);
+
+    // First import: new on the backend
+    let mut uploaded = Vec::new();
+    for group in &groups {
+        let schema = harness::process(&client, &group.master_file, &server.url).await.unwrap();
+        assert!(!schema.hothash.is_empty());
+        let existing = harness::find_backend_photo(&client, &server.url, &token, &schema.hothash).await.unwrap();
+        assert_eq!(existing, None);
+
+        let photo = harness::upload(&client, &server.url, &token, schema, CHANNEL_ID).await.unwrap();
+        assert!(!photo.is_duplicate);
+        uploaded.push((photo.hothash, photo.id));
+    }
+    assert_ne!(uploaded[0].0, uploaded[1].0, "different content, different hothash");
+
+    // Re-import: same hothashes, found by lookup, and a 409 on upload
+    for (group, (hothash, photo_id)) in groups.iter().zip(&uploaded) {
+        let schema = harness::process(&client, &group.master_file, &server.url).await.unwrap();
+        assert_eq!(&schema.hothash, hothash);
+        let existing = harness::find_backend_photo(&client, &server.url, &token, hothash).await.unwrap();
+        assert_eq!(existing, Some(*photo_id));
+
+        let photo = harness::upload(&client, &server.url, &token, schema, CHANNEL_ID).await.unwrap();
+        assert!(photo.is_duplicate);
+        assert_eq!(photo.id, *photo_id);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn flat_archive_keeps_first_of_same_named_files() {
+    let dir = same_named_dir();
+    let storage = std::env::temp_dir().join(format!("imalink-storage-{}", uuid::Uuid::new_v4()));
+    let groups = harness::group(harness::scan(&dir).unwrap());
+    assert_eq!(groups.len(), 2);
+    let first = std::fs::read(&groups[0].master_file).unwrap();
+
+    let claims = harness::Claims::default();
+    let written = harness::archive(&claims, &groups[0], &storage, &dir, false).unwrap();
+    assert_eq!(written, vec![storage.join("IMG_0001.jpg")]);
+
+    // Flat, the second lands on the same name: refused, first copy intact
+    let second = harness::archive(&claims, &groups[1], &storage, &dir, false);
+    assert!(
+        matches!(second, Err(ImalinkError::DestinationExists { .. })),
+        "second archive: {:?}",
+        second
+    );
+    assert_eq!(std::fs::read(storage.join("IMG_0001.jpg")).unwrap(), first);
+
+    // Keeping the folders, both fit
+    let structured = storage.join("structured");
+    for group in &groups {
+        harness::archive(&claims, group, &structured, &dir, true).unwrap();
+    }
+    assert!(structured.join("card1").join("IMG_0001.jpg").exists());
+    assert!(structured.join("card2").join("IMG_0001.jpg").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+    let _ = std::fs::remove_dir_all(&storage);
+}
+
+async fn core_hothash(client: &reqwest::Client, core_url: &str, part: reqwest::multipart::Part) -> String {
+    let form = reqwest::multipart::Form::new().part("file", part);
+    let body: serde_json::Value = client
+        .post(format!("{}/v1/hothash", core_url))
+        .multipart(form)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    body["hothash"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn file_parts_read_the_file_as_they_are_sent() {
+    let server = harness::start_mock().await.unwrap();
+    let client = reqwest::Client::new();
+    let dir = std::env::temp_dir().join(format!("imalink-mock-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("large.raw");
+    // Over one chunk, so the body goes out in pieces (and under axum's 2 MB
+    // default body limit)
+    let size = 1024 * 1024 + 512 * 1024;
+    std::fs::write(&path, vec![b'a'; size]).unwrap();
+    let before = core_hothash(&client, &server.url, harness::file_part(&path).await.unwrap()).await;
+
+    // Built, then the file changes before sending: a streamed part sends the
+    // new content, a buffered one would still send the old
+    let part = harness::file_part(&path).await.unwrap();
+    std::fs::write(&path, vec![b'b'; size]).unwrap();
+    let sent = core_hothash(&client, &server.url, part).await;
+    let after = core_hothash(&client, &server.url, harness::file_part(&path).await.unwrap()).await;
+
+    assert_ne!(sent, before);
+    assert_eq!(sent, after);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
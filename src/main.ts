@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 import { open } from "@tauri-apps/plugin-dialog";
 import { Store } from "@tauri-apps/plugin-store";
 
@@ -19,11 +21,44 @@ interface LoginResponse {
   user: User;
 }
 
+// Error returned by every Rust command - see src-tauri/src/error.rs
+interface ImalinkError {
+  code: string;
+  message: string;
+  params: Record<string, string>;
+  // Whether repeating the same call may succeed
+  retryable: boolean;
+}
+
 // ===== Global State =====
 
 let authToken: string | null = null;
 let currentUser: User | null = null;
 let credentialsStore: Store | null = null;
+let errorCatalog: Record<string, string> = {};
+
+// Render a command error in the UI language using the error catalog
+function formatError(error: unknown): string {
+  if (error && typeof error === "object" && "code" in error) {
+    const err = error as ImalinkError;
+    const template = errorCatalog[err.code];
+    if (!template) return err.message;
+    return Object.entries(err.params || {}).reduce(
+      (text, [key, value]) => text.split(`{${key}}`).join(value),
+      template
+    );
+  }
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
+
+async function loadErrorCatalog() {
+  try {
+    errorCatalog = await invoke("get_error_catalog", { locale: navigator.language || "nb" });
+  } catch (error) {
+    console.error("Failed to load error catalog:", error);
+  }
+}
 
 // PhotoCreateSchema structure - matches imalink-core v2.x API response
 // This is the canonical format from imalink-core API v2.x+
@@ -32,6 +67,8 @@ interface PhotoCreateSchema {
   hothash: string;
   
   // Hotpreview (always present)
+  // Base64 fields are stripped by Rust - load previews via
+  // convertFileSrc(`hot/${hothash}`, "imalink-preview") / `cold/${hothash}`
   hotpreview_base64: string;
   hotpreview_width: number;
   hotpreview_height: number;
@@ -76,6 +113,16 @@ interface ImageFileSchema {
   imported_info?: any;
 }
 
+// copy_file_to_storage result; checksum is "blake3:<hex>" when verified
+interface CopiedFile {
+  source_path: string;
+  destination_path: string;
+  checksum?: string;
+  action: "stored" | "skipped" | "overwritten" | "renamed";
+  // False for copies, and for moves whose source could not be deleted
+  source_removed: boolean;
+}
+
 // Companion file grouping
 interface CompanionGroup {
   basename: string;
@@ -112,12 +159,66 @@ interface PhotoCreateResponse {
   created_at: string;
   updated_at?: string | null;  // Optional - backend may not return it
   is_duplicate?: boolean;  // NEW in API v2.4 - indicates if photo already existed
+  // Position removed or coarsened by a privacy zone - see src-tauri/src/privacy.rs
+  gps_scrub?: { zone: string; action: "strip" | "fuzz" };
 }
 
+// Where the imalink-core sidecar listens; the port is picked at startup -
+// see src-tauri/src/remote_core.rs
+let coreUrl = "http://localhost:8765";
+
 let selectedFiles: string[] = [];
 let selectedDirPath: string | null = null;
 let selectedInputChannelId: number | null = null;
 
+interface EmailSource {
+  from: string | null;
+  date: string | null;
+  subject: string | null;
+  message: string;
+}
+
+interface EmailExtraction {
+  dir: string;
+  messages: {
+    succeeded: { path: string; source: EmailSource; attachments: { source: string; path: string; bytes: number }[] }[];
+    skipped: { item: string; reason: string }[];
+    failed: { item: string; error: string }[];
+  };
+}
+
+// Staged attachment path → the message it came in - see src-tauri/src/email_import.rs
+let emailSources: Record<string, EmailSource> = {};
+
+async function handleDroppedEmails(paths: string[]) {
+  const emails = paths.filter(p => /\.(eml|msg)$/i.test(p));
+  if (emails.length === 0) return;
+  const statusEl = document.querySelector("#status");
+  try {
+    const extraction: EmailExtraction = await invoke("extract_email_attachments", { paths: emails });
+    for (const message of extraction.messages.succeeded) {
+      for (const attachment of message.attachments) {
+        emailSources[attachment.path] = message.source;
+      }
+    }
+    if (extraction.messages.succeeded.length === 0) {
+      if (statusEl) {
+        statusEl.textContent = "Ingen bildevedlegg funnet i e-postene";
+        statusEl.className = "error";
+      }
+      return;
+    }
+    selectedDirPath = extraction.dir;
+    await scanDirectory(extraction.dir);
+  } catch (error) {
+    if (statusEl) {
+      statusEl.textContent = `Kunne ikke lese e-post: ${formatError(error)}`;
+      statusEl.className = "error";
+    }
+    console.error("Failed to extract email attachments:", error);
+  }
+}
+
 async function scanDirectory(dirPath: string) {
   const statusEl = document.querySelector("#status");
   const fileListEl = document.querySelector("#file-list") as HTMLElement;
@@ -134,9 +235,22 @@ async function scanDirectory(dirPath: string) {
     selectedFiles = await invoke("scan_directory", {
       dirPath: dirPath
     });
+
+    // A card imported from before: only what was shot since - see src-tauri/src/cards.rs
+    let cardNote = "";
+    try {
+      const card: CardCheck = await invoke("check_card", { dirPath, files: selectedFiles });
+      if (card.known) {
+        const lastImport = new Date(card.known.last_import_at).toLocaleString("nb-NO");
+        cardNote = ` – kjent minnekort${card.known.label ? ` ${card.known.label}` : ""}, sist importert ${lastImport}: ${card.selected.length} av ${card.files} filer er nye og valgt`;
+        selectedFiles = card.selected;
+      }
+    } catch (error) {
+      console.warn("Card check failed:", error);
+    }
     
     // Group files to detect companions
-    const companionGroups = groupCompanionFiles(selectedFiles);
+    const companionGroups = await groupCompanionFiles(selectedFiles);
     const totalFiles = companionGroups.reduce((sum, g) => sum + g.allFiles.length, 0);
     
     if (filesEl) {
@@ -162,94 +276,74 @@ async function scanDirectory(dirPath: string) {
     }
     
     if (statusEl) {
-      statusEl.textContent = selectedFiles.length > 0 ? `✓ Funnet ${totalFiles} filer i ${companionGroups.length} grupper` : "Ingen bildefiler funnet";
+      statusEl.textContent = selectedFiles.length > 0 ? `✓ Funnet ${totalFiles} filer i ${companionGroups.length} grupper${cardNote}` : `Ingen bildefiler funnet${cardNote}`;
       statusEl.className = selectedFiles.length > 0 ? "success" : "error";
     }
   } catch (error) {
     if (statusEl) {
-      statusEl.textContent = `Feil ved skanning: ${error}`;
+      statusEl.textContent = `Feil ved skanning: ${formatError(error)}`;
       statusEl.className = "error";
     }
     console.error("Failed to scan directory:", error);
   }
 }
 
-// ===== Companion File Detection =====
-
-function groupCompanionFiles(filePaths: string[]): CompanionGroup[] {
-  // File extension priorities (lower = preferred master)
-  const priorities: Record<string, number> = {
-    'jpg': 1,
-    'jpeg': 1,
-    'heic': 2,
-    'png': 3,
-    'cr2': 10,
-    'nef': 10,
-    'arw': 10,
-    'dng': 10,
-    'orf': 10,
-    'rw2': 10,
-    'raw': 10
-  };
+interface LowDiskStatus {
+  active: boolean;
+  path: string;
+  free_bytes: number | null;
+  threshold_bytes: number;
+}
 
-  // Group files by directory + basename
-  const groups = new Map<string, { files: string[], exts: string[], priorities: number[] }>();
+interface SpaceCheck {
+  low_disk: LowDiskStatus;
+  bytes: number;
+  destination: string | null;
+  destination_bytes: number;
+  destination_free: number | null;
+  temp_bytes: number;
+  fits: boolean;
+}
 
-  for (const filePath of filePaths) {
-    const parts = filePath.split('/');
-    const fileName = parts[parts.length - 1];
-    const directory = parts.slice(0, -1).join('/');
-    const lastDot = fileName.lastIndexOf('.');
-    
-    if (lastDot === -1) continue; // Skip files without extension
-    
-    const basename = fileName.substring(0, lastDot);
-    const ext = fileName.substring(lastDot + 1).toLowerCase();
-    const priority = priorities[ext] || 99;
-    
-    // Group key = directory + basename (same-directory matching only)
-    const groupKey = `${directory}/${basename}`;
-    
-    if (!groups.has(groupKey)) {
-      groups.set(groupKey, { files: [], exts: [], priorities: [] });
-    }
-    
-    const group = groups.get(groupKey)!;
-    group.files.push(filePath);
-    group.exts.push(ext);
-    group.priorities.push(priority);
-  }
+function toMb(bytes: number): number {
+  return Math.round(bytes / (1024 * 1024));
+}
 
-  // Convert to CompanionGroup array
-  const companionGroups: CompanionGroup[] = [];
+interface CardCheck {
+  known: { id: string; label: string | null; last_import_at: string; newest_imported: string | null; imports: number } | null;
+  files: number;
+  selected: string[];
+}
 
-  for (const [groupKey, group] of groups) {
-    const basename = groupKey.split('/').pop() || groupKey;
-    
-    // Find master file (lowest priority number)
-    let masterIndex = 0;
-    let lowestPriority = group.priorities[0];
-    
-    for (let i = 1; i < group.priorities.length; i++) {
-      if (group.priorities[i] < lowestPriority) {
-        lowestPriority = group.priorities[i];
-        masterIndex = i;
-      }
-    }
-    
-    const masterFile = group.files[masterIndex];
-    const companionFiles = group.files.filter((_, i) => i !== masterIndex);
-    
-    companionGroups.push({
-      basename,
-      masterFile,
-      companionFiles,
-      allFiles: group.files,
-      masterPriority: lowestPriority
-    });
+async function loadCoreUrl() {
+  try {
+    coreUrl = await invoke("get_core_url");
+  } catch (error) {
+    console.warn("Failed to get core URL:", error);
+    return;
   }
+  const coreUrlInput = document.querySelector("#core-url") as HTMLInputElement;
+  if (coreUrlInput && (!coreUrlInput.value || coreUrlInput.value === coreUrlInput.defaultValue)) {
+    coreUrlInput.value = coreUrl;
+    coreUrlInput.placeholder = coreUrl;
+  }
+}
+
+// ===== Companion File Detection =====
 
-  return companionGroups;
+// Grouping and master selection are done by the backend, so the
+// preview here matches what gets imported
+async function groupCompanionFiles(filePaths: string[]): Promise<CompanionGroup[]> {
+  const groups: { basename: string; master_file: string; companion_files: string[]; master_priority: number }[] =
+    await invoke("group_companion_files", { files: filePaths, masterOrder: null });
+
+  return groups.map(group => ({
+    basename: group.basename,
+    masterFile: group.master_file,
+    companionFiles: group.companion_files,
+    allFiles: [group.master_file, ...group.companion_files],
+    masterPriority: group.master_priority
+  }));
 }
 
 async function startImport() {
@@ -267,10 +361,8 @@ async function startImport() {
 
   // Get configuration
   const coreUrlInput = document.querySelector("#core-url") as HTMLInputElement;
-  const backendUrlInput = document.querySelector("#backend-url") as HTMLInputElement;
 
-  const coreApiUrl = coreUrlInput?.value || "http://localhost:8765";
-  const backendUrl = backendUrlInput?.value || "https://api.trollfjell.com";
+  const coreApiUrl = coreUrlInput?.value || coreUrl;
 
   if (!authToken) {
     if (statusEl) {
@@ -317,9 +409,36 @@ async function startImport() {
 
     const inputChannelId = selectedInputChannelId;
 
+    // Refuse up front what won't fit instead of failing halfway - see src-tauri/src/low_disk.rs
+    try {
+      const space: SpaceCheck = await invoke("check_import_space", {
+        options: {
+          source_dir: selectedDirPath ?? "",
+          files: selectedFiles,
+          core_api_url: coreApiUrl,
+          input_channel_id: inputChannelId,
+          destination_dir: isCopyMode ? destinationPath : null
+        }
+      });
+      if (!space.fits) {
+        if (statusEl) {
+          statusEl.textContent = `Feil: For lite plass på ${space.destination} – ${toMb(space.destination_bytes)} MB trengs, ${toMb(space.destination_free ?? 0)} MB ledig`;
+          statusEl.className = "error";
+        }
+        startImportBtn.disabled = false;
+        return;
+      }
+      if (space.low_disk.active && statusEl) {
+        statusEl.textContent = `⚠️ Lite ledig plass på systemdisken (${toMb(space.low_disk.free_bytes ?? 0)} MB) – importerer i sparemodus`;
+        statusEl.className = "warning";
+      }
+    } catch (error) {
+      console.warn("Space check failed:", error);
+    }
+
     // Step 2: Group files by companions
     console.log("Grouping companion files...");
-    const companionGroups = groupCompanionFiles(selectedFiles);
+    const companionGroups = await groupCompanionFiles(selectedFiles);
     console.log(`Found ${companionGroups.length} groups from ${selectedFiles.length} files`);
 
     // Step 3: Process each group
@@ -330,6 +449,7 @@ async function startImport() {
       resultsEl.style.display = "block";
     }
 
+    const importedFiles: string[] = [];
     const results: { file: string; success: boolean; error?: string; hothash?: string; isDuplicate?: boolean; isSkipped?: boolean; skipReason?: string; companionCount?: number; allFiles?: string[] }[] = [];
 
     for (let i = 0; i < companionGroups.length; i++) {
@@ -368,7 +488,7 @@ async function startImport() {
             file: masterFileName,
             success: false,
             isSkipped: true,
-            skipReason: `Cannot process file: ${String(coreError)}`,
+            skipReason: `Cannot process file: ${formatError(coreError)}`,
             companionCount,
             allFiles: group.allFiles.map(f => f.split('/').pop() || f)
           });
@@ -389,17 +509,20 @@ async function startImport() {
         if (isCopyMode && destinationPath) {
           console.log(`Copying master file to ${destinationPath}`);
           try {
-            finalPath = await invoke("copy_file_to_storage", {
+            const copied: CopiedFile = await invoke("copy_file_to_storage", {
               sourcePath: masterFilePath,
               destinationDir: destinationPath,
               preserveStructure: false,  // Flat copy for now
-              sourceBaseDir: null
+              sourceBaseDir: null,
+              options: { verify: true }
             });
+            finalPath = copied.destination_path;
             console.log(`Master file copied to: ${finalPath}`);
             localStorageInfo.storage_path = finalPath;
+            localStorageInfo.content_hash = copied.checksum;
           } catch (copyError) {
-            console.error(`Failed to copy file: ${copyError}`);
-            throw new Error(`Kunne ikke kopiere fil: ${copyError}`);
+            console.error(`Failed to copy file: ${formatError(copyError)}`);
+            throw new Error(`Kunne ikke kopiere fil: ${formatError(copyError)}`);
           }
         } else {
           // Register mode - file stays where it is
@@ -419,7 +542,8 @@ async function startImport() {
           photoCreateSchema.image_file_list[0].local_storage_info = localStorageInfo;
           photoCreateSchema.image_file_list[0].imported_info = {
             imported_at: new Date().toISOString(),
-            original_selection: selectedDirPath
+            original_selection: selectedDirPath,
+            ...(emailSources[masterFilePath] ? { email: emailSources[masterFilePath] } : {})
           };
         }
         
@@ -440,15 +564,18 @@ async function startImport() {
           if (isCopyMode && destinationPath) {
             console.log(`Copying companion file to ${destinationPath}`);
             try {
-              companionFinalPath = await invoke("copy_file_to_storage", {
+              const copied: CopiedFile = await invoke("copy_file_to_storage", {
                 sourcePath: companionPath,
                 destinationDir: destinationPath,
                 preserveStructure: false,
-                sourceBaseDir: null
+                sourceBaseDir: null,
+                options: { verify: true }
               });
+              companionFinalPath = copied.destination_path;
               companionLocalStorageInfo.storage_path = companionFinalPath;
+              companionLocalStorageInfo.content_hash = copied.checksum;
             } catch (copyError) {
-              console.error(`Failed to copy companion file: ${copyError}`);
+              console.error(`Failed to copy companion file: ${formatError(copyError)}`);
               // Continue anyway - companion copy is not critical
               companionLocalStorageInfo.storage_path = companionPath;
             }
@@ -483,10 +610,8 @@ async function startImport() {
         console.log(`Uploading ${masterFileName} to channel ${inputChannelId}`);
         
         const uploadResult: PhotoCreateResponse = await invoke("upload_photo_create_schema", {
-          backendUrl,
           photoCreateSchema,
-          inputChannelId,
-          authToken
+          inputChannelId
         });
         
         if (uploadResult.is_duplicate) {
@@ -494,7 +619,11 @@ async function startImport() {
         } else {
           console.log(`Upload successful for ${masterFileName}:`, uploadResult.hothash);
         }
+        if (uploadResult.gps_scrub) {
+          console.log(`GPS ${uploadResult.gps_scrub.action === "strip" ? "removed" : "coarsened"} for ${masterFileName} (zone ${uploadResult.gps_scrub.zone})`);
+        }
 
+        importedFiles.push(masterFilePath);
         results.push({
           file: masterFileName,
           success: true,
@@ -508,13 +637,17 @@ async function startImport() {
         results.push({
           file: masterFileName,
           success: false,
-          error: String(error),
+          error: formatError(error),
           companionCount,
           allFiles: group.allFiles.map(f => f.split('/').pop() || f)
         });
       }
     }
 
+    if (importedFiles.length > 0) {
+      invoke("record_card_import", { files: importedFiles }).catch((error) => console.warn("Failed to record card:", error));
+    }
+
     // Step 4: Show results
     const successCount = results.filter(r => r.success && !r.isDuplicate && !r.isSkipped).length;
     const duplicateCount = results.filter(r => r.success && r.isDuplicate).length;
@@ -597,7 +730,7 @@ async function startImport() {
     console.error("Error stringified:", JSON.stringify(error));
     
     if (statusEl) {
-      statusEl.textContent = `Feil: ${error}`;
+      statusEl.textContent = `Feil: ${formatError(error)}`;
       statusEl.className = "error";
     }
   } finally {
@@ -607,10 +740,69 @@ async function startImport() {
 
 // ===== Authentication Functions =====
 
+interface HealthStatus {
+  checked_at: string;
+  core: { url: string; up: boolean; version: string | null; latency_ms: number | null; error: string | null };
+  backend: { url: string; reachable: boolean; authenticated: boolean | null; latency_ms: number | null; error: string | null };
+  sidecar_pid: number | null;
+}
+
+function showHealthStatus(health: HealthStatus) {
+  const coreStatus = document.querySelector("#core-status");
+  if (!coreStatus) return;
+
+  const core = health.core.up
+    ? `✓ imalink-core kjører${health.core.version ? ` (${health.core.version})` : ""}, ${health.core.latency_ms} ms`
+    : `❌ imalink-core svarer ikke: ${health.core.error ?? "ukjent feil"}`;
+  const backend = !health.backend.reachable
+    ? `❌ backend utilgjengelig: ${health.backend.error ?? "ukjent feil"}`
+    : health.backend.authenticated === false
+      ? "⚠️ backend nås, men innloggingen er ugyldig"
+      : `✓ backend nås, ${health.backend.latency_ms} ms`;
+  coreStatus.textContent = `${core} · ${backend}`;
+  coreStatus.className = `info-text ${health.core.up && health.backend.reachable ? "success" : "error"}`;
+}
+
+interface CoreStatus {
+  state: "not_started" | "starting" | "ready" | "unreachable" | "restarting" | "crashed" | "stopped";
+  pid: number | null;
+  exit_code: number | null;
+  attempt: number;
+  max_attempts: number;
+  retry_in_seconds: number | null;
+  uptime_seconds: number | null;
+  last_health_check: string | null;
+}
+
+function showCoreStatus(status: CoreStatus) {
+  const coreStatus = document.querySelector("#core-status");
+  if (!coreStatus) return;
+  if (status.state === "restarting") {
+    coreStatus.textContent = `⚠️ imalink-core stoppet uventet – starter på nytt om ${status.retry_in_seconds} s (forsøk ${status.attempt} av ${status.max_attempts})`;
+    coreStatus.className = "info-text loading";
+  } else if (status.state === "crashed") {
+    const code = status.exit_code !== null ? ` (kode ${status.exit_code})` : "";
+    coreStatus.textContent = `❌ imalink-core krasjet ${status.max_attempts} ganger på rad${code} og er ikke startet igjen – bildebehandling er ikke tilgjengelig`;
+    coreStatus.className = "info-text error";
+  } else if (status.state === "starting") {
+    coreStatus.textContent = "Starter imalink-core...";
+    coreStatus.className = "info-text loading";
+  } else if (status.state === "unreachable") {
+    const checked = status.last_health_check ? `, sist sjekket ${new Date(status.last_health_check).toLocaleTimeString("nb-NO")}` : "";
+    coreStatus.textContent = `⚠️ imalink-core kjører (PID ${status.pid}), men svarer ikke${checked} – bildebehandling kan feile`;
+    coreStatus.className = "info-text error";
+  } else if (status.state === "ready" && status.attempt > 0) {
+    coreStatus.textContent = "✓ imalink-core er startet på nytt";
+    coreStatus.className = "info-text success";
+  }
+}
+
 async function checkCoreHealth() {
   const coreUrlInput = document.querySelector("#core-url") as HTMLInputElement;
+  const backendUrlInput = document.querySelector("#backend-url") as HTMLInputElement;
   const coreStatus = document.querySelector("#core-status");
-  const coreApiUrl = coreUrlInput?.value || "http://localhost:8765";
+  const coreApiUrl = coreUrlInput?.value || coreUrl;
+  const backendUrl = backendUrlInput?.value || "https://api.trollfjell.com";
   
   if (coreStatus) {
     coreStatus.textContent = "Tester tilkobling til imalink-core...";
@@ -618,29 +810,38 @@ async function checkCoreHealth() {
   }
   
   try {
-    const result: string = await invoke("check_core_health", { coreApiUrl });
-    if (coreStatus) {
-      coreStatus.textContent = result;
-      coreStatus.className = "info-text success";
-    }
-    console.log("Core health check passed:", result);
+    const health: HealthStatus = await invoke("get_health_status", {
+      coreApiUrl,
+      backendUrl,
+      authToken: authToken || null
+    });
+    showHealthStatus(health);
+    console.log("Health check:", health);
   } catch (error) {
     if (coreStatus) {
-      coreStatus.textContent = `❌ ${error}`;
+      coreStatus.textContent = `❌ ${formatError(error)}`;
       coreStatus.className = "info-text error";
     }
-    console.error("Core health check failed:", error);
+    console.error("Health check failed:", error);
   }
 }
 
 async function initializeAuth() {
   try {
-    // Initialize store
+    const backendUrl = (document.querySelector("#backend-url") as HTMLInputElement)?.value || "http://localhost:8000";
+
+    // Tokens saved by earlier versions sit in plain text in the plugin store;
+    // move them to the OS keychain
     credentialsStore = await Store.load("credentials.json");
-    
+    const legacyToken = await credentialsStore.get<string>("auth_token");
+    if (legacyToken) {
+      await invoke("secure_store_token", { backendUrl, authToken: legacyToken });
+      await credentialsStore.delete("auth_token");
+      await credentialsStore.save();
+    }
+
     // Try to load saved token
-    const savedToken = await credentialsStore.get<string>("auth_token");
-    const backendUrl = (document.querySelector("#backend-url") as HTMLInputElement)?.value || "http://localhost:8000";
+    const savedToken = await invoke<string | null>("secure_get_token", { backendUrl });
     
     if (savedToken) {
       // Validate token
@@ -702,16 +903,15 @@ async function handleLogin() {
     authToken = response.access_token;
     currentUser = response.user;
     
-    // Save token securely
-    if (credentialsStore) {
-      await credentialsStore.set("auth_token", authToken);
-      await credentialsStore.save();
-    }
+    // Save token in the OS keychain
+    await invoke("secure_store_token", { backendUrl, authToken }).catch((error) => {
+      console.error("Failed to save token:", error);
+    });
     
     showMainScreen();
   } catch (error) {
     if (loginStatus) {
-      loginStatus.textContent = `Innlogging feilet: ${error}`;
+      loginStatus.textContent = `Innlogging feilet: ${formatError(error)}`;
       loginStatus.className = "error";
     }
     console.error("Login failed:", error);
@@ -778,7 +978,7 @@ async function handleRegister() {
     
   } catch (error) {
     if (registerStatus) {
-      registerStatus.textContent = `Registrering feilet: ${error}`;
+      registerStatus.textContent = `Registrering feilet: ${formatError(error)}`;
       registerStatus.className = "error";
     }
     console.error("Registration failed:", error);
@@ -808,10 +1008,7 @@ async function handleLogout() {
   
   try {
     if (authToken) {
-      await invoke("logout", {
-        backendUrl,
-        authToken
-      });
+      await invoke("logout");
     }
   } catch (error) {
     console.error("Logout failed:", error);
@@ -820,10 +1017,7 @@ async function handleLogout() {
   // Clear credentials
   authToken = null;
   currentUser = null;
-  if (credentialsStore) {
-    await credentialsStore.delete("auth_token");
-    await credentialsStore.save();
-  }
+  await invoke("secure_store_token", { backendUrl, authToken: null }).catch(() => {});
   
   showLoginScreen();
 }
@@ -847,6 +1041,24 @@ function showMainScreen() {
     const displayName = currentUser.display_name || currentUser.username;
     userInfo.textContent = `Innlogget som: ${displayName} (${currentUser.username})`;
   }
+  if (currentUser && currentUser.default_author_id == null) {
+    offerSelfAuthor(currentUser);
+  }
+}
+
+// Accounts without a self-author upload author-less photos - see src-tauri/src/self_author.rs
+async function offerSelfAuthor(user: User) {
+  const name = prompt(
+    "Kontoen din mangler en fotograf for deg selv, så bildene dine får ingen fotograf. Opprett den nå med navnet:",
+    user.display_name || user.username
+  );
+  if (name === null) return;
+  try {
+    currentUser = await invoke<User>("create_self_author", { displayName: name });
+  } catch (error) {
+    console.error("Failed to create self-author:", error);
+    alert(`Kunne ikke opprette fotograf: ${formatError(error)}`);
+  }
 }
 
 async function openWebGallery() {
@@ -856,24 +1068,18 @@ async function openWebGallery() {
     });
   } catch (error) {
     console.error("Failed to open gallery:", error);
-    alert(`Kunne ikke åpne galleri: ${error}`);
+    alert(`Kunne ikke åpne galleri: ${formatError(error)}`);
   }
 }
 
 async function loadInputChannels() {
-  const backendUrlInput = document.querySelector("#backend-url") as HTMLInputElement;
-  const backendUrl = backendUrlInput?.value || "https://api.trollfjell.com";
-  
   if (!authToken) {
     alert("Du må være innlogget");
     return;
   }
 
   try {
-    const channels: InputChannel[] = await invoke("list_input_channels", {
-      backendUrl,
-      authToken
-    });
+    const channels: InputChannel[] = await invoke("list_input_channels");
 
     const selector = document.querySelector("#existing-channels") as HTMLSelectElement;
     const channelSelectorDiv = document.querySelector("#channel-selector") as HTMLElement;
@@ -897,7 +1103,7 @@ async function loadInputChannels() {
     }
   } catch (error) {
     console.error("Failed to load channels:", error);
-    alert(`Kunne ikke laste kanaler: ${error}`);
+    alert(`Kunne ikke laste kanaler: ${formatError(error)}`);
   }
 }
 
@@ -907,11 +1113,9 @@ function showCreateChannelForm() {
 }
 
 async function createNewChannel() {
-  const backendUrlInput = document.querySelector("#backend-url") as HTMLInputElement;
   const titleInput = document.querySelector("#session-title") as HTMLInputElement;
   const descriptionInput = document.querySelector("#session-description") as HTMLTextAreaElement;
 
-  const backendUrl = backendUrlInput?.value || "https://api.trollfjell.com";
   const title = titleInput?.value.trim();
   const description = descriptionInput?.value.trim() || null;
 
@@ -926,12 +1130,12 @@ async function createNewChannel() {
   }
 
   try {
+    // Without a description the backend writes one from the scanned files
     const channel: InputChannel = await invoke("create_input_channel", {
-      backendUrl,
       title,
       description,
       defaultAuthorId: null,
-      authToken
+      files: selectedFiles
     });
 
     selectedInputChannelId = channel.id;
@@ -954,7 +1158,7 @@ async function createNewChannel() {
     alert(`Kanal "${channel.title}" opprettet!`);
   } catch (error) {
     console.error("Failed to create channel:", error);
-    alert(`Kunne ikke opprette kanal: ${error}`);
+    alert(`Kunne ikke opprette kanal: ${formatError(error)}`);
   }
 }
 
@@ -1036,7 +1240,7 @@ async function selectDestinationDirectory() {
     }
   } catch (error) {
     console.error("Failed to select destination directory:", error);
-    alert(`Kunne ikke velge katalog: ${error}`);
+    alert(`Kunne ikke velge katalog: ${formatError(error)}`);
   }
 }
 
@@ -1066,6 +1270,9 @@ function selectExistingChannel() {
 }
 
 window.addEventListener("DOMContentLoaded", () => {
+  loadErrorCatalog();
+  loadCoreUrl();
+
   // Initialize authentication
   initializeAuth();
   
@@ -1086,6 +1293,53 @@ window.addEventListener("DOMContentLoaded", () => {
   openGalleryBtn?.addEventListener("click", openWebGallery);
   logoutBtn?.addEventListener("click", handleLogout);
   testCoreBtn?.addEventListener("click", checkCoreHealth);
+  // Refreshed by the backend every 30 s once checked
+  listen<HealthStatus>("health-status", (event) => showHealthStatus(event.payload));
+  // Caches shrink while the system drive is nearly full - see src-tauri/src/low_disk.rs
+  listen<LowDiskStatus>("low-disk-changed", (event) => {
+    const statusEl = document.querySelector("#status");
+    if (!statusEl) return;
+    if (event.payload.active) {
+      statusEl.textContent = `⚠️ Lite ledig plass på systemdisken (${toMb(event.payload.free_bytes ?? 0)} MB) – mellomlagring er redusert`;
+      statusEl.className = "warning";
+    } else {
+      statusEl.textContent = "Nok ledig plass igjen på systemdisken";
+      statusEl.className = "success";
+    }
+  });
+  // The imalink-core sidecar is restarted after a crash - see start_core_server
+  listen<CoreStatus>("core-status-changed", (event) => showCoreStatus(event.payload));
+  // Saved emails dropped on the window are imported from their attachments
+  getCurrentWebview().onDragDropEvent((event) => {
+    if (event.payload.type === "drop") handleDroppedEmails(event.payload.paths);
+  });
+  // The sidecar answers /health - see start_core_server
+  listen<CoreStatus>("core-ready", () => {
+    if (authToken) checkCoreHealth();
+  });
+  // Uploads pause while the backend is under maintenance - see src-tauri/src/maintenance.rs
+  listen<{ active: boolean; until: string | null; resume_at: string | null }>("backend-maintenance", (event) => {
+    const statusEl = document.querySelector("#status");
+    if (!statusEl) return;
+    if (event.payload.active) {
+      statusEl.textContent = `Serveren er under vedlikehold – fortsetter kl. ${event.payload.resume_at}`;
+      statusEl.className = "loading";
+    } else {
+      statusEl.textContent = "Serveren er tilbake – opplastingen fortsetter";
+      statusEl.className = "success";
+    }
+  });
+  listen<{ dir_path: string; files_found: number; current_dir: string; done: boolean }>("scan-progress", (event) => {
+    const statusEl = document.querySelector("#status");
+    if (!statusEl || event.payload.done) return;
+    statusEl.textContent = `Skanner katalog... ${event.payload.files_found} filer funnet (${event.payload.current_dir})`;
+    statusEl.className = "loading";
+  });
+  // The backend swapped an expired access token and updated the keychain
+  // copy - see src-tauri/src/auth.rs
+  listen<{ backend_url: string; access_token: string }>("auth-token-refreshed", (event) => {
+    authToken = event.payload.access_token;
+  });
   
   loadChannelsBtn?.addEventListener("click", loadInputChannels);
   showCreateChannelBtn?.addEventListener("click", showCreateChannelForm);
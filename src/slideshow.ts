@@ -0,0 +1,80 @@
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+
+// ===== Slideshow Window =====
+//
+// Opened by the start_slideshow command with ?id=<slideshow id>. Cycles the
+// coldpreviews of the show; slides whose preview isn't available (yet) are
+// skipped. Arrow keys step, space pauses, Escape closes the window.
+
+interface Slideshow {
+  id: string;
+  hothashes: string[];
+  interval_seconds: number;
+}
+
+let show: Slideshow | null = null;
+let index = -1;
+let paused = false;
+let timer: number | undefined;
+
+function slideUrl(hothash: string): string {
+  return convertFileSrc(`cold/${hothash}`, "imalink-preview");
+}
+
+// Show the next slide in `step` direction that loads, giving up after a full round
+async function advance(step: number) {
+  if (!show || show.hothashes.length === 0) return;
+  const img = document.querySelector<HTMLImageElement>("#slide")!;
+  const count = show.hothashes.length;
+
+  for (let tried = 0; tried < count; tried++) {
+    index = (index + step + count) % count;
+    const candidate = new Image();
+    candidate.src = slideUrl(show.hothashes[index]);
+    try {
+      await candidate.decode();
+      img.src = candidate.src;
+      break;
+    } catch {
+      // Not downloaded yet or unavailable - try the next one
+    }
+  }
+  schedule();
+}
+
+function schedule() {
+  window.clearTimeout(timer);
+  if (show && !paused) {
+    timer = window.setTimeout(() => advance(1), show.interval_seconds * 1000);
+  }
+}
+
+window.addEventListener("keydown", (event) => {
+  switch (event.key) {
+    case "Escape":
+      getCurrentWindow().close();
+      break;
+    case "ArrowRight":
+      advance(1);
+      break;
+    case "ArrowLeft":
+      advance(-1);
+      break;
+    case " ":
+      paused = !paused;
+      schedule();
+      break;
+  }
+});
+
+window.addEventListener("DOMContentLoaded", async () => {
+  const id = new URLSearchParams(window.location.search).get("id");
+  if (!id) return;
+  try {
+    show = await invoke<Slideshow>("get_slideshow", { id });
+    advance(1);
+  } catch (error) {
+    console.error("Failed to load slideshow:", error);
+  }
+});
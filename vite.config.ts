@@ -27,4 +27,13 @@ export default defineConfig(async () => ({
       ignored: ["**/src-tauri/**"],
     },
   },
+  // The slideshow window (see src-tauri/src/slideshow.rs) is a second page
+  build: {
+    rollupOptions: {
+      input: {
+        main: "index.html",
+        slideshow: "slideshow.html",
+      },
+    },
+  },
 }));